@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+use bevy_rapier2d::{
+    na::Vector2,
+    physics::{RapierConfiguration, RapierPhysicsPlugin},
+};
+use bevy_showcase::{
+    breakout_scene::{self, BreakoutScenePlugin},
+    cleanup::RapierCleanupPlugin,
+    handles::HandleToEntityPlugin,
+    scene::ScenePlugin,
+    showcase::ShowcasePlugin,
+};
+
+const WINDOW_WIDTH: u32 = 1280;
+const WINDOW_HEIGHT: u32 = 800;
+
+fn main() {
+    App::build()
+        .add_plugin(
+            ShowcasePlugin::new("Breakout")
+                .with_window(WINDOW_WIDTH, WINDOW_HEIGHT)
+                .with_clear_color(Color::rgb(0.02, 0.02, 0.04))
+                .without_debug_hud(),
+        )
+        .add_plugin(RapierPhysicsPlugin)
+        .add_plugin(RapierCleanupPlugin)
+        .add_plugin(HandleToEntityPlugin)
+        .add_resource(RapierConfiguration {
+            gravity: Vector2::zeros(),
+            ..Default::default()
+        })
+        .add_plugin(BreakoutScenePlugin)
+        .add_plugin(
+            ScenePlugin::default()
+                .with_scene(breakout_scene::SCENE)
+                .with_start(0),
+        )
+        .add_startup_system(setup.system())
+        .run();
+}
+
+/// The camera of the score and message texts, the scene having the 2d one.
+fn setup(mut commands: Commands) {
+    commands.spawn(UiCameraComponents::default());
+}
@@ -0,0 +1,416 @@
+#![allow(clippy::too_many_arguments)]
+
+use bevy::{
+    prelude::*,
+    render::{
+        camera::{OrthographicProjection, WindowOrigin},
+        pass::ClearColor,
+    },
+};
+use bevy_rapier2d::{
+    na::Vector2,
+    physics::{EventQueue, RapierConfiguration, RapierPhysicsPlugin, RigidBodyHandleComponent},
+    rapier::{
+        dynamics::{RigidBodyBuilder, RigidBodySet},
+        geometry::{ColliderBuilder, Proximity},
+    },
+};
+use bevy_showcase::{
+    damping::{damping_system, Damping},
+    handles::{ColliderHandleToEntity, HandleToEntityPlugin},
+    top_down_vehicle::{forward, forward_speed, lateral_friction_system, Grip},
+};
+
+const WINDOW_WIDTH: u32 = 1280;
+const WINDOW_HEIGHT: u32 = 800;
+const CAMERA_SCALE: f32 = 0.1;
+const ARENA_WIDTH: f32 = WINDOW_WIDTH as f32 * CAMERA_SCALE;
+const ARENA_HEIGHT: f32 = WINDOW_HEIGHT as f32 * CAMERA_SCALE;
+const WALL_HALF_THICKNESS: f32 = 1.0;
+/// Width of the track between the outer walls and the island in the middle
+const TRACK_WIDTH: f32 = 22.0;
+const CAR_HALF_WIDTH: f32 = 1.0;
+const CAR_HALF_LENGTH: f32 = 2.0;
+/// Engine force along the car facing, in N
+const ENGINE_FORCE: f32 = 320.0;
+/// Fraction of the engine force when reversing
+const REVERSE_FACTOR: f32 = 0.5;
+/// Steering torque, in N.m
+const STEER_TORQUE: f32 = 150.0;
+/// Speed along the facing below which the car cannot turn, in m/s
+const MIN_STEER_SPEED: f32 = 1.0;
+/// Fraction of the sideways velocity cancelled each frame, tires gripping
+const LATERAL_GRIP: f32 = 0.9;
+/// Fraction of the sideways velocity cancelled each frame, handbrake pulled
+const HANDBRAKE_GRIP: f32 = 0.05;
+/// x position of the start line, across the bottom straight driven left to
+/// right
+const START_LINE_X: f32 = ARENA_WIDTH / 2.0;
+
+fn main() {
+    App::build()
+        .add_resource(WindowDescriptor {
+            title: "Top-down car".to_string(),
+            width: WINDOW_WIDTH,
+            height: WINDOW_HEIGHT,
+            ..Default::default()
+        })
+        .add_resource(ClearColor(Color::rgb(0.12, 0.14, 0.12)))
+        .init_resource::<LapTimer>()
+        .add_plugin(RapierPhysicsPlugin)
+        .add_plugin(HandleToEntityPlugin)
+        .add_default_plugins()
+        .add_resource(RapierConfiguration {
+            gravity: Vector2::zeros(),
+            ..Default::default()
+        })
+        .add_startup_system(setup.system())
+        .add_startup_system(spawn_track.system())
+        .add_startup_system(spawn_car.system())
+        .add_system(car_control_system.system())
+        .add_system(lateral_friction_system.system())
+        .add_system(damping_system.system())
+        .add_system(lap_time_system.system())
+        .add_system(lap_text_system.system())
+        .add_system_to_stage(stage::POST_UPDATE, lap_line_system.system())
+        .run();
+}
+
+struct Car;
+
+/// A sensor across the track
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Line {
+    /// Starts and ends the laps, crossed left to right
+    Start,
+    /// Halfway around, so that going back and forth over the start line is
+    /// not a lap
+    Checkpoint,
+}
+
+/// Times of the laps, starting once the car first crosses the start line.
+#[derive(Default)]
+struct LapTimer {
+    /// Time in s since the start of the current lap
+    current: Option<f32>,
+    last: Option<f32>,
+    best: Option<f32>,
+    laps: u32,
+    /// Whether the checkpoint was passed during the current lap
+    checkpoint: bool,
+    /// Whether the car entered the start line from the left, the right
+    /// direction
+    entered_behind: bool,
+}
+
+struct LapText;
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let font_handle = asset_server.load("assets/DejaVuSansMono.ttf").unwrap();
+    commands
+        .spawn(Camera2dComponents {
+            orthographic_projection: OrthographicProjection {
+                window_origin: WindowOrigin::BottomLeft,
+                far: 1000.0 / CAMERA_SCALE,
+                ..Default::default()
+            },
+            transform: Transform::from_scale(CAMERA_SCALE),
+            ..Default::default()
+        })
+        .spawn(UiCameraComponents::default())
+        .spawn(NodeComponents {
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            material: materials.add(Color::NONE.into()),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(TextComponents {
+                    text: Text {
+                        value: String::new(),
+                        font: font_handle,
+                        style: TextStyle {
+                            font_size: 32.0,
+                            color: Color::WHITE,
+                        },
+                    },
+                    ..Default::default()
+                })
+                .with(LapText);
+        })
+        .spawn(TextComponents {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    bottom: Val::Px(10.0),
+                    left: Val::Px(10.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text {
+                value: "W/S: throttle  A/D: steer  Space: handbrake".to_string(),
+                font: font_handle,
+                style: TextStyle {
+                    font_size: 20.0,
+                    color: Color::rgb(0.7, 0.7, 0.7),
+                },
+            },
+            ..Default::default()
+        });
+}
+
+/// Static walls around the arena and an island in the middle, with the start
+/// line and the checkpoint across the track between them.
+fn spawn_track(mut commands: Commands, mut materials: ResMut<Assets<ColorMaterial>>) {
+    let material = materials.add(Color::rgb(0.45, 0.45, 0.5).into());
+    let island = materials.add(Color::rgb(0.2, 0.35, 0.2).into());
+    let center = Vec2::new(ARENA_WIDTH / 2.0, ARENA_HEIGHT / 2.0);
+    // Center, half extents and material of each wall
+    let walls = [
+        (
+            Vec2::new(center.x(), WALL_HALF_THICKNESS),
+            Vec2::new(ARENA_WIDTH / 2.0, WALL_HALF_THICKNESS),
+            material,
+        ),
+        (
+            Vec2::new(center.x(), ARENA_HEIGHT - WALL_HALF_THICKNESS),
+            Vec2::new(ARENA_WIDTH / 2.0, WALL_HALF_THICKNESS),
+            material,
+        ),
+        (
+            Vec2::new(WALL_HALF_THICKNESS, center.y()),
+            Vec2::new(WALL_HALF_THICKNESS, ARENA_HEIGHT / 2.0),
+            material,
+        ),
+        (
+            Vec2::new(ARENA_WIDTH - WALL_HALF_THICKNESS, center.y()),
+            Vec2::new(WALL_HALF_THICKNESS, ARENA_HEIGHT / 2.0),
+            material,
+        ),
+        (center, center - Vec2::new(TRACK_WIDTH, TRACK_WIDTH), island),
+    ];
+    for &(center, half_extents, material) in &walls {
+        commands
+            .spawn(SpriteComponents {
+                sprite: Sprite::new(half_extents * 2.0),
+                material,
+                ..Default::default()
+            })
+            .with(RigidBodyBuilder::new_static().translation(center.x(), center.y()))
+            .with(
+                ColliderBuilder::cuboid(half_extents.x(), half_extents.y())
+                    .restitution(0.3)
+                    .friction(0.2),
+            );
+    }
+    let lines = [
+        (
+            Line::Start,
+            TRACK_WIDTH / 2.0,
+            Color::rgba(1.0, 1.0, 1.0, 0.8),
+        ),
+        (
+            Line::Checkpoint,
+            ARENA_HEIGHT - TRACK_WIDTH / 2.0,
+            Color::rgba(1.0, 1.0, 1.0, 0.1),
+        ),
+    ];
+    for &(line, y, color) in &lines {
+        let half_extents = Vec2::new(0.25, TRACK_WIDTH / 2.0 - WALL_HALF_THICKNESS);
+        commands
+            .spawn(SpriteComponents {
+                sprite: Sprite::new(half_extents * 2.0),
+                material: materials.add(color.into()),
+                ..Default::default()
+            })
+            .with(line)
+            .with(RigidBodyBuilder::new_static().translation(START_LINE_X, y))
+            .with(ColliderBuilder::cuboid(half_extents.x(), half_extents.y()).sensor(true));
+    }
+}
+
+/// Spawns the car behind the start line, facing the right direction.
+fn spawn_car(mut commands: Commands, mut materials: ResMut<Assets<ColorMaterial>>) {
+    let body = RigidBodyBuilder::new_dynamic()
+        .translation(START_LINE_X - 10.0, TRACK_WIDTH / 2.0)
+        // Facing right, the car facing its local y axis
+        .rotation(-std::f32::consts::FRAC_PI_2);
+    commands
+        .spawn(SpriteComponents {
+            sprite: Sprite::new(Vec2::new(CAR_HALF_WIDTH, CAR_HALF_LENGTH) * 2.0),
+            material: materials.add(Color::rgb(0.85, 0.2, 0.2).into()),
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 1.0)),
+            ..Default::default()
+        })
+        .with(Car)
+        .with(Grip {
+            lateral: LATERAL_GRIP,
+            handbrake: HANDBRAKE_GRIP,
+            handbrake_on: false,
+        })
+        .with(Damping {
+            linear: 0.6,
+            angular: 0.02,
+        })
+        .with(body)
+        .with(
+            ColliderBuilder::cuboid(CAR_HALF_WIDTH, CAR_HALF_LENGTH)
+                .restitution(0.2)
+                .friction(0.1),
+        )
+        .with_children(|parent| {
+            // Windshield, showing the front of the car
+            parent.spawn(SpriteComponents {
+                sprite: Sprite::new(Vec2::new(CAR_HALF_WIDTH * 1.6, 0.8)),
+                material: materials.add(Color::rgb(0.6, 0.8, 0.95).into()),
+                transform: Transform::from_translation(Vec3::new(0.0, CAR_HALF_LENGTH / 2.0, 0.1)),
+                ..Default::default()
+            });
+        });
+}
+
+/// Pushes the car along its facing with W and S, and turns it with A and D
+/// only when moving, the steering being reversed when reversing, Space
+/// pulling the handbrake.
+fn car_control_system(
+    input: Res<Input<KeyCode>>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut cars: Query<With<Car, (Mut<Grip>, &RigidBodyHandleComponent)>>,
+) {
+    let mut throttle = 0.0;
+    if input.pressed(KeyCode::W) {
+        throttle += 1.0;
+    }
+    if input.pressed(KeyCode::S) {
+        throttle -= REVERSE_FACTOR;
+    }
+    let mut steer = 0.0;
+    if input.pressed(KeyCode::A) {
+        steer += 1.0;
+    }
+    if input.pressed(KeyCode::D) {
+        steer -= 1.0;
+    }
+    let handbrake_on = input.pressed(KeyCode::Space);
+    for (mut grip, body_handle) in &mut cars.iter() {
+        if grip.handbrake_on != handbrake_on {
+            grip.handbrake_on = handbrake_on;
+        }
+        let mut body = bodies.get_mut(body_handle.handle()).unwrap();
+        let forward = forward(&body);
+        if throttle != 0.0 {
+            body.wake_up(true);
+            body.apply_force(forward * throttle * ENGINE_FORCE);
+        }
+        let speed = forward_speed(body.linvel, forward);
+        if steer != 0.0 && speed.abs() > MIN_STEER_SPEED {
+            body.wake_up(true);
+            body.apply_torque(steer * STEER_TORQUE * speed.signum());
+        }
+    }
+}
+
+/// Counts the time of the current lap.
+fn lap_time_system(time: Res<Time>, mut timer: ResMut<LapTimer>) {
+    if let Some(current) = timer.current.as_mut() {
+        *current += time.delta_seconds;
+    }
+}
+
+/// Starts a lap each time the car crosses the start line left to right, ending
+/// the current one if the checkpoint was passed.
+///
+/// The crossing direction is the side the car entered the line from, against
+/// the side it left it to, so that a car stopping on the line and backing off
+/// does not start a lap.
+fn lap_line_system(
+    events: Res<EventQueue>,
+    h_to_e: Res<ColliderHandleToEntity>,
+    bodies: Res<RigidBodySet>,
+    mut timer: ResMut<LapTimer>,
+    cars: Query<With<Car, &RigidBodyHandleComponent>>,
+    lines: Query<&Line>,
+) {
+    while let Ok(proximity_event) = events.proximity_events.pop() {
+        let (e1, e2) = match (
+            h_to_e.get(proximity_event.collider1),
+            h_to_e.get(proximity_event.collider2),
+        ) {
+            (Some(e1), Some(e2)) => (e1, e2),
+            _ => continue,
+        };
+        for &(car, line) in &[(e1, e2), (e2, e1)] {
+            let car_x = match cars.get::<RigidBodyHandleComponent>(car) {
+                Ok(body_handle) => {
+                    bodies
+                        .get(body_handle.handle())
+                        .unwrap()
+                        .position
+                        .translation
+                        .vector
+                        .x
+                }
+                Err(_) => continue,
+            };
+            let line = match lines.get::<Line>(line) {
+                Ok(line) => *line,
+                Err(_) => continue,
+            };
+            match (line, proximity_event.new_status) {
+                (Line::Checkpoint, Proximity::Intersecting) => timer.checkpoint = true,
+                (Line::Start, Proximity::Intersecting) => {
+                    timer.entered_behind = car_x < START_LINE_X
+                }
+                (Line::Start, Proximity::Disjoint) => {
+                    if !timer.entered_behind || car_x < START_LINE_X {
+                        continue;
+                    }
+                    timer.entered_behind = false;
+                    match (timer.current, timer.checkpoint) {
+                        (Some(current), true) => {
+                            timer.laps += 1;
+                            timer.last = Some(current);
+                            timer.best = Some(timer.best.map_or(current, |best| best.min(current)));
+                        }
+                        // Back over the start line without going around
+                        (Some(_), false) => continue,
+                        (None, _) => {}
+                    }
+                    timer.current = Some(0.0);
+                    timer.checkpoint = false;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn lap_text_system(timer: Res<LapTimer>, mut texts: Query<With<LapText, Mut<Text>>>) {
+    let format_time = |time: Option<f32>| time.map_or("-".to_string(), |t| format!("{:.2}", t));
+    let value = match timer.current {
+        None => "Cross the start line to begin".to_string(),
+        Some(current) => format!(
+            "Lap {}  Time {:.2}  Last {}  Best {}",
+            timer.laps + 1,
+            current,
+            format_time(timer.last),
+            format_time(timer.best),
+        ),
+    };
+    for mut text in &mut texts.iter() {
+        if text.value != value {
+            text.value = value.clone();
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! Runs the same balls headless with the manual collision response of the
+//! ncollide2d example and with the rapier solver, printing the time taken by
+//! each step, the drift of the kinetic energy and the deepest overlap.
+//!
+//! Best run with `cargo run --release --example physics_bench`.
+
+use bevy_showcase::{
+    arena::Arena,
+    scenario::{kinetic_energy, scenario_balls, Backend},
+};
+use std::time::{Duration, Instant};
+
+const SEED: u64 = 214;
+/// 500 balls
+const COLUMNS: usize = 25;
+const ROWS: usize = 20;
+const MAX_SPEED: f32 = 100.0;
+const STEPS: usize = 600;
+/// Duration in s of each step
+const STEP: f32 = 1.0 / 60.0;
+const ARENA_WIDTH: f32 = 1280.0;
+const ARENA_HEIGHT: f32 = 800.0;
+
+fn main() {
+    let arena = Arena::new(ARENA_WIDTH, ARENA_HEIGHT);
+    let balls = scenario_balls(SEED, &arena, COLUMNS, ROWS, MAX_SPEED);
+    let initial_energy = kinetic_energy(balls.iter().map(|ball| (ball.velocity, ball.radius)));
+    println!(
+        "{} balls, {} steps of {:.1} ms",
+        balls.len(),
+        STEPS,
+        STEP * 1000.0
+    );
+    println!(
+        "{:<10} {:>10} {:>10} {:>13} {:>16}",
+        "backend", "mean (ms)", "max (ms)", "energy drift", "max overlap (px)"
+    );
+    for &backend in &[Backend::Ncollide, Backend::Rapier] {
+        let mut app = backend.app(&balls, Arena::new(ARENA_WIDTH, ARENA_HEIGHT), STEP);
+        let mut total = Duration::default();
+        let mut slowest = Duration::default();
+        let mut max_penetration: f32 = 0.0;
+        for _ in 0..STEPS {
+            let start = Instant::now();
+            app.update();
+            let elapsed = start.elapsed();
+            total += elapsed;
+            slowest = slowest.max(elapsed);
+            max_penetration = max_penetration.max(backend.measure(&app).max_penetration);
+        }
+        let measures = backend.measure(&app);
+        println!(
+            "{:<10} {:>10.3} {:>10.3} {:>12.2}% {:>16.2}",
+            format!("{:?}", backend),
+            total.as_secs_f64() * 1000.0 / STEPS as f64,
+            slowest.as_secs_f64() * 1000.0,
+            (measures.kinetic_energy / initial_energy - 1.0) * 100.0,
+            max_penetration
+        );
+    }
+}
@@ -0,0 +1,387 @@
+#![allow(clippy::too_many_arguments)]
+
+use bevy::{
+    prelude::*,
+    render::{
+        camera::{OrthographicProjection, WindowOrigin},
+        pass::ClearColor,
+    },
+};
+use bevy_rapier2d::{
+    na::{Isometry2, Vector2},
+    physics::{EventQueue, RapierConfiguration, RapierPhysicsPlugin, RigidBodyHandleComponent},
+    rapier::{
+        dynamics::{RigidBodyBuilder, RigidBodySet},
+        geometry::{ColliderBuilder, ContactEvent},
+    },
+};
+use bevy_showcase::handles::{ColliderHandleToEntity, HandleToEntityPlugin};
+use std::collections::HashSet;
+
+const WINDOW_WIDTH: u32 = 1280;
+const WINDOW_HEIGHT: u32 = 800;
+const CAMERA_SCALE: f32 = 0.1;
+const ARENA_WIDTH: f32 = WINDOW_WIDTH as f32 * CAMERA_SCALE;
+const ARENA_HEIGHT: f32 = WINDOW_HEIGHT as f32 * CAMERA_SCALE;
+const GRAVITY: f32 = -60.0;
+const PLAYER_HALF_WIDTH: f32 = 0.8;
+const PLAYER_HALF_HEIGHT: f32 = 1.2;
+const PLAYER_START: (f32, f32) = (10.0, 10.0);
+/// Horizontal target speed with A and D, in m/s
+const RUN_SPEED: f32 = 22.0;
+/// Largest change of the horizontal velocity towards the target, in m/s²
+const GROUND_ACCELERATION: f32 = 200.0;
+const AIR_ACCELERATION: f32 = 80.0;
+/// Vertical velocity given by the jump impulse, in m/s
+const JUMP_SPEED: f32 = 32.0;
+/// Frames after leaving the ground during which the player can still jump
+const COYOTE_FRAMES: u8 = 6;
+/// Smallest upward component of a contact normal for the contact to be ground,
+/// steeper contacts being walls or ceilings.
+const MIN_GROUND_NORMAL_Y: f32 = 0.7;
+/// Height below which the player fell off the level, and is put back at the
+/// start
+const FALL_LIMIT: f32 = -10.0;
+/// Half amplitude in m and angular frequency in rad/s of the moving platform
+const MOVING_PLATFORM_AMPLITUDE: f32 = 24.0;
+const MOVING_PLATFORM_PULSATION: f32 = 0.8;
+
+fn main() {
+    App::build()
+        .add_resource(WindowDescriptor {
+            title: "Platformer".to_string(),
+            width: WINDOW_WIDTH,
+            height: WINDOW_HEIGHT,
+            ..Default::default()
+        })
+        .add_resource(ClearColor(Color::rgb(0.35, 0.55, 0.8)))
+        .add_plugin(RapierPhysicsPlugin)
+        .add_plugin(HandleToEntityPlugin)
+        .add_default_plugins()
+        .add_resource(RapierConfiguration {
+            gravity: Vector2::new(0.0, GRAVITY),
+            ..Default::default()
+        })
+        .add_startup_system(setup.system())
+        .add_startup_system(spawn_platforms.system())
+        .add_startup_system(spawn_player.system())
+        .add_system(moving_platform_system.system())
+        .add_system(player_control_system.system())
+        .add_system(fall_system.system())
+        .add_system_to_stage(stage::POST_UPDATE, ground_contact_system.system())
+        .run();
+}
+
+/// A static or moving cuboid the player can stand on
+struct Platform {
+    half_extents: Vec2,
+}
+
+/// A kinematic platform swinging horizontally around `center`
+struct MovingPlatform {
+    center: Vec2,
+    /// Current horizontal velocity, given to the player standing on it
+    velocity: f32,
+}
+
+struct Player;
+
+/// Platforms the player is in contact with, following the contact events
+#[derive(Default)]
+struct Touching(HashSet<Entity>);
+
+/// Frames since the player last stood on a platform, `0` while standing on
+/// one, jumping being allowed for `COYOTE_FRAMES` after leaving the ground.
+struct Grounded {
+    frames_since_contact: u8,
+    /// Platform under the player, while standing on one
+    ground: Option<Entity>,
+}
+
+impl Grounded {
+    /// Whether the player stood on a platform recently enough to jump
+    fn can_jump(&self) -> bool {
+        self.frames_since_contact <= COYOTE_FRAMES
+    }
+}
+
+impl Default for Grounded {
+    fn default() -> Self {
+        Grounded {
+            frames_since_contact: u8::MAX,
+            ground: None,
+        }
+    }
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font_handle = asset_server.load("assets/DejaVuSansMono.ttf").unwrap();
+    commands
+        .spawn(Camera2dComponents {
+            orthographic_projection: OrthographicProjection {
+                window_origin: WindowOrigin::BottomLeft,
+                far: 1000.0 / CAMERA_SCALE,
+                ..Default::default()
+            },
+            transform: Transform::from_scale(CAMERA_SCALE),
+            ..Default::default()
+        })
+        .spawn(UiCameraComponents::default())
+        .spawn(TextComponents {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(10.0),
+                    left: Val::Px(10.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text {
+                value: "A/D: run  Space/W: jump".to_string(),
+                font: font_handle,
+                style: TextStyle {
+                    font_size: 24.0,
+                    color: Color::WHITE,
+                },
+            },
+            ..Default::default()
+        });
+}
+
+fn spawn_platforms(mut commands: Commands, mut materials: ResMut<Assets<ColorMaterial>>) {
+    let material = materials.add(Color::rgb(0.3, 0.25, 0.2).into());
+    // Center and half extents of each static platform, each one within a jump
+    // of the previous one
+    let platforms = [
+        (Vec2::new(20.0, 1.0), Vec2::new(20.0, 1.0)),
+        (Vec2::new(ARENA_WIDTH - 20.0, 1.0), Vec2::new(20.0, 1.0)),
+        (Vec2::new(36.0, 8.0), Vec2::new(8.0, 0.75)),
+        (Vec2::new(12.0, 15.0), Vec2::new(6.0, 0.75)),
+        (Vec2::new(ARENA_WIDTH - 12.0, 15.0), Vec2::new(6.0, 0.75)),
+        (Vec2::new(ARENA_WIDTH - 36.0, 8.0), Vec2::new(8.0, 0.75)),
+        (Vec2::new(ARENA_WIDTH / 2.0, 29.0), Vec2::new(10.0, 0.75)),
+        // Walls on the sides
+        (
+            Vec2::new(-1.0, ARENA_HEIGHT / 2.0),
+            Vec2::new(1.0, ARENA_HEIGHT),
+        ),
+        (
+            Vec2::new(ARENA_WIDTH + 1.0, ARENA_HEIGHT / 2.0),
+            Vec2::new(1.0, ARENA_HEIGHT),
+        ),
+    ];
+    for &(center, half_extents) in &platforms {
+        commands
+            .spawn(SpriteComponents {
+                sprite: Sprite::new(half_extents * 2.0),
+                material,
+                ..Default::default()
+            })
+            .with(Platform { half_extents })
+            .with(RigidBodyBuilder::new_static().translation(center.x(), center.y()))
+            .with(ColliderBuilder::cuboid(half_extents.x(), half_extents.y()).friction(0.0));
+    }
+    // Across the gap between the two floors, up to the side platforms
+    let center = Vec2::new(ARENA_WIDTH / 2.0, 22.0);
+    let half_extents = Vec2::new(6.0, 0.75);
+    commands
+        .spawn(SpriteComponents {
+            sprite: Sprite::new(half_extents * 2.0),
+            material: materials.add(Color::rgb(0.6, 0.45, 0.2).into()),
+            ..Default::default()
+        })
+        .with(Platform { half_extents })
+        .with(MovingPlatform {
+            center,
+            velocity: 0.0,
+        })
+        .with(RigidBodyBuilder::new_kinematic().translation(center.x(), center.y()))
+        .with(ColliderBuilder::cuboid(half_extents.x(), half_extents.y()).friction(0.0));
+}
+
+fn spawn_player(mut commands: Commands, mut materials: ResMut<Assets<ColorMaterial>>) {
+    commands
+        .spawn(SpriteComponents {
+            sprite: Sprite::new(Vec2::new(PLAYER_HALF_WIDTH, PLAYER_HALF_HEIGHT) * 2.0),
+            material: materials.add(Color::rgb(0.9, 0.3, 0.3).into()),
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 1.0)),
+            ..Default::default()
+        })
+        .with(Player)
+        .with(Touching::default())
+        .with(Grounded::default())
+        .with(RigidBodyBuilder::new_dynamic().translation(PLAYER_START.0, PLAYER_START.1))
+        // Frictionless, so that the player does not stick to walls, the
+        // moving platform carrying it through the control system instead
+        .with(ColliderBuilder::cuboid(PLAYER_HALF_WIDTH, PLAYER_HALF_HEIGHT).friction(0.0));
+}
+
+/// Swings the moving platforms around their center.
+fn moving_platform_system(
+    time: Res<Time>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut platforms: Query<(Mut<MovingPlatform>, &RigidBodyHandleComponent)>,
+) {
+    let phase = time.seconds_since_startup as f32 * MOVING_PLATFORM_PULSATION;
+    for (mut platform, body_handle) in &mut platforms.iter() {
+        let x = platform.center.x() + MOVING_PLATFORM_AMPLITUDE * phase.sin();
+        platform.velocity = MOVING_PLATFORM_AMPLITUDE * MOVING_PLATFORM_PULSATION * phase.cos();
+        let mut body = bodies.get_mut(body_handle.handle()).unwrap();
+        body.set_next_kinematic_position(Isometry2::translation(x, platform.center.y()));
+    }
+}
+
+/// Unit normal of the contact between two axis aligned boxes, pointing from
+/// the second one towards the first one.
+///
+/// Along the axis of the smallest overlap, the one the boxes would be
+/// separated along.
+fn box_contact_normal(
+    center1: Vec2,
+    half_extents1: Vec2,
+    center2: Vec2,
+    half_extents2: Vec2,
+) -> Vec2 {
+    let offset = center1 - center2;
+    let overlap = half_extents1 + half_extents2 - Vec2::new(offset.x().abs(), offset.y().abs());
+    if overlap.y() < overlap.x() {
+        Vec2::new(0.0, offset.y().signum())
+    } else {
+        Vec2::new(offset.x().signum(), 0.0)
+    }
+}
+
+/// Keeps track of the platforms the player touches from the contact events,
+/// the player being grounded while one of them is below it, the contact normal
+/// pointing up enough.
+fn ground_contact_system(
+    events: Res<EventQueue>,
+    h_to_e: Res<ColliderHandleToEntity>,
+    bodies: Res<RigidBodySet>,
+    mut players: Query<With<Player, (Mut<Touching>, Mut<Grounded>, &RigidBodyHandleComponent)>>,
+    platforms: Query<(&Platform, &RigidBodyHandleComponent)>,
+) {
+    while let Ok(contact_event) = events.contact_events.pop() {
+        let (h1, h2, started) = match contact_event {
+            ContactEvent::Started(h1, h2) => (h1, h2, true),
+            ContactEvent::Stopped(h1, h2) => (h1, h2, false),
+        };
+        let (e1, e2) = match (h_to_e.get(h1), h_to_e.get(h2)) {
+            (Some(e1), Some(e2)) => (e1, e2),
+            _ => continue,
+        };
+        for &(player, platform) in &[(e1, e2), (e2, e1)] {
+            if platforms.get::<Platform>(platform).is_err() {
+                continue;
+            }
+            if let Ok(mut touching) = players.get_mut::<Touching>(player) {
+                if started {
+                    touching.0.insert(platform);
+                } else {
+                    touching.0.remove(&platform);
+                }
+            }
+        }
+    }
+    let position = |body_handle: &RigidBodyHandleComponent| {
+        let translation = bodies
+            .get(body_handle.handle())
+            .unwrap()
+            .position
+            .translation
+            .vector;
+        Vec2::new(translation.x, translation.y)
+    };
+    for (mut touching, mut grounded, body_handle) in &mut players.iter() {
+        let center = position(body_handle);
+        // Despawned platforms do not send a stopped event
+        touching
+            .0
+            .retain(|&platform| platforms.get::<Platform>(platform).is_ok());
+        let ground = touching.0.iter().copied().find(|&platform| {
+            let half_extents = platforms.get::<Platform>(platform).unwrap().half_extents;
+            let platform_center =
+                position(&platforms.get::<RigidBodyHandleComponent>(platform).unwrap());
+            let normal = box_contact_normal(
+                center,
+                Vec2::new(PLAYER_HALF_WIDTH, PLAYER_HALF_HEIGHT),
+                platform_center,
+                half_extents,
+            );
+            normal.y() >= MIN_GROUND_NORMAL_Y
+        });
+        grounded.ground = ground;
+        grounded.frames_since_contact = if ground.is_some() {
+            0
+        } else {
+            grounded.frames_since_contact.saturating_add(1)
+        };
+    }
+}
+
+/// Runs towards a target velocity with A and D, relative to the platform below,
+/// and jumps with Space or W, within the coyote time after leaving the ground.
+///
+/// The player rotation is locked, the box tipping over at each ledge otherwise.
+fn player_control_system(
+    time: Res<Time>,
+    input: Res<Input<KeyCode>>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut players: Query<With<Player, (Mut<Grounded>, &RigidBodyHandleComponent)>>,
+    moving_platforms: Query<&MovingPlatform>,
+) {
+    let mut direction = 0.0;
+    if input.pressed(KeyCode::A) {
+        direction -= 1.0;
+    }
+    if input.pressed(KeyCode::D) {
+        direction += 1.0;
+    }
+    let jump = input.just_pressed(KeyCode::Space) || input.just_pressed(KeyCode::W);
+    for (mut grounded, body_handle) in &mut players.iter() {
+        let mut body = bodies.get_mut(body_handle.handle()).unwrap();
+        body.wake_up(true);
+        body.angvel = 0.0;
+        if body.position.rotation.angle() != 0.0 {
+            let translation = body.position.translation.vector;
+            body.set_position(Isometry2::translation(translation.x, translation.y));
+        }
+        let carried = grounded
+            .ground
+            .and_then(|ground| moving_platforms.get::<MovingPlatform>(ground).ok())
+            .map_or(0.0, |platform| platform.velocity);
+        let target = direction * RUN_SPEED + carried;
+        let acceleration = if grounded.ground.is_some() {
+            GROUND_ACCELERATION
+        } else {
+            AIR_ACCELERATION
+        };
+        let max_change = acceleration * time.delta_seconds;
+        let change = (target - body.linvel.x).max(-max_change).min(max_change);
+        body.linvel.x += change;
+        if jump && grounded.can_jump() {
+            // The same jump height whether falling off a ledge or not
+            body.linvel.y = body.linvel.y.max(0.0);
+            let mass = body.mass();
+            body.apply_impulse(Vector2::new(0.0, JUMP_SPEED * mass));
+            // No coyote jump in the air after this one
+            grounded.frames_since_contact = u8::MAX;
+            grounded.ground = None;
+        }
+    }
+}
+
+/// Puts the player back at the start once fallen off the level.
+fn fall_system(
+    mut bodies: ResMut<RigidBodySet>,
+    mut players: Query<With<Player, &RigidBodyHandleComponent>>,
+) {
+    for body_handle in &mut players.iter() {
+        let mut body = bodies.get_mut(body_handle.handle()).unwrap();
+        if body.position.translation.vector.y < FALL_LIMIT {
+            body.set_position(Isometry2::translation(PLAYER_START.0, PLAYER_START.1));
+            body.linvel = Vector2::zeros();
+        }
+    }
+}
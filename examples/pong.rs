@@ -0,0 +1,339 @@
+#![allow(clippy::too_many_arguments)]
+
+use bevy::{
+    prelude::*,
+    render::{
+        camera::{OrthographicProjection, WindowOrigin},
+        pass::ClearColor,
+    },
+};
+use bevy_showcase::{
+    arena::Arena,
+    collide::{add_cuboid, add_sphere, Velocity},
+    response::{ContactEvent, Immovable, NCollideResponsePlugin},
+};
+use ncollide2d::{na::Vector2, pipeline::CollisionGroups, world::CollisionWorld};
+use rand::prelude::*;
+
+const WINDOW_WIDTH: u32 = 1280;
+const WINDOW_HEIGHT: u32 = 800;
+const ARENA_WIDTH: f32 = WINDOW_WIDTH as f32;
+const ARENA_HEIGHT: f32 = WINDOW_HEIGHT as f32;
+const WALL_HALF_THICKNESS: f32 = 10.0;
+const PADDLE_HALF_WIDTH: f32 = 10.0;
+const PADDLE_HALF_HEIGHT: f32 = 60.0;
+/// Distance of the paddle centers from the left and right borders
+const PADDLE_INSET: f32 = 40.0;
+/// Paddle speed in px/s
+const PADDLE_SPEED: f32 = 600.0;
+const BALL_RADIUS: f32 = 12.0;
+/// Ball speed in px/s when served
+const BALL_SPEED: f32 = 450.0;
+/// Ball speed factor at each paddle hit
+const BALL_SPEEDUP: f32 = 1.05;
+const BALL_MAX_SPEED: f32 = 1100.0;
+/// Angle in rad from the horizontal of a ball leaving a paddle end
+const MAX_BOUNCE_ANGLE: f32 = 1.0;
+/// Largest angle in rad from the horizontal of a served ball
+const MAX_SERVE_ANGLE: f32 = 0.4;
+/// Delay in s before the ball is served after a point
+const SERVE_DELAY: f32 = 1.0;
+/// Collision group of the ball, the paddles and walls being in the other one
+const BALL_GROUP: usize = 1;
+const OBSTACLE_GROUP: usize = 0;
+
+fn main() {
+    App::build()
+        .add_resource(WindowDescriptor {
+            title: "Pong".to_string(),
+            width: WINDOW_WIDTH,
+            height: WINDOW_HEIGHT,
+            ..Default::default()
+        })
+        .add_resource(ClearColor(Color::rgb(0.01, 0.01, 0.03)))
+        .add_resource(Arena::new(ARENA_WIDTH, ARENA_HEIGHT))
+        .init_resource::<Score>()
+        .add_resource(Serve {
+            timer: Timer::from_seconds(SERVE_DELAY, false),
+            towards: Side::Left,
+        })
+        .add_default_plugins()
+        // Moves the ball and the paddles, bouncing the ball off the paddles
+        // and walls
+        .add_plugin(NCollideResponsePlugin::default())
+        .add_startup_system(setup.system())
+        .add_system(paddle_system.system())
+        .add_system(serve_system.system())
+        .add_system(point_system.system())
+        .add_system(score_text_system.system())
+        // After the response, sending the contacts
+        .add_system_to_stage(stage::POST_UPDATE, paddle_hit_system.system())
+        .run();
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Side {
+    Left,
+    Right,
+}
+
+impl Side {
+    /// Sign of the x direction towards this side
+    fn sign(self) -> f32 {
+        match self {
+            Side::Left => -1.0,
+            Side::Right => 1.0,
+        }
+    }
+
+    fn opponent(self) -> Side {
+        match self {
+            Side::Left => Side::Right,
+            Side::Right => Side::Left,
+        }
+    }
+}
+
+struct Paddle {
+    up: KeyCode,
+    down: KeyCode,
+}
+
+struct Ball;
+
+struct ScoreText;
+
+/// Points of the left and right players
+#[derive(Default)]
+struct Score([u32; 2]);
+
+/// The ball waits at the center until `timer` finishes, then leaves `towards`
+/// a side.
+struct Serve {
+    timer: Timer,
+    towards: Side,
+}
+
+/// Spawns the paddles, the top and bottom walls and the ball, each with its
+/// collision object, the paddles and walls being `Immovable`.
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut world: ResMut<CollisionWorld<f32, Entity>>,
+) {
+    let mut obstacle_groups = CollisionGroups::new();
+    obstacle_groups.set_membership(&[OBSTACLE_GROUP]);
+    obstacle_groups.set_whitelist(&[BALL_GROUP]);
+    let mut ball_groups = CollisionGroups::new();
+    ball_groups.set_membership(&[BALL_GROUP]);
+    ball_groups.set_whitelist(&[OBSTACLE_GROUP]);
+    let font_handle = asset_server.load("assets/DejaVuSansMono.ttf").unwrap();
+    commands
+        .spawn(Camera2dComponents {
+            orthographic_projection: OrthographicProjection {
+                window_origin: WindowOrigin::BottomLeft,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .spawn(UiCameraComponents::default())
+        .spawn(NodeComponents {
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                position: Rect {
+                    top: Val::Px(20.0),
+                    ..Default::default()
+                },
+                justify_content: JustifyContent::Center,
+                ..Default::default()
+            },
+            material: materials.add(Color::NONE.into()),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(TextComponents {
+                    text: Text {
+                        value: String::new(),
+                        font: font_handle,
+                        style: TextStyle {
+                            font_size: 48.0,
+                            color: Color::WHITE,
+                        },
+                    },
+                    ..Default::default()
+                })
+                .with(ScoreText);
+        });
+
+    let white = materials.add(Color::rgb(0.9, 0.9, 0.95).into());
+    let mut add_obstacle = |commands: &mut Commands, center: Vec2, half_extents: Vec2| {
+        commands
+            .spawn(SpriteComponents {
+                sprite: Sprite::new(half_extents * 2.0),
+                material: white,
+                transform: Transform::from_translation(center.extend(0.0)),
+                ..Default::default()
+            })
+            .with(Immovable);
+        let entity = commands.current_entity().unwrap();
+        let handle = add_cuboid(&mut world, obstacle_groups, entity, center, half_extents);
+        commands.insert_one(entity, handle);
+        entity
+    };
+    for &y in &[-WALL_HALF_THICKNESS, ARENA_HEIGHT + WALL_HALF_THICKNESS] {
+        add_obstacle(
+            &mut commands,
+            Vec2::new(ARENA_WIDTH / 2.0, y),
+            Vec2::new(ARENA_WIDTH / 2.0, WALL_HALF_THICKNESS),
+        );
+    }
+    let paddles = [
+        (PADDLE_INSET, KeyCode::W, KeyCode::S),
+        (ARENA_WIDTH - PADDLE_INSET, KeyCode::Up, KeyCode::Down),
+    ];
+    for &(x, up, down) in &paddles {
+        let entity = add_obstacle(
+            &mut commands,
+            Vec2::new(x, ARENA_HEIGHT / 2.0),
+            Vec2::new(PADDLE_HALF_WIDTH, PADDLE_HALF_HEIGHT),
+        );
+        // Moved along its velocity by the response, unaffected by the ball
+        commands.insert(entity, (Paddle { up, down }, Velocity(Vector2::zeros())));
+    }
+
+    let center = Vec2::new(ARENA_WIDTH / 2.0, ARENA_HEIGHT / 2.0);
+    let texture_handle = asset_server
+        .load("assets/sprite_sphere_256x256.png")
+        .unwrap();
+    commands
+        .spawn(SpriteComponents {
+            transform: Transform::from_translation(center.extend(1.0))
+                .with_scale(BALL_RADIUS * 2.0 / 256.0),
+            material: materials.add(texture_handle.into()),
+            ..Default::default()
+        })
+        .with(Ball)
+        .with(Velocity(Vector2::zeros()));
+    let entity = commands.current_entity().unwrap();
+    let handle = add_sphere(&mut world, ball_groups, entity, center, BALL_RADIUS);
+    commands.insert_one(entity, handle);
+}
+
+/// Moves the paddles up and down with their keys, within the walls.
+fn paddle_system(
+    input: Res<Input<KeyCode>>,
+    mut paddles: Query<(&Paddle, Mut<Transform>, Mut<Velocity>)>,
+) {
+    let (bottom, top) = (PADDLE_HALF_HEIGHT, ARENA_HEIGHT - PADDLE_HALF_HEIGHT);
+    for (paddle, mut transform, mut velocity) in &mut paddles.iter() {
+        let mut direction = 0.0;
+        if input.pressed(paddle.up) {
+            direction += 1.0;
+        }
+        if input.pressed(paddle.down) {
+            direction -= 1.0;
+        }
+        let y = transform.translation().y();
+        if (direction > 0.0 && y >= top) || (direction < 0.0 && y <= bottom) {
+            direction = 0.0;
+        }
+        velocity.0 = Vector2::new(0.0, direction * PADDLE_SPEED);
+        if y < bottom || y > top {
+            transform.translation_mut().set_y(y.max(bottom).min(top));
+        }
+    }
+}
+
+/// Sends the ball waiting at the center once the serve delay is over, at a
+/// random angle.
+fn serve_system(
+    time: Res<Time>,
+    mut serve: ResMut<Serve>,
+    mut balls: Query<With<Ball, Mut<Velocity>>>,
+) {
+    serve.timer.tick(time.delta_seconds);
+    if !serve.timer.just_finished {
+        return;
+    }
+    let angle = thread_rng().gen_range(-MAX_SERVE_ANGLE, MAX_SERVE_ANGLE);
+    let direction = Vector2::new(serve.towards.sign() * angle.cos(), angle.sin());
+    for mut velocity in &mut balls.iter() {
+        velocity.0 = direction * BALL_SPEED;
+    }
+}
+
+/// Sends the ball bouncing off a paddle at an angle depending on where along
+/// the paddle it hit, steeper towards the ends, and a bit faster each time.
+///
+/// The response already bounced it off the walls and paddles.
+fn paddle_hit_system(
+    mut reader: Local<EventReader<ContactEvent>>,
+    contacts: Res<Events<ContactEvent>>,
+    balls: Query<With<Ball, (&Transform, Mut<Velocity>)>>,
+    paddles: Query<With<Paddle, &Transform>>,
+) {
+    for contact in reader.iter(&contacts) {
+        // Still overlapping after the bounce of a previous step
+        if contact.impulse <= 0.0 {
+            continue;
+        }
+        let (ball, paddle) = if balls.get::<Transform>(contact.entity1).is_ok() {
+            (contact.entity1, contact.entity2)
+        } else {
+            (contact.entity2, contact.entity1)
+        };
+        let (ball_transform, paddle_transform) = match (
+            balls.get::<Transform>(ball),
+            paddles.get::<Transform>(paddle),
+        ) {
+            (Ok(ball_transform), Ok(paddle_transform)) => (ball_transform, paddle_transform),
+            _ => continue,
+        };
+        let (ball_position, paddle_position) =
+            (ball_transform.translation(), paddle_transform.translation());
+        let offset = (ball_position.y() - paddle_position.y()) / (PADDLE_HALF_HEIGHT + BALL_RADIUS);
+        let angle = offset.max(-1.0).min(1.0) * MAX_BOUNCE_ANGLE;
+        let sign = (ball_position.x() - paddle_position.x()).signum();
+        let mut velocity = balls.get_mut::<Velocity>(ball).unwrap();
+        let speed = (velocity.0.norm() * BALL_SPEEDUP).min(BALL_MAX_SPEED);
+        velocity.0 = Vector2::new(sign * angle.cos(), angle.sin()) * speed;
+    }
+}
+
+/// Scores a point once the ball leaves through a side, then puts the ball back
+/// at the center, to be served towards the player who lost the point.
+fn point_system(
+    mut score: ResMut<Score>,
+    mut serve: ResMut<Serve>,
+    mut balls: Query<With<Ball, (Mut<Transform>, Mut<Velocity>)>>,
+) {
+    for (mut transform, mut velocity) in &mut balls.iter() {
+        let x = transform.translation().x();
+        let scorer = if x < -BALL_RADIUS {
+            Side::Right
+        } else if x > ARENA_WIDTH + BALL_RADIUS {
+            Side::Left
+        } else {
+            continue;
+        };
+        score.0[scorer as usize] += 1;
+        serve.towards = scorer.opponent();
+        serve.timer.reset();
+        velocity.0 = Vector2::zeros();
+        let center = Vec2::new(ARENA_WIDTH / 2.0, ARENA_HEIGHT / 2.0);
+        // The response moves its collision object along
+        transform.set_translation(center.extend(1.0));
+    }
+}
+
+fn score_text_system(score: Res<Score>, mut texts: Query<With<ScoreText, Mut<Text>>>) {
+    let value = format!("{}   {}", score.0[0], score.0[1]);
+    for mut text in &mut texts.iter() {
+        if text.value != value {
+            text.value = value.clone();
+        }
+    }
+}
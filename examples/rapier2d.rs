@@ -1,141 +1,1870 @@
+#![allow(clippy::too_many_arguments)]
+
 use bevy::{
     prelude::*,
-    render::{
-        camera::{OrthographicProjection, WindowOrigin},
-        pass::ClearColor,
-    },
+    render::camera::{OrthographicProjection, WindowOrigin},
 };
 use bevy_rapier2d::{
-    na::Vector2,
-    physics::{EventQueue, RapierConfiguration, RapierPhysicsPlugin, RigidBodyHandleComponent},
+    na::{DVector, Vector2},
+    physics::{
+        ColliderHandleComponent, EventQueue, RapierConfiguration, RapierPhysicsPlugin,
+        RigidBodyHandleComponent,
+    },
     rapier::{
-        dynamics::{RigidBodyBuilder, RigidBodySet},
-        geometry::ColliderBuilder,
+        dynamics::{
+            BallJoint, JointHandle, JointParams, JointSet, MassProperties, RigidBodyBuilder,
+            RigidBodyHandle, RigidBodySet,
+        },
+        geometry::{ColliderBuilder, ColliderSet, Proximity},
+        math::Point,
+        pipeline::PhysicsPipeline,
     },
 };
+use bevy_showcase::{
+    arena::{Arena, ArenaOrigin},
+    balls::{ball_builders, jittered_grid, wrap_system},
+    camera_controller::{CameraController, CameraControllerPlugin},
+    capture::CapturePlugin,
+    cleanup::RapierCleanupPlugin,
+    damping::{damping_system, Damping},
+    debug_hud::rapier_body_count_system,
+    game_assets::GameAssets,
+    handles::{ColliderHandleToEntity, HandleToEntityPlugin},
+    inspector::{Inspector, InspectorPlugin},
+    mouse::{window_to_world, CursorCamera, MousePosition},
+    platform,
+    rapier_scene::spawn_grid_ball,
+    settings::Settings,
+    showcase::{SeededRng, ShowcasePlugin},
+    spawn::{capped_requests, MaxBodies, SpawnRequest},
+    z_order::{topmost, ZOrderPlugin, ZOrderPolicy, ZOrdered},
+};
 use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+};
 
 const WINDOW_WIDTH: u32 = 1280;
 const WINDOW_HEIGHT: u32 = 800;
+/// File the scene is saved to with F6, and loaded from with F7, only kept in
+/// memory in the browser
+const SCENE_FILE: &str = "rapier2d_scene.ron";
+const RNG_SEED: u64 = 214;
+const MIN_ZOOM: f32 = 0.5;
+const MAX_ZOOM: f32 = 3.0;
+/// Gravity in px/s² when enabled
+const GRAVITY: f32 = -500.0;
+const TERRAIN_SAMPLES: usize = 64;
+const BENCHMARK_COLUMNS: usize = 40;
+const BENCHMARK_ROWS: usize = 25;
+/// Benchmark duration in s
+const BENCHMARK_DURATION: f32 = 30.0;
+const STRESS_COLUMNS: usize = 25;
+const STRESS_ROWS: usize = 16;
+/// Default cap on the number of bodies, spawn requests are dropped above it
+const MAX_BODIES: usize = 4000;
+/// Wind change for each press of an arrow key, in px/s
+const WIND_STEP: f32 = 20.0;
+/// Force per unit of wind speed and of ball radius
+const WIND_DRAG: f32 = 40.0;
+/// Wind speed below which sleeping bodies are left asleep
+const WIND_WAKE_THRESHOLD: f32 = 30.0;
+/// Damping given to spawned balls, when enabled
+const BALL_DAMPING: Damping = Damping {
+    linear: 0.7,
+    angular: 0.5,
+};
+/// Distance in px under which a magnet attracts dynamic bodies
+const MAGNET_RANGE: f32 = 250.0;
+/// Acceleration in px/s² given to a body at 1px of a magnet, decreasing in 1/d
+const MAGNET_STRENGTH: f32 = 30000.0;
+const MAGNET_RADIUS: f32 = 16.0;
+const CHAIN_LINKS: usize = 12;
+const CHAIN_LINK_RADIUS: f32 = 8.0;
+/// Impulse, applied by a chain joint during one step, above which the joint snaps
+const CHAIN_BREAK_IMPULSE: f32 = 150_000.0;
+/// Number of frames a snapped link stays red
+const SNAP_FLASH_FRAMES: u32 = 10;
+const ZONE_WIDTH: f32 = 200.0;
+const ZONE_HEIGHT: f32 = 400.0;
+/// Period in s after which the zone holding the most balls flashes
+const ZONE_SCORE_PERIOD: f32 = 5.0;
+const ZONE_FLASH_DURATION: f32 = 0.5;
 
 fn main() {
-    App::build()
-        .init_resource::<MousePosition>()
-        .add_resource(WindowDescriptor {
-            title: "Rapier2D Bevy showcase".to_string(),
-            width: WINDOW_WIDTH,
-            height: WINDOW_HEIGHT,
-            ..Default::default()
-        })
-        .add_resource(ClearColor(Color::rgb(0.01, 0.01, 0.03)))
+    let mut app = App::build();
+    app.add_plugin(
+        ShowcasePlugin::new("Rapier2D Bevy showcase")
+            .with_window(WINDOW_WIDTH, WINDOW_HEIGHT)
+            .with_clear_color(Color::rgb(0.01, 0.01, 0.03))
+            .with_seed(RNG_SEED),
+    );
+    // Rendering through WebGL2 in the browser, built from the manifest of web/
+    #[cfg(target_arch = "wasm32")]
+    app.add_plugin(bevy_webgl2::WebGL2Plugin);
+    app.add_resource(MaxBodies(MAX_BODIES))
+        .add_event::<SpawnRequest<SpawnKind>>()
+        .init_resource::<ChainJoints>()
+        .init_resource::<Benchmark>()
+        .add_resource(Wind(Vector2::zeros()))
+        .init_resource::<Spawner>()
         .add_plugin(RapierPhysicsPlugin)
-        .add_default_plugins()
+        .add_plugin(RapierCleanupPlugin)
+        .add_plugin(HandleToEntityPlugin)
+        .add_plugin(InspectorPlugin::default())
+        .add_plugin(CapturePlugin::default())
+        .add_plugin(ZOrderPlugin)
+        .add_resource(ZOrderPolicy::Newest)
+        .add_system(rapier_body_count_system.system())
         .add_resource(RapierConfiguration {
             gravity: Vector2::zeros(),
             ..Default::default()
         })
         .add_startup_system(setup.system())
-        .add_system(mouse_position_system.system())
-        .add_system(spawn_sphere_system.system())
-        .add_system(position_system.system())
+        .add_startup_system(setup_settings.system())
+        .add_startup_system(spawn_zones.system())
+        .add_startup_system(spawn_preset_preview.system())
+        .add_system(preset_cycle_system.system())
+        .add_system(spawn_system.system())
+        .add_system_to_stage(stage::POST_UPDATE, spawner_system.system())
+        .add_system(preset_preview_system.system())
+        .add_startup_system(spawn_trajectory_preview.system())
+        .add_system(trajectory_preview_system.system())
+        .add_system(remove_magnet_system.system())
+        .add_system(magnet_system.system())
+        .add_system(spawn_chain_system.system())
+        .add_system(snap_flash_system.system())
+        .add_system(zone_score_system.system())
+        .add_system(gravity_toggle_system.system())
+        .add_system(body_count_setting_system.system())
+        .add_system(terrain_system.system())
+        .add_system(clear_all_system.system())
+        .add_system(freeze_system.system())
+        .add_system(save_scene_system.system())
+        .add_system(load_scene_system.system())
+        .add_system(start_benchmark_system.system())
+        .add_system(stress_grid_system.system())
+        .add_startup_system(spawn_wind_indicator.system())
+        .add_system(wind_input_system.system())
+        .add_system(wind_system.system())
+        .add_system(wind_indicator_system.system())
+        .add_system(ball_damping_toggle_system.system())
+        .add_system(damping_system.system())
+        .add_system(origin_toggle_system.system())
+        .add_plugin(CameraControllerPlugin)
+        .add_system(camera_home_system.system())
+        .add_system(wrap_system.system())
         .add_system_to_stage(stage::POST_UPDATE, collision_system.system())
+        .add_system_to_stage(stage::POST_UPDATE, break_joint_system.system())
+        .add_system_to_stage(stage::POST_UPDATE, zone_membership_system.system())
+        .add_system_to_stage(stage::POST_UPDATE, benchmark_system.system())
         .run();
 }
 
 fn setup(mut commands: Commands) {
-    commands.spawn(Camera2dComponents {
-        orthographic_projection: OrthographicProjection {
-            window_origin: WindowOrigin::BottomLeft,
+    commands
+        .spawn(Camera2dComponents {
+            orthographic_projection: OrthographicProjection {
+                window_origin: WindowOrigin::BottomLeft,
+                // Keeps the sprites within the far plane at the smallest scale
+                far: 1000.0 / MIN_ZOOM,
+                ..Default::default()
+            },
             ..Default::default()
-        },
-        ..Default::default()
+        })
+        .with(CursorCamera)
+        .with(CameraController::new(Vec2::zero(), 1.0).with_zoom_range(MIN_ZOOM, MAX_ZOOM))
+        .spawn(UiCameraComponents::default());
+}
+
+/// Places the home view of the camera on the arena, and the camera back to
+/// it, at startup and when the arena origin changes.
+///
+/// The projection keeps its bottom left window origin whatever the arena
+/// origin, only the camera transform follows the arena.
+fn camera_home_system(
+    mut origin: Local<Option<ArenaOrigin>>,
+    arena: Res<Arena>,
+    mut cameras: Query<(Mut<CameraController>, Mut<Transform>)>,
+) {
+    if *origin == Some(arena.origin) {
+        return;
+    }
+    *origin = Some(arena.origin);
+    let window = Vec2::new(WINDOW_WIDTH as f32, WINDOW_HEIGHT as f32);
+    let corner = arena.center() - window / 2.0;
+    for (mut controller, mut transform) in &mut cameras.iter() {
+        controller.set_home(corner, 1.0);
+        controller.reset(&mut transform);
+    }
+}
+
+/// Root sprites without a body
+type LooseSprite<'a> =
+    Without<RigidBodyHandleComponent, Without<Parent, (&'a Sprite, Mut<'a, Transform>)>>;
+
+/// Switches the world origin between the arena bottom left corner and its
+/// center, moving everything in the scene to the new coordinates.
+fn origin_toggle_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut arena: ResMut<Arena>,
+    mut spawner: ResMut<Spawner>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut sprites: Query<LooseSprite>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::O) {
+        return;
+    }
+    let origin = match arena.origin {
+        ArenaOrigin::BottomLeft => ArenaOrigin::Center,
+        ArenaOrigin::Center => ArenaOrigin::BottomLeft,
+    };
+    let offset = arena.offset_to(origin);
+    arena.origin = origin;
+    for (_, mut body) in bodies.iter_mut() {
+        let mut position = body.position;
+        position.translation.vector += Vector2::new(offset.x(), offset.y());
+        body.set_position(position);
+        body.wake_up(true);
+    }
+    // Root sprites without a body, like the terrain. Children follow their
+    // parent, and the camera is placed by `camera_home_system`.
+    for (_sprite, mut transform) in &mut sprites.iter() {
+        transform.translate(offset.extend(0.0));
+    }
+    if let Some(start) = spawner.drag.as_mut() {
+        *start += offset;
+    }
+    println!("World origin at the arena {:?}", origin);
+}
+
+// Proximity events are consumed by the zone_membership_system
+fn collision_system(events: Res<EventQueue>) {
+    while let Ok(contact_event) = events.contact_events.pop() {
+        println!("Contact event {:?}", contact_event);
+    }
+}
+
+/// What a click spawns, cycled with Tab and Shift-Tab
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SpawnPreset {
+    Ball,
+    Box,
+    Capsule,
+    Compound,
+    Pellet,
+    Magnet,
+}
+
+const SPAWN_PRESETS: [SpawnPreset; 6] = [
+    SpawnPreset::Ball,
+    SpawnPreset::Box,
+    SpawnPreset::Capsule,
+    SpawnPreset::Compound,
+    SpawnPreset::Pellet,
+    SpawnPreset::Magnet,
+];
+
+/// Everything a preset needs to spawn its entity
+struct SpawnContext<'a> {
+    commands: &'a mut Commands,
+    materials: &'a mut Assets<ColorMaterial>,
+    assets: &'a mut GameAssets,
+    bodies: &'a mut RigidBodySet,
+    colliders: &'a mut ColliderSet,
+    restitution: f32,
+    damping: Damping,
+}
+
+impl Default for SpawnPreset {
+    fn default() -> Self {
+        SpawnPreset::Ball
+    }
+}
+
+impl SpawnPreset {
+    fn next(self, step: isize) -> Self {
+        let index = SPAWN_PRESETS.iter().position(|p| *p == self).unwrap() as isize;
+        let count = SPAWN_PRESETS.len() as isize;
+        SPAWN_PRESETS[((index + step + count) % count) as usize]
+    }
+
+    /// Bounding radius of the spawned shape
+    fn radius(self) -> f32 {
+        match self {
+            SpawnPreset::Ball => 128.0 * 0.2,
+            SpawnPreset::Box => 20.0,
+            SpawnPreset::Capsule => 12.0,
+            SpawnPreset::Compound => 14.0,
+            SpawnPreset::Pellet => 5.0,
+            SpawnPreset::Magnet => MAGNET_RADIUS,
+        }
+    }
+
+    fn restitution(self) -> f32 {
+        match self {
+            SpawnPreset::Pellet => 0.9,
+            SpawnPreset::Box | SpawnPreset::Capsule => 0.3,
+            _ => 0.0,
+        }
+    }
+
+    /// Maximum speed, along each axis, of a body spawned by a simple click
+    fn speed(self) -> f32 {
+        match self {
+            SpawnPreset::Pellet => WINDOW_WIDTH as f32 / 2.0,
+            SpawnPreset::Magnet => 0.0,
+            _ => WINDOW_WIDTH as f32 / 4.0,
+        }
+    }
+
+    /// Size of the spawned sprite, and whether it is round
+    fn preview(self) -> (Vec2, bool) {
+        let r = self.radius();
+        match self {
+            SpawnPreset::Box => (Vec2::new(2.0 * r, 2.0 * r), false),
+            SpawnPreset::Capsule => (Vec2::new(2.0 * r, 4.0 * r), false),
+            SpawnPreset::Compound => (Vec2::new(4.0 * r, 2.0 * r), false),
+            _ => (Vec2::new(2.0 * r, 2.0 * r), true),
+        }
+    }
+
+    /// Spawns the preset at `position`, the magnet drawn above all the others
+    fn spawn(self, ctx: &mut SpawnContext, position: Vec2, velocity: Vec2) -> Entity {
+        let (x, y) = (position.x(), position.y());
+        let r = self.radius();
+        let body = RigidBodyBuilder::new_dynamic()
+            .translation(x, y)
+            .linvel(velocity.x(), velocity.y());
+        // The z of the others is set by the z order system
+        let sprite = |size: Vec2, material: Handle<ColorMaterial>, z: f32| SpriteComponents {
+            transform: Transform::from_translation(Vec3::new(x, y, z)),
+            material,
+            sprite: Sprite::new(size),
+            ..Default::default()
+        };
+        let sphere_material =
+            |ctx: &mut SpawnContext, color: Color| ctx.assets.sphere_tinted(color, ctx.materials);
+        match self {
+            SpawnPreset::Ball | SpawnPreset::Pellet => {
+                let color = if self == SpawnPreset::Ball {
+                    Color::WHITE
+                } else {
+                    Color::rgb(1.0, 0.9, 0.4)
+                };
+                let material = sphere_material(ctx, color);
+                let (body, collider) = ball_builders(position, velocity, r, ctx.restitution);
+                ctx.commands
+                    .spawn(sprite(Vec2::new(2.0 * r, 2.0 * r), material, 0.0))
+                    .with(Ball)
+                    .with(body)
+                    .with(collider);
+            }
+            SpawnPreset::Box => {
+                let material = ctx
+                    .assets
+                    .material_for(Color::rgb(0.4, 0.6, 0.9), ctx.materials);
+                let collider = ColliderBuilder::cuboid(r, r).restitution(ctx.restitution);
+                ctx.commands
+                    .spawn(sprite(Vec2::new(2.0 * r, 2.0 * r), material, 0.0))
+                    .with(body)
+                    .with(collider);
+            }
+            SpawnPreset::Capsule => {
+                let material = ctx
+                    .assets
+                    .material_for(Color::rgb(0.5, 0.9, 0.5), ctx.materials);
+                let end_material = sphere_material(ctx, Color::rgb(0.5, 0.9, 0.5));
+                let collider = ColliderBuilder::capsule_y(r, r).restitution(ctx.restitution);
+                ctx.commands
+                    .spawn(sprite(Vec2::new(2.0 * r, 2.0 * r), material, 0.0))
+                    .with(body)
+                    .with(collider)
+                    .with_children(|parent| {
+                        for &dy in &[-r, r] {
+                            parent.spawn(SpriteComponents {
+                                transform: Transform::from_translation(Vec3::new(0.0, dy, 0.0)),
+                                material: end_material,
+                                sprite: Sprite::new(Vec2::new(2.0 * r, 2.0 * r)),
+                                ..Default::default()
+                            });
+                        }
+                    });
+            }
+            SpawnPreset::Compound => {
+                // The plugin only builds single collider bodies, so this one is
+                // inserted directly in the sets.
+                let handle = ctx.bodies.insert(body.build());
+                let mut first_collider = None;
+                for &dx in &[-r, r] {
+                    let collider = ColliderBuilder::ball(r)
+                        .translation(dx, 0.0)
+                        .restitution(ctx.restitution)
+                        .build();
+                    let collider = ctx.colliders.insert(collider, handle, ctx.bodies);
+                    first_collider.get_or_insert(collider);
+                }
+                let material = sphere_material(ctx, Color::rgb(0.9, 0.5, 0.9));
+                ctx.commands
+                    .spawn(sprite(Vec2::zero(), material, 0.0))
+                    .with(RigidBodyHandleComponent::from(handle))
+                    .with(ColliderHandleComponent::from(first_collider.unwrap()))
+                    .with_children(|parent| {
+                        for &dx in &[-r, r] {
+                            parent.spawn(SpriteComponents {
+                                transform: Transform::from_translation(Vec3::new(dx, 0.0, 0.0)),
+                                material,
+                                sprite: Sprite::new(Vec2::new(2.0 * r, 2.0 * r)),
+                                ..Default::default()
+                            });
+                        }
+                    });
+            }
+            SpawnPreset::Magnet => {
+                let material = sphere_material(ctx, Color::rgb(1.0, 0.2, 0.2));
+                let body = RigidBodyBuilder::new_static().translation(x, y);
+                let collider = ColliderBuilder::ball(r);
+                ctx.commands
+                    .spawn(sprite(Vec2::new(2.0 * r, 2.0 * r), material, 1.0))
+                    .with(Magnet)
+                    .with(body)
+                    .with(collider);
+                return ctx.commands.current_entity().unwrap();
+            }
+        }
+        let entity = ctx.commands.current_entity().unwrap();
+        ctx.commands.insert(entity, spawned_components(ctx.damping));
+        entity
+    }
+}
+
+/// Components of the bodies spawned in the sandbox, cleared together and drawn
+/// in the z order of the `ZOrderPolicy`
+fn spawned_components(damping: Damping) -> (Spawned, Damping, ZOrdered) {
+    (Spawned, damping, ZOrdered::default())
+}
+
+/// Active preset, and start position of an in progress drag-launch
+#[derive(Default)]
+struct Spawner {
+    preset: SpawnPreset,
+    drag: Option<Vec2>,
+    /// Whether newly spawned balls are damped
+    damped: bool,
+    /// Restitution of the spawned bodies, reset to the one of the preset when
+    /// it changes
+    restitution: f32,
+}
+
+impl Spawner {
+    /// Damping of the newly spawned balls
+    fn damping(&self) -> Damping {
+        if self.damped {
+            BALL_DAMPING
+        } else {
+            Damping::NONE
+        }
+    }
+}
+
+/// Velocity, in px/s, given per px of drag
+const LAUNCH_FACTOR: f32 = 3.0;
+/// Drags shorter than this, in px, are simple clicks
+const LAUNCH_MIN_DRAG: f32 = 5.0;
+/// Dots of the trajectory preview of a drag-launch
+const TRAJECTORY_DOTS: usize = 20;
+/// Duration in s of the flight shown by the trajectory preview
+const TRAJECTORY_DURATION: f32 = 2.0;
+
+/// Requests the active preset on click, or launches it when the mouse is
+/// dragged before release, slingshot style. Escape cancels the drag.
+fn spawn_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_button_input: Res<Input<MouseButton>>,
+    mouse_position: Res<MousePosition>,
+    inspector: Res<Inspector>,
+    arena: Res<Arena>,
+    mut spawner: ResMut<Spawner>,
+    mut rng: ResMut<SeededRng>,
+    mut requests: ResMut<Events<SpawnRequest<SpawnKind>>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        spawner.drag = None;
+    }
+    let cursor = mouse_position.position_in_arena(&arena);
+    // Ctrl + click freezes bodies instead
+    let ctrl =
+        keyboard_input.pressed(KeyCode::LControl) || keyboard_input.pressed(KeyCode::RControl);
+    // Clicks pick the entities to inspect instead
+    let click = mouse_button_input.just_pressed(MouseButton::Left) && !ctrl && !inspector.active();
+    if let (true, Some(cursor)) = (click, cursor) {
+        // M + click always spawns a magnet
+        let preset = if keyboard_input.pressed(KeyCode::M) {
+            SpawnPreset::Magnet
+        } else {
+            spawner.preset
+        };
+        if preset == SpawnPreset::Magnet {
+            let position = arena.clamp(cursor, preset.radius());
+            requests.send(SpawnRequest::new(
+                SpawnKind::Preset(preset),
+                position,
+                Vec2::zero(),
+            ));
+        } else {
+            spawner.drag = Some(cursor);
+        }
+    }
+    if !mouse_button_input.just_released(MouseButton::Left) {
+        return;
+    }
+    if let Some(start) = spawner.drag.take() {
+        let preset = spawner.preset;
+        let rng = &mut rng.0;
+        // Released out of the window, the last known position is still the
+        // best estimate of the pull.
+        let pull = start - mouse_position.world_position().unwrap_or(start);
+        let velocity = if pull.length() < LAUNCH_MIN_DRAG {
+            let speed = preset.speed();
+            Vec2::new(rng.gen_range(-speed, speed), rng.gen_range(-speed, speed))
+        } else {
+            pull * LAUNCH_FACTOR
+        };
+        let position = arena.clamp(start, preset.radius());
+        requests.send(SpawnRequest::new(
+            SpawnKind::Preset(preset),
+            position,
+            velocity,
+        ));
+    }
+}
+
+/// What the spawn requests of the sandbox create
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SpawnKind {
+    Preset(SpawnPreset),
+    /// A small ball of the benchmark and stress grids
+    GridBall,
+}
+
+/// Spawns the requested bodies, in the order of the requests, up to the
+/// `MaxBodies` cap.
+fn spawner_system(
+    mut commands: Commands,
+    mut reader: Local<EventReader<SpawnRequest<SpawnKind>>>,
+    requests: Res<Events<SpawnRequest<SpawnKind>>>,
+    max_bodies: Res<MaxBodies>,
+    spawner: Res<Spawner>,
+    mut assets: ResMut<GameAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut colliders: ResMut<ColliderSet>,
+) {
+    let alive = bodies.len();
+    let batch = capped_requests(&mut reader, &requests, alive, &max_bodies);
+    // The grid balls are inserted directly in the sets, so that the timing
+    // covers their creation by rapier. There is no clock within a frame in the
+    // browser, where `Instant` panics.
+    #[cfg(not(target_arch = "wasm32"))]
+    let start = std::time::Instant::now();
+    for request in &batch {
+        let (position, velocity) = (request.position, request.velocity);
+        match request.kind {
+            SpawnKind::Preset(preset) => {
+                let mut ctx = SpawnContext {
+                    commands: &mut commands,
+                    materials: &mut materials,
+                    assets: &mut assets,
+                    bodies: &mut bodies,
+                    colliders: &mut colliders,
+                    restitution: spawner.restitution,
+                    damping: spawner.damping(),
+                };
+                preset.spawn(&mut ctx, position, velocity);
+            }
+            SpawnKind::GridBall => {
+                spawn_grid_ball(
+                    &mut commands,
+                    &mut bodies,
+                    &mut colliders,
+                    assets.sphere_material,
+                    position,
+                    velocity,
+                )
+                .with(Ball)
+                .with(Spawned);
+            }
+        }
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let grid_balls = batch
+            .iter()
+            .filter(|request| request.kind == SpawnKind::GridBall)
+            .count();
+        if grid_balls > 0 {
+            println!(
+                "Grid of {} balls built in {:.2} ms",
+                grid_balls,
+                start.elapsed().as_secs_f64() * 1000.0
+            );
+        }
+    }
+}
+
+fn preset_cycle_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut spawner: ResMut<Spawner>,
+    mut settings: ResMut<Settings>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Tab) {
+        let shift =
+            keyboard_input.pressed(KeyCode::LShift) || keyboard_input.pressed(KeyCode::RShift);
+        spawner.preset = spawner.preset.next(if shift { -1 } else { 1 });
+        spawner.restitution = spawner.preset.restitution();
+        settings.set("restitution", spawner.restitution);
+    }
+}
+
+struct PresetLabel;
+
+/// Ghost of the active preset, following the cursor
+struct SpawnPreview;
+
+fn spawn_preset_preview(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    commands
+        .spawn(TextComponents {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    bottom: Val::Px(10.0),
+                    left: Val::Px(10.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text {
+                value: String::new(),
+                font: assets.font,
+                style: TextStyle {
+                    font_size: 18.0,
+                    color: Color::WHITE,
+                },
+            },
+            ..Default::default()
+        })
+        .with(PresetLabel)
+        // Its own material, which texture follows the preset
+        .spawn(SpriteComponents {
+            material: materials.add(Color::rgba(1.0, 1.0, 1.0, 0.3).into()),
+            ..Default::default()
+        })
+        .with(SpawnPreview);
+}
+
+fn preset_preview_system(
+    assets: Res<GameAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    spawner: Res<Spawner>,
+    mouse_position: Res<MousePosition>,
+    arena: Res<Arena>,
+    mut labels: Query<(&PresetLabel, Mut<Text>)>,
+    mut previews: Query<(&SpawnPreview, Mut<Transform>, Mut<Sprite>)>,
+    mut preview_draws: Query<(&SpawnPreview, Mut<Draw>, &Handle<ColorMaterial>)>,
+) {
+    let preset = spawner.preset;
+    for (_label, mut text) in &mut labels.iter() {
+        text.value = format!(
+            "{:?}  radius {:.1}  restitution {:.1}  speed {:.0}",
+            preset,
+            preset.radius(),
+            spawner.restitution,
+            preset.speed()
+        );
+    }
+    let (size, round) = preset.preview();
+    let cursor = mouse_position.position_in_arena(&arena);
+    for (_preview, mut transform, mut sprite) in &mut previews.iter() {
+        sprite.size = size;
+        if let Some(cursor) = cursor {
+            let position = arena.clamp(cursor, preset.radius());
+            transform.set_translation(position.extend(2.0));
+        }
+    }
+    for (_preview, mut draw, material) in &mut preview_draws.iter() {
+        draw.is_visible = spawner.drag.is_none() && cursor.is_some();
+        let material = materials.get_mut(&material).unwrap();
+        material.texture = if round {
+            Some(assets.sphere_texture)
+        } else {
+            None
+        };
+    }
+}
+
+/// A dot of the trajectory preview, the `n`th one along the flight
+struct TrajectoryDot(usize);
+
+/// Spawns the hidden dots of the trajectory preview, fading along the flight.
+fn spawn_trajectory_preview(
+    mut commands: Commands,
+    mut assets: ResMut<GameAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for n in 0..TRAJECTORY_DOTS {
+        let alpha = 0.8 * (1.0 - n as f32 / TRAJECTORY_DOTS as f32);
+        commands
+            .spawn(SpriteComponents {
+                sprite: Sprite::new(Vec2::new(6.0, 6.0)),
+                material: assets.sphere_tinted(Color::rgba(1.0, 1.0, 1.0, alpha), &mut materials),
+                draw: Draw {
+                    is_visible: false,
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .with(TrajectoryDot(n));
+    }
+}
+
+/// Shows where a drag-launch in progress would send the ball over the next
+/// `TRAJECTORY_DURATION`, falling under the gravity but going through the
+/// other bodies, a straight ray without gravity.
+fn trajectory_preview_system(
+    spawner: Res<Spawner>,
+    mouse_position: Res<MousePosition>,
+    arena: Res<Arena>,
+    configuration: Res<RapierConfiguration>,
+    mut dots: Query<(&TrajectoryDot, Mut<Transform>, Mut<Draw>)>,
+) {
+    // As launched by the spawn system on release
+    let launch = spawner.drag.and_then(|start| {
+        let pull = start - mouse_position.world_position()?;
+        if pull.length() < LAUNCH_MIN_DRAG {
+            return None;
+        }
+        Some((
+            arena.clamp(start, spawner.preset.radius()),
+            pull * LAUNCH_FACTOR,
+        ))
     });
+    let gravity = Vec2::new(configuration.gravity.x, configuration.gravity.y);
+    for (dot, mut transform, mut draw) in &mut dots.iter() {
+        draw.is_visible = launch.is_some();
+        if let Some((position, velocity)) = launch {
+            let t = TRAJECTORY_DURATION * (dot.0 + 1) as f32 / TRAJECTORY_DOTS as f32;
+            let position = position + velocity * t + gravity * (0.5 * t * t);
+            transform.set_translation(position.extend(2.0));
+        }
+    }
+}
+
+struct Ball;
+
+/// Marks the bodies spawned in the sandbox, that clear-all removes
+struct Spawned;
+
+fn ball_damping_toggle_system(keyboard_input: Res<Input<KeyCode>>, mut spawner: ResMut<Spawner>) {
+    if keyboard_input.just_pressed(KeyCode::D) {
+        spawner.damped = !spawner.damped;
+        println!(
+            "Damping of spawned balls {}",
+            if spawner.damped { "on" } else { "off" }
+        );
+    }
 }
 
-fn position_system(mut bodies: ResMut<RigidBodySet>, mut query: Query<&RigidBodyHandleComponent>) {
-    for body_handle in &mut query.iter() {
-        let mut body = bodies.get_mut(body_handle.handle()).unwrap();
-        let mut x = body.position.translation.vector.x;
-        let mut y = body.position.translation.vector.y;
-        let mut updated = false;
-        // Wrap around screen edges
-        if x < 0.0 && body.linvel.x < 0.0 {
-            x = WINDOW_WIDTH as f32;
-            updated = true;
-        } else if x > WINDOW_WIDTH as f32 && body.linvel.x > 0.0 {
-            x = 0.0;
-            updated = true;
+/// Registers the settings shown in the window title, the gravity, the
+/// restitution of the spawned bodies and the body count, and applies their
+/// values given on the command line.
+fn setup_settings(
+    mut settings: ResMut<Settings>,
+    mut configuration: ResMut<RapierConfiguration>,
+    mut spawner: ResMut<Spawner>,
+) {
+    let gravity = settings.register("gravity", "off".to_string());
+    configuration.gravity = match gravity.as_str() {
+        "down" => Vector2::new(0.0, GRAVITY),
+        "off" => Vector2::zeros(),
+        _ => {
+            println!("Unknown gravity {:?}, either down or off", gravity);
+            settings.set("gravity", "off".to_string());
+            Vector2::zeros()
         }
-        if y < 0.0 && body.linvel.y < 0.0 {
-            y = WINDOW_HEIGHT as f32;
-            updated = true;
-        } else if y > WINDOW_HEIGHT as f32 && body.linvel.y > 0.0 {
-            y = 0.0;
-            updated = true;
+    };
+    let restitution = spawner.preset.restitution();
+    spawner.restitution = settings.register("restitution", restitution);
+    settings.register("bodies", 0usize);
+}
+
+fn body_count_setting_system(bodies: Res<RigidBodySet>, mut settings: ResMut<Settings>) {
+    settings.set("bodies", bodies.len());
+}
+
+fn gravity_toggle_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut configuration: ResMut<RapierConfiguration>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut settings: ResMut<Settings>,
+) {
+    if keyboard_input.just_pressed(KeyCode::G) {
+        if configuration.gravity == Vector2::zeros() {
+            configuration.gravity = Vector2::new(0.0, GRAVITY);
+            settings.set("gravity", "down".to_string());
+        } else {
+            configuration.gravity = Vector2::zeros();
+            settings.set("gravity", "off".to_string());
         }
-        if updated {
-            let mut new_position = body.position.clone();
-            new_position.translation.vector.x = x;
-            new_position.translation.vector.y = y;
-            body.set_position(new_position);
+        // Sleeping bodies would otherwise ignore the change
+        for (_, mut body) in bodies.iter_mut() {
+            body.wake_up(true);
         }
     }
 }
 
-fn collision_system(events: Res<EventQueue>) {
-    while let Ok(contact_event) = events.contact_events.pop() {
-        println!("Contact event {:?}", contact_event);
+/// Despawns all the spawned bodies, `RapierCleanupPlugin` removing them from
+/// the physics world.
+fn clear_spawned(commands: &mut Commands, spawned: &mut Query<(Entity, &Spawned)>) {
+    for (entity, _spawned) in &mut spawned.iter() {
+        commands.despawn_recursive(entity);
     }
-    while let Ok(proximity_event) = events.proximity_events.pop() {
-        println!("Received proximity event: {:?}", proximity_event);
+}
+
+fn clear_all_system(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    benchmark: Res<Benchmark>,
+    mut spawned: Query<(Entity, &Spawned)>,
+) {
+    if keyboard_input.just_pressed(KeyCode::C) && !benchmark.active {
+        clear_spawned(&mut commands, &mut spawned);
+    }
+}
+
+/// Logical description of a ball, from which its rapier body is rebuilt
+#[derive(Serialize, Deserialize)]
+struct SavedBall {
+    position: (f32, f32),
+    rotation: f32,
+    linvel: (f32, f32),
+    angvel: f32,
+    radius: f32,
+    restitution: f32,
+    color: (f32, f32, f32, f32),
+}
+
+/// Content of the scene file. Positions are world coordinates for `origin`.
+#[derive(Serialize, Deserialize)]
+struct SavedScene {
+    origin: ArenaOrigin,
+    balls: Vec<SavedBall>,
+}
+
+fn save_scene_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    arena: Res<Arena>,
+    materials: Res<Assets<ColorMaterial>>,
+    bodies: Res<RigidBodySet>,
+    colliders: Res<ColliderSet>,
+    mut balls: Query<(
+        Entity,
+        &Ball,
+        &RigidBodyHandleComponent,
+        &ColliderHandleComponent,
+    )>,
+    ball_materials: Query<(&Ball, &Handle<ColorMaterial>)>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F6) {
+        return;
+    }
+    let mut scene = SavedScene {
+        origin: arena.origin,
+        balls: Vec::new(),
+    };
+    for (entity, _ball, body_handle, collider_handle) in &mut balls.iter() {
+        let (body, collider) = match (
+            bodies.get(body_handle.handle()),
+            colliders.get(collider_handle.handle()),
+        ) {
+            (Some(body), Some(collider)) => (body, collider),
+            _ => continue,
+        };
+        let radius = match collider.shape().as_ball() {
+            Some(ball) => ball.radius,
+            None => continue,
+        };
+        let color = ball_materials
+            .get::<Handle<ColorMaterial>>(entity)
+            .ok()
+            .and_then(|material| materials.get(&material))
+            .map_or(Color::WHITE, |material| material.color);
+        let translation = body.position.translation.vector;
+        scene.balls.push(SavedBall {
+            position: (translation.x, translation.y),
+            rotation: body.position.rotation.angle(),
+            linvel: (body.linvel.x, body.linvel.y),
+            angvel: body.angvel,
+            radius,
+            restitution: collider.restitution,
+            color: (color.r, color.g, color.b, color.a),
+        });
+    }
+    let result = ron::ser::to_string_pretty(&scene, Default::default())
+        .map_err(|error| error.to_string())
+        .and_then(|text| platform::write(SCENE_FILE, text).map_err(|error| error.to_string()));
+    match result {
+        Ok(()) => println!("Saved {} balls to {}", scene.balls.len(), SCENE_FILE),
+        Err(error) => eprintln!("Could not save {}: {}", SCENE_FILE, error),
+    }
+}
+
+/// Replaces the spawned bodies with the balls of the scene file. The current
+/// scene is left untouched if the file can't be read.
+fn load_scene_system(
+    mut commands: Commands,
+    mut assets: ResMut<GameAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    arena: Res<Arena>,
+    benchmark: Res<Benchmark>,
+    spawner: Res<Spawner>,
+    mut spawned: Query<(Entity, &Spawned)>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F7) || benchmark.active {
+        return;
+    }
+    let scene: SavedScene = match platform::read_to_string(SCENE_FILE)
+        .map_err(|error| error.to_string())
+        .and_then(|text| ron::de::from_str(&text).map_err(|error| error.to_string()))
+    {
+        Ok(scene) => scene,
+        Err(error) => {
+            eprintln!("Could not load {}: {}", SCENE_FILE, error);
+            return;
+        }
+    };
+    clear_spawned(&mut commands, &mut spawned);
+    // Saved with the other origin convention
+    let offset = Arena {
+        origin: scene.origin,
+        ..*arena
+    }
+    .offset_to(arena.origin);
+    // The damping being not saved, the balls get the one of the spawner
+    for ball in &scene.balls {
+        let position = Vec2::new(ball.position.0, ball.position.1) + offset;
+        let velocity = Vec2::new(ball.linvel.0, ball.linvel.1);
+        let color = Color::rgba(ball.color.0, ball.color.1, ball.color.2, ball.color.3);
+        let material = assets.sphere_tinted(color, &mut materials);
+        let (body, collider) = ball_builders(position, velocity, ball.radius, ball.restitution);
+        commands
+            .spawn(SpriteComponents {
+                transform: Transform::from_translation(position.extend(0.0)),
+                material,
+                sprite: Sprite::new(Vec2::new(2.0 * ball.radius, 2.0 * ball.radius)),
+                ..Default::default()
+            })
+            .with(Ball)
+            .with(body.rotation(ball.rotation).angvel(ball.angvel))
+            .with(collider)
+            .with_bundle(spawned_components(spawner.damping()));
+    }
+    println!("Loaded {} balls from {}", scene.balls.len(), SCENE_FILE);
+}
+
+/// Frame and physics step timings of a running benchmark
+#[derive(Default)]
+struct Benchmark {
+    active: bool,
+    elapsed: f32,
+    /// Number of frames, total frame time and total physics step time in ms,
+    /// since the last log.
+    period: (u32, f64, f64),
+    /// Same as `period`, for the whole benchmark.
+    total: (u32, f64, f64),
+    worst_frame: f64,
+}
+
+fn start_benchmark_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    arena: Res<Arena>,
+    mut rng: ResMut<SeededRng>,
+    mut benchmark: ResMut<Benchmark>,
+    mut pipeline: ResMut<PhysicsPipeline>,
+    mut requests: ResMut<Events<SpawnRequest<SpawnKind>>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F10) {
+        return;
+    }
+    if benchmark.active {
+        println!("Benchmark already running");
+        return;
+    }
+    *benchmark = Benchmark {
+        active: true,
+        ..Default::default()
+    };
+    pipeline.counters.enable();
+    let rng = &mut rng.0;
+    for (x, y, vx, vy) in jittered_grid(rng, &arena, BENCHMARK_COLUMNS, BENCHMARK_ROWS) {
+        requests.send(SpawnRequest::new(
+            SpawnKind::GridBall,
+            Vec2::new(x, y),
+            Vec2::new(vx, vy),
+        ));
+    }
+    println!(
+        "Benchmark started with {} balls",
+        BENCHMARK_COLUMNS * BENCHMARK_ROWS
+    );
+}
+
+/// Requests a jittered grid of balls on T, to compare the solver cost with the
+/// ncollide example.
+fn stress_grid_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    arena: Res<Arena>,
+    mut rng: ResMut<SeededRng>,
+    mut requests: ResMut<Events<SpawnRequest<SpawnKind>>>,
+    #[cfg(target_arch = "wasm32")] time: Res<Time>,
+    #[cfg(target_arch = "wasm32")] mut requested_last_frame: Local<bool>,
+) {
+    // Without a clock within a frame in the browser, where `Instant` panics,
+    // the time of the whole frame spawning the grid. The spawner times the
+    // grid itself natively.
+    #[cfg(target_arch = "wasm32")]
+    {
+        if std::mem::take(&mut *requested_last_frame) {
+            println!(
+                "Stress grid frame took {:.2} ms",
+                time.delta_seconds_f64 * 1000.0
+            );
+        }
+    }
+    if !keyboard_input.just_pressed(KeyCode::T) {
+        return;
+    }
+    for (x, y, vx, vy) in jittered_grid(&mut rng.0, &arena, STRESS_COLUMNS, STRESS_ROWS) {
+        requests.send(SpawnRequest::new(
+            SpawnKind::GridBall,
+            Vec2::new(x, y),
+            Vec2::new(vx, vy),
+        ));
+    }
+    println!(
+        "Stress grid of {} balls requested",
+        STRESS_COLUMNS * STRESS_ROWS
+    );
+    #[cfg(target_arch = "wasm32")]
+    {
+        *requested_last_frame = true;
+    }
+}
+
+fn benchmark_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut benchmark: ResMut<Benchmark>,
+    mut pipeline: ResMut<PhysicsPipeline>,
+    bodies: Res<RigidBodySet>,
+    mut spawned: Query<(Entity, &Spawned)>,
+) {
+    if !benchmark.active {
+        return;
+    }
+    let frame_time = time.delta_seconds as f64 * 1000.0;
+    let step_time = pipeline.counters.step_time();
+    benchmark.period.0 += 1;
+    benchmark.period.1 += frame_time;
+    benchmark.period.2 += step_time;
+    benchmark.worst_frame = benchmark.worst_frame.max(frame_time);
+    let previous_second = benchmark.elapsed.floor();
+    benchmark.elapsed += time.delta_seconds;
+    if benchmark.elapsed.floor() > previous_second {
+        let (frames, frame_total, step_total) = benchmark.period;
+        println!(
+            "Benchmark {:>2}s: frame {:.2} ms, physics step {:.2} ms, {} bodies",
+            benchmark.elapsed.floor(),
+            frame_total / frames as f64,
+            step_total / frames as f64,
+            bodies.len()
+        );
+        benchmark.total.0 += frames;
+        benchmark.total.1 += frame_total;
+        benchmark.total.2 += step_total;
+        benchmark.period = (0, 0.0, 0.0);
+    }
+    if benchmark.elapsed >= BENCHMARK_DURATION {
+        let (frames, frame_total, step_total) = benchmark.total;
+        println!(
+            "Benchmark done: {} frames, average frame {:.2} ms (worst {:.2} ms), average physics step {:.2} ms",
+            frames,
+            frame_total / frames as f64,
+            benchmark.worst_frame,
+            step_total / frames as f64
+        );
+        benchmark.active = false;
+        pipeline.counters.disable();
+        clear_spawned(&mut commands, &mut spawned);
+    }
+}
+
+/// Wind speed in px/s, pushing all dynamic bodies
+struct Wind(Vector2<f32>);
+
+fn wind_input_system(keyboard_input: Res<Input<KeyCode>>, mut wind: ResMut<Wind>) {
+    for &(key, dx, dy) in &[
+        (KeyCode::Left, -1.0, 0.0),
+        (KeyCode::Right, 1.0, 0.0),
+        (KeyCode::Down, 0.0, -1.0),
+        (KeyCode::Up, 0.0, 1.0),
+    ] {
+        if keyboard_input.just_pressed(key) {
+            wind.0 += Vector2::new(dx, dy) * WIND_STEP;
+        }
+    }
+}
+
+fn wind_system(wind: Res<Wind>, mut bodies: ResMut<RigidBodySet>, colliders: Res<ColliderSet>) {
+    let speed = wind.0.norm();
+    if speed == 0.0 {
+        return;
+    }
+    for (_, mut body) in bodies.iter_mut() {
+        if !body.is_dynamic() || (body.is_sleeping() && speed < WIND_WAKE_THRESHOLD) {
+            continue;
+        }
+        // The ball radius is used as a crude cross-section
+        let radius = body
+            .colliders()
+            .first()
+            .and_then(|handle| colliders.get(*handle))
+            .and_then(|collider| collider.shape().as_ball())
+            .map_or(1.0, |ball| ball.radius);
+        body.wake_up(true);
+        body.apply_force(wind.0 * WIND_DRAG * radius);
+    }
+}
+
+/// Arrow in the top left corner showing the wind direction and strength
+struct WindIndicator;
+struct WindShaft;
+struct WindHead;
+
+fn spawn_wind_indicator(mut commands: Commands, mut materials: ResMut<Assets<ColorMaterial>>) {
+    let material = materials.add(Color::rgb(0.8, 0.8, 0.9).into());
+    commands
+        .spawn(SpriteComponents {
+            transform: Transform::from_translation(Vec3::new(
+                60.0,
+                WINDOW_HEIGHT as f32 - 60.0,
+                2.0,
+            )),
+            material,
+            sprite: Sprite::new(Vec2::new(4.0, 4.0)),
+            ..Default::default()
+        })
+        .with(WindIndicator)
+        .with_children(|parent| {
+            // Both parts are laid along the local x axis, the parent gives the direction
+            parent
+                .spawn(SpriteComponents {
+                    material,
+                    sprite: Sprite::new(Vec2::zero()),
+                    ..Default::default()
+                })
+                .with(WindShaft)
+                .spawn(SpriteComponents {
+                    material,
+                    transform: Transform::from_rotation(Quat::from_rotation_z(
+                        std::f32::consts::FRAC_PI_4,
+                    )),
+                    sprite: Sprite::new(Vec2::zero()),
+                    ..Default::default()
+                })
+                .with(WindHead);
+        });
+}
+
+fn wind_indicator_system(
+    wind: Res<Wind>,
+    mut cameras: Query<(&CursorCamera, &Transform)>,
+    mut indicators: Query<(&WindIndicator, Mut<Transform>)>,
+    mut shafts: Query<(&WindShaft, Mut<Transform>, Mut<Sprite>)>,
+    mut heads: Query<(&WindHead, Mut<Transform>, Mut<Sprite>)>,
+) {
+    let speed = wind.0.norm();
+    // 1px per px/s, capped so that the arrow stays in its corner
+    let length = speed.min(50.0);
+    // Stays in the top left corner of the window, whatever the camera
+    let mut corner = Vec2::new(60.0, WINDOW_HEIGHT as f32 - 60.0);
+    let mut scale = 1.0;
+    for (_camera, camera_transform) in &mut cameras.iter() {
+        corner = window_to_world(corner, camera_transform);
+        scale = camera_transform.scale().x();
+    }
+    for (_indicator, mut transform) in &mut indicators.iter() {
+        *transform = Transform::from_translation_rotation_scale(
+            corner.extend(2.0),
+            Quat::from_rotation_z(wind.0.y.atan2(wind.0.x)),
+            scale,
+        );
+    }
+    for (_shaft, mut transform, mut sprite) in &mut shafts.iter() {
+        transform.set_translation(Vec3::new(length / 2.0, 0.0, 0.0));
+        sprite.size = Vec2::new(length, 3.0);
+    }
+    for (_head, mut transform, mut sprite) in &mut heads.iter() {
+        let size = if speed > 0.0 { 9.0 } else { 0.0 };
+        transform.set_translation(Vec3::new(length, 0.0, 0.0));
+        sprite.size = Vec2::new(size, size);
     }
 }
 
-fn spawn_sphere_system(
+/// Marks the entities, body and sprites, of the terrain floor
+struct Terrain;
+
+fn terrain_system(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
+    keyboard_input: Res<Input<KeyCode>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
+    arena: Res<Arena>,
+    mut rng: ResMut<SeededRng>,
+    mut previous: Query<(Entity, &Terrain)>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F) {
+        return;
+    }
+    for (entity, _terrain) in &mut previous.iter() {
+        commands.despawn(entity);
+    }
+    // Sine plus noise, sampled uniformly across the arena width
+    let rng = &mut rng.0;
+    let phase = rng.gen_range(0.0, std::f32::consts::PI * 2.0);
+    let waves = rng.gen_range(1.5, 3.5);
+    let heights: Vec<f32> = (0..TERRAIN_SAMPLES)
+        .map(|i| {
+            let u = i as f32 / (TERRAIN_SAMPLES - 1) as f32;
+            80.0 + 50.0 * (u * waves * std::f32::consts::PI * 2.0 + phase).sin()
+                + rng.gen_range(-12.0, 12.0)
+        })
+        .collect();
+    // The heightfield is centered on its body and spans `scale.x` horizontally
+    let width = arena.width;
+    let min = arena.min();
+    let body = RigidBodyBuilder::new_static().translation(arena.center().x(), min.y());
+    let collider =
+        ColliderBuilder::heightfield(DVector::from_vec(heights.clone()), Vector2::new(width, 1.0));
+    commands.spawn((Terrain, body, collider));
+    let fill_material = materials.add(Color::rgb(0.25, 0.18, 0.12).into());
+    let edge_material = materials.add(Color::rgb(0.45, 0.65, 0.3).into());
+    let dx = width / (TERRAIN_SAMPLES - 1) as f32;
+    for (i, pair) in heights.windows(2).enumerate() {
+        let (x0, x1) = (min.x() + i as f32 * dx, min.x() + (i + 1) as f32 * dx);
+        let (y0, y1) = (pair[0], pair[1]);
+        let fill_height = (y0 + y1) / 2.0;
+        commands
+            .spawn(SpriteComponents {
+                transform: Transform::from_translation(Vec3::new(
+                    (x0 + x1) / 2.0,
+                    min.y() + fill_height / 2.0,
+                    0.0,
+                )),
+                material: fill_material,
+                sprite: Sprite::new(Vec2::new(dx, fill_height)),
+                ..Default::default()
+            })
+            .with(Terrain);
+        // Slanted quad covering the segment top
+        let length = (dx * dx + (y1 - y0) * (y1 - y0)).sqrt();
+        commands
+            .spawn(SpriteComponents {
+                transform: Transform::from_translation(Vec3::new(
+                    (x0 + x1) / 2.0,
+                    min.y() + (y0 + y1) / 2.0,
+                    0.0,
+                ))
+                .with_rotation(Quat::from_rotation_z((y1 - y0).atan2(dx))),
+                material: edge_material,
+                sprite: Sprite::new(Vec2::new(length, 6.0)),
+                ..Default::default()
+            })
+            .with(Terrain);
+    }
+}
+
+/// A ball made immovable, with what it had before being frozen
+struct Frozen {
+    mass_properties: MassProperties,
+    material: Handle<ColorMaterial>,
+}
+
+/// Toggles the ball under the cursor between dynamic and frozen, on Ctrl +
+/// click.
+///
+/// Frozen balls keep their dynamic status, rapier 0.2 not allowing to change
+/// it in place, but get an infinite mass so that nothing moves them.
+fn freeze_system(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
     mouse_button_input: Res<Input<MouseButton>>,
     mouse_position: Res<MousePosition>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut bodies: ResMut<RigidBodySet>,
+    colliders: Res<ColliderSet>,
+    mut balls: Query<(
+        Entity,
+        &Ball,
+        &Transform,
+        &RigidBodyHandleComponent,
+        &ColliderHandleComponent,
+    )>,
+    frozen: Query<&Frozen>,
+    ball_materials: Query<(&Ball, Mut<Handle<ColorMaterial>>)>,
 ) {
-    if mouse_button_input.just_pressed(MouseButton::Left) {
-        let mut rng = thread_rng();
-        let x = mouse_position.0.x();
-        let y = mouse_position.0.y();
-        let z = rng.gen_range(0.0, 1.0);
-        let vx = rng.gen_range(-(WINDOW_WIDTH as f32) / 4.0, (WINDOW_WIDTH as f32) / 4.0);
-        let vy = rng.gen_range(-(WINDOW_HEIGHT as f32) / 4.0, (WINDOW_HEIGHT as f32) / 4.0);
-        let texture_handle = asset_server
-            .load("assets/sprite_sphere_256x256.png")
+    let ctrl =
+        keyboard_input.pressed(KeyCode::LControl) || keyboard_input.pressed(KeyCode::RControl);
+    let cursor = match (
+        ctrl && mouse_button_input.just_pressed(MouseButton::Left),
+        mouse_position.world_position(),
+    ) {
+        (true, Some(cursor)) => Vector2::new(cursor.x(), cursor.y()),
+        _ => return,
+    };
+    // The ball drawn on top of those under the cursor
+    let mut covering = Vec::new();
+    for (entity, _ball, transform, body_handle, collider_handle) in &mut balls.iter() {
+        let radius = match colliders
+            .get(collider_handle.handle())
+            .and_then(|collider| collider.shape().as_ball())
+        {
+            Some(ball) => ball.radius,
+            None => continue,
+        };
+        let body = bodies.get(body_handle.handle()).unwrap();
+        if (body.position.translation.vector - cursor).norm() <= radius {
+            covering.push((entity, transform.translation().z()));
+        }
+    }
+    let entity = match topmost(covering) {
+        Some(entity) => entity,
+        None => return,
+    };
+    let handle = balls
+        .get::<RigidBodyHandleComponent>(entity)
+        .unwrap()
+        .handle();
+    let mut body = bodies.get_mut(handle).unwrap();
+    body.linvel = Vector2::zeros();
+    body.angvel = 0.0;
+    let mut material = ball_materials
+        .get_mut::<Handle<ColorMaterial>>(entity)
+        .unwrap();
+    if let Ok(frozen) = frozen.get::<Frozen>(entity) {
+        body.mass_properties = frozen.mass_properties;
+        body.wake_up(true);
+        *material = frozen.material;
+        commands.remove_one::<Frozen>(entity);
+    } else {
+        commands.insert_one(
+            entity,
+            Frozen {
+                mass_properties: body.mass_properties,
+                material: *material,
+            },
+        );
+        body.mass_properties.inv_mass = 0.0;
+        body.mass_properties.inv_principal_inertia_sqrt = 0.0;
+        // Materials may be shared between balls, so the tint is a new one
+        let texture = materials
+            .get(&material)
+            .and_then(|material| material.texture);
+        *material = materials.add(ColorMaterial {
+            color: Color::rgb(0.6, 0.8, 1.0),
+            texture,
+        });
+    }
+}
+
+struct Magnet;
+
+fn remove_magnet_system(
+    mut commands: Commands,
+    mouse_button_input: Res<Input<MouseButton>>,
+    mouse_position: Res<MousePosition>,
+    bodies: Res<RigidBodySet>,
+    mut query: Query<(Entity, &Magnet, &RigidBodyHandleComponent)>,
+) {
+    if let (true, Some(cursor)) = (
+        mouse_button_input.just_pressed(MouseButton::Right),
+        mouse_position.world_position(),
+    ) {
+        let cursor = Vector2::new(cursor.x(), cursor.y());
+        for (entity, _magnet, body_handle) in &mut query.iter() {
+            let handle = body_handle.handle();
+            let position = bodies.get(handle).unwrap().position.translation.vector;
+            if (position - cursor).norm() <= MAGNET_RADIUS {
+                commands.despawn(entity);
+            }
+        }
+    }
+}
+
+fn magnet_system(
+    mut bodies: ResMut<RigidBodySet>,
+    mut magnets: Query<(&Magnet, &RigidBodyHandleComponent)>,
+) {
+    let mut magnet_positions = Vec::new();
+    for (_magnet, body_handle) in &mut magnets.iter() {
+        let body = bodies.get(body_handle.handle()).unwrap();
+        magnet_positions.push(body.position.translation.vector);
+    }
+    if magnet_positions.is_empty() {
+        return;
+    }
+    // Bucket dynamic bodies in a grid of MAGNET_RANGE cells, so each magnet only
+    // has to look at the 3x3 cells around it.
+    let cell = |p: &Vector2<f32>| {
+        (
+            (p.x / MAGNET_RANGE).floor() as i32,
+            (p.y / MAGNET_RANGE).floor() as i32,
+        )
+    };
+    let mut grid = HashMap::new();
+    for (handle, body) in bodies.iter() {
+        if body.is_dynamic() {
+            let position = body.position.translation.vector;
+            grid.entry(cell(&position))
+                .or_insert_with(Vec::new)
+                .push((handle, position));
+        }
+    }
+    // Sum the pull of all magnets before applying it, so they stack
+    let mut forces: HashMap<RigidBodyHandle, Vector2<f32>> = HashMap::new();
+    for magnet in &magnet_positions {
+        let (cx, cy) = cell(magnet);
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                if let Some(candidates) = grid.get(&(cx + dx, cy + dy)) {
+                    for (handle, position) in candidates {
+                        let delta = magnet - position;
+                        let distance = delta.norm();
+                        if distance > 1.0 && distance < MAGNET_RANGE {
+                            let pull = delta / distance * (MAGNET_STRENGTH / distance);
+                            *forces.entry(*handle).or_insert_with(Vector2::zeros) += pull;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    for (handle, acceleration) in forces {
+        let mut body = bodies.get_mut(handle).unwrap();
+        let mass = body.mass();
+        body.wake_up(true);
+        body.apply_force(acceleration * mass);
+    }
+}
+
+struct ChainLink {
+    radius: f32,
+}
+
+/// A joint between two chain links, that snaps under a too strong impulse
+struct ChainJoint {
+    entity1: Entity,
+    entity2: Entity,
+    params: JointParams,
+    break_impulse: f32,
+}
+
+/// Joints of all the chains, as rapier does not expose which entities a joint binds.
+#[derive(Default)]
+struct ChainJoints(HashMap<JointHandle, ChainJoint>);
+
+/// Marks a link that just snapped, it is tinted until `frames` reaches 0.
+struct Snapped {
+    frames: u32,
+    color: Color,
+}
+
+fn spawn_chain_system(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_position: Res<MousePosition>,
+    arena: Res<Arena>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut colliders: ResMut<ColliderSet>,
+    mut joints: ResMut<JointSet>,
+    mut chain_joints: ResMut<ChainJoints>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::J) {
+        return;
+    }
+    let cursor = match mouse_position.position_in_arena(&arena) {
+        Some(cursor) => cursor,
+        None => return,
+    };
+    let x = cursor.x();
+    let y = cursor.y();
+    // Bodies are inserted directly in the sets, so that joints can be created
+    // in the same frame.
+    let anchor_handle = bodies.insert(RigidBodyBuilder::new_static().translation(x, y).build());
+    let anchor_collider = colliders.insert(
+        ColliderBuilder::ball(CHAIN_LINK_RADIUS / 2.0).build(),
+        anchor_handle,
+        &mut bodies,
+    );
+    commands
+        .spawn(SpriteComponents {
+            transform: Transform::from_translation(Vec3::new(x, y, 1.0))
+                .with_scale(CHAIN_LINK_RADIUS / 2.0 / 128.0),
+            material: assets.sphere_material,
+            ..Default::default()
+        })
+        .with(RigidBodyHandleComponent::from(anchor_handle))
+        .with(ColliderHandleComponent::from(anchor_collider));
+    let mut previous = (commands.current_entity().unwrap(), anchor_handle);
+    for i in 0..CHAIN_LINKS {
+        let link_y = y - CHAIN_LINK_RADIUS * (2 * i + 1) as f32;
+        let handle = bodies.insert(
+            RigidBodyBuilder::new_dynamic()
+                .translation(x, link_y)
+                .build(),
+        );
+        let collider = colliders.insert(
+            ColliderBuilder::ball(CHAIN_LINK_RADIUS).build(),
+            handle,
+            &mut bodies,
+        );
+        commands
+            .spawn(SpriteComponents {
+                transform: Transform::from_translation(Vec3::new(x, link_y, 1.0))
+                    .with_scale(CHAIN_LINK_RADIUS / 128.0),
+                // Its own material, tinted when one of its joints snaps
+                material: materials.add(ColorMaterial::modulated_texture(
+                    assets.sphere_texture,
+                    Color::rgb(0.7, 0.7, 0.8),
+                )),
+                ..Default::default()
+            })
+            .with(ChainLink {
+                radius: CHAIN_LINK_RADIUS,
+            })
+            .with(RigidBodyHandleComponent::from(handle))
+            .with(ColliderHandleComponent::from(collider));
+        let entity = commands.current_entity().unwrap();
+        // The anchor is half a link radius, the links touch each other
+        let anchor_offset = if i == 0 { 0.0 } else { CHAIN_LINK_RADIUS };
+        let params: JointParams = BallJoint::new(
+            Point::new(0.0, -anchor_offset),
+            Point::new(0.0, CHAIN_LINK_RADIUS),
+        )
+        .into();
+        let joint_handle = joints.insert(&mut bodies, previous.1, handle, params);
+        chain_joints.0.insert(
+            joint_handle,
+            ChainJoint {
+                entity1: previous.0,
+                entity2: entity,
+                params,
+                break_impulse: CHAIN_BREAK_IMPULSE,
+            },
+        );
+        previous = (entity, handle);
+    }
+}
+
+/// Removes the joints whose last step impulse exceeded their breaking threshold.
+///
+/// Rapier has no way to remove a single joint, so the lower body of a snapped
+/// joint is removed, which drops all its joints, and inserted back with its
+/// current state. Its joints that did not snap are then restored.
+fn break_joint_system(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut colliders: ResMut<ColliderSet>,
+    mut joints: ResMut<JointSet>,
+    mut chain_joints: ResMut<ChainJoints>,
+    body_handles: Query<&RigidBodyHandleComponent>,
+    links: Query<(&ChainLink, &Handle<ColorMaterial>)>,
+) {
+    let mut broken = Vec::new();
+    for (joint_handle, chain_joint) in chain_joints.0.iter() {
+        let impulse = joints
+            .get(*joint_handle)
+            .and_then(|joint| joint.params.as_ball_joint())
+            .map_or(0.0, |ball_joint| ball_joint.impulse.norm());
+        if impulse > chain_joint.break_impulse {
+            broken.push(*joint_handle);
+        }
+    }
+    if broken.is_empty() {
+        return;
+    }
+    let mut rebuilt = HashSet::new();
+    for joint_handle in broken {
+        let chain_joint = chain_joints.0.remove(&joint_handle).unwrap();
+        rebuilt.insert(chain_joint.entity2);
+        for &entity in &[chain_joint.entity1, chain_joint.entity2] {
+            if let Ok(material) = links.get::<Handle<ColorMaterial>>(entity) {
+                let material = materials.get_mut(&material).unwrap();
+                commands.insert_one(
+                    entity,
+                    Snapped {
+                        frames: SNAP_FLASH_FRAMES,
+                        color: material.color,
+                    },
+                );
+                material.color = Color::RED;
+            }
+        }
+    }
+    let mut new_handles = HashMap::new();
+    for entity in rebuilt {
+        let old_handle = body_handles
+            .get::<RigidBodyHandleComponent>(entity)
+            .unwrap()
+            .handle();
+        let radius = links.get::<ChainLink>(entity).unwrap().radius;
+        let old_body = bodies
+            .remove(old_handle, &mut colliders, &mut joints)
             .unwrap();
-        let body = RigidBodyBuilder::new_dynamic()
-            .translation(x, y)
-            .linvel(vx, vy);
-        // Negative friction to kind of simulate no loss of energy
-        let collider = ColliderBuilder::ball(128.0 * 0.2).friction(-0.5);
+        let handle = bodies.insert(
+            RigidBodyBuilder::new_dynamic()
+                .position(old_body.position)
+                .linvel(old_body.linvel.x, old_body.linvel.y)
+                .angvel(old_body.angvel)
+                .build(),
+        );
+        let collider = colliders.insert(ColliderBuilder::ball(radius).build(), handle, &mut bodies);
+        commands.insert_one(entity, RigidBodyHandleComponent::from(handle));
+        commands.insert_one(entity, ColliderHandleComponent::from(collider));
+        new_handles.insert(entity, handle);
+    }
+    // Restore the joints that were dropped with the rebuilt bodies
+    let body_handle = |entity: Entity| {
+        new_handles.get(&entity).copied().unwrap_or_else(|| {
+            body_handles
+                .get::<RigidBodyHandleComponent>(entity)
+                .unwrap()
+                .handle()
+        })
+    };
+    let dropped: Vec<JointHandle> = chain_joints
+        .0
+        .iter()
+        .filter(|(_, chain_joint)| {
+            new_handles.contains_key(&chain_joint.entity1)
+                || new_handles.contains_key(&chain_joint.entity2)
+        })
+        .map(|(joint_handle, _)| *joint_handle)
+        .collect();
+    for joint_handle in dropped {
+        let chain_joint = chain_joints.0.remove(&joint_handle).unwrap();
+        let joint_handle = joints.insert(
+            &mut bodies,
+            body_handle(chain_joint.entity1),
+            body_handle(chain_joint.entity2),
+            chain_joint.params,
+        );
+        chain_joints.0.insert(joint_handle, chain_joint);
+    }
+}
+
+fn snap_flash_system(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut query: Query<(Entity, Mut<Snapped>, &Handle<ColorMaterial>)>,
+) {
+    for (entity, mut snapped, material) in &mut query.iter() {
+        if snapped.frames == 0 {
+            materials.get_mut(&material).unwrap().color = snapped.color;
+            commands.remove_one::<Snapped>(entity);
+        } else {
+            snapped.frames -= 1;
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ZoneSide {
+    Left,
+    Right,
+}
+
+/// A sensor area counting the balls inside it
+struct Zone {
+    side: ZoneSide,
+    members: HashSet<Entity>,
+    flash: Timer,
+}
+
+struct ZoneLabel;
+
+fn spawn_zones(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    arena: Res<Arena>,
+) {
+    for &(side, x) in &[
+        (ZoneSide::Left, arena.min().x() + ZONE_WIDTH / 2.0),
+        (ZoneSide::Right, arena.max().x() - ZONE_WIDTH / 2.0),
+    ] {
+        let y = arena.center().y();
+        let body = RigidBodyBuilder::new_static().translation(x, y);
+        let collider = ColliderBuilder::cuboid(ZONE_WIDTH / 2.0, ZONE_HEIGHT / 2.0).sensor(true);
         commands
             .spawn(SpriteComponents {
-                transform: Transform::from_translation(Vec3::new(x, y, z)).with_scale(0.2),
-                material: materials.add(texture_handle.into()),
+                transform: Transform::from_translation(Vec3::new(x, y, 0.0)),
+                material: materials.add(Color::rgba(0.3, 0.3, 0.6, 0.15).into()),
+                sprite: Sprite::new(Vec2::new(ZONE_WIDTH, ZONE_HEIGHT)),
                 ..Default::default()
             })
+            .with(Zone {
+                side,
+                members: HashSet::new(),
+                flash: Timer::from_seconds(ZONE_FLASH_DURATION, false),
+            })
             .with(body)
             .with(collider);
     }
+    commands
+        .spawn(TextComponents {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(10.0),
+                    left: Val::Px(WINDOW_WIDTH as f32 / 2.0 - 60.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text {
+                value: "L: 0  R: 0".to_string(),
+                font: assets.font,
+                style: TextStyle {
+                    font_size: 24.0,
+                    color: Color::WHITE,
+                },
+            },
+            ..Default::default()
+        })
+        .with(ZoneLabel);
 }
 
-#[derive(Default)]
-struct MousePosition(Vec2);
-
-#[derive(Default)]
-struct LocalStateMousePositionSystem(EventReader<CursorMoved>);
+fn zone_membership_system(
+    events: Res<EventQueue>,
+    h_to_e: Res<ColliderHandleToEntity>,
+    bodies: Res<RigidBodySet>,
+    colliders: Res<ColliderSet>,
+    mut zones: Query<(Entity, Mut<Zone>, &RigidBodyHandleComponent)>,
+    balls: Query<(&Ball, &RigidBodyHandleComponent)>,
+    mut labels: Query<(&ZoneLabel, Mut<Text>)>,
+) {
+    while let Ok(proximity_event) = events.proximity_events.pop() {
+        let e1 = h_to_e.get(proximity_event.collider1);
+        let e2 = h_to_e.get(proximity_event.collider2);
+        if let (Some(e1), Some(e2)) = (e1, e2) {
+            for (zone_entity, mut zone, _) in &mut zones.iter() {
+                let ball = if zone_entity == e1 {
+                    e2
+                } else if zone_entity == e2 {
+                    e1
+                } else {
+                    continue;
+                };
+                if balls.get::<Ball>(ball).is_err() {
+                    continue;
+                }
+                match proximity_event.new_status {
+                    Proximity::Intersecting => zone.members.insert(ball),
+                    _ => zone.members.remove(&ball),
+                };
+            }
+        }
+    }
+    // Evict despawned balls and balls that left without a proximity event,
+    // e.g. when wrapping around the screen edges.
+    let mut counts = (0, 0);
+    for (_, mut zone, zone_handle) in &mut zones.iter() {
+        let center = bodies
+            .get(zone_handle.handle())
+            .unwrap()
+            .position
+            .translation
+            .vector;
+        zone.members.retain(|ball| {
+            balls
+                .get::<RigidBodyHandleComponent>(*ball)
+                .ok()
+                .and_then(|body_handle| bodies.get(body_handle.handle()))
+                .map_or(false, |body| {
+                    // Still in while overlapping the zone, as for the sensor
+                    let radius = body
+                        .colliders()
+                        .first()
+                        .and_then(|handle| colliders.get(*handle))
+                        .and_then(|collider| collider.shape().as_ball())
+                        .map_or(0.0, |ball| ball.radius);
+                    let offset = body.position.translation.vector - center;
+                    offset.x.abs() <= ZONE_WIDTH / 2.0 + radius
+                        && offset.y.abs() <= ZONE_HEIGHT / 2.0 + radius
+                })
+        });
+        match zone.side {
+            ZoneSide::Left => counts.0 = zone.members.len(),
+            ZoneSide::Right => counts.1 = zone.members.len(),
+        }
+    }
+    for (_label, mut text) in &mut labels.iter() {
+        text.value = format!("L: {}  R: {}", counts.0, counts.1);
+    }
+}
 
-fn mouse_position_system(
-    mut state: Local<LocalStateMousePositionSystem>,
-    cursor_moved_events: Res<Events<CursorMoved>>,
-    mut mouse_position: ResMut<MousePosition>,
+fn zone_score_system(
+    time: Res<Time>,
+    mut score_timer: Local<Option<Timer>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut zones: Query<(Mut<Zone>, &Handle<ColorMaterial>)>,
 ) {
-    for event in state.0.iter(&cursor_moved_events) {
-        mouse_position.0 = event.position;
+    let score_timer =
+        score_timer.get_or_insert_with(|| Timer::from_seconds(ZONE_SCORE_PERIOD, true));
+    score_timer.tick(time.delta_seconds);
+    let mut winner = None;
+    if score_timer.just_finished {
+        let mut counts = (0, 0);
+        for (zone, _) in &mut zones.iter() {
+            match zone.side {
+                ZoneSide::Left => counts.0 = zone.members.len(),
+                ZoneSide::Right => counts.1 = zone.members.len(),
+            }
+        }
+        winner = match counts.0.cmp(&counts.1) {
+            Ordering::Greater => Some(ZoneSide::Left),
+            Ordering::Less => Some(ZoneSide::Right),
+            Ordering::Equal => None,
+        };
+    }
+    for (mut zone, material) in &mut zones.iter() {
+        let material = materials.get_mut(&material).unwrap();
+        if winner == Some(zone.side) {
+            zone.flash.reset();
+            material.color = Color::rgba(0.2, 0.9, 0.2, 0.4);
+        }
+        zone.flash.tick(time.delta_seconds);
+        if zone.flash.just_finished {
+            material.color = Color::rgba(0.3, 0.3, 0.6, 0.15);
+        }
     }
 }
@@ -0,0 +1,260 @@
+use bevy::{prelude::*, render::camera::PerspectiveProjection, render::pass::ClearColor};
+use bevy_rapier3d::{
+    physics::{RapierPhysicsPlugin, RigidBodyHandleComponent},
+    rapier::{
+        dynamics::{JointSet, RigidBodyBuilder, RigidBodySet},
+        geometry::{ColliderBuilder, ColliderSet},
+    },
+};
+use bevy_showcase::{
+    debug_hud::{DebugHud, DebugHudPlugin},
+    mouse::{MousePosition, MousePositionPlugin},
+    showcase::SeededRng,
+};
+use rand::prelude::*;
+
+const WINDOW_WIDTH: u32 = 1280;
+const WINDOW_HEIGHT: u32 = 800;
+const RNG_SEED: u64 = 214;
+/// Half the inner width and depth of the box, in m
+const BOX_HALF_SIZE: f32 = 5.0;
+/// Height of the box walls, in m
+const BOX_HEIGHT: f32 = 4.0;
+/// Half the thickness of the box floor and walls, in m
+const BOX_HALF_THICKNESS: f32 = 0.1;
+/// Height in m of the horizontal plane the balls are dropped from
+const SPAWN_HEIGHT: f32 = 8.0;
+const MIN_BALL_RADIUS: f32 = 0.3;
+const MAX_BALL_RADIUS: f32 = 0.6;
+const CAMERA_EYE: [f32; 3] = [0.0, 16.0, 20.0];
+const CAMERA_TARGET: [f32; 3] = [0.0, 2.0, 0.0];
+const BALL_COLORS: [Color; 5] = [
+    Color::rgb(0.9, 0.3, 0.2),
+    Color::rgb(0.95, 0.75, 0.2),
+    Color::rgb(0.3, 0.8, 0.4),
+    Color::rgb(0.25, 0.5, 0.95),
+    Color::rgb(0.7, 0.35, 0.9),
+];
+
+fn main() {
+    App::build()
+        .add_resource(SeededRng(StdRng::seed_from_u64(RNG_SEED)))
+        .add_resource(WindowDescriptor {
+            title: "Rapier3D Bevy showcase".to_string(),
+            width: WINDOW_WIDTH,
+            height: WINDOW_HEIGHT,
+            ..Default::default()
+        })
+        .add_resource(ClearColor(Color::rgb(0.01, 0.01, 0.03)))
+        .add_plugin(RapierPhysicsPlugin)
+        .add_plugin(MousePositionPlugin)
+        .add_default_plugins()
+        .add_plugin(DebugHudPlugin)
+        .add_system(body_count_system.system())
+        .add_startup_system(setup.system())
+        .add_startup_system(spawn_box.system())
+        .add_system(spawn_system.system())
+        .add_system(clear_all_system.system())
+        .run();
+}
+
+/// Shared mesh and materials of the balls, the mesh being of radius 1 and
+/// scaled by each ball.
+struct BallAssets {
+    mesh: Handle<Mesh>,
+    materials: Vec<Handle<StandardMaterial>>,
+}
+
+struct Ball;
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands
+        .spawn(Camera3dComponents {
+            transform: Transform::new(Mat4::face_toward(
+                CAMERA_EYE.into(),
+                CAMERA_TARGET.into(),
+                Vec3::unit_y(),
+            )),
+            ..Default::default()
+        })
+        .spawn(LightComponents {
+            transform: Transform::from_translation(Vec3::new(4.0, 12.0, 8.0)),
+            ..Default::default()
+        })
+        .spawn(UiCameraComponents::default());
+    commands.insert_resource(BallAssets {
+        mesh: meshes.add(Mesh::from(shape::Icosphere {
+            radius: 1.0,
+            subdivisions: 3,
+        })),
+        materials: BALL_COLORS
+            .iter()
+            .map(|&color| materials.add(color.into()))
+            .collect(),
+    });
+}
+
+/// Spawns the open top glass box, a floor and four walls, each a static
+/// cuboid.
+fn spawn_box(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // Scaled to the half extents of each part
+    let cube = meshes.add(Mesh::from(shape::Cube { size: 1.0 }));
+    let glass = materials.add(Color::rgba(0.6, 0.8, 1.0, 0.25).into());
+    let outer = BOX_HALF_SIZE + BOX_HALF_THICKNESS;
+    let half_height = BOX_HEIGHT / 2.0;
+    // Center and half extents of the floor and walls
+    let parts = [
+        (
+            Vec3::new(0.0, -BOX_HALF_THICKNESS, 0.0),
+            Vec3::new(outer, BOX_HALF_THICKNESS, outer),
+        ),
+        (
+            Vec3::new(-outer, half_height, 0.0),
+            Vec3::new(BOX_HALF_THICKNESS, half_height, outer),
+        ),
+        (
+            Vec3::new(outer, half_height, 0.0),
+            Vec3::new(BOX_HALF_THICKNESS, half_height, outer),
+        ),
+        (
+            Vec3::new(0.0, half_height, -outer),
+            Vec3::new(BOX_HALF_SIZE, half_height, BOX_HALF_THICKNESS),
+        ),
+        (
+            Vec3::new(0.0, half_height, outer),
+            Vec3::new(BOX_HALF_SIZE, half_height, BOX_HALF_THICKNESS),
+        ),
+    ];
+    for &(center, half_extents) in &parts {
+        commands
+            .spawn(PbrComponents {
+                mesh: cube,
+                material: glass,
+                draw: Draw {
+                    is_transparent: true,
+                    ..Default::default()
+                },
+                transform: Transform::from_non_uniform_scale(half_extents),
+                ..Default::default()
+            })
+            .with(RigidBodyBuilder::new_static().translation(center.x(), center.y(), center.z()))
+            .with(ColliderBuilder::cuboid(
+                half_extents.x(),
+                half_extents.y(),
+                half_extents.z(),
+            ));
+    }
+}
+
+/// Point of the spawn plane under the cursor, kept above the inside of the
+/// box so that the balls fall in.
+///
+/// The cursor is unprojected along a ray from the camera, `None` if the ray
+/// does not go down to the spawn plane.
+fn spawn_point(
+    cursor: Vec2,
+    window: Vec2,
+    camera: &Transform,
+    projection: &PerspectiveProjection,
+    radius: f32,
+) -> Option<Vec3> {
+    let ndc = cursor / window * 2.0 - Vec2::one();
+    let half_height = (projection.fov / 2.0).tan();
+    let direction = camera.rotation()
+        * Vec3::new(
+            ndc.x() * half_height * projection.aspect_ratio,
+            ndc.y() * half_height,
+            -1.0,
+        );
+    let eye = camera.translation();
+    if direction.y() >= 0.0 || eye.y() <= SPAWN_HEIGHT {
+        return None;
+    }
+    let point = eye + direction * ((SPAWN_HEIGHT - eye.y()) / direction.y());
+    let limit = BOX_HALF_SIZE - radius;
+    Some(Vec3::new(
+        point.x().max(-limit).min(limit),
+        SPAWN_HEIGHT,
+        point.z().max(-limit).min(limit),
+    ))
+}
+
+/// Drops a ball of random size and color from the spawn plane under the cursor
+/// on left click.
+fn spawn_system(
+    mut commands: Commands,
+    mouse_input: Res<Input<MouseButton>>,
+    mouse_position: Res<MousePosition>,
+    windows: Res<Windows>,
+    assets: Res<BallAssets>,
+    mut rng: ResMut<SeededRng>,
+    mut cameras: Query<(&Transform, &PerspectiveProjection)>,
+) {
+    if !mouse_input.just_pressed(MouseButton::Left) || !mouse_position.in_window() {
+        return;
+    }
+    let (cursor, window) = match (mouse_position.position(), windows.get_primary()) {
+        (Some(cursor), Some(window)) => {
+            (cursor, Vec2::new(window.width as f32, window.height as f32))
+        }
+        _ => return,
+    };
+    let radius = rng.0.gen_range(MIN_BALL_RADIUS, MAX_BALL_RADIUS);
+    let material = assets.materials[rng.0.gen_range(0, assets.materials.len())];
+    for (transform, projection) in &mut cameras.iter() {
+        let position = match spawn_point(cursor, window, transform, projection, radius) {
+            Some(position) => position,
+            None => continue,
+        };
+        commands
+            .spawn(PbrComponents {
+                mesh: assets.mesh,
+                material,
+                transform: Transform::from_scale(radius),
+                ..Default::default()
+            })
+            .with(Ball)
+            .with(RigidBodyBuilder::new_dynamic().translation(
+                position.x(),
+                position.y(),
+                position.z(),
+            ))
+            .with(ColliderBuilder::ball(radius).density(1.0));
+    }
+}
+
+/// Removes all the balls on C.
+///
+/// There is no rapier3d cleanup plugin, so the bodies are removed from the
+/// physics world along with the entities.
+fn clear_all_system(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut colliders: ResMut<ColliderSet>,
+    mut joints: ResMut<JointSet>,
+    mut balls: Query<With<Ball, (Entity, &RigidBodyHandleComponent)>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::C) {
+        return;
+    }
+    for (entity, body_handle) in &mut balls.iter() {
+        bodies.remove(body_handle.handle(), &mut colliders, &mut joints);
+        commands.despawn(entity);
+    }
+}
+
+/// Counts the rapier3d bodies for the debug HUD.
+fn body_count_system(bodies: Res<RigidBodySet>, mut hud: ResMut<DebugHud>) {
+    if hud.visible {
+        hud.bodies = Some(bodies.len());
+    }
+}
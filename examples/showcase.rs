@@ -0,0 +1,173 @@
+//! Launcher of the scenes of the other examples, switching between them
+//! within a single running app.
+//!
+//! Up and Down select a scene in the menu, Enter starts it, and Escape tears
+//! it down back to the menu. A scene leaving entities, bodies, collision
+//! objects or materials behind is reported on the console.
+
+use bevy::prelude::*;
+use bevy_rapier2d::{
+    na::Vector2,
+    physics::{RapierConfiguration, RapierPhysicsPlugin},
+};
+use bevy_showcase::{
+    breakout_scene::{self, BreakoutScenePlugin},
+    cleanup::RapierCleanupPlugin,
+    game_assets::GameAssets,
+    handles::HandleToEntityPlugin,
+    ncollide_scene::{self, NCollideScenePlugin},
+    rapier_scene::{self, RapierScenePlugin},
+    response::NCollideResponsePlugin,
+    scene::{ActiveScene, SceneId, ScenePlugin},
+    showcase::ShowcasePlugin,
+    spaceship_scene::{self, SpaceshipScenePlugin},
+    spawn::MaxBodies,
+};
+
+const WINDOW_WIDTH: u32 = 1280;
+const WINDOW_HEIGHT: u32 = 800;
+const RNG_SEED: u64 = 214;
+/// Cap on the number of bodies of each kind, spawn requests are dropped above it
+const MAX_BODIES: usize = 4000;
+
+fn main() {
+    App::build()
+        .add_plugin(
+            ShowcasePlugin::new("Bevy showcase")
+                .with_window(WINDOW_WIDTH, WINDOW_HEIGHT)
+                .with_clear_color(Color::rgb(0.01, 0.01, 0.03))
+                .with_seed(RNG_SEED),
+        )
+        .add_plugin(RapierPhysicsPlugin)
+        .add_plugin(RapierCleanupPlugin)
+        .add_plugin(HandleToEntityPlugin)
+        .add_resource(RapierConfiguration {
+            gravity: Vector2::zeros(),
+            ..Default::default()
+        })
+        // Only the ncollide balls have collision objects
+        .add_plugin(NCollideResponsePlugin::default().wrapping())
+        .add_resource(MaxBodies(MAX_BODIES))
+        .add_plugin(NCollideScenePlugin)
+        .add_plugin(RapierScenePlugin)
+        .add_plugin(SpaceshipScenePlugin)
+        .add_plugin(BreakoutScenePlugin)
+        .add_plugin(
+            ScenePlugin::default()
+                .with_scene(ncollide_scene::SCENE)
+                .with_scene(rapier_scene::SCENE)
+                .with_scene(spaceship_scene::SCENE)
+                .with_scene(breakout_scene::SCENE),
+        )
+        .init_resource::<Menu>()
+        .add_startup_system(setup.system())
+        .add_startup_system(setup_menu.system())
+        .add_system(menu_system.system())
+        .add_system(menu_text_system.system())
+        .run();
+}
+
+/// The camera of the menu and of the scene texts, each scene having its own
+/// 2d camera.
+fn setup(mut commands: Commands) {
+    commands.spawn(UiCameraComponents::default());
+}
+
+#[derive(Default)]
+struct Menu {
+    selected: SceneId,
+}
+
+/// A line of the menu, the scene of this id or else the key hints
+struct MenuLine(usize);
+
+fn setup_menu(
+    mut commands: Commands,
+    mut assets: ResMut<GameAssets>,
+    active: Res<ActiveScene>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let lines = active.names().count() + 1;
+    let font = assets.font;
+    commands
+        .spawn(NodeComponents {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(10.0),
+                    left: Val::Px(10.0),
+                    ..Default::default()
+                },
+                flex_direction: FlexDirection::Column,
+                ..Default::default()
+            },
+            material: assets.material_for(Color::NONE, &mut materials),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            // The first child being at the bottom
+            for line in (0..lines).rev() {
+                parent
+                    .spawn(TextComponents {
+                        text: Text {
+                            value: String::new(),
+                            font,
+                            style: TextStyle {
+                                font_size: 24.0,
+                                color: Color::WHITE,
+                            },
+                        },
+                        ..Default::default()
+                    })
+                    .with(MenuLine(line));
+            }
+        });
+}
+
+/// Up and Down select a scene in the menu, Enter starts it, and Escape goes
+/// back to the menu.
+fn menu_system(
+    input: Res<Input<KeyCode>>,
+    mut menu: ResMut<Menu>,
+    mut active: ResMut<ActiveScene>,
+) {
+    if active.switching() {
+        return;
+    }
+    if active.current().is_some() {
+        if input.just_pressed(KeyCode::Escape) {
+            active.stop();
+        }
+        return;
+    }
+    let scenes = active.names().count();
+    if input.just_pressed(KeyCode::Up) {
+        menu.selected = (menu.selected + scenes - 1) % scenes;
+    }
+    if input.just_pressed(KeyCode::Down) {
+        menu.selected = (menu.selected + 1) % scenes;
+    }
+    if input.just_pressed(KeyCode::Return) {
+        active.start(menu.selected);
+    }
+}
+
+fn menu_text_system(
+    menu: Res<Menu>,
+    active: Res<ActiveScene>,
+    mut query: Query<(&MenuLine, Mut<Text>)>,
+) {
+    let names: Vec<&str> = active.names().collect();
+    for (line, mut text) in &mut query.iter() {
+        let value = match (active.current(), names.get(line.0)) {
+            (None, Some(name)) if line.0 == menu.selected => format!("> {}", name),
+            (None, Some(name)) => format!("  {}", name),
+            (None, None) => "Up/Down: select, Enter: start".to_string(),
+            (Some(_), Some(_)) => String::new(),
+            (Some(current), None) => format!("{} — Esc: back to the menu", names[current]),
+        };
+        if text.value != value {
+            text.value = value;
+        }
+    }
+}
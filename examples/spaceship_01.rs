@@ -1,86 +1,1759 @@
+#![allow(clippy::too_many_arguments)]
+
 use bevy::{
+    asset::AddAsset,
+    audio::{AudioOutput, AudioSource, Mp3Loader},
     prelude::*,
-    render::{camera::OrthographicProjection, pass::ClearColor},
+    render::{
+        camera::{ActiveCameras, OrthographicProjection},
+        pass::{
+            LoadOp, Operations, PassDescriptor, RenderPassColorAttachmentDescriptor,
+            RenderPassDepthStencilAttachmentDescriptor, TextureAttachment,
+        },
+        render_graph::{
+            base::MainPass, CameraNode, PassNode, RenderGraph, WindowSwapChainNode,
+            WindowTextureNode,
+        },
+        texture::{TextureDescriptor, TextureFormat, TextureUsage},
+    },
+    window::{CreateWindow, WindowId},
 };
 use bevy_rapier2d::{
-    na::Vector2,
-    physics::{RapierConfiguration, RapierPhysicsPlugin, RigidBodyHandleComponent},
+    na::{UnitComplex, Vector2},
+    physics::{
+        ColliderHandleComponent, EventQueue, RapierConfiguration, RapierPhysicsPlugin,
+        RigidBodyHandleComponent,
+    },
     rapier::{
-        dynamics::{RigidBodyBuilder, RigidBodySet},
-        geometry::ColliderBuilder,
+        dynamics::{RigidBody, RigidBodyBuilder, RigidBodyHandle, RigidBodySet},
+        geometry::{
+            ColliderBuilder, ColliderHandle, ColliderSet, ContactEvent, Proximity, ProximityEvent,
+        },
     },
     render::RapierRenderPlugin,
 };
+use bevy_showcase::{
+    animation::{sprite_sheet_animation_system, AnimationTimer},
+    arena::Arena,
+    capture::CapturePlugin,
+    cleanup::RapierCleanupPlugin,
+    config::{config_gravity_system, ConfigChanged, ConfigPlugin, ShowcaseConfig},
+    damping::{damping_system, Damping},
+    debug_hud::{rapier_body_count_system, DebugHud},
+    game_assets::{GameAssets, EXPLOSION_FRAMES},
+    handles::{ColliderHandleToEntity, HandleToEntityPlugin},
+    inspector::{Inspector, InspectorPlugin, Name},
+    lifetime::{despawn_outside_system, lifetime_system, DespawnOutside, Lifetime, WrapCount},
+    mouse::{CursorCamera, MousePosition},
+    physics_math::wrap_position,
+    platform,
+    score::{score_text_system, GameState, Score, ScoreText, Scoreboard},
+    settings::Settings,
+    showcase::{SeededRng, ShowcasePlugin},
+    spaceship_abilities::{
+        bullet_time_system, dash_system, hyperspace_destination, BulletTime, Dash, Hyperspace,
+        TimeScale, DASH_SPEED,
+    },
+    spaceship_controls::ShipControls,
+    spaceship_menu::{options_line, restart_mode, GameMode, MenuInput, MenuText},
+    spaceship_replay::{
+        fixed_step_system, replay_exit_system, RecordedControls, Replay, ReplayStep,
+    },
+    spaceship_stats::{
+        asteroid_builders, asteroid_collider, ship_builders, AsteroidSize, ASTEROID_RESTITUTION,
+        SHIP_RADIUS,
+    },
+    spaceship_weapons::{
+        boost_system, Boosts, Bounces, Powerup, Weapon, WeaponEnergy, RICOCHET_BOUNCES, SHOT_ENERGY,
+    },
+    spawn::{capped_requests, safe_spawn_point, MaxBodies, SpawnRequest},
+};
+use groups::CollisionGroups;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    io, panic,
+    path::PathBuf,
+};
 
+/// Next to the executable, only kept in memory in the browser
+const HIGH_SCORE_FILE: &str = "highscore.ron";
 const WINDOW_WIDTH: u32 = 1280;
 const WINDOW_HEIGHT: u32 = 800;
 const CAMERA_SCALE: f32 = 0.1;
-const ARENA_WIDTH: f32 = WINDOW_WIDTH as f32 * CAMERA_SCALE;
-const ARENA_HEIGHT: f32 = WINDOW_HEIGHT as f32 * CAMERA_SCALE;
+/// Arena dimensions scale of the camera follow mode, for 4× the area
+const FOLLOW_ARENA_SCALE: f32 = 2.0;
+/// Name of the camera of the overview window
+const OVERVIEW_CAMERA: &str = "Overview";
+/// Size in px of the overview window
+const OVERVIEW_WIDTH: u32 = 480;
+const OVERVIEW_HEIGHT: u32 = 300;
+/// Offset in m along x of the overview markers, far from anything else
+const OVERVIEW_OFFSET: f32 = 10_000.0;
+/// The camera follows the ship once further than this from its center
+const FOLLOW_DEADZONE: f32 = 10.0;
+/// Rate in 1/s at which the camera catches up with the ship
+const FOLLOW_RATE: f32 = 3.0;
+/// A ship moving further than this in a frame was teleported
+const TELEPORT_DISTANCE: f32 = 10.0;
+/// Bullet speed relative to the ship, in world units per s
+const BULLET_SPEED: f32 = 60.0;
+const BULLET_RADIUS: f32 = 0.25;
+/// Bullet lifetime in s
+const BULLET_LIFETIME: f32 = 1.5;
+/// Bullets wrap once around the arena edges, and fly off it at the next one
+const BULLET_WRAPS: u8 = 1;
+/// Distance in m outside the arena beyond which bullets out of wraps and
+/// debris are despawned
+const DESPAWN_MARGIN: f32 = 5.0;
+/// Minimum time in s between two shots of the balanced ship
+const FIRE_COOLDOWN: f32 = 0.2;
+/// Number of asteroids spawned at startup, and by each press of F1
+const ASTEROID_COUNT: usize = 8;
+/// Asteroids never spawn closer than this to the player
+const ASTEROID_SAFE_RADIUS: f32 = 20.0;
+/// Maximum asteroid speed, along each axis, in world units per s
+const ASTEROID_SPEED: f32 = 8.0;
+/// Maximum asteroid angular velocity in rad/s, so that they tumble
+const ASTEROID_SPIN: f32 = 2.0;
+/// Relative speed in m/s below which two asteroids in contact fuse, in the
+/// sticky mode
+const FUSE_SPEED: f32 = 4.0;
+/// Most asteroids in a clump, its core included
+const MAX_CLUMP_PIECES: usize = 5;
+const START_LIVES: u32 = 3;
+const MAX_PLAYERS: usize = 2;
+/// Horizontal offset of the spawn point of each player, from the arena center
+const PLAYER_SPAWN_X: [f32; MAX_PLAYERS] = [0.0, 6.0];
+/// Keys of each player
+const KEY_MAPS: [KeyMap; MAX_PLAYERS] = [
+    KeyMap {
+        thrust: KeyCode::W,
+        reverse: KeyCode::S,
+        left: KeyCode::A,
+        right: KeyCode::D,
+        fire: KeyCode::Space,
+        hyperspace: KeyCode::H,
+        missile: KeyCode::X,
+        beam: KeyCode::C,
+        tractor: KeyCode::T,
+        mine: KeyCode::V,
+        dash: KeyCode::LShift,
+        bullet_time: KeyCode::B,
+    },
+    KeyMap {
+        thrust: KeyCode::Up,
+        reverse: KeyCode::Down,
+        left: KeyCode::Left,
+        right: KeyCode::Right,
+        fire: KeyCode::RControl,
+        hyperspace: KeyCode::RShift,
+        missile: KeyCode::RAlt,
+        beam: KeyCode::Numpad0,
+        tractor: KeyCode::Numpad1,
+        mine: KeyCode::Numpad2,
+        dash: KeyCode::Numpad3,
+        bullet_time: KeyCode::Numpad4,
+    },
+];
+/// Hull points of the balanced ship
+const HULL_MAX: f32 = 100.0;
+/// Ships to choose from in the menu
+const SHIP_PROFILES: [ShipProfile; PROFILE_COUNT] = [
+    ShipProfile {
+        name: "Scout",
+        tint: Color::rgb(0.6, 0.8, 1.0),
+        rotation_speed: 60.0,
+        rotation_speed_target: 3.0 * std::f32::consts::PI,
+        thrust: 20.0,
+        max_speed: 45.0,
+        max_angvel: 11.0,
+        fire_cooldown: 0.12,
+        weapon_energy: 12.0,
+        weapon_regen: 5.0,
+        hull: 60.0,
+    },
+    ShipProfile {
+        name: "Balanced",
+        tint: Color::WHITE,
+        rotation_speed: 40.0,
+        // A full turn in about a second
+        rotation_speed_target: 2.0 * std::f32::consts::PI,
+        thrust: 30.0,
+        max_speed: 40.0,
+        max_angvel: 8.0,
+        fire_cooldown: FIRE_COOLDOWN,
+        weapon_energy: 15.0,
+        weapon_regen: 4.0,
+        hull: HULL_MAX,
+    },
+    ShipProfile {
+        name: "Heavy",
+        tint: Color::rgb(1.0, 0.7, 0.5),
+        rotation_speed: 25.0,
+        rotation_speed_target: 1.2 * std::f32::consts::PI,
+        thrust: 45.0,
+        max_speed: 35.0,
+        max_angvel: 5.0,
+        fire_cooldown: 0.3,
+        weapon_energy: 10.0,
+        weapon_regen: 3.0,
+        hull: 180.0,
+    },
+];
+const PROFILE_COUNT: usize = 3;
+/// Index in `SHIP_PROFILES` of the ship of a player joining the game
+const DEFAULT_PROFILE: usize = 1;
+/// Tint of the ships of each player
+const PLAYER_TINTS: [Color; MAX_PLAYERS] = [Color::WHITE, Color::rgb(0.5, 1.0, 0.6)];
+/// Impulse in N.s on the ship below which asteroid hits do no damage, a
+/// velocity change of about 5 m/s
+const HULL_MIN_IMPULSE: f32 = 15.0;
+/// Damage per N.s of impulse above `HULL_MIN_IMPULSE`
+const HULL_DAMAGE: f32 = 1.5;
+/// Damage of a saucer bullet
+const SAUCER_BULLET_DAMAGE: f32 = 40.0;
+/// Delay in s after a hit during which the hull takes no damage
+const HULL_GRACE: f32 = 0.5;
+/// Below this fraction of the hull, its bar turns red
+const HULL_LOW: f32 = 0.3;
+/// Duration in s of each frame of the ship explosion
+const EXPLOSION_FRAME_TIME: f32 = 0.08;
+/// Diameter in m of the ship explosion
+const EXPLOSION_SIZE: f32 = 6.0;
+/// Size in px of a digit of `digits.png`
+const DIGIT_SIZE: (f32, f32) = (16.0, 24.0);
+/// Height in m of the digits of a score popup
+const POPUP_HEIGHT: f32 = 1.2;
+/// Lifetime in s of a score popup, fading out meanwhile
+const POPUP_DURATION: f32 = 1.0;
+/// Upward drift in m/s of a score popup
+const POPUP_RISE: f32 = 2.0;
+/// Kills closer than this in s keep the combo going
+const COMBO_WINDOW: f32 = 2.0;
+/// Combo multiplier cap
+const MAX_COMBO: u32 = 5;
+/// Score popups shown at most at once, the points of further hits going
+/// without one
+const MAX_POPUPS: usize = 20;
+/// Seed of the layout of the static rocks of the obstacles arena
+const OBSTACLE_SEED: u64 = 214;
+const MIN_OBSTACLES: usize = 4;
+const MAX_OBSTACLES: usize = 6;
+/// Range of the obstacle radii in m
+const OBSTACLE_RADIUS: (f32, f32) = (2.0, 4.5);
+/// Minimum free space between two obstacles
+const OBSTACLE_GAP: f32 = 6.0;
+/// Minimum free space between an obstacle and a spawn point
+const OBSTACLE_SPAWN_CLEARANCE: f32 = 10.0;
+/// Obstacles are kept this far from the edges, not to catch the wrapping bodies
+const OBSTACLE_EDGE_MARGIN: f32 = 8.0;
+/// Candidate positions tried per obstacle
+const OBSTACLE_TRIES: usize = 20;
+const OBSTACLE_RESTITUTION: f32 = 0.8;
+/// Thickness in m of the walls of the bounded arena, outside of it
+const WALL_THICKNESS: f32 = 0.5;
+/// Pull of the black hole in m³/s², its acceleration being this over the
+/// squared distance
+const BLACK_HOLE_PULL: f32 = 200.0;
+/// Closer than this in m, the pull stops growing
+const BLACK_HOLE_MIN_DISTANCE: f32 = 4.0;
+/// Bodies whose center comes closer than this in m are destroyed
+const BLACK_HOLE_HORIZON: f32 = 1.5;
+/// The black hole moves every this many s
+const BLACK_HOLE_PERIOD: f32 = 45.0;
+/// The next position of the black hole is shown this many s before it moves
+const BLACK_HOLE_WARNING: f32 = 3.0;
+/// Minimum distance in m between the black hole and the spawn points
+const BLACK_HOLE_CLEARANCE: f32 = 15.0;
+/// Angular velocity in rad/s of the accretion disk
+const ACCRETION_SPIN: f32 = 0.5;
+/// Delay in s between the end of the ship explosion and its respawn
+const RESPAWN_DELAY: f32 = 1.5;
+/// Minimum free space between a respawned ship and the asteroids
+const RESPAWN_CLEARANCE: f32 = 3.0;
+/// Distance between the candidate respawn points
+const RESPAWN_SPACING: f32 = 4.0;
+/// Delay in s before trying again when there is no room to respawn
+const RESPAWN_RETRY: f32 = 0.5;
+/// Duration in s of the invulnerability of a respawned ship
+const INVULNERABILITY: f32 = 2.0;
+/// Period in s of the blinking of an invulnerable ship
+const BLINK_PERIOD: f32 = 0.2;
+/// An extra life is granted every time the score crosses a multiple of this
+const EXTRA_LIFE_SCORE: u32 = 10_000;
+/// Size in px of the lives icons
+const LIFE_ICON_SIZE: f32 = 24.0;
+/// Duration in s of the score flash on an extra life
+const SCORE_FLASH: f32 = 1.0;
+/// Below this speed in m/s, the directional scheme keeps the ship heading
+const HEADING_MIN_SPEED: f32 = 0.5;
+/// Maximum turn rate in rad/s of the directional scheme
+const HEADING_TURN_RATE: f32 = 6.0;
+/// Duration in s of the notices
+const NOTICE_DURATION: f32 = 2.0;
+/// Gamepad stick deflections below this are ignored
+const GAMEPAD_DEADZONE: f32 = 0.1;
+/// Delay in s between two shield powerups
+const POWERUP_PERIOD: f32 = 20.0;
+/// Duration in s of the shield
+const SHIELD_DURATION: f32 = 8.0;
+/// Velocity change in m/s of asteroids bouncing on the shield
+const SHIELD_BOUNCE: f32 = 10.0;
+/// Chance of a destroyed large asteroid to leave a weapon powerup
+const BOOST_DROP_CHANCE: f64 = 0.2;
+/// Maximum drift speed of a dropped powerup, along each axis, in m/s
+const BOOST_DROP_SPEED: f32 = 1.5;
+/// Time in s before a dropped powerup vanishes
+const BOOST_DROP_LIFETIME: f32 = 10.0;
+/// Maximum charge time in s of the beam
+const BEAM_CHARGE: f32 = 1.5;
+/// Beam range in m at full charge, scaling with the charge
+const BEAM_RANGE: f32 = 40.0;
+const BEAM_WIDTH: f32 = 0.4;
+/// Duration in s of the beam visual
+const BEAM_FLASH: f32 = 0.1;
+/// Ship velocity change in m/s opposite to a full charge beam
+const BEAM_RECOIL: f32 = 4.0;
+/// Maximum distance in m of an asteroid caught by the tractor beam
+const TRACTOR_RANGE: f32 = 15.0;
+/// Half angle in rad of the cone in front of the ship where asteroids are caught
+const TRACTOR_CONE: f32 = 15.0 * std::f32::consts::PI / 180.0;
+/// Distance in m in front of the ship where the caught asteroid is pulled
+const TRACTOR_HOLD: f32 = 5.0;
+/// Tractor spring stiffness in 1/s², and damping in 1/s
+const TRACTOR_STIFFNESS: f32 = 20.0;
+const TRACTOR_DAMPING: f32 = 4.0;
+const TRACTOR_WIDTH: f32 = 1.2;
+/// Tractor energy used per s while active, the full meter being 1
+const TRACTOR_DRAIN: f32 = 0.3;
+/// Tractor energy recovered per s while inactive
+const TRACTOR_RECHARGE: f32 = 0.15;
+/// Mines of a player at most at once
+const MAX_MINES: usize = 3;
+/// Delay in s before a laid mine can trigger
+const MINE_ARMING: f32 = 5.0;
+/// Period in s of the blinking of an arming mine
+const MINE_BLINK: f32 = 0.25;
+/// Unused mines vanish after this, in s
+const MINE_LIFETIME: f32 = 30.0;
+/// Radius in m of the sensor triggering a mine
+const MINE_SENSOR_RADIUS: f32 = 3.0;
+/// Asteroids closer than this in m to the mine are destroyed by its explosion
+const MINE_BLAST_RADIUS: f32 = 6.0;
+/// Ships closer than this in m to the mine are pushed away by its explosion
+const MINE_PUSH_RADIUS: f32 = 10.0;
+/// Velocity change in m/s of a ship right at the mine
+const MINE_PUSH: f32 = 20.0;
+/// Fuel of a full tank, in s of thrust
+const FUEL_MAX: f32 = 8.0;
+/// Fuel regained per s while not thrusting, 0 to refuel only with pickups
+const FUEL_REGEN: f32 = 0.4;
+/// Fraction of the tank below which the fuel gauge flashes
+const FUEL_LOW: f32 = 0.2;
+/// Period in s of the flashing of a low fuel gauge
+const FUEL_BLINK: f32 = 0.5;
+/// Fraction of the tank restored by a fuel pickup
+const FUEL_PICKUP: f32 = 0.4;
+/// Chance of a destroyed asteroid of any size to leave a fuel pickup
+const FUEL_DROP_CHANCE: f64 = 0.1;
+/// Large asteroids of the wave N, plus N
+const WAVE_ASTEROIDS: usize = 3;
+/// Asteroid speed increase of each wave, relative to the base speed
+const WAVE_SPEEDUP: f32 = 0.1;
+/// Asteroids alive at most, beyond which the survival stream holds off
+const MAX_ASTEROIDS: usize = 40;
+/// Cap on the rapier bodies, far above a busy game, spawn requests being
+/// dropped above it
+const MAX_BODIES: usize = 1000;
+/// Every this many waves, a boss is sent alone
+const BOSS_WAVE_PERIOD: u32 = 5;
+/// Bullet hits to break a boss
+const BOSS_HEALTH: u32 = 10;
+/// Maximum boss speed, along each axis, in m/s
+const BOSS_SPEED: f32 = 2.0;
+/// Distance in m between the health pips of a boss
+const PIP_SPACING: f32 = 0.8;
+/// Duration in s of the banner announcing a wave, before it is sent
+const WAVE_BANNER: f32 = 2.0;
+/// Points given to each player still in the game when a wave is cleared
+const WAVE_CLEAR_BONUS: u32 = 500;
+/// Duration in s of the intermission after a wave is cleared, Enter ending it
+const WAVE_CLEAR_BANNER: f32 = 2.5;
+/// Delay in s between the asteroids of a wave, released one at a time
+const DRIP_PERIOD: f32 = 0.7;
+/// Duration in s of the warning shown where an asteroid is about to appear
+const SPAWN_WARNING: f32 = 0.5;
+/// Length in m of the arms of the warning chevron
+const WARNING_SIZE: f32 = 1.5;
+/// Delay in s between two missiles
+const MISSILE_COOLDOWN: f32 = 5.0;
+const MISSILE_LIFETIME: f32 = 4.0;
+/// Missile speed cap in m/s
+const MISSILE_SPEED: f32 = 35.0;
+/// Maximum steering acceleration in m/s², limiting the turn rate
+const MISSILE_STEERING: f32 = 60.0;
+/// Radius of the explosion pushing the nearby bodies
+const MISSILE_BLAST_RADIUS: f32 = 8.0;
+/// Velocity change in m/s given at the explosion center
+const MISSILE_BLAST_IMPULSE: f32 = 10.0;
+/// Delay in s between two puffs of the missile exhaust trail
+const TRAIL_PERIOD: f32 = 0.03;
+const TRAIL_LIFETIME: f32 = 0.3;
+/// Delay in s between two retriggers of the thrust rumble
+const THRUST_SOUND_PERIOD: f32 = 0.3;
+/// Cap on the explosion sounds started in a frame
+const MAX_EXPLOSION_SOUNDS: usize = 3;
+/// Distance in m traveled by the ship between two puffs of its engine trail
+const ENGINE_TRAIL_SPACING: f32 = 0.5;
+const ENGINE_TRAIL_LIFETIME: f32 = 0.6;
+/// Trauma lost per s by the screen shake
+const SHAKE_DECAY: f32 = 1.5;
+/// Camera offset in m at full trauma
+const SHAKE_OFFSET: f32 = 1.5;
+/// Camera roll in rad at full trauma
+const SHAKE_ROLL: f32 = 0.05;
+/// Impulse in N.s on the ship hit by an asteroid giving full trauma
+const SHAKE_IMPULSE: f32 = 120.0;
+/// Distance in m from a ship beyond which explosions do not shake the screen
+const SHAKE_RADIUS: f32 = 30.0;
+/// Cap on the debris particles alive at once
+const MAX_PARTICLES: usize = 500;
+/// Number of alpha steps of the fading particles
+const PARTICLE_FADE_STEPS: usize = 4;
+/// Delay between two saucers in s
+const SAUCER_PERIOD: f32 = 30.0;
+/// Time before a saucer leaves, in s
+const SAUCER_LIFETIME: f32 = 20.0;
+const SAUCER_RADIUS: f32 = 1.5;
+/// Saucer speed cap in m/s
+const SAUCER_SPEED: f32 = 12.0;
+/// Rate at which the saucer velocity steers toward its target, in 1/s
+const SAUCER_STEERING: f32 = 2.0;
+/// Distance of the saucer seek point from the targeted ship
+const SAUCER_OFFSET: f32 = 12.0;
+const SAUCER_FIRE_PERIOD: f32 = 2.0;
+/// Maximum aim error in rad
+const SAUCER_AIM_ERROR: f32 = 0.3;
+const SAUCER_BULLET_SPEED: f32 = 30.0;
+const SAUCER_SCORE: u32 = 200;
+/// Distance of the saucer indicator from the screen edges
+const INDICATOR_MARGIN: f32 = 1.5;
+/// Width in px of the minimap, its height following the arena aspect ratio
+const MINIMAP_WIDTH: f32 = 160.0;
+/// Side in px of the ship triangles of the minimap
+const MINIMAP_SHIP_SIZE: f32 = 9.0;
+/// Side in px of the saucer and powerup blips of the minimap
+const MINIMAP_BLIP_SIZE: f32 = 4.0;
+
+/// Collision groups of the colliders. Rapier 0.2 has none, so they are a
+/// component next to the collider, and the proximity events between colliders
+/// that do not interact are dropped before reaching the game systems. The
+/// solver needs no filter, the colliders which must not push each other being
+/// sensors.
+///
+/// Each group is a bit of the membership of a collider:
+///
+/// | Bit | Group         | Interacts with                         |
+/// |-----|---------------|----------------------------------------|
+/// | 0   | Ship          | ships, asteroids, saucers, saucer bullets, powerups |
+/// | 1   | Bullet        | asteroids, saucers, obstacles          |
+/// | 2   | Missile       | asteroids, obstacles                   |
+/// | 3   | Asteroid      | ships, bullets, missiles, asteroids, saucers, mines |
+/// | 4   | Saucer        | ships, bullets, asteroids, mines       |
+/// | 5   | Saucer bullet | ships, obstacles                       |
+/// | 6   | Powerup       | ships                                  |
+/// | 7   | Mine          | asteroids, saucers                     |
+/// | 8   | Obstacle      | bullets, missiles, saucer bullets      |
+mod groups {
+    pub const SHIP: u32 = 1 << 0;
+    pub const BULLET: u32 = 1 << 1;
+    pub const MISSILE: u32 = 1 << 2;
+    pub const ASTEROID: u32 = 1 << 3;
+    pub const SAUCER: u32 = 1 << 4;
+    pub const SAUCER_BULLET: u32 = 1 << 5;
+    pub const POWERUP: u32 = 1 << 6;
+    pub const MINE: u32 = 1 << 7;
+    pub const OBSTACLE: u32 = 1 << 8;
+
+    /// Groups a collider is in, and groups it interacts with
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct CollisionGroups {
+        pub memberships: u32,
+        pub filter: u32,
+    }
+
+    impl CollisionGroups {
+        /// Groups of a collider in the single `group`, interacting as in the
+        /// table above.
+        pub fn of(group: u32) -> Self {
+            let filter = match group {
+                SHIP => SHIP | ASTEROID | SAUCER | SAUCER_BULLET | POWERUP,
+                BULLET => ASTEROID | SAUCER | OBSTACLE,
+                MISSILE => ASTEROID | OBSTACLE,
+                ASTEROID => SHIP | BULLET | MISSILE | ASTEROID | SAUCER | MINE,
+                SAUCER => SHIP | BULLET | ASTEROID | MINE,
+                MINE => ASTEROID | SAUCER,
+                SAUCER_BULLET => SHIP | OBSTACLE,
+                POWERUP => SHIP,
+                OBSTACLE => BULLET | MISSILE | SAUCER_BULLET,
+                _ => 0,
+            };
+            CollisionGroups {
+                memberships: group,
+                filter,
+            }
+        }
+
+        /// Both colliders must be in a group the other interacts with.
+        pub fn interacts_with(self, other: CollisionGroups) -> bool {
+            self.memberships & other.filter != 0 && other.memberships & self.filter != 0
+        }
+    }
+}
 
 fn main() {
+    let replay = Replay::from_args();
+    let seed = replay.seed();
+    let config = ShowcaseConfig::load_or_default();
     App::build()
-        .add_resource(WindowDescriptor {
-            title: "Spaceship 01".to_string(),
-            width: WINDOW_WIDTH,
-            height: WINDOW_HEIGHT,
-            ..Default::default()
-        })
-        .add_resource(ClearColor(Color::rgb(0.02, 0.02, 0.04)))
+        // The default plugins are those of GamePlugins, and the clear color
+        // that of the config
+        .add_plugin(
+            ShowcasePlugin::new("Spaceship 01")
+                .with_window(config.window_width, config.window_height)
+                .with_seed(seed)
+                .without_default_plugins(),
+        )
+        .add_plugin(ConfigPlugin(config))
+        // Also syncs the body positions and rotations into the transforms, so
+        // that the sprites turn with their bodies, wraps and jumps included
         .add_plugin(RapierPhysicsPlugin)
+        .add_plugin(RapierCleanupPlugin)
+        .add_plugin(HandleToEntityPlugin)
         .add_plugin(RapierRenderPlugin)
-        .add_default_plugins()
+        .add_plugin(GamePlugins)
+        .add_system(rapier_body_count_system.system())
+        .add_system(debug_hud_ship_system.system())
         .add_resource(RapierConfiguration {
             gravity: Vector2::zeros(),
             ..Default::default()
         })
+        .add_event::<ScoreEvent>()
+        .add_event::<ProximityEvent>()
+        .add_event::<ContactEvent>()
+        .add_event::<BurstEvent>()
+        .add_event::<SpawnRequest<Spawnable>>()
+        .add_resource(MaxBodies(MAX_BODIES))
+        // After the hit systems of the post update stage, which split the
+        // asteroids
+        .add_system_to_stage(stage::LAST, spawner_system.system())
+        .add_startup_system(setup_particles.system())
+        .add_system(particle_burst_system.system())
+        .add_system(particle_system.system())
+        .add_system(ship_explosion_system.system())
+        .add_system(sprite_sheet_animation_system.system())
+        .add_resource(EngineTrails(true))
+        .add_system(engine_trails_toggle_system.system())
+        .add_system(engine_trail_system.system())
+        .add_resource(GameState::Menu)
+        .add_system(menu_system.system())
+        .init_resource::<Wave>()
+        .add_resource(GameMode::Waves)
+        .init_resource::<SpawnRateCurve>()
+        .init_resource::<Survival>()
+        .add_resource(load_high_score())
+        .add_system(high_score_text_system.system())
         .add_startup_system(setup.system())
+        .add_system(thrust_sound_system.system())
+        .add_system(explosion_sound_system.system())
+        .add_system_to_stage(stage::POST_UPDATE, speaker_system.system())
+        .add_startup_system(setup_asteroids.system())
+        .add_system(starfield_system.system())
+        .add_system(config_system.system())
+        .add_system(config_gravity_system.system())
+        .init_resource::<Gamepads>()
+        .add_system(join_system.system())
+        .add_resource(ControlScheme::Rotational)
+        .init_resource::<ArenaDescriptor>()
+        .add_system(edge_behavior_system.system())
+        .add_startup_system(setup_arena_settings.system())
+        .add_system(arena_settings_system.system())
+        .add_resource(CameraMode::Fixed)
+        .add_system(camera_mode_system.system())
+        .add_system(camera_follow_system.system())
+        .add_resource(ScreenShake {
+            trauma: 0.0,
+            intensity: 1.0,
+        })
+        .add_system(screen_shake_toggle_system.system())
+        .add_system(explosion_shake_system.system())
+        .add_system(screen_shake_system.system())
+        .add_resource(DebugWindow::from_args())
+        .add_startup_system(setup_overview_window.system())
+        .add_system_to_stage(stage::POST_UPDATE, overview_marker_system.system())
+        .add_system(control_scheme_system.system())
+        .add_system(control_model_system.system())
+        .add_system_to_stage(stage::POST_UPDATE, speed_cap_system.system())
+        .add_system_to_stage(stage::POST_UPDATE, asteroid_speed_cap_system.system())
+        .add_system(speed_text_system.system())
+        .add_system(heading_system.system())
+        .add_system(notice_system.system())
+        .add_system_to_stage(stage::PRE_UPDATE, gamepad_connection_system.system())
+        .add_system_to_stage(stage::PRE_UPDATE, ship_controls_system.system())
+        // After the time update
+        .add_system_to_stage(stage::FIRST, fixed_step_system.system())
+        .add_resource(replay)
+        .add_system_to_stage(stage::PRE_UPDATE, replay_system.system())
+        .add_system_to_stage(stage::LAST, replay_exit_system.system())
         .add_system(position_system.system())
         .add_system(user_input_system.system())
-        .add_system(player_dampening_system.system())
+        .add_system(weapon_system.system())
+        .add_system(flame_system.system())
+        .add_system(hyperspace_system.system())
+        .add_system(launcher_system.system())
+        .add_system(beam_system.system())
+        .add_system(beam_glow_system.system())
+        .add_resource(TimeScale(1.0))
+        .add_system(bullet_time_system.system())
+        .add_system(tractor_system.system())
+        .add_system(tractor_beam_system.system())
+        .add_system(meter_bar_system.system())
+        .add_system(dash_system.system())
+        .add_startup_system(setup_mines.system())
+        .add_system(mine_layer_system.system())
+        .add_system(mine_system.system())
+        .add_system(mine_trigger_system.system())
+        .add_system(missile_system.system())
+        .add_system(trail_system.system())
+        // After the plugin mapping the colliders to their entities, and before
+        // the systems reading the events
+        .add_system_to_stage(stage::POST_UPDATE, proximity_events_system.system())
+        .add_system_to_stage(stage::POST_UPDATE, contact_events_system.system())
+        .add_system_to_stage(stage::POST_UPDATE, ricochet_system.system())
+        .init_resource::<StickyAsteroids>()
+        .add_startup_system(setup_sticky_setting.system())
+        .add_system(sticky_toggle_system.system())
+        .add_system_to_stage(stage::POST_UPDATE, fuse_system.system())
+        .add_system(clump_release_system.system())
+        .add_system_to_stage(stage::POST_UPDATE, missile_hit_system.system())
+        .add_system_to_stage(stage::POST_UPDATE, obstacle_hit_system.system())
+        .add_system(black_hole_spawn_system.system())
+        .add_system(black_hole_system.system())
+        .add_system(black_hole_pull_system.system())
+        // Before the update stage, as the restart system
+        .add_system_to_stage(stage::PRE_UPDATE, black_hole_horizon_system.system())
+        .add_system(asteroid_debug_system.system())
+        .add_system(mouse_debug_system.system())
+        .add_plugin(InspectorPlugin::default().with_describer(describe_spaceship))
+        // F9 cycles the control models
+        .add_plugin(CapturePlugin::default().with_key(KeyCode::F2))
+        .init_resource::<Combos>()
+        .add_system(score_system.system())
+        .add_system(combo_system.system())
+        .add_system(combo_text_system.system())
+        .add_startup_system(setup_score_popups.system())
+        .add_system(score_popup_system.system())
+        .add_resource(PowerupSpawner(Timer::from_seconds(POWERUP_PERIOD, true)))
+        .add_system(powerup_spawn_system.system())
+        .add_system(shield_system.system())
+        .add_system(powerup_drop_system.system())
+        .add_system(boost_system.system())
+        .add_system(boost_text_system.system())
+        .add_resource(SaucerSpawner(Timer::from_seconds(SAUCER_PERIOD, true)))
+        .add_startup_system(setup_saucer_indicator.system())
+        .add_system(saucer_spawn_system.system())
+        .add_system(saucer_system.system())
+        .add_system(saucer_indicator_system.system())
+        .add_startup_system(setup_minimap.system())
+        .add_system(minimap_toggle_system.system())
+        // Once the despawns of the frame are applied
+        .add_system_to_stage(stage::LAST, minimap_system.system())
+        .add_system_to_stage(stage::POST_UPDATE, bullet_hit_system.system())
+        .add_system_to_stage(stage::POST_UPDATE, powerup_pickup_system.system())
+        .add_system_to_stage(stage::POST_UPDATE, ship_collision_system.system())
+        .add_system_to_stage(stage::LAST, frame_velocity_system.system())
+        .add_system_to_stage(stage::POST_UPDATE, saucer_hit_system.system())
+        .add_system_to_stage(stage::POST_UPDATE, saucer_bullet_hit_system.system())
+        .add_system(respawn_system.system())
+        .add_system(invulnerability_system.system())
+        .add_system(hull_system.system())
+        .add_system(hull_bar_system.system())
+        .add_system(lives_display_system.system())
+        .add_system(score_text_system::<Players>.system())
+        .add_system(wave_system.system())
+        .add_system(wave_drip_system.system())
+        .add_system(spawn_warning_system.system())
+        .add_system(survival_system.system())
+        .add_system(survival_text_system.system())
+        .add_system(health_pip_system.system())
+        .add_system(wave_text_system.system())
+        .add_system(game_over_text_system.system())
+        // Before the update stage, so that no other system despawns the same
+        // entities in the same frame
+        .add_system_to_stage(stage::PRE_UPDATE, restart_system.system())
+        .add_system(damping_system.system())
+        .add_system(lifetime_system.system())
+        .add_system(arena_sync_system.system())
+        .add_system(despawn_outside_system.thread_local_system())
         .run();
 }
 
-struct Player(Entity);
+/// The default plugins, with the audio output only if there is an output
+/// device. The bevy audio plugin panics without one.
+///
+/// Rendering through WebGL2 in the browser.
+struct GamePlugins;
+
+impl Plugin for GamePlugins {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_plugin(bevy::type_registry::TypeRegistryPlugin::default())
+            .add_plugin(bevy::core::CorePlugin::default())
+            .add_plugin(bevy::transform::TransformPlugin::default())
+            .add_plugin(bevy::diagnostic::DiagnosticsPlugin::default())
+            .add_plugin(bevy::input::InputPlugin::default())
+            .add_plugin(bevy::window::WindowPlugin::default())
+            .add_plugin(bevy::asset::AssetPlugin::default())
+            .add_plugin(bevy::scene::ScenePlugin::default())
+            .add_plugin(bevy::render::RenderPlugin::default())
+            .add_plugin(bevy::sprite::SpritePlugin::default())
+            .add_plugin(bevy::pbr::PbrPlugin::default())
+            .add_plugin(bevy::ui::UiPlugin::default())
+            .add_plugin(bevy::text::TextPlugin::default())
+            .add_asset::<AudioSource>()
+            .add_asset_loader::<AudioSource, Mp3Loader>()
+            .add_resource(Speaker(try_audio_output()))
+            .add_plugin(bevy::winit::WinitPlugin::default());
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_plugin(bevy_gilrs::GilrsPlugin::default())
+            .add_plugin(bevy::gltf::GltfPlugin::default())
+            .add_plugin(bevy::wgpu::WgpuPlugin::default());
+        // Without gamepads, the browser build having no gilrs backend
+        #[cfg(target_arch = "wasm32")]
+        app.add_plugin(bevy_webgl2::WebGL2Plugin);
+    }
+}
+
+/// The audio output of the default device, if any
+#[cfg(not(target_arch = "wasm32"))]
+fn try_audio_output() -> Option<AudioOutput> {
+    // Silences the panic of rodio when there is no device
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let output = panic::catch_unwind(AudioOutput::<AudioSource>::default).ok();
+    panic::set_hook(hook);
+    if output.is_none() {
+        println!("No audio output device, playing without sound");
+    }
+    output
+}
+
+/// Silent in the browser, where a missing device aborts rather than panics
+#[cfg(target_arch = "wasm32")]
+fn try_audio_output() -> Option<AudioOutput> {
+    None
+}
+
+/// Plays the sounds, if there is an audio output
+struct Speaker(Option<AudioOutput>);
+
+impl Speaker {
+    fn play(&self, sound: &Option<Handle<AudioSource>>) {
+        if let (Some(output), Some(sound)) = (&self.0, sound) {
+            output.play(*sound);
+        }
+    }
+}
+
+/// Index of the player owning a ship or a bullet in `Players`
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct PlayerId(usize);
+
+struct PlayerState {
+    /// `None` while waiting for a respawn or out of lives
+    ship: Option<Entity>,
+    score: Score,
+    /// Countdown to the respawn of the lost ship
+    respawn: Option<Timer>,
+    /// Index in `SHIP_PROFILES` of the ship, kept for the whole run
+    profile: usize,
+}
+
+impl Default for PlayerState {
+    fn default() -> Self {
+        PlayerState {
+            ship: None,
+            score: Score::new(START_LIVES),
+            respawn: None,
+            profile: DEFAULT_PROFILE,
+        }
+    }
+}
+
+/// The players who joined, indexed by `PlayerId`
+struct Players(Vec<PlayerState>);
+
+impl Players {
+    /// The player flying `ship`
+    fn player_of(&self, ship: Entity) -> Option<usize> {
+        self.0.iter().position(|player| player.ship == Some(ship))
+    }
+
+    fn ships(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.0.iter().filter_map(|player| player.ship)
+    }
+}
+
+impl Scoreboard for Players {
+    fn player_count(&self) -> usize {
+        self.0.len()
+    }
+
+    fn score(&self, player: usize) -> Option<Score> {
+        self.0.get(player).map(|player| player.score)
+    }
+}
+
+struct KeyMap {
+    thrust: KeyCode,
+    reverse: KeyCode,
+    left: KeyCode,
+    right: KeyCode,
+    fire: KeyCode,
+    hyperspace: KeyCode,
+    missile: KeyCode,
+    beam: KeyCode,
+    tractor: KeyCode,
+    mine: KeyCode,
+    dash: KeyCode,
+    bullet_time: KeyCode,
+}
+
+/// A respawned ship, ignoring asteroids for a while
+struct Invulnerable(Timer);
+
+/// Hull points of a ship, destroyed once at zero
+struct Hull {
+    current: f32,
+    max: f32,
+    /// Runs after a hit, until the hull can take damage again
+    grace: Timer,
+}
+
+impl Hull {
+    fn new(max: f32) -> Self {
+        Hull {
+            current: max,
+            max,
+            grace: Timer {
+                finished: true,
+                ..Timer::from_seconds(HULL_GRACE, false)
+            },
+        }
+    }
+
+    /// Takes `damage` unless in the grace period, returning whether the hull
+    /// is destroyed.
+    fn damage(&mut self, damage: f32) -> bool {
+        if damage > 0.0 && self.grace.finished {
+            self.current = (self.current - damage).max(0.0);
+            self.grace.reset();
+        }
+        self.current <= 0.0
+    }
+}
+
+/// The hull bar of a player in the HUD, its child being the fill
+struct HullBar {
+    player: usize,
+}
+
+struct HullBarFill {
+    player: usize,
+    normal: Handle<ColorMaterial>,
+    low: Handle<ColorMaterial>,
+}
+
+/// Small gauges of a player in the HUD, under the hull bar
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Meter {
+    /// Weapon energy, red while overheated
+    Weapon,
+    /// Tractor energy
+    Energy,
+    /// Dash cooldown, full when available
+    Dash,
+    /// Fuel, flashing when low
+    Fuel,
+    /// Bullet time energy
+    BulletTime,
+}
+
+/// A meter of a player in the HUD, its child being the fill
+struct MeterBar {
+    player: usize,
+}
+
+struct MeterFill {
+    player: usize,
+    meter: Meter,
+}
+
+/// Materials of the weapon meter fill
+struct WeaponFill {
+    normal: Handle<ColorMaterial>,
+    overheated: Handle<ColorMaterial>,
+}
+
+/// Stats of a kind of ship, spawned by `spawn_ship`
+struct ShipProfile {
+    name: &'static str,
+    /// Over the tint of the player
+    tint: Color,
+    rotation_speed: f32,
+    rotation_speed_target: f32,
+    thrust: f32,
+    max_speed: f32,
+    max_angvel: f32,
+    /// Minimum time in s between two shots
+    fire_cooldown: f32,
+    /// Weapon energy of a full charge, in shots
+    weapon_energy: f32,
+    /// Weapon energy regained per s, the sustained rate of fire in shots per s
+    weapon_regen: f32,
+    hull: f32,
+}
+
+struct ShipMaterials {
+    /// By player, then by profile
+    ships: [[Handle<ColorMaterial>; PROFILE_COUNT]; MAX_PLAYERS],
+    flame: Handle<ColorMaterial>,
+    beam_glow: Handle<ColorMaterial>,
+}
+
+#[derive(Default)]
+struct Gamepads(HashSet<Gamepad>);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ControlScheme {
+    /// Rotation with A/D and thrust with W/S
+    Rotational,
+    /// Thrust in the world direction of WASD, the ship facing its velocity
+    Directional,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum EdgeBehavior {
+    /// The ship reappears on the opposite edge
+    Wrap,
+    /// The ship bounces back on the edges
+    Bounce,
+}
+
+impl EdgeBehavior {
+    fn name(self) -> &'static str {
+        match self {
+            EdgeBehavior::Wrap => "wrap",
+            EdgeBehavior::Bounce => "bounce",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "wrap" => Some(EdgeBehavior::Wrap),
+            "bounce" => Some(EdgeBehavior::Bounce),
+            _ => None,
+        }
+    }
+}
+
+/// Arena centered on the origin. Bodies other than the ship always wrap.
+struct ArenaDescriptor {
+    half_extents: Vec2,
+    edge: EdgeBehavior,
+    /// Restitution of the asteroids spawned from now on
+    asteroid_restitution: f32,
+    /// Centers and radii of the static rocks, none in the classic arena
+    obstacles: Vec<(Vec2, f32)>,
+    /// Bounded by static walls which everything bounces off, nothing wrapping
+    walls: bool,
+    /// With a black hole hazard
+    black_hole: bool,
+}
+
+impl Default for ArenaDescriptor {
+    /// The arena seen by the fixed camera
+    fn default() -> Self {
+        ArenaDescriptor {
+            half_extents: Vec2::new(WINDOW_WIDTH as f32, WINDOW_HEIGHT as f32)
+                * (CAMERA_SCALE / 2.0),
+            edge: EdgeBehavior::Wrap,
+            asteroid_restitution: ASTEROID_RESTITUTION,
+            obstacles: Vec::new(),
+            walls: false,
+            black_hole: false,
+        }
+    }
+}
+
+impl ArenaDescriptor {
+    /// The library arena, for the library systems
+    fn arena(&self) -> Arena {
+        Arena::centered(self.half_extents.x() * 2.0, self.half_extents.y() * 2.0)
+    }
+
+    /// Half extents of the area where a body of `radius` may be placed, inside
+    /// the walls if any.
+    fn inner_half_extents(&self, radius: f32) -> Vec2 {
+        if self.walls {
+            self.half_extents - Vec2::new(radius, radius)
+        } else {
+            self.half_extents
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CameraMode {
+    /// Centered on the arena, showing all of it
+    Fixed,
+    /// Following the ship in a larger arena
+    Follow,
+}
+
+#[derive(Default)]
+struct MainCamera {
+    /// Position of the camera, without the screen shake
+    base: Vec2,
+}
+
+/// Camera shake, proportional to the square of `trauma`
+struct ScreenShake {
+    /// Between 0 and 1, decaying over time
+    trauma: f32,
+    /// Multiplies the shake amplitude, 0 disabling it
+    intensity: f32,
+}
+
+impl ScreenShake {
+    fn add(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).min(1.0);
+    }
+}
+
+struct SpeedText;
+
+/// Short message at the bottom of the screen, cleared after a while
+struct NoticeText {
+    timer: Timer,
+}
+
+/// Thrust input applied to the ship this frame: 1 forward, -1 reverse, 0 none
+#[derive(Default)]
+struct ThrustState(i32);
+
+/// The thruster flame, child of the ship
+struct Flame;
+
+/// Whether the ships leave an engine trail, toggled with F6
+struct EngineTrails(bool);
+
+/// Engine trail of a ship
+#[derive(Default)]
+struct EngineTrail {
+    /// Exhaust point of the last puff, `None` when not thrusting
+    last: Option<Vector2<f32>>,
+}
+
+/// One of the lives icons of the UI
+struct LifeIcon;
+
+/// Asteroids streamed per s in survival mode, growing with the time survived
+struct SpawnRateCurve {
+    initial: f32,
+    /// Rate increase per s
+    growth: f32,
+    max: f32,
+}
+
+impl Default for SpawnRateCurve {
+    fn default() -> Self {
+        SpawnRateCurve {
+            initial: 0.5,
+            growth: 0.02,
+            max: 2.0,
+        }
+    }
+}
+
+impl SpawnRateCurve {
+    /// The rate after `time` s survived
+    fn rate(&self, time: f32) -> f32 {
+        (self.initial + self.growth * time).min(self.max)
+    }
+}
+
+/// Progress of a survival game
+#[derive(Default)]
+struct Survival {
+    /// Time survived in s
+    time: f32,
+    /// Asteroids due to the stream, one being sent each time it reaches 1
+    pending: f32,
+}
+
+/// The large survival timer
+struct SurvivalText;
+
+struct Wave {
+    /// The current wave of asteroids, starting at 1, 0 before the first
+    number: u32,
+    /// Runs while the next wave is announced, before it is sent
+    countdown: Option<Timer>,
+    /// Asteroids of the wave still to be released, the wave being fully
+    /// spawned once empty and without a pending warning
+    queue: VecDeque<AsteroidSize>,
+    /// Maximum speed of the asteroids of the wave, along each axis
+    speed: f32,
+    /// Repeating, releasing the next asteroid of the queue
+    drip: Timer,
+    /// Edge of the next released asteroid, going around the arena
+    edge: usize,
+    /// Whether the current wave was cleared and its bonus given
+    cleared: bool,
+}
+
+impl Default for Wave {
+    fn default() -> Self {
+        Wave {
+            number: 0,
+            countdown: None,
+            queue: VecDeque::new(),
+            speed: ASTEROID_SPEED,
+            drip: Timer::from_seconds(DRIP_PERIOD, true),
+            edge: 0,
+            cleared: false,
+        }
+    }
+}
+
+/// Flashing chevron at an arena edge, where an asteroid of the wave appears
+/// once `timer` finishes. Its children are the arms.
+struct SpawnWarning {
+    timer: Timer,
+    size: AsteroidSize,
+    position: Vector2<f32>,
+    linvel: Vector2<f32>,
+}
+
+struct WarningMaterial(Handle<ColorMaterial>);
+
+/// The centered banner announcing a wave
+struct WaveBanner;
+
+/// The banner of a cleared wave, shown during the intermission before the
+/// next one until its `Lifetime` runs out
+struct ClearedBanner;
+
+struct WaveText;
+
+/// Best results of all the games, saved in `HIGH_SCORE_FILE`
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+struct HighScore {
+    score: u32,
+    /// Longest survival in s
+    #[serde(default)]
+    survival: f32,
+}
+
+struct HighScoreText;
+
+struct GameOverText;
+
+/// Consecutive kills of a player, each within `COMBO_WINDOW` of the previous
+/// one, multiplying the points of the next kill
+struct Combo {
+    multiplier: u32,
+    /// Running while the combo goes on
+    timer: Timer,
+    /// Hull of the ship when last seen, the combo breaking when it drops
+    hull: Option<f32>,
+}
+
+impl Default for Combo {
+    fn default() -> Self {
+        Combo {
+            multiplier: 1,
+            timer: Timer {
+                finished: true,
+                ..Timer::from_seconds(COMBO_WINDOW, false)
+            },
+            hull: None,
+        }
+    }
+}
+
+impl Combo {
+    /// Counts a kill, returning the multiplier of its points.
+    fn kill(&mut self) -> u32 {
+        self.multiplier = if self.timer.finished {
+            1
+        } else {
+            (self.multiplier + 1).min(MAX_COMBO)
+        };
+        self.timer.reset();
+        self.multiplier
+    }
+
+    fn reset(&mut self) {
+        self.multiplier = 1;
+        self.timer.finished = true;
+    }
+}
+
+/// The combos by player
+#[derive(Default)]
+struct Combos([Combo; MAX_PLAYERS]);
+
+/// The combo multiplier of a player, next to its score
+struct ComboText {
+    player: usize,
+}
 
 struct Ship {
-    /// Ship rotation speed in rad/s
+    /// Rotation torque in N·m of the force model
     rotation_speed: f32,
+    /// Turn rate in rad/s at full rotation input. The force model only applies
+    /// its torque below it, the impulse model sets it directly.
+    rotation_speed_target: f32,
     /// Ship thrust N
     thrust: f32,
+    control_model: ControlModel,
+    /// Linear velocity cap in m/s
+    max_speed: f32,
+    /// Angular velocity cap in rad/s, e.g. after collisions
+    max_angvel: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ControlModel {
+    /// Continuous forces and torques
+    Force,
+    /// Thrust impulses of `thrust * dt` and direct angular velocity
+    Impulse,
+}
+
+impl Default for ControlModel {
+    fn default() -> Self {
+        ControlModel::Force
+    }
+}
+
+/// Fired by the given player
+struct Bullet(PlayerId);
+
+/// Fuel burnt by thrusting, one unit per s. The ship only rotates when empty.
+struct Fuel {
+    current: f32,
+    max: f32,
+}
+
+impl Fuel {
+    /// A full tank
+    fn new(max: f32) -> Self {
+        Fuel { current: max, max }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.current <= 0.0
+    }
+
+    /// Between 0 empty and 1 full
+    fn level(&self) -> f32 {
+        self.current / self.max
+    }
+
+    /// Adds `amount`, up to a full tank. Negative to burn.
+    fn add(&mut self, amount: f32) {
+        self.current = (self.current + amount).max(0.0).min(self.max);
+    }
+}
+
+/// The active weapon powerups of a player in the HUD
+struct BoostText {
+    player: usize,
+}
+
+/// Time to the next powerup
+struct PowerupSpawner(Timer);
+
+/// Makes asteroids bounce on the ship instead of destroying it
+struct Shield {
+    /// Remaining time in s
+    remaining: f32,
+}
+
+/// The shield visual, child of the ship
+struct ShieldBubble;
+
+/// Enemy hunting the ships, leaving once its `Lifetime` runs out
+struct Saucer {
+    /// Offset of the seek point from the targeted ship
+    offset: Vector2<f32>,
+    fire: Timer,
+}
+
+/// Fired by a saucer, hitting the ships only
+struct SaucerBullet;
+
+/// Points toward the saucer from the screen edge while it is out of view
+struct SaucerIndicator;
+
+/// Time to the next saucer
+struct SaucerSpawner(Timer);
+
+/// Alternate weapon, a beam charged while held and fired on release
+#[derive(Default)]
+struct BeamCharger {
+    /// Charging time in s, up to `BEAM_CHARGE`
+    charge: f32,
+}
+
+/// Glow at the ship nose growing with the beam charge, child of the ship
+struct BeamGlow;
+
+/// Drags an asteroid in front of the ship while held
+struct Tractor {
+    target: Option<Entity>,
+    /// Between 0 and 1, drained while active
+    energy: f32,
+}
+
+impl Default for Tractor {
+    fn default() -> Self {
+        Tractor {
+            target: None,
+            energy: 1.0,
+        }
+    }
+}
+
+/// Mine laid by a ship, triggered by the asteroids and saucers entering its
+/// sensor once armed
+struct Mine {
+    owner: PlayerId,
+    /// Finished once armed
+    arming: Timer,
+    blink: Timer,
+}
+
+/// Mine materials, blinking between the two while arming
+struct MineMaterials {
+    dark: Handle<ColorMaterial>,
+    lit: Handle<ColorMaterial>,
+}
+
+/// The tractor beam visual of a player, between its ship and the target
+struct TractorBeam {
+    player: usize,
+}
+
+/// Secondary weapon, firing homing missiles
+struct Launcher {
+    /// Finished when a missile is available
+    cooldown: Timer,
+}
+
+/// Homing missile, seeking the nearest asteroid
+struct Missile {
+    /// Player credited with the destroyed asteroid
+    owner: PlayerId,
+    target: Option<Entity>,
+    /// Time to the next puff of the exhaust trail
+    trail: Timer,
 }
 
+/// A puff of a missile exhaust, shrinking over its `Lifetime`
+struct Trail;
+
 fn setup(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
+    assets: Res<GameAssets>,
     mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
-    commands.spawn(Camera2dComponents {
-        orthographic_projection: OrthographicProjection {
-            far: 1000.0 / CAMERA_SCALE,
+    commands
+        .spawn(Camera2dComponents {
+            orthographic_projection: OrthographicProjection {
+                far: 1000.0 / CAMERA_SCALE,
+                ..Default::default()
+            },
+            transform: Transform::from_scale(CAMERA_SCALE),
             ..Default::default()
-        },
-        transform: Transform::from_scale(CAMERA_SCALE),
-        ..Default::default()
-    });
-    let texture_handle = asset_server.load("assets/spaceship.png").unwrap();
-    let body = RigidBodyBuilder::new_dynamic();
-    let collider = ColliderBuilder::ball(1.0);
+        })
+        .with(MainCamera::default())
+        .with(CursorCamera);
+    commands.spawn(UiCameraComponents::default());
+    let font_handle = assets.font;
+    // In opposite corners, the second one empty until the player joins
+    for (player, &left) in [true, false].iter().enumerate() {
+        let position = if left {
+            Rect {
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            }
+        } else {
+            Rect {
+                top: Val::Px(10.0),
+                right: Val::Px(10.0),
+                ..Default::default()
+            }
+        };
+        // The score followed by the combo multiplier
+        commands
+            .spawn(NodeComponents {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position,
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::FlexEnd,
+                    ..Default::default()
+                },
+                material: materials.add(Color::NONE.into()),
+                ..Default::default()
+            })
+            .with_children(|parent| {
+                parent
+                    .spawn(TextComponents {
+                        text: Text {
+                            value: String::new(),
+                            font: font_handle,
+                            style: TextStyle {
+                                font_size: 24.0,
+                                color: Color::WHITE,
+                            },
+                        },
+                        ..Default::default()
+                    })
+                    .with(ScoreText::new(player, false, SCORE_FLASH))
+                    .spawn(TextComponents {
+                        text: Text {
+                            value: String::new(),
+                            font: font_handle,
+                            style: TextStyle {
+                                font_size: 20.0,
+                                color: Color::WHITE,
+                            },
+                        },
+                        ..Default::default()
+                    })
+                    .with(ComboText { player });
+            });
+    }
+    // Under the lives of each player
+    let bar_background = materials.add(Color::rgb(0.2, 0.2, 0.2).into());
+    let normal = materials.add(Color::rgb(0.2, 0.8, 0.3).into());
+    let low = materials.add(Color::rgb(0.9, 0.2, 0.2).into());
+    let energy = materials.add(Color::rgb(0.3, 0.7, 1.0).into());
+    let dash = materials.add(Color::rgb(1.0, 0.6, 0.2).into());
+    let fuel = materials.add(Powerup::Fuel.color().into());
+    let bullet_time = materials.add(Color::rgb(0.7, 0.5, 1.0).into());
+    let weapon = materials.add(Color::rgb(1.0, 0.9, 0.3).into());
+    let tractor_beam = materials.add(Color::rgba(0.4, 0.8, 1.0, 0.25).into());
+    for player in 0..MAX_PLAYERS {
+        let position = if player == 0 {
+            Rect {
+                top: Val::Px(70.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            }
+        } else {
+            Rect {
+                top: Val::Px(70.0),
+                right: Val::Px(10.0),
+                ..Default::default()
+            }
+        };
+        commands
+            .spawn(NodeComponents {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position,
+                    size: Size::new(Val::Px(100.0), Val::Px(8.0)),
+                    ..Default::default()
+                },
+                material: bar_background,
+                ..Default::default()
+            })
+            .with(HullBar { player })
+            .with_children(|parent| {
+                parent
+                    .spawn(NodeComponents {
+                        style: Style {
+                            size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                            ..Default::default()
+                        },
+                        material: normal,
+                        ..Default::default()
+                    })
+                    .with(HullBarFill {
+                        player,
+                        normal,
+                        low,
+                    });
+            })
+            .spawn(TextComponents {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: Rect {
+                        top: Val::Px(108.0),
+                        ..position
+                    },
+                    ..Default::default()
+                },
+                text: Text {
+                    value: String::new(),
+                    font: font_handle,
+                    style: TextStyle {
+                        font_size: 14.0,
+                        color: Color::WHITE,
+                    },
+                },
+                ..Default::default()
+            })
+            .with(BoostText { player })
+            // Stretched between the ship and its target
+            .spawn(SpriteComponents {
+                sprite: Sprite::new(Vec2::new(TRACTOR_WIDTH, 1.0)),
+                material: tractor_beam,
+                draw: Draw {
+                    is_visible: false,
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .with(TractorBeam { player });
+        // Under the hull bar
+        for &(top, height, material, meter) in &[
+            (81.0, 4.0, energy, Meter::Energy),
+            (87.0, 3.0, dash, Meter::Dash),
+            (92.0, 3.0, fuel, Meter::Fuel),
+            (97.0, 3.0, bullet_time, Meter::BulletTime),
+            (102.0, 3.0, weapon, Meter::Weapon),
+        ] {
+            commands
+                .spawn(NodeComponents {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: Rect {
+                            top: Val::Px(top),
+                            ..position
+                        },
+                        size: Size::new(Val::Px(100.0), Val::Px(height)),
+                        ..Default::default()
+                    },
+                    material: bar_background,
+                    ..Default::default()
+                })
+                .with(MeterBar { player })
+                .with_children(|parent| {
+                    parent
+                        .spawn(NodeComponents {
+                            style: Style {
+                                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                                ..Default::default()
+                            },
+                            material,
+                            ..Default::default()
+                        })
+                        .with(MeterFill { player, meter });
+                    if meter == Meter::Weapon {
+                        parent.with(WeaponFill {
+                            normal: weapon,
+                            overheated: low,
+                        });
+                    }
+                });
+        }
+    }
     commands
-        .spawn(SpriteComponents {
-            transform: Transform::from_translation(Vec3::new(0.0, 0.0, -1.0))
-                .with_scale(1.0 / 150.0),
-            material: materials.add(texture_handle.into()),
+        .spawn(TextComponents {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    bottom: Val::Px(10.0),
+                    right: Val::Px(10.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text {
+                value: String::new(),
+                font: font_handle,
+                style: TextStyle {
+                    font_size: 18.0,
+                    color: Color::WHITE,
+                },
+            },
             ..Default::default()
         })
-        .with(Ship {
-            rotation_speed: 10.0,
-            thrust: 30.0,
+        .with(SpeedText);
+    commands
+        .spawn(TextComponents {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    bottom: Val::Px(10.0),
+                    left: Val::Px(10.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text {
+                value: String::new(),
+                font: font_handle,
+                style: TextStyle {
+                    font_size: 18.0,
+                    color: Color::WHITE,
+                },
+            },
+            ..Default::default()
         })
-        .with(body)
-        .with(collider);
-    let player_entity = commands.current_entity().unwrap();
-    commands.insert_resource(Player(player_entity));
+        .with(NoticeText {
+            timer: Timer {
+                finished: true,
+                ..Timer::from_seconds(NOTICE_DURATION, false)
+            },
+        });
+    // Full width root at the top, centering the survival timer, the wave
+    // number and the high score, the first child being at the bottom
+    commands
+        .spawn(NodeComponents {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(10.0),
+                    ..Default::default()
+                },
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            material: materials.add(Color::NONE.into()),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(TextComponents {
+                    text: Text {
+                        value: String::new(),
+                        font: font_handle,
+                        style: TextStyle {
+                            font_size: 18.0,
+                            color: Color::WHITE,
+                        },
+                    },
+                    ..Default::default()
+                })
+                .with(HighScoreText)
+                .spawn(TextComponents {
+                    text: Text {
+                        value: String::new(),
+                        font: font_handle,
+                        style: TextStyle {
+                            font_size: 24.0,
+                            color: Color::WHITE,
+                        },
+                    },
+                    ..Default::default()
+                })
+                .with(WaveText)
+                .spawn(TextComponents {
+                    text: Text {
+                        value: String::new(),
+                        font: font_handle,
+                        style: TextStyle {
+                            font_size: 48.0,
+                            color: Color::WHITE,
+                        },
+                    },
+                    ..Default::default()
+                })
+                .with(SurvivalText);
+        });
+    // Full screen root, centering the game over text and the wave banner
+    commands
+        .spawn(NodeComponents {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            material: materials.add(Color::NONE.into()),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            for &banner in &[false, true] {
+                parent.spawn(TextComponents {
+                    text: Text {
+                        value: String::new(),
+                        font: font_handle,
+                        style: TextStyle {
+                            font_size: 32.0,
+                            color: Color::WHITE,
+                        },
+                    },
+                    ..Default::default()
+                });
+                if banner {
+                    parent.with(WaveBanner);
+                } else {
+                    parent.with(GameOverText);
+                }
+            }
+            for &(line, font_size) in &[(MenuText::Options, 24.0), (MenuText::Ship, 32.0)] {
+                parent
+                    .spawn(TextComponents {
+                        text: Text {
+                            value: String::new(),
+                            font: font_handle,
+                            style: TextStyle {
+                                font_size,
+                                color: Color::WHITE,
+                            },
+                        },
+                        ..Default::default()
+                    })
+                    .with(line);
+            }
+        });
+    let texture_handle = assets.ship_texture;
+    let mut ships = [[Handle::default(); PROFILE_COUNT]; MAX_PLAYERS];
+    for (player_ships, player_tint) in ships.iter_mut().zip(&PLAYER_TINTS) {
+        for (ship, profile) in player_ships.iter_mut().zip(&SHIP_PROFILES) {
+            let tint = Color::rgb(
+                player_tint.r * profile.tint.r,
+                player_tint.g * profile.tint.g,
+                player_tint.b * profile.tint.b,
+            );
+            *ship = materials.add(ColorMaterial::modulated_texture(texture_handle, tint));
+        }
+    }
+    let ship_materials = ShipMaterials {
+        ships,
+        flame: materials.add(Color::rgb(1.0, 0.6, 0.1).into()),
+        beam_glow: materials.add(ColorMaterial::modulated_texture(
+            assets.sphere_texture,
+            Color::rgba(0.6, 1.0, 1.0, 0.8),
+        )),
+    };
+    // The ship is spawned once chosen in the menu
+    commands.insert_resource(Players(vec![PlayerState::default()]));
+    commands.insert_resource(ship_materials);
 
     //let texture_handle = asset_server
     //    .load("assets/sprite_sphere_256x256.png")
@@ -98,93 +1771,5518 @@ fn setup(
     //    .with(collider);
 }
 
-fn position_system(mut bodies: ResMut<RigidBodySet>, mut query: Query<&RigidBodyHandleComponent>) {
-    for body_handle in &mut query.iter() {
-        let mut body = bodies.get_mut(body_handle.handle()).unwrap();
-        let mut x = body.position.translation.vector.x;
-        let mut y = body.position.translation.vector.y;
-        let mut updated = false;
-        // Wrap around screen edges
-        let half_width = ARENA_WIDTH / 2.0;
-        let half_height = ARENA_HEIGHT / 2.0;
-        if x < -half_width && body.linvel.x < 0.0 {
-            x = half_width;
-            updated = true;
-        } else if x > half_width && body.linvel.x > 0.0 {
-            x = -half_width;
-            updated = true;
-        }
-        if y < -half_height && body.linvel.y < 0.0 {
-            y = half_height;
-            updated = true;
-        } else if y > half_height && body.linvel.y > 0.0 {
-            y = -half_height;
-            updated = true;
-        }
-        if updated {
-            let mut new_position = body.position.clone();
-            new_position.translation.vector.x = x;
-            new_position.translation.vector.y = y;
-            body.set_position(new_position);
-        }
-    }
+/// Spawn point of the ship of `player`, near the arena center
+fn spawn_point(player: PlayerId) -> Vec2 {
+    Vec2::new(PLAYER_SPAWN_X[player.0], 0.0)
 }
-fn player_dampening_system(
-    time: Res<Time>,
-    player: Res<Player>,
+
+/// Spawns the ship of `player` at its spawn point, at rest.
+fn spawn_ship(
+    commands: &mut Commands,
+    materials: &ShipMaterials,
+    player: PlayerId,
+    profile: usize,
+) -> Entity {
+    spawn_ship_at(commands, materials, player, profile, spawn_point(player))
+}
+
+/// Spawns the ship of `player` at `position`, at rest, with the stats of
+/// `SHIP_PROFILES[profile]`.
+fn spawn_ship_at(
+    commands: &mut Commands,
+    materials: &ShipMaterials,
+    player: PlayerId,
+    profile: usize,
+    position: Vec2,
+) -> Entity {
+    let stats = &SHIP_PROFILES[profile];
+    let (x, y) = (position.x(), position.y());
+    let (body, collider) = ship_builders(position);
+    commands
+        .spawn(SpriteComponents {
+            transform: Transform::from_translation(Vec3::new(x, y, -1.0)).with_scale(1.0 / 150.0),
+            material: materials.ships[player.0][profile],
+            ..Default::default()
+        })
+        .with(player)
+        .with(Name(format!("Player {} ship", player.0 + 1)))
+        .with(ShipControls::default())
+        .with(Ship {
+            rotation_speed: stats.rotation_speed,
+            rotation_speed_target: stats.rotation_speed_target,
+            thrust: stats.thrust,
+            control_model: ControlModel::Force,
+            max_speed: stats.max_speed,
+            max_angvel: stats.max_angvel,
+        })
+        .with(Damping {
+            linear: 0.8,
+            angular: 0.1,
+        })
+        .with(Weapon {
+            cooldown: Timer::from_seconds(stats.fire_cooldown, false),
+            projectile_speed: BULLET_SPEED,
+        })
+        .with(WeaponEnergy::new(stats.weapon_energy, stats.weapon_regen))
+        .with(Boosts::default())
+        .with(BeamCharger::default())
+        .with(Tractor::default())
+        .with(Dash::default())
+        .with(Fuel::new(FUEL_MAX))
+        .with(BulletTime::default())
+        .with(ThrustState::default())
+        .with(FrameVelocity::default())
+        .with(EngineTrail::default())
+        .with(Hull::new(stats.hull))
+        .with(Hyperspace::default())
+        .with(Launcher {
+            cooldown: Timer {
+                finished: true,
+                ..Timer::from_seconds(MISSILE_COOLDOWN, false)
+            },
+        })
+        .with(body)
+        .with(collider)
+        .with(CollisionGroups::of(groups::SHIP))
+        .with_children(|parent| {
+            // In the ship texture pixels, hidden until thrust is applied
+            parent
+                .spawn(SpriteComponents {
+                    sprite: Sprite::new(Vec2::new(60.0, 120.0)),
+                    transform: Transform::from_translation(Vec3::new(0.0, -200.0, -0.1)),
+                    material: materials.flame,
+                    draw: Draw {
+                        is_visible: false,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .with(Flame)
+                // At the nose, hidden until charging
+                .spawn(SpriteComponents {
+                    sprite: Sprite::new(Vec2::new(120.0, 120.0)),
+                    transform: Transform::from_translation(Vec3::new(0.0, 200.0, 0.1)),
+                    material: materials.beam_glow,
+                    draw: Draw {
+                        is_visible: false,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .with(BeamGlow);
+        });
+    commands.current_entity().unwrap()
+}
+
+/// Shows the flame behind the ship while thrusting, or a smaller one at the nose
+/// in reverse, flickering its size.
+fn flame_system(
+    mut ships: Query<(&ThrustState, &Children)>,
+    flames: Query<(&Flame, Mut<Transform>, Mut<Draw>)>,
+) {
+    let mut rng = thread_rng();
+    for (thrust_state, children) in &mut ships.iter() {
+        for &child in children.iter() {
+            let (mut transform, mut draw) = match (
+                flames.get_mut::<Transform>(child),
+                flames.get_mut::<Draw>(child),
+            ) {
+                (Ok(transform), Ok(draw)) => (transform, draw),
+                _ => continue,
+            };
+            draw.is_visible = thrust_state.0 != 0;
+            if !draw.is_visible {
+                continue;
+            }
+            let flicker = rng.gen_range(0.8, 1.2);
+            let (y, scale, rotation) = if thrust_state.0 > 0 {
+                (-200.0, flicker, Quat::identity())
+            } else {
+                (
+                    200.0,
+                    0.5 * flicker,
+                    Quat::from_rotation_z(std::f32::consts::PI),
+                )
+            };
+            *transform = Transform::from_translation_rotation_scale(
+                Vec3::new(0.0, y, -0.1),
+                rotation,
+                scale,
+            );
+        }
+    }
+}
+
+/// Teleports `body` to the opposite edge when leaving the arena, returns
+/// whether it did.
+fn wrap_body(body: &mut RigidBody, half_extents: Vec2) -> bool {
+    let translation = body.position.translation.vector;
+    let position = Vec2::new(translation.x, translation.y);
+    let velocity = Vec2::new(body.linvel.x, body.linvel.y);
+    let arena = Arena::centered(half_extents.x() * 2.0, half_extents.y() * 2.0);
+    match wrap_position(position, velocity, &arena) {
+        Some(wrapped) => {
+            let mut new_position = body.position;
+            new_position.translation.vector.x = wrapped.x();
+            new_position.translation.vector.y = wrapped.y();
+            body.set_position(new_position);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Reflects the velocity of `body` when leaving the arena, and clamps it back
+/// inside.
+fn bounce_body(body: &mut RigidBody, half_extents: Vec2) {
+    let mut position = body.position;
+    let translation = &mut position.translation.vector;
+    let mut updated = false;
+    for (i, half) in [half_extents.x(), half_extents.y()].iter().enumerate() {
+        if translation[i].abs() > *half && translation[i] * body.linvel[i] > 0.0 {
+            body.linvel[i] = -body.linvel[i];
+            translation[i] = translation[i].max(-half).min(*half);
+            updated = true;
+        }
+    }
+    if updated {
+        body.set_position(position);
+    }
+}
+
+/// Bodies wrapping around the arena, until they run out of wraps
+type WrappingBody<'a> = Without<
+    Ship,
+    Without<
+        DespawnOutside,
+        (
+            Entity,
+            &'a RigidBodyHandleComponent,
+            Option<Mut<'a, WrapCount>>,
+        ),
+    >,
+>;
+
+fn position_system(
+    mut commands: Commands,
+    arena: Res<ArenaDescriptor>,
     mut bodies: ResMut<RigidBodySet>,
-    query: Query<&RigidBodyHandleComponent>,
+    mut query: Query<WrappingBody>,
+    mut ships: Query<With<Ship, &RigidBodyHandleComponent>>,
 ) {
-    let elapsed = time.delta_seconds;
-    let body_handle = query.get::<RigidBodyHandleComponent>(player.0).unwrap();
-    let mut body = bodies.get_mut(body_handle.handle()).unwrap();
-    body.angvel = body.angvel * 0.1f32.powf(elapsed);
-    body.linvel = body.linvel * 0.8f32.powf(elapsed);
+    // Kept in by the walls
+    if arena.walls {
+        return;
+    }
+    for (entity, body_handle, wrap_count) in &mut query.iter() {
+        let mut body = bodies.get_mut(body_handle.handle()).unwrap();
+        if wrap_body(&mut body, arena.half_extents) {
+            if let Some(mut wrap_count) = wrap_count {
+                wrap_count.remaining = wrap_count.remaining.saturating_sub(1);
+                if wrap_count.remaining == 0 {
+                    commands.insert_one(
+                        entity,
+                        DespawnOutside {
+                            margin: DESPAWN_MARGIN,
+                        },
+                    );
+                }
+            }
+        }
+    }
+    for body_handle in &mut ships.iter() {
+        let mut body = bodies.get_mut(body_handle.handle()).unwrap();
+        match arena.edge {
+            EdgeBehavior::Wrap => {
+                wrap_body(&mut body, arena.half_extents);
+            }
+            EdgeBehavior::Bounce => bounce_body(&mut body, arena.half_extents),
+        }
+    }
 }
 
-fn user_input_system(
+/// Keeps the library `Arena` the same as the arena descriptor, for the
+/// `DespawnOutside` entities.
+fn arena_sync_system(descriptor: Res<ArenaDescriptor>, mut arena: ResMut<Arena>) {
+    let synced = descriptor.arena();
+    if arena.size() != synced.size() || arena.origin != synced.origin {
+        *arena = synced;
+    }
+}
+
+/// Registers the arena settings shown in the window title, started with
+/// `--walls` and `--edge bounce`.
+fn setup_arena_settings(mut settings: ResMut<Settings>, mut arena: ResMut<ArenaDescriptor>) {
+    arena.walls = settings.register("walls", arena.walls);
+    let edge = settings.register("edge", arena.edge.name().to_string());
+    match EdgeBehavior::from_name(&edge) {
+        Some(edge) => arena.edge = edge,
+        None => println!("Unknown edge {:?}, either wrap or bounce", edge),
+    }
+}
+
+fn arena_settings_system(arena: Res<ArenaDescriptor>, mut settings: ResMut<Settings>) {
+    settings.set("walls", arena.walls);
+    settings.set("edge", arena.edge.name().to_string());
+}
+
+/// Switches the ship edge behavior with F8.
+fn edge_behavior_system(
     input: Res<Input<KeyCode>>,
-    player: Res<Player>,
-    mut bodies: ResMut<RigidBodySet>,
-    query: Query<(&RigidBodyHandleComponent, &Ship)>,
+    mut arena: ResMut<ArenaDescriptor>,
+    mut notices: Query<(Mut<NoticeText>, Mut<Text>)>,
+) {
+    // Nothing reaching the edges of the bounded arena
+    if !input.just_pressed(KeyCode::F8) || arena.walls {
+        return;
+    }
+    arena.edge = match arena.edge {
+        EdgeBehavior::Wrap => EdgeBehavior::Bounce,
+        EdgeBehavior::Bounce => EdgeBehavior::Wrap,
+    };
+    for (mut notice, mut text) in &mut notices.iter() {
+        text.value = format!("Ship edges: {:?}", arena.edge);
+        notice.timer.reset();
+    }
+}
+
+/// Keeps track of the connected gamepads.
+fn gamepad_connection_system(
+    mut reader: Local<EventReader<GamepadEvent>>,
+    gamepad_events: Res<Events<GamepadEvent>>,
+    mut gamepads: ResMut<Gamepads>,
+) {
+    for GamepadEvent(gamepad, event_type) in reader.iter(&gamepad_events) {
+        match event_type {
+            GamepadEventType::Connected => {
+                println!("Gamepad {} connected", gamepad.0);
+                gamepads.0.insert(*gamepad);
+            }
+            GamepadEventType::Disconnected => {
+                println!("Gamepad {} disconnected", gamepad.0);
+                gamepads.0.remove(gamepad);
+            }
+        }
+    }
+}
+
+/// Merges the keyboard and gamepads inputs of each player into the controls
+/// of their ship.
+fn ship_controls_system(
+    keys: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    players: Res<Players>,
+    mut ships: Query<(&PlayerId, Mut<ShipControls>)>,
+) {
+    for (player, mut controls) in &mut ships.iter() {
+        update_controls(
+            &mut controls,
+            &KEY_MAPS[player.0],
+            &keys,
+            // Gamepads beyond the players count go to the last player
+            gamepads
+                .0
+                .iter()
+                .filter(|gamepad| gamepad.0.min(players.0.len() - 1) == player.0),
+            &buttons,
+            &axes,
+        );
+    }
+}
+
+fn update_controls<'a>(
+    controls: &mut ShipControls,
+    key_map: &KeyMap,
+    keys: &Input<KeyCode>,
+    gamepads: impl Iterator<Item = &'a Gamepad>,
+    buttons: &Input<GamepadButton>,
+    axes: &Axis<GamepadAxis>,
 ) {
-    let mut rotation = 0;
+    let mut rotation = 0.0;
     let mut thrust = 0;
-    if input.pressed(KeyCode::W) {
-        thrust += 1
+    let mut direction = Vec2::zero();
+    let mut fire = keys.pressed(key_map.fire);
+    let mut hyperspace = keys.just_pressed(key_map.hyperspace);
+    let mut missile = keys.just_pressed(key_map.missile);
+    let mut beam = keys.pressed(key_map.beam);
+    let mut tractor = keys.pressed(key_map.tractor);
+    let mut mine = keys.just_pressed(key_map.mine);
+    let mut dash = keys.just_pressed(key_map.dash);
+    let mut bullet_time = keys.pressed(key_map.bullet_time);
+    if keys.pressed(key_map.thrust) {
+        thrust += 1;
+        *direction.y_mut() += 1.0;
+    }
+    if keys.pressed(key_map.reverse) {
+        thrust -= 1;
+        *direction.y_mut() -= 1.0;
     }
-    if input.pressed(KeyCode::S) {
-        thrust -= 1
+    if keys.pressed(key_map.left) {
+        rotation += 1.0;
+        *direction.x_mut() -= 1.0;
     }
-    if input.pressed(KeyCode::A) {
-        rotation += 1
+    if keys.pressed(key_map.right) {
+        rotation -= 1.0;
+        *direction.x_mut() += 1.0;
     }
-    if input.pressed(KeyCode::D) {
-        rotation -= 1
+    for &gamepad in gamepads {
+        let pressed = |button| buttons.pressed(GamepadButton(gamepad, button));
+        if pressed(GamepadButtonType::RightTrigger2) || pressed(GamepadButtonType::South) {
+            thrust += 1;
+        }
+        fire |= pressed(GamepadButtonType::West) || pressed(GamepadButtonType::RightTrigger);
+        hyperspace |= buttons.just_pressed(GamepadButton(gamepad, GamepadButtonType::North));
+        missile |= buttons.just_pressed(GamepadButton(gamepad, GamepadButtonType::East));
+        beam |= pressed(GamepadButtonType::LeftTrigger2);
+        tractor |= pressed(GamepadButtonType::LeftTrigger);
+        mine |= buttons.just_pressed(GamepadButton(gamepad, GamepadButtonType::DPadDown));
+        dash |= buttons.just_pressed(GamepadButton(gamepad, GamepadButtonType::RightThumb));
+        bullet_time |= pressed(GamepadButtonType::LeftThumb);
+        let stick_x = axes
+            .get(&GamepadAxis(gamepad, GamepadAxisType::LeftStickX))
+            .unwrap_or(0.0);
+        let stick_y = axes
+            .get(&GamepadAxis(gamepad, GamepadAxisType::LeftStickY))
+            .unwrap_or(0.0);
+        if stick_x.abs() > GAMEPAD_DEADZONE {
+            // Stick to the right turns clockwise
+            rotation -= stick_x;
+        }
+        let stick = Vec2::new(stick_x, stick_y);
+        if stick.length() > GAMEPAD_DEADZONE {
+            direction += stick;
+        }
     }
-    if rotation != 0 || thrust != 0 {
-        let body_handle = query.get::<RigidBodyHandleComponent>(player.0).unwrap();
+    controls.rotation = rotation.max(-1.0).min(1.0);
+    controls.thrust = thrust.max(-1).min(1);
+    controls.direction = if direction.length() > 1.0 {
+        direction.normalize()
+    } else {
+        direction
+    };
+    controls.fire = fire;
+    controls.hyperspace = hyperspace;
+    controls.missile = missile;
+    controls.beam = beam;
+    controls.tractor = tractor;
+    controls.mine = mine;
+    controls.dash = dash;
+    controls.bullet_time = bullet_time;
+}
+
+/// Ships with the inputs of their player
+type ControlledShip<'a> = (
+    Entity,
+    &'a Ship,
+    &'a ShipControls,
+    Mut<'a, ThrustState>,
+    &'a RigidBodyHandleComponent,
+);
+
+/// Applies the rotation and thrust inputs, thrusting burning fuel.
+///
+/// Fuel regenerates while not thrusting, and at zero the thrust input is
+/// ignored, rotation still working.
+fn user_input_system(
+    time: Res<Time>,
+    scheme: Res<ControlScheme>,
+    time_scale: Res<TimeScale>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut query: Query<ControlledShip>,
+    fuels: Query<Mut<Fuel>>,
+) {
+    for (entity, ship, controls, mut thrust_state, body_handle) in &mut query.iter() {
+        let (rotation, mut thrust) = match *scheme {
+            ControlScheme::Rotational => (controls.rotation, controls.thrust),
+            // Turning is left to the heading system
+            ControlScheme::Directional => (0.0, (controls.direction != Vec2::zero()) as i32),
+        };
+        // Ships without a tank thrust freely
+        if let Ok(mut fuel) = fuels.get_mut::<Fuel>(entity) {
+            if thrust != 0 && fuel.is_empty() {
+                thrust = 0;
+            }
+            if thrust != 0 {
+                fuel.add(-time.delta_seconds);
+            } else {
+                fuel.add(FUEL_REGEN * time.delta_seconds);
+            }
+        }
+        thrust_state.0 = thrust;
+        if rotation == 0.0 && thrust == 0 {
+            continue;
+        }
         let mut body = bodies.get_mut(body_handle.handle()).unwrap();
-        let ship = query.get::<Ship>(player.0).unwrap();
-        //println!(
-        //    "Body world_inv_inertia_sqrt {:?}",
-        //    body.world_inv_inertia_sqrt
-        //);
-        //println!(
-        //    "Body mass_properties.inv_mass {:?}",
-        //    body.mass_properties.inv_mass
-        //);
-        if rotation != 0 {
-            let rotation = rotation as f32 * ship.rotation_speed;
+        if rotation != 0.0 {
             body.wake_up(true);
-            body.apply_torque(rotation);
+            // Turning at full speed in real time whatever the time scale
+            let target = rotation * ship.rotation_speed_target / time_scale.0;
+            match ship.control_model {
+                // Spinning up to the target turn rate, rather than
+                // accumulating torque faster than the damping bleeds it
+                ControlModel::Force => {
+                    if (target - body.angvel) * rotation > 0.0 {
+                        body.apply_torque(rotation * ship.rotation_speed / time_scale.0);
+                    }
+                }
+                ControlModel::Impulse => body.angvel = target,
+            }
         }
         if thrust != 0 {
-            let force = body.position.rotation.transform_vector(&Vector2::y())
-                * thrust as f32
-                * ship.thrust;
+            let direction = match *scheme {
+                ControlScheme::Rotational => {
+                    body.position.rotation.transform_vector(&Vector2::y()) * thrust as f32
+                }
+                ControlScheme::Directional => {
+                    Vector2::new(controls.direction.x(), controls.direction.y())
+                }
+            };
+            body.wake_up(true);
+            match ship.control_model {
+                // Over the physics step, shorter than the frame in bullet time
+                ControlModel::Force => body.apply_force(direction * ship.thrust / time_scale.0),
+                // The same velocity change as the force over this frame
+                ControlModel::Impulse => {
+                    body.apply_impulse(direction * ship.thrust * time.delta_seconds)
+                }
+            }
+        }
+    }
+}
+
+/// Switches the control model of the ships with F9.
+fn control_model_system(
+    input: Res<Input<KeyCode>>,
+    mut model: Local<ControlModel>,
+    mut ships: Query<Mut<Ship>>,
+    mut notices: Query<(Mut<NoticeText>, Mut<Text>)>,
+) {
+    if input.just_pressed(KeyCode::F9) {
+        *model = match *model {
+            ControlModel::Force => ControlModel::Impulse,
+            ControlModel::Impulse => ControlModel::Force,
+        };
+        for (mut notice, mut text) in &mut notices.iter() {
+            text.value = format!("Control model: {:?}", *model);
+            notice.timer.reset();
+        }
+    }
+    // Also applies the model to respawned ships
+    for mut ship in &mut ships.iter() {
+        if ship.control_model != *model {
+            ship.control_model = *model;
+        }
+    }
+}
+
+/// Switches the control scheme with F4.
+fn control_scheme_system(
+    input: Res<Input<KeyCode>>,
+    mut scheme: ResMut<ControlScheme>,
+    mut notices: Query<(Mut<NoticeText>, Mut<Text>)>,
+) {
+    if !input.just_pressed(KeyCode::F4) {
+        return;
+    }
+    *scheme = match *scheme {
+        ControlScheme::Rotational => ControlScheme::Directional,
+        ControlScheme::Directional => ControlScheme::Rotational,
+    };
+    for (mut notice, mut text) in &mut notices.iter() {
+        text.value = format!("Controls: {:?}", *scheme);
+        notice.timer.reset();
+    }
+}
+
+/// Turns the ship toward its velocity in the directional scheme.
+fn heading_system(
+    time: Res<Time>,
+    scheme: Res<ControlScheme>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut query: Query<With<Ship, &RigidBodyHandleComponent>>,
+) {
+    if *scheme != ControlScheme::Directional {
+        return;
+    }
+    for body_handle in &mut query.iter() {
+        let mut body = bodies.get_mut(body_handle.handle()).unwrap();
+        if body.linvel.norm() < HEADING_MIN_SPEED {
+            continue;
+        }
+        // The ship nose is along its y axis
+        let target = body.linvel.y.atan2(body.linvel.x) - std::f32::consts::FRAC_PI_2;
+        let angle = body.position.rotation.angle();
+        let delta = UnitComplex::new(target - angle).angle();
+        let max_step = HEADING_TURN_RATE * time.delta_seconds;
+        let mut position = body.position;
+        position.rotation = UnitComplex::new(angle + delta.max(-max_step).min(max_step));
+        body.set_position(position);
+        body.angvel = 0.0;
+    }
+}
+
+fn notice_system(time: Res<Time>, mut notices: Query<(Mut<NoticeText>, Mut<Text>)>) {
+    for (mut notice, mut text) in &mut notices.iter() {
+        if notice.timer.finished {
+            continue;
+        }
+        notice.timer.tick(time.delta_seconds);
+        if notice.timer.finished {
+            text.value.clear();
+        }
+    }
+}
+
+/// Cycles the ship of the first player with the left and right arrows before
+/// the first game, picks the waves mode with 1 and the survival one with 2,
+/// toggles the obstacles with O, the walls with W and the black hole with G,
+/// and starts it with Enter. Replays start right away with the default ship in
+/// the classic arena and the waves mode, which the input log does not record.
+fn menu_system(
+    mut commands: Commands,
+    input: Res<Input<KeyCode>>,
+    replay: Res<Replay>,
+    materials: Res<ShipMaterials>,
+    obstacle_material: Res<ObstacleMaterial>,
+    mut state: ResMut<GameState>,
+    mut mode: ResMut<GameMode>,
+    mut players: ResMut<Players>,
+    mut arena: ResMut<ArenaDescriptor>,
+    mut query: Query<(&MenuText, Mut<Text>)>,
+) {
+    if *state != GameState::Menu {
+        return;
+    }
+    let player = &mut players.0[0];
+    let menu = MenuInput::new(&input);
+    player.profile = menu.next_ship(player.profile, PROFILE_COUNT);
+    *mode = menu.mode.unwrap_or(*mode);
+    if menu.obstacles {
+        arena.obstacles = if arena.obstacles.is_empty() {
+            obstacle_layout(ArenaDescriptor::default().half_extents)
+        } else {
+            Vec::new()
+        };
+    }
+    arena.walls ^= menu.walls;
+    arena.black_hole ^= menu.black_hole;
+    let start = menu.start || !replay.is_off();
+    if start {
+        if !replay.is_off() {
+            *mode = GameMode::Waves;
+            arena.obstacles.clear();
+            arena.walls = false;
+            arena.black_hole = false;
+        }
+        for &(center, radius) in &arena.obstacles {
+            spawn_obstacle(&mut commands, obstacle_material.0, center, radius);
+        }
+        if arena.walls {
+            spawn_walls(&mut commands, obstacle_material.1, arena.half_extents);
+        }
+        player.ship = Some(spawn_ship(
+            &mut commands,
+            &materials,
+            PlayerId(0),
+            player.profile,
+        ));
+        *state = GameState::Playing;
+    }
+    let profile = &SHIP_PROFILES[player.profile];
+    for (line, mut text) in &mut query.iter() {
+        let value = match line {
+            _ if start => String::new(),
+            MenuText::Ship => format!(
+                "< {} >  thrust {:.0}, {:.1} turn/s, {:.0} shots/s ({:.0} sustained), hull {:.0}",
+                profile.name.to_uppercase(),
+                profile.thrust,
+                profile.rotation_speed_target / (2.0 * std::f32::consts::PI),
+                1.0 / profile.fire_cooldown,
+                profile.weapon_regen / SHOT_ENERGY,
+                profile.hull,
+            ),
+            MenuText::Options => options_line(
+                *mode,
+                !arena.obstacles.is_empty(),
+                arena.walls,
+                arena.black_hole,
+            ),
+        };
+        if text.value != value {
+            text.value = value;
+        }
+    }
+}
+
+/// Seeded layout of the rocks of the obstacles arena, as centers and radii,
+/// clear of each other and of the spawn points.
+fn obstacle_layout(half_extents: Vec2) -> Vec<(Vec2, f32)> {
+    let mut rng = StdRng::seed_from_u64(OBSTACLE_SEED);
+    let count = rng.gen_range(MIN_OBSTACLES, MAX_OBSTACLES + 1);
+    let spawn_points: Vec<Vec2> = (0..MAX_PLAYERS)
+        .map(|player| spawn_point(PlayerId(player)))
+        .collect();
+    let (half_width, half_height) = (
+        half_extents.x() - OBSTACLE_EDGE_MARGIN,
+        half_extents.y() - OBSTACLE_EDGE_MARGIN,
+    );
+    let mut rocks: Vec<(Vec2, f32)> = Vec::new();
+    for _ in 0..count * OBSTACLE_TRIES {
+        if rocks.len() == count {
+            break;
+        }
+        let radius = rng.gen_range(OBSTACLE_RADIUS.0, OBSTACLE_RADIUS.1);
+        let center = Vec2::new(
+            rng.gen_range(-half_width, half_width),
+            rng.gen_range(-half_height, half_height),
+        );
+        let clear = rocks.iter().all(|&(other, other_radius)| {
+            (center - other).length() > radius + other_radius + OBSTACLE_GAP
+        }) && spawn_points
+            .iter()
+            .all(|&point| (center - point).length() > radius + OBSTACLE_SPAWN_CLEARANCE);
+        if clear {
+            rocks.push((center, radius));
+        }
+    }
+    rocks
+}
+
+/// A static rock of the obstacles arena or a wall of the bounded one, which
+/// bounces the ships and the asteroids off and stops the bullets
+struct Obstacle;
+
+/// Of the rocks, then of the walls
+struct ObstacleMaterial(Handle<ColorMaterial>, Handle<ColorMaterial>);
+
+fn spawn_obstacle(
+    commands: &mut Commands,
+    material: Handle<ColorMaterial>,
+    center: Vec2,
+    radius: f32,
+) {
+    let (x, y) = (center.x(), center.y());
+    commands
+        .spawn(SpriteComponents {
+            // The rock fills most of the width of the texture
+            transform: Transform::from_translation(Vec3::new(x, y, -2.5))
+                .with_scale(2.2 * radius / 101.0),
+            material,
+            ..Default::default()
+        })
+        .with(Obstacle)
+        .with(RigidBodyBuilder::new_static().translation(x, y))
+        .with(ColliderBuilder::ball(radius).restitution(OBSTACLE_RESTITUTION))
+        .with(CollisionGroups::of(groups::OBSTACLE));
+}
+
+/// Spawns the 4 walls of the bounded arena, just outside of `half_extents`.
+fn spawn_walls(commands: &mut Commands, material: Handle<ColorMaterial>, half_extents: Vec2) {
+    let (half_width, half_height) = (half_extents.x(), half_extents.y());
+    let half_thickness = WALL_THICKNESS / 2.0;
+    // Centers and half sizes, the horizontal walls covering the corners
+    let walls = [
+        (
+            Vec2::new(0.0, half_height + half_thickness),
+            Vec2::new(half_width + WALL_THICKNESS, half_thickness),
+        ),
+        (
+            Vec2::new(0.0, -half_height - half_thickness),
+            Vec2::new(half_width + WALL_THICKNESS, half_thickness),
+        ),
+        (
+            Vec2::new(half_width + half_thickness, 0.0),
+            Vec2::new(half_thickness, half_height),
+        ),
+        (
+            Vec2::new(-half_width - half_thickness, 0.0),
+            Vec2::new(half_thickness, half_height),
+        ),
+    ];
+    for &(center, half_size) in &walls {
+        commands
+            .spawn(SpriteComponents {
+                sprite: Sprite::new(half_size * 2.0),
+                transform: Transform::from_translation(center.extend(-2.5)),
+                material,
+                ..Default::default()
+            })
+            .with(Obstacle)
+            .with(RigidBodyBuilder::new_static().translation(center.x(), center.y()))
+            .with(
+                ColliderBuilder::cuboid(half_size.x(), half_size.y())
+                    .restitution(OBSTACLE_RESTITUTION),
+            )
+            .with(CollisionGroups::of(groups::OBSTACLE));
+    }
+}
+
+/// A hazard pulling all the bodies toward it, and destroying those reaching its
+/// event horizon. Its children are the core and the accretion disk.
+struct BlackHole {
+    /// Repeating, moving the black hole once finished
+    relocation: Timer,
+    /// Where it moves next
+    next: Vec2,
+}
+
+/// Turns slowly around the black hole, its children being the dust clouds
+struct AccretionDisk;
+
+/// Shows where the black hole moves next, shortly before it does
+struct BlackHoleMarker;
+
+/// A random point clear of the spawn points and the obstacles for the black
+/// hole, or the last candidate if there is no room.
+fn black_hole_position(rng: &mut impl Rng, arena: &ArenaDescriptor) -> Vec2 {
+    let half_extents = arena.inner_half_extents(BLACK_HOLE_HORIZON);
+    let mut position = Vec2::zero();
+    for _ in 0..OBSTACLE_TRIES {
+        position = Vec2::new(
+            rng.gen_range(-half_extents.x(), half_extents.x()),
+            rng.gen_range(-half_extents.y(), half_extents.y()),
+        );
+        let clear = (0..MAX_PLAYERS).all(|player| {
+            (position - spawn_point(PlayerId(player))).length() > BLACK_HOLE_CLEARANCE
+        }) && arena.obstacles.iter().all(|&(center, radius)| {
+            (position - center).length() > radius + BLACK_HOLE_MIN_DISTANCE
+        });
+        if clear {
+            break;
+        }
+    }
+    position
+}
+
+/// Spawns the black hole once the game starts with the hazard on.
+fn black_hole_spawn_system(
+    mut commands: Commands,
+    state: Res<GameState>,
+    arena: Res<ArenaDescriptor>,
+    mut assets: ResMut<GameAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut rng: ResMut<SeededRng>,
+    mut holes: Query<With<BlackHole, Entity>>,
+) {
+    if *state != GameState::Playing || !arena.black_hole || holes.iter().iter().next().is_some() {
+        return;
+    }
+    let mut tinted = |color| assets.sphere_tinted(color, &mut materials);
+    let halo = tinted(Color::rgba(0.5, 0.3, 0.8, 0.3));
+    let core = tinted(Color::rgb(0.0, 0.0, 0.0));
+    let dust = tinted(Color::rgba(1.0, 0.6, 0.2, 0.7));
+    let marker = tinted(Color::rgba(0.5, 0.3, 0.8, 0.5));
+    let rng = &mut rng.0;
+    let position = black_hole_position(rng, &arena);
+    let sphere_scale = |diameter: f32| diameter / 256.0;
+    commands
+        .spawn((
+            BlackHole {
+                relocation: Timer::from_seconds(BLACK_HOLE_PERIOD, true),
+                next: black_hole_position(rng, &arena),
+            },
+            Transform::from_translation(position.extend(-2.2)),
+            GlobalTransform::default(),
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(SpriteComponents {
+                    transform: Transform::from_scale(sphere_scale(6.0 * BLACK_HOLE_HORIZON)),
+                    material: halo,
+                    ..Default::default()
+                })
+                .spawn(SpriteComponents {
+                    transform: Transform::from_translation(Vec3::new(0.0, 0.0, 0.2))
+                        .with_scale(sphere_scale(2.0 * BLACK_HOLE_HORIZON)),
+                    material: core,
+                    ..Default::default()
+                })
+                .spawn((
+                    AccretionDisk,
+                    Transform::from_translation(Vec3::new(0.0, 0.0, 0.1)),
+                    GlobalTransform::default(),
+                ))
+                .with_children(|parent| {
+                    for i in 0..10 {
+                        let angle = i as f32 * std::f32::consts::PI / 5.0;
+                        // Uneven, so that the turning shows
+                        let distance = BLACK_HOLE_HORIZON * (1.3 + 0.1 * (i % 3) as f32);
+                        parent.spawn(SpriteComponents {
+                            transform: Transform::from_translation(Vec3::new(
+                                distance * angle.cos(),
+                                distance * angle.sin(),
+                                0.0,
+                            ))
+                            .with_scale(sphere_scale(0.5 + 0.15 * (i % 4) as f32)),
+                            material: dust,
+                            ..Default::default()
+                        });
+                    }
+                });
+        })
+        .spawn(SpriteComponents {
+            transform: Transform::from_translation(Vec3::zero())
+                .with_scale(sphere_scale(2.0 * BLACK_HOLE_HORIZON)),
+            material: marker,
+            draw: Draw {
+                is_visible: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .with(BlackHoleMarker);
+}
+
+/// Pulls the dynamic bodies toward the black hole with an inverse square
+/// acceleration, clamped near its center.
+fn black_hole_pull_system(
+    mut bodies: ResMut<RigidBodySet>,
+    mut holes: Query<With<BlackHole, &Transform>>,
+    mut handles: Query<&RigidBodyHandleComponent>,
+) {
+    for transform in &mut holes.iter() {
+        let center = transform.translation();
+        let center = Vector2::new(center.x(), center.y());
+        for body_handle in &mut handles.iter() {
+            let mut body = match bodies.get_mut(body_handle.handle()) {
+                Some(body) if body.is_dynamic() => body,
+                _ => continue,
+            };
+            let offset = center - body.position.translation.vector;
+            let distance = offset.norm().max(BLACK_HOLE_MIN_DISTANCE);
+            let acceleration = BLACK_HOLE_PULL / (distance * distance);
+            let force = offset.normalize() * acceleration * body.mass();
+            if force.x.is_finite() && force.y.is_finite() {
+                body.apply_force(force);
+                body.wake_up(true);
+            }
+        }
+    }
+}
+
+/// Destroys the bodies whose center is within the event horizon of the black
+/// hole, a ship costing a life. Their rapier objects are removed by the
+/// cleanup plugin.
+fn black_hole_horizon_system(
+    mut commands: Commands,
+    bodies: Res<RigidBodySet>,
+    mut players: ResMut<Players>,
+    mut state: ResMut<GameState>,
+    mut bursts: ResMut<Events<BurstEvent>>,
+    mut holes: Query<With<BlackHole, &Transform>>,
+    mut handles: Query<(Entity, &RigidBodyHandleComponent)>,
+    asteroids: Query<&Asteroid>,
+) {
+    if *state != GameState::Playing {
+        return;
+    }
+    for transform in &mut holes.iter() {
+        let center = transform.translation();
+        let center = Vector2::new(center.x(), center.y());
+        for (entity, body_handle) in &mut handles.iter() {
+            let position = match bodies.get(body_handle.handle()) {
+                Some(body) if body.is_dynamic() => body.position.translation.vector,
+                _ => continue,
+            };
+            if (position - center).norm() > BLACK_HOLE_HORIZON {
+                continue;
+            }
+            if let Some(index) = players.player_of(entity) {
+                bursts.send(BurstEvent {
+                    position,
+                    kind: BurstKind::Ship,
+                });
+                lose_ship(&mut commands, &mut players, &mut state, index);
+                continue;
+            }
+            let size = asteroids
+                .get::<Asteroid>(entity)
+                .map_or(AsteroidSize::Small, |asteroid| asteroid.size);
+            bursts.send(BurstEvent {
+                position,
+                kind: BurstKind::Asteroid(size),
+            });
+            commands.despawn_recursive(entity);
+        }
+    }
+}
+
+/// The marker of the next position of the black hole
+type HoleMarker<'a> = With<BlackHoleMarker, (Mut<'a, Transform>, Mut<'a, Draw>)>;
+
+/// Moves the black hole every `BLACK_HOLE_PERIOD`, showing where it goes
+/// shortly before, and turns its accretion disk.
+fn black_hole_system(
+    time: Res<Time>,
+    arena: Res<ArenaDescriptor>,
+    mut rng: ResMut<SeededRng>,
+    mut holes: Query<(Mut<BlackHole>, Mut<Transform>)>,
+    mut disks: Query<With<AccretionDisk, Mut<Transform>>>,
+    mut markers: Query<HoleMarker>,
+) {
+    let rng = &mut rng.0;
+    for (mut hole, mut transform) in &mut holes.iter() {
+        hole.relocation.tick(time.delta_seconds);
+        if hole.relocation.just_finished {
+            let z = transform.translation().z();
+            transform.set_translation(hole.next.extend(z));
+            hole.next = black_hole_position(rng, &arena);
+        }
+        let warning = hole.relocation.elapsed > BLACK_HOLE_PERIOD - BLACK_HOLE_WARNING;
+        for (mut marker_transform, mut draw) in &mut markers.iter() {
+            marker_transform.set_translation(hole.next.extend(-2.3));
+            draw.is_visible = warning && (hole.relocation.elapsed * 4.0) as u32 % 2 == 0;
+        }
+    }
+    for mut transform in &mut disks.iter() {
+        transform.rotate(Quat::from_rotation_z(ACCRETION_SPIN * time.delta_seconds));
+    }
+}
+
+/// Stops the bullets, missiles and saucer bullets hitting an obstacle.
+fn obstacle_hit_system(
+    mut reader: Local<EventReader<ProximityEvent>>,
+    proximity_events: Res<Events<ProximityEvent>>,
+    h_to_e: Res<ColliderHandleToEntity>,
+    obstacles: Query<&Obstacle>,
+    lifetimes: Query<Mut<Lifetime>>,
+) {
+    for proximity_event in reader.iter(&proximity_events) {
+        if proximity_event.new_status != Proximity::Intersecting {
+            continue;
+        }
+        let (e1, e2) = match (
+            h_to_e.get(proximity_event.collider1),
+            h_to_e.get(proximity_event.collider2),
+        ) {
+            (Some(e1), Some(e2)) => (e1, e2),
+            _ => continue,
+        };
+        let projectile = if obstacles.get::<Obstacle>(e1).is_ok() {
+            e2
+        } else if obstacles.get::<Obstacle>(e2).is_ok() {
+            e1
+        } else {
+            continue;
+        };
+        // Left to the lifetime system to despawn, as the hit systems may
+        // already do so this frame
+        if let Ok(mut lifetime) = lifetimes.get_mut::<Lifetime>(projectile) {
+            lifetime.0 = 0.0;
+        }
+    }
+}
+
+/// Spawns the ship of a second player when 2 is pressed during a game.
+fn join_system(
+    mut commands: Commands,
+    input: Res<Input<KeyCode>>,
+    state: Res<GameState>,
+    materials: Res<ShipMaterials>,
+    mut players: ResMut<Players>,
+    mut notices: Query<(Mut<NoticeText>, Mut<Text>)>,
+) {
+    if !input.just_pressed(KeyCode::Key2)
+        || *state != GameState::Playing
+        || players.0.len() >= MAX_PLAYERS
+    {
+        return;
+    }
+    let player = PlayerId(players.0.len());
+    players.0.push(PlayerState {
+        ship: Some(spawn_ship(
+            &mut commands,
+            &materials,
+            player,
+            DEFAULT_PROFILE,
+        )),
+        ..Default::default()
+    });
+    for (mut notice, mut text) in &mut notices.iter() {
+        text.value = format!("Player {} joined", player.0 + 1);
+        notice.timer.reset();
+    }
+}
+
+/// Ships able to fire
+type ArmedShip<'a> = (
+    &'a PlayerId,
+    &'a ShipControls,
+    &'a RigidBodyHandleComponent,
+    &'a Boosts,
+    Mut<'a, Weapon>,
+    Mut<'a, WeaponEnergy>,
+);
+
+/// Fires a bullet from the ship nose while fire is held, at most once per
+/// cooldown, or a fan of 3 with the spread shot.
+///
+/// Each bullet uses `SHOT_ENERGY` of the weapon energy, the weapon not firing
+/// while empty or overheated.
+///
+/// Ricochet bullets are solid, to bounce off the static colliders. Without
+/// solver filtering in rapier 0.2, they also bounce off the ships, and are
+/// stopped by the ricochet system when they do.
+fn weapon_system(
+    time: Res<Time>,
+    speaker: Res<Speaker>,
+    assets: Res<GameAssets>,
+    bodies: Res<RigidBodySet>,
+    mut requests: ResMut<Events<SpawnRequest<Spawnable>>>,
+    mut query: Query<ArmedShip>,
+) {
+    for (player, controls, body_handle, boosts, mut weapon, mut energy) in &mut query.iter() {
+        let angles = match weapon.update(&mut energy, boosts, controls.fire, time.delta_seconds) {
+            Some(angles) => angles,
+            None => continue,
+        };
+        speaker.play(&assets.sounds.laser);
+        let body = bodies.get(body_handle.handle()).unwrap();
+        let nose = body.position.rotation.transform_vector(&Vector2::y());
+        let position = body.position.translation.vector + nose * 1.2;
+        for &angle in angles {
+            let direction = UnitComplex::new(angle).transform_vector(&nose);
+            let linvel = direction * weapon.projectile_speed + body.linvel;
+            requests.send(SpawnRequest::new(
+                Spawnable::Bullet {
+                    player: *player,
+                    ricochet: boosts.ricochet > 0.0,
+                },
+                to_vec2(position),
+                to_vec2(linvel),
+            ));
+        }
+    }
+}
+
+/// Ships able to jump
+type JumpingShip<'a> = (
+    &'a ShipControls,
+    &'a RigidBodyHandleComponent,
+    Mut<'a, Hyperspace>,
+    Mut<'a, Transform>,
+);
+
+/// Teleports the ship at rest to a random place clear of asteroids with the
+/// hyperspace key.
+fn hyperspace_system(
+    time: Res<Time>,
+    arena: Res<ArenaDescriptor>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut rng: ResMut<SeededRng>,
+    mut ships: Query<JumpingShip>,
+    mut asteroids: Query<(&Asteroid, &RigidBodyHandleComponent)>,
+) {
+    let rng = &mut rng.0;
+    for (controls, body_handle, mut hyperspace, mut transform) in &mut ships.iter() {
+        hyperspace.cooldown.tick(time.delta_seconds);
+        if !controls.hyperspace || !hyperspace.cooldown.finished {
+            continue;
+        }
+        let obstacles: Vec<(Vector2<f32>, f32)> = asteroids
+            .iter()
+            .iter()
+            .filter_map(|(asteroid, body_handle)| {
+                let body = bodies.get(body_handle.handle())?;
+                Some((body.position.translation.vector, asteroid.size.radius()))
+            })
+            .collect();
+        let half_extents = arena.inner_half_extents(SHIP_RADIUS);
+        let destination = match hyperspace_destination(rng, half_extents, &obstacles) {
+            Some(destination) => destination,
+            None => {
+                println!("No room for a hyperspace jump");
+                continue;
+            }
+        };
+        hyperspace.cooldown.reset();
+        let mut body = bodies.get_mut(body_handle.handle()).unwrap();
+        let mut position = body.position;
+        position.translation.vector = destination;
+        body.set_position(position);
+        body.linvel = Vector2::zeros();
+        body.angvel = 0.0;
+        body.wake_up(true);
+        // The sprite is only synced after the next physics step
+        let z = transform.translation().z();
+        transform.set_translation(Vec3::new(destination.x, destination.y, z));
+    }
+}
+
+/// Ships able to fire missiles
+type LaunchingShip<'a> = (
+    &'a PlayerId,
+    &'a ShipControls,
+    &'a RigidBodyHandleComponent,
+    Mut<'a, Launcher>,
+);
+
+/// Launches a homing missile from the ship nose with the missile key, at most
+/// once per cooldown.
+fn launcher_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut assets: ResMut<GameAssets>,
+    bodies: Res<RigidBodySet>,
+    mut query: Query<LaunchingShip>,
+) {
+    for (player, controls, body_handle, mut launcher) in &mut query.iter() {
+        launcher.cooldown.tick(time.delta_seconds);
+        if !controls.missile || !launcher.cooldown.finished {
+            continue;
+        }
+        launcher.cooldown.reset();
+        let body = bodies.get(body_handle.handle()).unwrap();
+        let direction = body.position.rotation.transform_vector(&Vector2::y());
+        let position = body.position.translation.vector + direction * 1.5;
+        let linvel = body.linvel + direction * (MISSILE_SPEED / 2.0);
+        let material = assets.material_for(Color::rgb(0.9, 0.9, 1.0), &mut materials);
+        commands
+            .spawn(SpriteComponents {
+                // Along the y axis, as the ship
+                sprite: Sprite::new(Vec2::new(0.25, 1.0)),
+                transform: Transform::from_translation(Vec3::new(position.x, position.y, -1.0)),
+                material,
+                ..Default::default()
+            })
+            .with(Missile {
+                owner: *player,
+                target: None,
+                trail: Timer::from_seconds(TRAIL_PERIOD, true),
+            })
+            .with(Lifetime(MISSILE_LIFETIME))
+            .with(
+                RigidBodyBuilder::new_dynamic()
+                    .translation(position.x, position.y)
+                    .rotation(body.position.rotation.angle())
+                    .linvel(linvel.x, linvel.y),
+            )
+            .with(ColliderBuilder::ball(0.3).sensor(true))
+            .with(CollisionGroups::of(groups::MISSILE));
+    }
+}
+
+/// Whether the segment from `start` to `end` passes within `radius` of
+/// `center`.
+fn segment_hits_circle(
+    start: Vector2<f32>,
+    end: Vector2<f32>,
+    center: Vector2<f32>,
+    radius: f32,
+) -> bool {
+    let segment = end - start;
+    let length_squared = segment.norm_squared();
+    // Parameter of the point of the segment closest to the center
+    let t = if length_squared > std::f32::EPSILON {
+        ((center - start).dot(&segment) / length_squared)
+            .max(0.0)
+            .min(1.0)
+    } else {
+        0.0
+    };
+    (start + segment * t - center).norm() <= radius
+}
+
+/// Charges the beam while held, and on release fires it from the ship nose,
+/// destroying every asteroid along it within a range growing with the charge.
+fn beam_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut assets: ResMut<GameAssets>,
+    mut score_events: ResMut<Events<ScoreEvent>>,
+    mut bursts: ResMut<Events<BurstEvent>>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut ships: Query<(
+        &PlayerId,
+        &ShipControls,
+        &RigidBodyHandleComponent,
+        Mut<BeamCharger>,
+    )>,
+    // Bosses are only worn down by bullets
+    mut asteroids: Query<Without<Health, (Entity, &Asteroid, &RigidBodyHandleComponent)>>,
+) {
+    let mut destroyed = HashSet::new();
+    for (player, controls, body_handle, mut charger) in &mut ships.iter() {
+        if controls.beam {
+            charger.charge = (charger.charge + time.delta_seconds).min(BEAM_CHARGE);
+            continue;
+        }
+        if charger.charge <= 0.0 {
+            continue;
+        }
+        let ratio = charger.charge / BEAM_CHARGE;
+        charger.charge = 0.0;
+        let range = BEAM_RANGE * ratio;
+        let (start, end, angle) = {
+            let mut body = bodies.get_mut(body_handle.handle()).unwrap();
+            let direction = body.position.rotation.transform_vector(&Vector2::y());
+            let start = body.position.translation.vector + direction * 1.2;
+            let recoil = -direction * BEAM_RECOIL * ratio * body.mass();
+            body.apply_impulse(recoil);
             body.wake_up(true);
-            body.apply_force(force);
+            (
+                start,
+                start + direction * range,
+                body.position.rotation.angle(),
+            )
+        };
+        for (asteroid, component, asteroid_handle) in &mut asteroids.iter() {
+            let center = bodies
+                .get(asteroid_handle.handle())
+                .unwrap()
+                .position
+                .translation
+                .vector;
+            let size = component.size;
+            if !segment_hits_circle(start, end, center, size.radius())
+                || !destroyed.insert(asteroid)
+            {
+                continue;
+            }
+            commands.despawn(asteroid);
+            score_events.send(ScoreEvent {
+                player: player.0,
+                points: size.score(),
+                position: center,
+            });
+            bursts.send(BurstEvent {
+                position: center,
+                kind: BurstKind::Asteroid(size),
+            });
+        }
+        let middle = (start + end) / 2.0;
+        let material = assets.material_for(Color::rgb(0.8, 1.0, 1.0), &mut materials);
+        commands
+            .spawn(SpriteComponents {
+                sprite: Sprite::new(Vec2::new(BEAM_WIDTH, range)),
+                transform: Transform::from_translation_rotation(
+                    Vec3::new(middle.x, middle.y, -0.5),
+                    Quat::from_rotation_z(angle),
+                ),
+                material,
+                ..Default::default()
+            })
+            .with(Lifetime(BEAM_FLASH));
+    }
+}
+
+/// Grows the glow at the ship nose with the beam charge.
+fn beam_glow_system(
+    mut ships: Query<(&BeamCharger, &Children)>,
+    glows: Query<(&BeamGlow, Mut<Transform>, Mut<Draw>)>,
+) {
+    for (charger, children) in &mut ships.iter() {
+        for &child in children.iter() {
+            let (mut transform, mut draw) = match (
+                glows.get_mut::<Transform>(child),
+                glows.get_mut::<Draw>(child),
+            ) {
+                (Ok(transform), Ok(draw)) => (transform, draw),
+                _ => continue,
+            };
+            draw.is_visible = charger.charge > 0.0;
+            transform.set_scale(charger.charge / BEAM_CHARGE);
+        }
+    }
+}
+
+/// Asteroid caught by the tractor of the ship at `position` facing `direction`:
+/// the held one while in range, else the nearest one in the cone in front.
+fn tractor_target(
+    position: Vector2<f32>,
+    direction: Vector2<f32>,
+    held: Option<Entity>,
+    bodies: &RigidBodySet,
+    asteroids: &mut Query<(Entity, &Asteroid, &RigidBodyHandleComponent)>,
+) -> Option<Entity> {
+    let mut nearest: Option<(Entity, f32)> = None;
+    for (asteroid, _, body_handle) in &mut asteroids.iter() {
+        let offset = match bodies.get(body_handle.handle()) {
+            Some(body) => body.position.translation.vector - position,
+            None => continue,
+        };
+        let distance = offset.norm();
+        if distance > TRACTOR_RANGE {
+            continue;
+        }
+        if Some(asteroid) == held {
+            return held;
+        }
+        let in_cone =
+            distance < std::f32::EPSILON || offset.dot(&direction) / distance >= TRACTOR_CONE.cos();
+        if in_cone && nearest.map_or(true, |(_, nearest)| distance < nearest) {
+            nearest = Some((asteroid, distance));
+        }
+    }
+    nearest.map(|(asteroid, _)| asteroid)
+}
+
+/// While held, pulls an asteroid toward a point in front of the ship with a
+/// spring, the ship being tugged the other way. Releasing lets the asteroid fly
+/// on, e.g. thrown at others.
+fn tractor_system(
+    time: Res<Time>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut ships: Query<(&ShipControls, &RigidBodyHandleComponent, Mut<Tractor>)>,
+    mut asteroids: Query<(Entity, &Asteroid, &RigidBodyHandleComponent)>,
+) {
+    for (controls, body_handle, mut tractor) in &mut ships.iter() {
+        if !controls.tractor || tractor.energy <= 0.0 {
+            tractor.target = None;
+            tractor.energy = (tractor.energy + TRACTOR_RECHARGE * time.delta_seconds).min(1.0);
+            continue;
         }
+        tractor.energy = (tractor.energy - TRACTOR_DRAIN * time.delta_seconds).max(0.0);
+        let ship = bodies.get(body_handle.handle()).unwrap();
+        let direction = ship.position.rotation.transform_vector(&Vector2::y());
+        let (position, linvel, ship_mass) =
+            (ship.position.translation.vector, ship.linvel, ship.mass());
+        tractor.target =
+            tractor_target(position, direction, tractor.target, &bodies, &mut asteroids);
+        let target_handle = match tractor
+            .target
+            .and_then(|target| asteroids.get::<RigidBodyHandleComponent>(target).ok())
+        {
+            Some(target_handle) => target_handle.handle(),
+            None => continue,
+        };
+        let force = {
+            let mut target = bodies.get_mut(target_handle).unwrap();
+            // Scaled by the reduced mass, so that the spring acts the same on the
+            // distance between the bodies whatever their masses
+            let mass = ship_mass * target.mass() / (ship_mass + target.mass());
+            let anchor = position + direction * TRACTOR_HOLD;
+            let force = ((anchor - target.position.translation.vector) * TRACTOR_STIFFNESS
+                - (target.linvel - linvel) * TRACTOR_DAMPING)
+                * mass;
+            target.apply_force(force);
+            target.wake_up(true);
+            force
+        };
+        let mut ship = bodies.get_mut(body_handle.handle()).unwrap();
+        ship.apply_force(-force);
+        ship.wake_up(true);
+    }
+}
+
+/// Tractor beam visuals
+type StretchedBeam<'a> = (
+    &'a TractorBeam,
+    Mut<'a, Sprite>,
+    Mut<'a, Transform>,
+    Mut<'a, Draw>,
+);
+
+/// Stretches the tractor beam of each player between its ship and the caught
+/// asteroid, hidden when there is none.
+fn tractor_beam_system(
+    players: Res<Players>,
+    bodies: Res<RigidBodySet>,
+    tractors: Query<(&Tractor, &RigidBodyHandleComponent)>,
+    targets: Query<&RigidBodyHandleComponent>,
+    mut beams: Query<StretchedBeam>,
+) {
+    for (beam, mut sprite, mut transform, mut draw) in &mut beams.iter() {
+        let ends = players
+            .0
+            .get(beam.player)
+            .and_then(|player| player.ship)
+            .and_then(|ship| {
+                let tractor = tractors.get::<Tractor>(ship).ok()?;
+                let ship_handle = tractors.get::<RigidBodyHandleComponent>(ship).ok()?;
+                let target_handle = targets
+                    .get::<RigidBodyHandleComponent>(tractor.target?)
+                    .ok()?;
+                Some((
+                    bodies
+                        .get(ship_handle.handle())?
+                        .position
+                        .translation
+                        .vector,
+                    bodies
+                        .get(target_handle.handle())?
+                        .position
+                        .translation
+                        .vector,
+                ))
+            });
+        draw.is_visible = ends.is_some();
+        let (start, end) = match ends {
+            Some(ends) => ends,
+            None => continue,
+        };
+        let offset = end - start;
+        let middle = (start + end) / 2.0;
+        sprite.size = Vec2::new(TRACTOR_WIDTH, offset.norm());
+        *transform = Transform::from_translation_rotation(
+            Vec3::new(middle.x, middle.y, -1.5),
+            // The sprite length being along y
+            Quat::from_rotation_z(offset.y.atan2(offset.x) - std::f32::consts::FRAC_PI_2),
+        );
+    }
+}
+
+/// Shows the tractor energy, dash cooldown, fuel, bullet time energy and
+/// weapon energy of the ship of each player, the meters of the players who did
+/// not join being hidden.
+///
+/// The weapon meter turns red while overheated, refilling from empty.
+fn meter_bar_system(
+    time: Res<Time>,
+    players: Res<Players>,
+    tractors: Query<&Tractor>,
+    dashes: Query<&Dash>,
+    fuels: Query<&Fuel>,
+    bullet_times: Query<&BulletTime>,
+    weapons: Query<&WeaponEnergy>,
+    mut bars: Query<(&MeterBar, Mut<Draw>)>,
+    mut fills: Query<(&MeterFill, Mut<Style>, Mut<Draw>)>,
+    mut weapon_fills: Query<(&MeterFill, &WeaponFill, Mut<Handle<ColorMaterial>>)>,
+) {
+    for (bar, mut draw) in &mut bars.iter() {
+        draw.is_visible = bar.player < players.0.len();
+    }
+    for (fill, mut style, mut draw) in &mut fills.iter() {
+        let ship = match players.0.get(fill.player).and_then(|player| player.ship) {
+            Some(ship) => ship,
+            None => {
+                draw.is_visible = false;
+                continue;
+            }
+        };
+        let level = match fill.meter {
+            Meter::Energy => tractors
+                .get::<Tractor>(ship)
+                .map_or(0.0, |tractor| tractor.energy),
+            Meter::Dash => dashes
+                .get::<Dash>(ship)
+                .map_or(0.0, |dash| dash.readiness()),
+            Meter::Fuel => fuels.get::<Fuel>(ship).map_or(0.0, |fuel| fuel.level()),
+            Meter::BulletTime => bullet_times
+                .get::<BulletTime>(ship)
+                .map_or(0.0, |bullet_time| bullet_time.energy),
+            Meter::Weapon => weapons
+                .get::<WeaponEnergy>(ship)
+                .map_or(0.0, |energy| energy.level()),
+        };
+        let flashing = fill.meter == Meter::Fuel && level < FUEL_LOW;
+        draw.is_visible = level > 0.0
+            && (!flashing
+                || (time.seconds_since_startup as f32 / (FUEL_BLINK / 2.0)) as u32 % 2 == 0);
+        style.size.width = Val::Percent(100.0 * level);
+    }
+    for (fill, colors, mut material) in &mut weapon_fills.iter() {
+        let overheated = players
+            .0
+            .get(fill.player)
+            .and_then(|player| player.ship)
+            .and_then(|ship| weapons.get::<WeaponEnergy>(ship).ok())
+            .map_or(false, |energy| energy.overheat > 0.0);
+        *material = if overheated {
+            colors.overheated
+        } else {
+            colors.normal
+        };
+    }
+}
+
+fn setup_mines(
+    mut commands: Commands,
+    mut assets: ResMut<GameAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let mut material = |color| assets.sphere_tinted(color, &mut materials);
+    commands.insert_resource(MineMaterials {
+        dark: material(Color::rgb(0.4, 0.1, 0.1)),
+        lit: material(Color::rgb(1.0, 0.2, 0.2)),
+    });
+}
+
+/// Lays a mine behind the ship with the mine key, unless its player already has
+/// `MAX_MINES` of them.
+fn mine_layer_system(
+    mut commands: Commands,
+    materials: Res<MineMaterials>,
+    bodies: Res<RigidBodySet>,
+    mut ships: Query<(&PlayerId, &ShipControls, &RigidBodyHandleComponent)>,
+    mut mines: Query<&Mine>,
+) {
+    for (player, controls, body_handle) in &mut ships.iter() {
+        if !controls.mine
+            || mines
+                .iter()
+                .iter()
+                .filter(|mine| mine.owner == *player)
+                .count()
+                >= MAX_MINES
+        {
+            continue;
+        }
+        let body = bodies.get(body_handle.handle()).unwrap();
+        let direction = body.position.rotation.transform_vector(&Vector2::y());
+        let position = body.position.translation.vector - direction * 1.5;
+        commands
+            .spawn(SpriteComponents {
+                transform: Transform::from_translation(Vec3::new(position.x, position.y, -1.5))
+                    .with_scale(1.0 / 256.0),
+                material: materials.dark,
+                ..Default::default()
+            })
+            .with(Mine {
+                owner: *player,
+                arming: Timer::from_seconds(MINE_ARMING, false),
+                blink: Timer::from_seconds(MINE_BLINK, true),
+            })
+            .with(Lifetime(MINE_LIFETIME))
+            // Dropped with the ship velocity, stopping at once
+            .with(Damping {
+                linear: 0.01,
+                angular: 0.01,
+            })
+            .with(
+                RigidBodyBuilder::new_dynamic()
+                    .translation(position.x, position.y)
+                    .linvel(body.linvel.x, body.linvel.y),
+            )
+            .with(ColliderBuilder::ball(MINE_SENSOR_RADIUS).sensor(true))
+            .with(CollisionGroups::of(groups::MINE));
+    }
+}
+
+/// Blinks the mines while arming, lit once armed.
+fn mine_system(
+    time: Res<Time>,
+    materials: Res<MineMaterials>,
+    mut mines: Query<(Mut<Mine>, Mut<Handle<ColorMaterial>>)>,
+) {
+    for (mut mine, mut material) in &mut mines.iter() {
+        mine.arming.tick(time.delta_seconds);
+        mine.blink.tick(time.delta_seconds);
+        let lit = if mine.arming.finished {
+            true
+        } else if mine.blink.just_finished {
+            *material != materials.lit
+        } else {
+            continue;
+        };
+        let wanted = if lit { materials.lit } else { materials.dark };
+        if *material != wanted {
+            *material = wanted;
+        }
+    }
+}
+
+/// Explodes the armed mines entered by an asteroid or a saucer, destroying the
+/// asteroids in the blast and pushing the ships around away.
+///
+/// In the update stage, on the events of the previous frame, so that the
+/// destroyed asteroids are gone before the other hit systems.
+fn mine_trigger_system(
+    mut commands: Commands,
+    mut reader: Local<EventReader<ProximityEvent>>,
+    proximity_events: Res<Events<ProximityEvent>>,
+    h_to_e: Res<ColliderHandleToEntity>,
+    mut score_events: ResMut<Events<ScoreEvent>>,
+    mut bursts: ResMut<Events<BurstEvent>>,
+    mut bodies: ResMut<RigidBodySet>,
+    mines: Query<(&Mine, &RigidBodyHandleComponent, Mut<Lifetime>)>,
+    triggers: Query<Or<(&Asteroid, &Saucer)>>,
+    // Bosses only take the blast
+    mut asteroids: Query<Without<Health, (Entity, &Asteroid, &RigidBodyHandleComponent)>>,
+    mut ships: Query<With<Ship, &RigidBodyHandleComponent>>,
+) {
+    let mut exploded = HashSet::new();
+    let mut destroyed = HashSet::new();
+    for proximity_event in reader.iter(&proximity_events) {
+        if proximity_event.new_status != Proximity::Intersecting {
+            continue;
+        }
+        let e1 = h_to_e.get(proximity_event.collider1);
+        let e2 = h_to_e.get(proximity_event.collider2);
+        let (mine, other) = match (e1, e2) {
+            (Some(e1), Some(e2)) if mines.get::<Mine>(e1).is_ok() => (e1, e2),
+            (Some(e1), Some(e2)) if mines.get::<Mine>(e2).is_ok() => (e2, e1),
+            _ => continue,
+        };
+        let (owner, mine_handle) = match (
+            mines.get::<Mine>(mine),
+            mines.get::<RigidBodyHandleComponent>(mine),
+        ) {
+            (Ok(component), Ok(mine_handle)) if component.arming.finished => {
+                (component.owner, mine_handle.handle())
+            }
+            _ => continue,
+        };
+        let triggered =
+            triggers.get::<Asteroid>(other).is_ok() || triggers.get::<Saucer>(other).is_ok();
+        if !triggered || !exploded.insert(mine) {
+            continue;
+        }
+        // Left to the lifetime system to despawn, as it may already do so
+        // this frame
+        commands.remove_one::<Mine>(mine);
+        if let Ok(mut lifetime) = mines.get_mut::<Lifetime>(mine) {
+            lifetime.0 = 0.0;
+        }
+        let center = match bodies.get(mine_handle) {
+            Some(body) => body.position.translation.vector,
+            None => continue,
+        };
+        bursts.send(BurstEvent {
+            position: center,
+            kind: BurstKind::Mine,
+        });
+        for (asteroid, component, body_handle) in &mut asteroids.iter() {
+            let position = match bodies.get(body_handle.handle()) {
+                Some(body) => body.position.translation.vector,
+                None => continue,
+            };
+            let size = component.size;
+            if (position - center).norm() > MINE_BLAST_RADIUS + size.radius()
+                || !destroyed.insert(asteroid)
+            {
+                continue;
+            }
+            commands.despawn(asteroid);
+            score_events.send(ScoreEvent {
+                player: owner.0,
+                points: size.score(),
+                position,
+            });
+            bursts.send(BurstEvent {
+                position,
+                kind: BurstKind::Asteroid(size),
+            });
+        }
+        for body_handle in &mut ships.iter() {
+            let mut body = match bodies.get_mut(body_handle.handle()) {
+                Some(body) => body,
+                None => continue,
+            };
+            let offset = body.position.translation.vector - center;
+            let distance = offset.norm();
+            if distance > MINE_PUSH_RADIUS || distance < std::f32::EPSILON {
+                continue;
+            }
+            let impulse =
+                offset / distance * (MINE_PUSH * (1.0 - distance / MINE_PUSH_RADIUS) * body.mass());
+            body.apply_impulse(impulse);
+            body.wake_up(true);
+        }
+    }
+}
+
+/// Steers the missiles toward their target asteroid, picking the nearest one
+/// when they have none, and leaves their exhaust trail.
+fn missile_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut assets: ResMut<GameAssets>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut missiles: Query<(Mut<Missile>, &RigidBodyHandleComponent)>,
+    mut asteroids: Query<With<Asteroid, (Entity, &RigidBodyHandleComponent)>>,
+) {
+    let targets: Vec<(Entity, Vector2<f32>)> = asteroids
+        .iter()
+        .iter()
+        .filter_map(|(entity, body_handle)| {
+            bodies
+                .get(body_handle.handle())
+                .map(|body| (entity, body.position.translation.vector))
+        })
+        .collect();
+    let material = assets.material_for(Color::rgb(1.0, 0.6, 0.2), &mut materials);
+    let dt = time.delta_seconds;
+    for (mut missile, body_handle) in &mut missiles.iter() {
+        let mut body = bodies.get_mut(body_handle.handle()).unwrap();
+        let position = body.position.translation.vector;
+        // A destroyed target is no longer listed
+        let target = match missile
+            .target
+            .and_then(|target| targets.iter().find(|(entity, _)| *entity == target))
+        {
+            Some(&(_, target)) => Some(target),
+            None => {
+                let nearest = targets.iter().min_by(|(_, a), (_, b)| {
+                    let (a, b) = ((a - position).norm(), (b - position).norm());
+                    a.partial_cmp(&b).unwrap()
+                });
+                missile.target = nearest.map(|&(entity, _)| entity);
+                nearest.map(|&(_, target)| target)
+            }
+        };
+        if let Some(target) = target {
+            let desired = (target - position)
+                .try_normalize(std::f32::EPSILON)
+                .unwrap_or_else(Vector2::zeros)
+                * MISSILE_SPEED;
+            let mut steering = desired - body.linvel;
+            let norm = steering.norm();
+            if norm > MISSILE_STEERING * dt {
+                steering *= MISSILE_STEERING * dt / norm;
+            }
+            body.linvel += steering;
+        }
+        let speed = body.linvel.norm();
+        if speed > MISSILE_SPEED {
+            body.linvel *= MISSILE_SPEED / speed;
+        }
+        // The missile nose is along its y axis
+        if speed > std::f32::EPSILON {
+            let mut isometry = body.position;
+            isometry.rotation =
+                UnitComplex::new(body.linvel.y.atan2(body.linvel.x) - std::f32::consts::FRAC_PI_2);
+            body.set_position(isometry);
+        }
+        body.angvel = 0.0;
+        body.wake_up(true);
+        missile.trail.tick(dt);
+        if missile.trail.just_finished {
+            let behind = body.position.rotation.transform_vector(&-Vector2::y()) * 0.6;
+            let puff = position + behind;
+            commands
+                .spawn(SpriteComponents {
+                    sprite: Sprite::new(Vec2::new(0.3, 0.3)),
+                    transform: Transform::from_translation(Vec3::new(puff.x, puff.y, -1.1)),
+                    material,
+                    ..Default::default()
+                })
+                .with(Trail)
+                .with(Lifetime(TRAIL_LIFETIME));
+        }
+    }
+}
+
+fn trail_system(mut puffs: Query<(&Trail, &Lifetime, Mut<Transform>)>) {
+    for (_, lifetime, mut transform) in &mut puffs.iter() {
+        transform.set_scale((lifetime.0 / TRAIL_LIFETIME).max(0.0));
+    }
+}
+
+/// Blows the missiles up on the asteroids they touch, destroying them
+/// whatever their size and pushing the nearby bodies away.
+fn missile_hit_system(
+    mut commands: Commands,
+    mut reader: Local<EventReader<ProximityEvent>>,
+    proximity_events: Res<Events<ProximityEvent>>,
+    h_to_e: Res<ColliderHandleToEntity>,
+    mut score_events: ResMut<Events<ScoreEvent>>,
+    mut bursts: ResMut<Events<BurstEvent>>,
+    mut bodies: ResMut<RigidBodySet>,
+    missiles: Query<(&Missile, &RigidBodyHandleComponent)>,
+    asteroids: Query<&Asteroid>,
+    bosses: Query<&Health>,
+) {
+    let mut exploded = HashSet::new();
+    let mut destroyed = HashSet::new();
+    for proximity_event in reader.iter(&proximity_events) {
+        if proximity_event.new_status != Proximity::Intersecting {
+            continue;
+        }
+        let e1 = h_to_e.get(proximity_event.collider1);
+        let e2 = h_to_e.get(proximity_event.collider2);
+        let (missile, asteroid) = match (e1, e2) {
+            (Some(e1), Some(e2)) if missiles.get::<Missile>(e1).is_ok() => (e1, e2),
+            (Some(e1), Some(e2)) if missiles.get::<Missile>(e2).is_ok() => (e2, e1),
+            _ => continue,
+        };
+        let size = match asteroids.get::<Asteroid>(asteroid) {
+            Ok(asteroid) => asteroid.size,
+            Err(_) => continue,
+        };
+        if exploded.contains(&missile) || !destroyed.insert(asteroid) {
+            continue;
+        }
+        exploded.insert(missile);
+        commands.despawn(missile);
+        // Bosses only take the blast
+        let boss = bosses.get::<Health>(asteroid).is_ok();
+        let missile_handle = missiles
+            .get::<RigidBodyHandleComponent>(missile)
+            .unwrap()
+            .handle();
+        let center = bodies
+            .get(missile_handle)
+            .unwrap()
+            .position
+            .translation
+            .vector;
+        if !boss {
+            commands.despawn(asteroid);
+            let owner = missiles.get::<Missile>(missile).unwrap().owner;
+            score_events.send(ScoreEvent {
+                player: owner.0,
+                points: size.score(),
+                position: center,
+            });
+        }
+        bursts.send(BurstEvent {
+            position: center,
+            // A mere puff on a boss
+            kind: BurstKind::Asteroid(if boss { AsteroidSize::Small } else { size }),
+        });
+        for (_, mut body) in bodies.iter_mut() {
+            if !body.is_dynamic() {
+                continue;
+            }
+            let offset = body.position.translation.vector - center;
+            let distance = offset.norm();
+            if distance > MISSILE_BLAST_RADIUS || distance < std::f32::EPSILON {
+                continue;
+            }
+            let impulse = offset / distance
+                * (MISSILE_BLAST_IMPULSE * (1.0 - distance / MISSILE_BLAST_RADIUS) * body.mass());
+            body.apply_impulse(impulse);
+            body.wake_up(true);
+        }
+    }
+}
+
+struct Asteroid {
+    size: AsteroidSize,
+}
+
+/// Remaining hits of a boss asteroid, only worn down by bullets
+struct Health(u32);
+
+/// One hit point of a boss, floating above it
+struct HealthPip {
+    boss: Entity,
+    index: u32,
+}
+
+/// What the spawn requests of the game create
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Spawnable {
+    Asteroid {
+        size: AsteroidSize,
+        angvel: f32,
+    },
+    /// A bullet of the ship of `player`, solid and bouncing with the ricochet
+    /// boost
+    Bullet {
+        player: PlayerId,
+        ricochet: bool,
+    },
+    SaucerBullet,
+}
+
+fn to_vec2(v: Vector2<f32>) -> Vec2 {
+    Vec2::new(v.x, v.y)
+}
+
+/// Requests an asteroid, spawned at the end of the frame.
+fn request_asteroid(
+    requests: &mut Events<SpawnRequest<Spawnable>>,
+    size: AsteroidSize,
+    position: Vector2<f32>,
+    linvel: Vector2<f32>,
+    angvel: f32,
+) {
+    requests.send(SpawnRequest::new(
+        Spawnable::Asteroid { size, angvel },
+        to_vec2(position),
+        to_vec2(linvel),
+    ));
+}
+
+/// Spawns the requested asteroids and bullets, in the order of the requests,
+/// up to the `MaxBodies` cap.
+fn spawner_system(
+    mut commands: Commands,
+    mut reader: Local<EventReader<SpawnRequest<Spawnable>>>,
+    requests: Res<Events<SpawnRequest<Spawnable>>>,
+    max_bodies: Res<MaxBodies>,
+    arena: Res<ArenaDescriptor>,
+    asteroid_material: Res<AsteroidMaterial>,
+    mut assets: ResMut<GameAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut colliders: ResMut<ColliderSet>,
+) {
+    let alive = bodies.len();
+    for request in capped_requests(&mut reader, &requests, alive, &max_bodies) {
+        let position = Vector2::new(request.position.x(), request.position.y());
+        let linvel = Vector2::new(request.velocity.x(), request.velocity.y());
+        match request.kind {
+            Spawnable::Asteroid { size, angvel } => {
+                spawn_asteroid(
+                    &mut commands,
+                    &mut bodies,
+                    &mut colliders,
+                    asteroid_material.0,
+                    arena.asteroid_restitution,
+                    size,
+                    position,
+                    linvel,
+                    angvel,
+                );
+                if size == AsteroidSize::Boss {
+                    commands.with(Health(BOSS_HEALTH));
+                }
+            }
+            Spawnable::Bullet { player, ricochet } => {
+                let material = assets.sphere_tinted(Color::rgb(1.0, 0.9, 0.3), &mut materials);
+                let collider = ColliderBuilder::ball(BULLET_RADIUS);
+                let collider = if ricochet {
+                    // Light, not to shove what they hit
+                    collider.restitution(1.0).friction(0.0).density(0.05)
+                } else {
+                    collider.sensor(true)
+                };
+                spawn_bullet(&mut commands, material, position, linvel, collider)
+                    .with(Bullet(player))
+                    .with(CollisionGroups::of(groups::BULLET));
+                if ricochet {
+                    commands.with(Bounces {
+                        remaining: RICOCHET_BOUNCES,
+                    });
+                }
+            }
+            Spawnable::SaucerBullet => {
+                let material = assets.sphere_tinted(Color::rgb(1.0, 0.3, 0.3), &mut materials);
+                let collider = ColliderBuilder::ball(BULLET_RADIUS).sensor(true);
+                spawn_bullet(&mut commands, material, position, linvel, collider)
+                    .with(SaucerBullet)
+                    .with(CollisionGroups::of(groups::SAUCER_BULLET));
+            }
+        }
+    }
+}
+
+/// Spawns the components shared by the bullets of the ships and saucers.
+fn spawn_bullet<'a>(
+    commands: &'a mut Commands,
+    material: Handle<ColorMaterial>,
+    position: Vector2<f32>,
+    linvel: Vector2<f32>,
+    collider: ColliderBuilder,
+) -> &'a mut Commands {
+    commands
+        .spawn(SpriteComponents {
+            transform: Transform::from_translation(Vec3::new(position.x, position.y, -1.0))
+                .with_scale(2.0 * BULLET_RADIUS / 256.0),
+            material,
+            ..Default::default()
+        })
+        .with(Lifetime(BULLET_LIFETIME))
+        .with(WrapCount {
+            remaining: BULLET_WRAPS,
+        })
+        .with(
+            RigidBodyBuilder::new_dynamic()
+                .translation(position.x, position.y)
+                .linvel(linvel.x, linvel.y),
+        )
+        .with(collider)
+}
+
+/// Spawns an asteroid, inserting its body directly in the sets so that it
+/// exists in the same frame as its request, e.g. when splitting.
+fn spawn_asteroid(
+    commands: &mut Commands,
+    bodies: &mut RigidBodySet,
+    colliders: &mut ColliderSet,
+    material: Handle<ColorMaterial>,
+    restitution: f32,
+    size: AsteroidSize,
+    position: Vector2<f32>,
+    linvel: Vector2<f32>,
+    angvel: f32,
+) {
+    let tier = size.tier();
+    let radius = tier.radius;
+    let (body, collider) = asteroid_builders(
+        size,
+        restitution,
+        Vec2::new(position.x, position.y),
+        Vec2::new(linvel.x, linvel.y),
+        angvel,
+    );
+    let body_handle = bodies.insert(body.build());
+    let collider = collider.build();
+    let collider_handle = colliders.insert(collider, body_handle, bodies);
+    commands
+        .spawn(SpriteComponents {
+            transform: Transform::from_translation(Vec3::new(position.x, position.y, -2.0))
+                .with_scale(2.0 * radius * tier.sprite_scale / 256.0),
+            material,
+            ..Default::default()
+        })
+        .with(Asteroid { size })
+        .with(RigidBodyHandleComponent::from(body_handle))
+        .with(ColliderHandleComponent::from(collider_handle))
+        .with(CollisionGroups::of(groups::ASTEROID));
+}
+
+/// Spawns asteroids of random `sizes` along the arena edges or walls, away
+/// from `player_positions` and clear of the obstacles, with velocities up to
+/// `speed` along each axis.
+fn spawn_asteroids(
+    requests: &mut Events<SpawnRequest<Spawnable>>,
+    arena: &ArenaDescriptor,
+    count: usize,
+    sizes: &[AsteroidSize],
+    speed: f32,
+    player_positions: &[Vector2<f32>],
+    rng: &mut impl Rng,
+) {
+    for _ in 0..count {
+        let size = *sizes.choose(rng).unwrap();
+        let edge = rng.gen_range(0, 4);
+        let position = edge_spawn_point(rng, arena, edge, size, player_positions);
+        let linvel = Vector2::new(rng.gen_range(-speed, speed), rng.gen_range(-speed, speed));
+        let angvel = rng.gen_range(-ASTEROID_SPIN, ASTEROID_SPIN);
+        request_asteroid(requests, size, position, linvel, angvel);
+    }
+}
+
+/// A random point on `edge` of the arena, the left, right, bottom or top one
+/// from 0 to 3, for an asteroid of `size`, far enough from the players and
+/// clear of the obstacles. The arena being much larger than the safe radius, a
+/// few tries are enough.
+fn edge_spawn_point(
+    rng: &mut impl Rng,
+    arena: &ArenaDescriptor,
+    edge: usize,
+    size: AsteroidSize,
+    player_positions: &[Vector2<f32>],
+) -> Vector2<f32> {
+    let half_extents = arena.inner_half_extents(size.radius());
+    let (half_width, half_height) = (half_extents.x(), half_extents.y());
+    let mut position = Vector2::zeros();
+    for _ in 0..16 {
+        let along = rng.gen_range(-1.0, 1.0);
+        position = match edge {
+            0 => Vector2::new(-half_width, along * half_height),
+            1 => Vector2::new(half_width, along * half_height),
+            2 => Vector2::new(along * half_width, -half_height),
+            _ => Vector2::new(along * half_width, half_height),
+        };
+        let point = Vec2::new(position.x, position.y);
+        if player_positions
+            .iter()
+            .all(|player| (position - player).norm() > ASTEROID_SAFE_RADIUS)
+            && arena
+                .obstacles
+                .iter()
+                .all(|&(center, radius)| (point - center).length() > radius + size.radius())
+        {
+            break;
+        }
+    }
+    position
+}
+
+/// Positions of the ships, and of the arena center where they respawn.
+fn player_positions(
+    players: &Players,
+    bodies: &RigidBodySet,
+    query: &Query<&RigidBodyHandleComponent>,
+) -> Vec<Vector2<f32>> {
+    players
+        .ships()
+        .filter_map(|ship| query.get::<RigidBodyHandleComponent>(ship).ok())
+        .filter_map(|body_handle| bodies.get(body_handle.handle()))
+        .map(|body| body.position.translation.vector)
+        .chain(std::iter::once(Vector2::zeros()))
+        .collect()
+}
+
+/// Spawns a medium asteroid at rest under the cursor with the middle button,
+/// and prints the world position of a right click with the nearest body.
+///
+/// The cursor world position follows the camera, moved by the follow mode,
+/// the screen shake or the config scale.
+fn mouse_debug_system(
+    mouse_button_input: Res<Input<MouseButton>>,
+    mouse_position: Res<MousePosition>,
+    inspector: Res<Inspector>,
+    bodies: Res<RigidBodySet>,
+    mut requests: ResMut<Events<SpawnRequest<Spawnable>>>,
+    mut query: Query<(Entity, &RigidBodyHandleComponent)>,
+) {
+    let position = match mouse_position.world_position() {
+        Some(position) if mouse_position.in_window() => position,
+        _ => return,
+    };
+    if mouse_button_input.just_pressed(MouseButton::Middle) && !inspector.active() {
+        request_asteroid(
+            &mut requests,
+            AsteroidSize::Medium,
+            Vector2::new(position.x(), position.y()),
+            Vector2::zeros(),
+            0.0,
+        );
+    }
+    if mouse_button_input.just_pressed(MouseButton::Right) {
+        let mut nearest: Option<(Entity, f32)> = None;
+        for (entity, body_handle) in &mut query.iter() {
+            if let Some(body) = bodies.get(body_handle.handle()) {
+                let translation = body.position.translation.vector;
+                let distance = (Vec2::new(translation.x, translation.y) - position).length();
+                if nearest.map_or(true, |(_, nearest)| distance < nearest) {
+                    nearest = Some((entity, distance));
+                }
+            }
+        }
+        match nearest {
+            Some((entity, distance)) => println!(
+                "Click at ({:.2}, {:.2}), nearest body {:?} at {:.2}",
+                position.x(),
+                position.y(),
+                entity,
+                distance
+            ),
+            None => println!(
+                "Click at ({:.2}, {:.2}), no body",
+                position.x(),
+                position.y()
+            ),
+        }
+    }
+}
+
+/// Inspector lines of the asteroids, bullets and ships.
+fn describe_spaceship(world: &World, entity: Entity) -> Vec<String> {
+    let mut lines = Vec::new();
+    if let Ok(asteroid) = world.get::<Asteroid>(entity) {
+        lines.push(format!("asteroid: {:?}", asteroid.size));
+    }
+    if let Ok(bullet) = world.get::<Bullet>(entity) {
+        lines.push(format!("bullet of player {}", (bullet.0).0 + 1));
+    }
+    if let Ok(ship) = world.get::<Ship>(entity) {
+        lines.push(format!(
+            "ship: {:?} model, rotation speed {:.1} up to {:.1} rad/s, thrust {:.1} N",
+            ship.control_model, ship.rotation_speed, ship.rotation_speed_target, ship.thrust
+        ));
+        lines.push(format!(
+            "ship caps: {:.1} m/s, {:.1} rad/s",
+            ship.max_speed, ship.max_angvel
+        ));
+    }
+    lines
+}
+
+/// Material shared by all asteroids
+struct AsteroidMaterial(Handle<ColorMaterial>);
+
+/// Loads the asteroid, obstacle and spawn warning materials, the first wave
+/// being sent by the wave system.
+fn setup_asteroids(
+    mut commands: Commands,
+    mut assets: ResMut<GameAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let material = assets.sphere_tinted(Color::rgb(0.55, 0.5, 0.45), &mut materials);
+    commands.insert_resource(AsteroidMaterial(material));
+    let material = materials.add(ColorMaterial::modulated_texture(
+        assets.meteor_texture,
+        Color::rgb(0.5, 0.5, 0.55),
+    ));
+    commands.insert_resource(ObstacleMaterial(
+        material,
+        assets.material_for(Color::rgb(0.4, 0.45, 0.6), &mut materials),
+    ));
+    commands.insert_resource(WarningMaterial(
+        assets.material_for(Color::rgb(1.0, 0.35, 0.2), &mut materials),
+    ));
+}
+
+/// Spawns `ASTEROID_COUNT` random asteroids with F1, and one small, medium or
+/// large asteroid at rest in front of the first ship with F10, F11 or F12.
+fn asteroid_debug_system(
+    input: Res<Input<KeyCode>>,
+    arena: Res<ArenaDescriptor>,
+    players: Res<Players>,
+    bodies: Res<RigidBodySet>,
+    mut requests: ResMut<Events<SpawnRequest<Spawnable>>>,
+    mut rng: ResMut<SeededRng>,
+    query: Query<&RigidBodyHandleComponent>,
+) {
+    let tiers = [
+        (KeyCode::F10, AsteroidSize::Small),
+        (KeyCode::F11, AsteroidSize::Medium),
+        (KeyCode::F12, AsteroidSize::Large),
+    ];
+    for &(key, size) in &tiers {
+        if !input.just_pressed(key) {
+            continue;
+        }
+        let ship_position = players
+            .ships()
+            .next()
+            .and_then(|ship| query.get::<RigidBodyHandleComponent>(ship).ok())
+            .and_then(|body_handle| bodies.get(body_handle.handle()))
+            .map(|body| body.position);
+        if let Some(ship_position) = ship_position {
+            let direction = ship_position.rotation.transform_vector(&Vector2::y());
+            request_asteroid(
+                &mut requests,
+                size,
+                ship_position.translation.vector + direction * (size.radius() + 4.0),
+                Vector2::zeros(),
+                0.0,
+            );
+        }
+    }
+    if !input.just_pressed(KeyCode::F1) {
+        return;
+    }
+    let player_positions = player_positions(&players, &bodies, &query);
+    spawn_asteroids(
+        &mut requests,
+        &arena,
+        ASTEROID_COUNT,
+        &[
+            AsteroidSize::Small,
+            AsteroidSize::Medium,
+            AsteroidSize::Large,
+        ],
+        ASTEROID_SPEED,
+        &player_positions,
+        &mut rng.0,
+    );
+}
+
+/// Points to add to the score of a player
+struct ScoreEvent {
+    player: usize,
+    points: u32,
+    /// Where they were scored, to show them
+    position: Vector2<f32>,
+}
+
+/// Points floating up where they were scored, its children being the digits
+struct ScorePopup {
+    timer: Timer,
+}
+
+/// Sprite sheet of the digits 0 to 9
+struct DigitsAtlas(Handle<TextureAtlas>);
+
+/// Score popups, moved with their digits
+type RisingPopup<'a> = (
+    Entity,
+    Mut<'a, ScorePopup>,
+    Mut<'a, Transform>,
+    &'a Children,
+);
+
+/// Adds the points of the hits to the scores, multiplied by the combo of the
+/// player, granting the extra lives, and shows them where they were scored.
+/// Hits score nothing in survival mode.
+///
+/// Each hit bumps the combo, those of a same frame in the order of the events.
+fn score_system(
+    mut commands: Commands,
+    mut state: Local<EventReader<ScoreEvent>>,
+    score_events: Res<Events<ScoreEvent>>,
+    mode: Res<GameMode>,
+    atlas: Res<DigitsAtlas>,
+    mut players: ResMut<Players>,
+    mut combos: ResMut<Combos>,
+    mut popups: Query<&ScorePopup>,
+) {
+    let mut popup_count = popups.iter().iter().count();
+    for event in state.iter(&score_events) {
+        if *mode == GameMode::Survival {
+            continue;
+        }
+        let points = match combos.0.get_mut(event.player) {
+            Some(combo) => event.points * combo.kill(),
+            None => event.points,
+        };
+        if popup_count < MAX_POPUPS {
+            spawn_score_popup(&mut commands, atlas.0, points, event.position);
+            popup_count += 1;
+        }
+        if let Some(player) = players.0.get_mut(event.player) {
+            if player.score.add(points, Some(EXTRA_LIFE_SCORE)) > 0 {
+                println!(
+                    "Extra life for player {}, {} lives left",
+                    event.player + 1,
+                    player.score.lives
+                );
+            }
+        }
+    }
+}
+
+/// Ends the combos once their timer lapses, or when the ship of the player
+/// takes damage or is destroyed.
+fn combo_system(
+    time: Res<Time>,
+    players: Res<Players>,
+    mut combos: ResMut<Combos>,
+    hulls: Query<&Hull>,
+) {
+    for (index, combo) in combos.0.iter_mut().enumerate() {
+        combo.timer.tick(time.delta_seconds);
+        if combo.timer.finished {
+            combo.multiplier = 1;
+        }
+        let hull = players
+            .0
+            .get(index)
+            .and_then(|player| player.ship)
+            .and_then(|ship| hulls.get::<Hull>(ship).ok())
+            .map(|hull| hull.current);
+        if let Some(previous) = combo.hull {
+            if hull.map_or(true, |hull| hull < previous) {
+                combo.reset();
+            }
+        }
+        combo.hull = hull;
+    }
+}
+
+/// Shows the combo multipliers above x1, from yellow to red as they grow.
+fn combo_text_system(combos: Res<Combos>, mut query: Query<(&ComboText, Mut<Text>)>) {
+    for (combo_text, mut text) in &mut query.iter() {
+        let multiplier = combos.0[combo_text.player].multiplier;
+        let value = if multiplier > 1 {
+            format!(" x{}", multiplier)
+        } else {
+            String::new()
+        };
+        if text.value != value {
+            let heat = (multiplier as f32 - 2.0) / (MAX_COMBO as f32 - 2.0);
+            text.style.color = Color::rgb(1.0, 1.0 - 0.8 * heat.max(0.0).min(1.0), 0.2);
+            text.value = value;
+        }
+    }
+}
+
+fn setup_score_popups(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    mut atlases: ResMut<Assets<TextureAtlas>>,
+) {
+    let atlas = TextureAtlas::from_grid(
+        assets.digits_texture,
+        Vec2::new(DIGIT_SIZE.0 * 10.0, DIGIT_SIZE.1),
+        10,
+        1,
+    );
+    commands.insert_resource(DigitsAtlas(atlases.add(atlas)));
+}
+
+/// Spawns the digits of `points` centered on `position`, as mere sprites out
+/// of the physics.
+fn spawn_score_popup(
+    commands: &mut Commands,
+    atlas: Handle<TextureAtlas>,
+    points: u32,
+    position: Vector2<f32>,
+) {
+    let digits: Vec<u32> = points
+        .to_string()
+        .chars()
+        .filter_map(|digit| digit.to_digit(10))
+        .collect();
+    let scale = POPUP_HEIGHT / DIGIT_SIZE.1;
+    let width = DIGIT_SIZE.0 * scale;
+    let left = -width * (digits.len() as f32 - 1.0) / 2.0;
+    commands
+        .spawn((
+            ScorePopup {
+                timer: Timer::from_seconds(POPUP_DURATION, false),
+            },
+            Transform::from_translation(Vec3::new(position.x, position.y, 0.5)),
+            GlobalTransform::default(),
+        ))
+        .with_children(|parent| {
+            for (index, &digit) in digits.iter().enumerate() {
+                parent.spawn(SpriteSheetComponents {
+                    texture_atlas: atlas,
+                    sprite: TextureAtlasSprite::new(digit),
+                    transform: Transform::from_translation(Vec3::new(
+                        left + index as f32 * width,
+                        0.0,
+                        0.0,
+                    ))
+                    .with_scale(scale),
+                    ..Default::default()
+                });
+            }
+        });
+}
+
+/// Drifts the score popups upward, fading them out until they vanish.
+fn score_popup_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut popups: Query<RisingPopup>,
+    digits: Query<Mut<TextureAtlasSprite>>,
+) {
+    for (entity, mut popup, mut transform, children) in &mut popups.iter() {
+        popup.timer.tick(time.delta_seconds);
+        if popup.timer.finished {
+            commands.despawn_recursive(entity);
+            continue;
+        }
+        transform.translate(Vec3::new(0.0, POPUP_RISE * time.delta_seconds, 0.0));
+        let alpha = 1.0 - popup.timer.elapsed / popup.timer.duration;
+        for &child in children.iter() {
+            if let Ok(mut sprite) = digits.get_mut::<TextureAtlasSprite>(child) {
+                sprite.color.a = alpha;
+            }
+        }
+    }
+}
+
+/// Shows the remaining lives of each player as ship icons under their score,
+/// rebuilt whenever a count changes.
+fn lives_display_system(
+    mut commands: Commands,
+    mut shown: Local<Vec<(u32, usize)>>,
+    players: Res<Players>,
+    materials: Res<ShipMaterials>,
+    mut icons: Query<With<LifeIcon, Entity>>,
+) {
+    let lives: Vec<(u32, usize)> = players
+        .0
+        .iter()
+        .map(|player| (player.score.lives, player.profile))
+        .collect();
+    if *shown == lives {
+        return;
+    }
+    for entity in &mut icons.iter() {
+        commands.despawn(entity);
+    }
+    for (player, &(count, profile)) in lives.iter().enumerate() {
+        for i in 0..count {
+            let offset = Val::Px(10.0 + i as f32 * (LIFE_ICON_SIZE + 4.0));
+            // Player 1 on the left, player 2 on the right, as their scores
+            let position = if player == 0 {
+                Rect {
+                    top: Val::Px(40.0),
+                    left: offset,
+                    ..Default::default()
+                }
+            } else {
+                Rect {
+                    top: Val::Px(40.0),
+                    right: offset,
+                    ..Default::default()
+                }
+            };
+            commands
+                .spawn(ImageComponents {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position,
+                        size: Size::new(Val::Px(LIFE_ICON_SIZE), Val::Px(LIFE_ICON_SIZE)),
+                        ..Default::default()
+                    },
+                    material: materials.ships[player][profile],
+                    ..Default::default()
+                })
+                .with(LifeIcon);
+        }
+    }
+    *shown = lives;
+}
+
+/// Forwards the rapier proximity events, to be read by several systems,
+/// dropping those between colliders whose groups do not interact.
+fn proximity_events_system(
+    events: Res<EventQueue>,
+    h_to_e: Res<ColliderHandleToEntity>,
+    mut proximity_events: ResMut<Events<ProximityEvent>>,
+    groups: Query<&CollisionGroups>,
+) {
+    let groups_of = |collider| {
+        h_to_e
+            .get(collider)
+            .and_then(|entity| groups.get::<CollisionGroups>(entity).ok())
+            .map(|groups| *groups)
+    };
+    while let Ok(proximity_event) = events.proximity_events.pop() {
+        let interact = match (
+            groups_of(proximity_event.collider1),
+            groups_of(proximity_event.collider2),
+        ) {
+            (Some(groups1), Some(groups2)) => groups1.interacts_with(groups2),
+            _ => true,
+        };
+        if interact {
+            proximity_events.send(proximity_event);
+        }
+    }
+}
+
+/// Forwards the rapier contact events, to be read by several systems.
+///
+/// The ricochet bullets being solid, their contacts with the colliders that
+/// bullets hit, but the obstacles, are sent as proximity events instead, so
+/// that they hit as the other bullets.
+fn contact_events_system(
+    events: Res<EventQueue>,
+    h_to_e: Res<ColliderHandleToEntity>,
+    mut contact_events: ResMut<Events<ContactEvent>>,
+    mut proximity_events: ResMut<Events<ProximityEvent>>,
+    bullets: Query<&Bounces>,
+    others: Query<Without<Obstacle, &CollisionGroups>>,
+) {
+    let hits = |bullet, other| {
+        bullets.get::<Bounces>(bullet).is_ok()
+            && others
+                .get::<CollisionGroups>(other)
+                .map_or(false, |other_groups| {
+                    other_groups.interacts_with(CollisionGroups::of(groups::BULLET))
+                })
+    };
+    while let Ok(contact_event) = events.contact_events.pop() {
+        if let ContactEvent::Started(h1, h2) = contact_event {
+            if let (Some(e1), Some(e2)) = (h_to_e.get(h1), h_to_e.get(h2)) {
+                if hits(e1, e2) || hits(e2, e1) {
+                    proximity_events.send(ProximityEvent::new(
+                        h1,
+                        h2,
+                        Proximity::Disjoint,
+                        Proximity::Intersecting,
+                    ));
+                    continue;
+                }
+            }
+        }
+        contact_events.send(contact_event);
+    }
+}
+
+/// Counts down the bounces of the ricochet bullets off the obstacles and walls,
+/// stopping them once out of bounces, or when hitting a ship.
+fn ricochet_system(
+    mut reader: Local<EventReader<ContactEvent>>,
+    contact_events: Res<Events<ContactEvent>>,
+    h_to_e: Res<ColliderHandleToEntity>,
+    obstacles: Query<&Obstacle>,
+    ships: Query<&Ship>,
+    bullets: Query<(Mut<Bounces>, Mut<Lifetime>)>,
+) {
+    for &contact_event in reader.iter(&contact_events) {
+        let (e1, e2) = match contact_event {
+            ContactEvent::Started(h1, h2) => match (h_to_e.get(h1), h_to_e.get(h2)) {
+                (Some(e1), Some(e2)) => (e1, e2),
+                _ => continue,
+            },
+            ContactEvent::Stopped(_, _) => continue,
+        };
+        let (bullet, other) = if bullets.get::<Bounces>(e1).is_ok() {
+            (e1, e2)
+        } else if bullets.get::<Bounces>(e2).is_ok() {
+            (e2, e1)
+        } else {
+            continue;
+        };
+        if obstacles.get::<Obstacle>(other).is_ok() {
+            if let Ok(mut bounces) = bullets.get_mut::<Bounces>(bullet) {
+                if bounces.remaining > 0 {
+                    bounces.remaining -= 1;
+                    continue;
+                }
+            }
+        } else if ships.get::<Ship>(other).is_err() {
+            continue;
+        }
+        // Left to the lifetime system to despawn, as the hit systems may
+        // already do so this frame
+        if let Ok(mut lifetime) = bullets.get_mut::<Lifetime>(bullet) {
+            lifetime.0 = 0.0;
+        }
+    }
+}
+
+/// Destroys the asteroids hit by bullets, splitting them in two smaller ones.
+fn bullet_hit_system(
+    mut commands: Commands,
+    mut reader: Local<EventReader<ProximityEvent>>,
+    proximity_events: Res<Events<ProximityEvent>>,
+    mut h_to_e: ResMut<ColliderHandleToEntity>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut colliders: ResMut<ColliderSet>,
+    mut score_events: ResMut<Events<ScoreEvent>>,
+    mut bursts: ResMut<Events<BurstEvent>>,
+    mut requests: ResMut<Events<SpawnRequest<Spawnable>>>,
+    mut rng: ResMut<SeededRng>,
+    bullets: Query<(&Bullet, &RigidBodyHandleComponent)>,
+    asteroids: Query<(&Asteroid, &RigidBodyHandleComponent)>,
+    healths: Query<Mut<Health>>,
+    clumps: Query<Mut<Clump>>,
+) {
+    let rng = &mut rng.0;
+    // A bullet destroys a single asteroid, and an asteroid is split once, even
+    // if several events involve them this frame.
+    let mut used_bullets = HashSet::new();
+    let mut destroyed = HashSet::new();
+    for proximity_event in reader.iter(&proximity_events) {
+        if proximity_event.new_status != Proximity::Intersecting {
+            continue;
+        }
+        let e1 = h_to_e.get(proximity_event.collider1);
+        let e2 = h_to_e.get(proximity_event.collider2);
+        let (e1, e2) = match (e1, e2) {
+            (Some(e1), Some(e2)) => (e1, e2),
+            _ => continue,
+        };
+        let (bullet, asteroid) = if bullets.get::<Bullet>(e1).is_ok() {
+            (e1, e2)
+        } else {
+            (e2, e1)
+        };
+        let (shooter, bullet_handle, size, asteroid_handle) = match (
+            bullets.get::<Bullet>(bullet),
+            bullets.get::<RigidBodyHandleComponent>(bullet),
+            asteroids.get::<Asteroid>(asteroid),
+            asteroids.get::<RigidBodyHandleComponent>(asteroid),
+        ) {
+            (
+                Ok(bullet_component),
+                Ok(bullet_handle),
+                Ok(asteroid_component),
+                Ok(asteroid_handle),
+            ) => (
+                bullet_component.0,
+                bullet_handle.handle(),
+                asteroid_component.size,
+                asteroid_handle.handle(),
+            ),
+            _ => continue,
+        };
+        if used_bullets.contains(&bullet) || destroyed.contains(&asteroid) {
+            continue;
+        }
+        used_bullets.insert(bullet);
+        commands.despawn(bullet);
+        let bullet_body = bodies.get(bullet_handle).unwrap();
+        let (bullet_position, bullet_velocity) =
+            (bullet_body.position.translation.vector, bullet_body.linvel);
+        // A clump loses the piece nearest to the bullet instead of splitting
+        if let Ok(mut clump) = clumps.get_mut::<Clump>(asteroid) {
+            if !clump.pieces.is_empty() {
+                knock_off(
+                    &mut commands,
+                    &mut h_to_e,
+                    &mut bodies,
+                    &mut colliders,
+                    &mut requests,
+                    &mut clump,
+                    asteroid_handle,
+                    bullet_position,
+                    rng,
+                );
+                continue;
+            }
+        }
+        // A boss is not split until its last hit, each other one knocking a
+        // small asteroid off toward the shooter
+        if let Ok(mut health) = healths.get_mut::<Health>(asteroid) {
+            health.0 = health.0.saturating_sub(1);
+            if health.0 > 0 {
+                let asteroid_body = bodies.get(asteroid_handle).unwrap();
+                let back = -bullet_velocity
+                    .try_normalize(std::f32::EPSILON)
+                    .unwrap_or_else(Vector2::y);
+                let position = asteroid_body.position.translation.vector
+                    + back * (size.radius() + AsteroidSize::Small.radius());
+                let linvel = asteroid_body.linvel + back * ASTEROID_SPEED;
+                request_asteroid(
+                    &mut requests,
+                    AsteroidSize::Small,
+                    position,
+                    linvel,
+                    rng.gen_range(-ASTEROID_SPIN, ASTEROID_SPIN),
+                );
+                continue;
+            }
+        }
+        destroyed.insert(asteroid);
+        commands.despawn(asteroid);
+        let asteroid_body = bodies.get(asteroid_handle).unwrap();
+        let position = asteroid_body.position.translation.vector;
+        score_events.send(ScoreEvent {
+            player: shooter.0,
+            points: size.score(),
+            position,
+        });
+        bursts.send(BurstEvent {
+            position,
+            kind: BurstKind::Asteroid(size),
+        });
+        let smaller = match size.smaller() {
+            Some(smaller) => smaller,
+            None => continue,
+        };
+        let linvel = asteroid_body.linvel;
+        let speed = linvel.norm().max(ASTEROID_SPEED / 2.0);
+        // Pieces fly apart evenly around the asteroid, two of them roughly
+        // perpendicular to the bullet
+        let direction = bullet_velocity
+            .try_normalize(std::f32::EPSILON)
+            .unwrap_or_else(Vector2::x);
+        let perpendicular = Vector2::new(-direction.y, direction.x);
+        let children = size.tier().children;
+        for child in 0..children {
+            let angle = child as f32 * 2.0 * std::f32::consts::PI / children as f32
+                + rng.gen_range(-0.4, 0.4);
+            let away = UnitComplex::new(angle).transform_vector(&perpendicular);
+            request_asteroid(
+                &mut requests,
+                smaller,
+                // Within the former asteroid, apart from each other
+                position + away * size.radius().max(2.0 * smaller.radius()),
+                linvel + away * speed * rng.gen_range(0.8, 1.2),
+                rng.gen_range(-ASTEROID_SPIN, ASTEROID_SPIN),
+            );
+        }
+    }
+}
+
+/// Whether asteroids colliding slowly fuse into clumps, toggled with K
+#[derive(Default)]
+struct StickyAsteroids(bool);
+
+/// Asteroids fused to the body of this one, the core of a clump, each with
+/// its own collider on the core body and a sprite child of the core.
+#[derive(Default)]
+struct Clump {
+    pieces: Vec<ClumpPiece>,
+}
+
+struct ClumpPiece {
+    sprite: Entity,
+    collider: ColliderHandle,
+    size: AsteroidSize,
+    /// Center of the piece in the frame of the core body
+    offset: Vector2<f32>,
+}
+
+/// Sprite of a piece of the clump of `core`
+struct ClumpSprite {
+    core: Entity,
+    size: AsteroidSize,
+}
+
+fn setup_sticky_setting(mut settings: ResMut<Settings>, mut sticky: ResMut<StickyAsteroids>) {
+    sticky.0 = settings.register("sticky", sticky.0);
+}
+
+/// Switches the sticky asteroids with K.
+fn sticky_toggle_system(
+    input: Res<Input<KeyCode>>,
+    mut sticky: ResMut<StickyAsteroids>,
+    mut settings: ResMut<Settings>,
+    mut notices: Query<(Mut<NoticeText>, Mut<Text>)>,
+) {
+    if !input.just_pressed(KeyCode::K) {
+        return;
+    }
+    sticky.0 = !sticky.0;
+    settings.set("sticky", sticky.0);
+    for (mut notice, mut text) in &mut notices.iter() {
+        text.value = format!("Sticky asteroids: {}", if sticky.0 { "on" } else { "off" });
+        notice.timer.reset();
+    }
+}
+
+/// Fuses the asteroids colliding slower than `FUSE_SPEED` in the sticky mode.
+///
+/// The smaller one loses its body, its collider being attached to the body of
+/// the larger one at the same place, and its sprite becoming a child of the
+/// larger one. The clump moves on with the momentum of both. Bosses never
+/// fuse, nor two clumps, and a clump holds at most `MAX_CLUMP_PIECES`.
+fn fuse_system(
+    mut commands: Commands,
+    sticky: Res<StickyAsteroids>,
+    mut reader: Local<EventReader<ContactEvent>>,
+    contact_events: Res<Events<ContactEvent>>,
+    arena: Res<ArenaDescriptor>,
+    mut h_to_e: ResMut<ColliderHandleToEntity>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut colliders: ResMut<ColliderSet>,
+    asteroids: Query<(&Asteroid, &RigidBodyHandleComponent, &Transform)>,
+    healths: Query<&Health>,
+    clumps: Query<Mut<Clump>>,
+) {
+    // An asteroid fuses once a frame, the clumps inserted this frame not
+    // being visible yet
+    let mut fused = HashSet::new();
+    for &contact_event in reader.iter(&contact_events) {
+        if !sticky.0 {
+            continue;
+        }
+        let (e1, e2) = match contact_event {
+            ContactEvent::Started(h1, h2) => match (h_to_e.get(h1), h_to_e.get(h2)) {
+                (Some(e1), Some(e2)) if e1 != e2 => (e1, e2),
+                _ => continue,
+            },
+            ContactEvent::Stopped(_, _) => continue,
+        };
+        if fused.contains(&e1)
+            || fused.contains(&e2)
+            || healths.get::<Health>(e1).is_ok()
+            || healths.get::<Health>(e2).is_ok()
+        {
+            continue;
+        }
+        let (size1, size2) = match (asteroids.get::<Asteroid>(e1), asteroids.get::<Asteroid>(e2)) {
+            (Ok(a1), Ok(a2)) => (a1.size, a2.size),
+            _ => continue,
+        };
+        // The larger one keeps its body
+        let (core, piece, size) = if size1.radius() >= size2.radius() {
+            (e1, e2, size2)
+        } else {
+            (e2, e1, size1)
+        };
+        let pieces = |entity| {
+            clumps
+                .get::<Clump>(entity)
+                .map_or(0, |clump| clump.pieces.len())
+        };
+        if pieces(piece) > 0 || pieces(core) + 2 > MAX_CLUMP_PIECES {
+            continue;
+        }
+        let (core_handle, piece_handle) = match (
+            asteroids.get::<RigidBodyHandleComponent>(core),
+            asteroids.get::<RigidBodyHandleComponent>(piece),
+        ) {
+            (Ok(core_handle), Ok(piece_handle)) => (core_handle.handle(), piece_handle.handle()),
+            _ => continue,
+        };
+        let (piece_position, piece_linvel, piece_angvel, piece_mass) =
+            match bodies.get(piece_handle) {
+                Some(body) => (
+                    body.position.translation.vector,
+                    body.linvel,
+                    body.angvel,
+                    body.mass(),
+                ),
+                None => continue,
+            };
+        let offset = match bodies.get(core_handle) {
+            Some(body) if (body.linvel - piece_linvel).norm() < FUSE_SPEED => body
+                .position
+                .rotation
+                .inverse_transform_vector(&(piece_position - body.position.translation.vector)),
+            _ => continue,
+        };
+
+        let collider = asteroid_collider(size, arena.asteroid_restitution)
+            .translation(offset.x, offset.y)
+            .build();
+        let collider = colliders.insert(collider, core_handle, &mut bodies);
+        h_to_e.insert(collider, core);
+        let mut core_body = bodies.get_mut(core_handle).unwrap();
+        // The mass of the core body now includes the piece
+        let core_mass = core_body.mass() - piece_mass;
+        let total_mass = core_body.mass();
+        core_body.linvel = (core_body.linvel * core_mass + piece_linvel * piece_mass) / total_mass;
+        core_body.angvel = (core_body.angvel * core_mass + piece_angvel * piece_mass) / total_mass;
+        core_body.wake_up(true);
+
+        // The rapier cleanup removes the body of the piece along with its
+        // former collider
+        commands.remove::<(
+            Asteroid,
+            RigidBodyHandleComponent,
+            ColliderHandleComponent,
+            CollisionGroups,
+        )>(piece);
+        let (core_transform, piece_transform) = (
+            asteroids.get::<Transform>(core).unwrap(),
+            asteroids.get::<Transform>(piece).unwrap(),
+        );
+        let core_scale = core_transform.scale().x();
+        // Relative to the core, whose scale applies to its children
+        let mut transform = Transform::from_rotation(
+            core_transform.rotation().conjugate() * piece_transform.rotation(),
+        )
+        .with_scale(piece_transform.scale().x() / core_scale);
+        transform.set_translation(Vec3::new(offset.x, offset.y, 0.0) / core_scale);
+        commands
+            .insert(piece, (transform, ClumpSprite { core, size }))
+            .push_children(core, &[piece]);
+        let clump_piece = ClumpPiece {
+            sprite: piece,
+            collider,
+            size,
+            offset,
+        };
+        match clumps.get_mut::<Clump>(core) {
+            Ok(mut clump) => clump.pieces.push(clump_piece),
+            Err(_) => {
+                commands.insert_one(
+                    core,
+                    Clump {
+                        pieces: vec![clump_piece],
+                    },
+                );
+            }
+        }
+        fused.insert(core);
+        fused.insert(piece);
+    }
+}
+
+/// Detaches the piece of the `clump` of body `core_handle` nearest to
+/// `position`, back into an asteroid of its own flying off the clump.
+fn knock_off(
+    commands: &mut Commands,
+    h_to_e: &mut ColliderHandleToEntity,
+    bodies: &mut RigidBodySet,
+    colliders: &mut ColliderSet,
+    requests: &mut Events<SpawnRequest<Spawnable>>,
+    clump: &mut Clump,
+    core_handle: RigidBodyHandle,
+    position: Vector2<f32>,
+    rng: &mut impl Rng,
+) {
+    let core_position = bodies.get(core_handle).unwrap().position;
+    let center = |piece: &ClumpPiece| {
+        core_position.translation.vector + core_position.rotation * piece.offset
+    };
+    let nearest = (1..clump.pieces.len()).fold(0, |nearest, index| {
+        if (center(&clump.pieces[index]) - position).norm()
+            < (center(&clump.pieces[nearest]) - position).norm()
+        {
+            index
+        } else {
+            nearest
+        }
+    });
+    let piece = clump.pieces.swap_remove(nearest);
+    colliders.remove(piece.collider, bodies);
+    h_to_e.remove(piece.collider);
+    commands.despawn(piece.sprite);
+    let piece_center = center(&piece);
+    let core_body = bodies.get(core_handle).unwrap();
+    let away = (piece_center - core_position.translation.vector)
+        .try_normalize(std::f32::EPSILON)
+        .unwrap_or_else(Vector2::y);
+    request_asteroid(
+        requests,
+        piece.size,
+        piece_center,
+        core_body.linvel + away * ASTEROID_SPEED,
+        rng.gen_range(-ASTEROID_SPIN, ASTEROID_SPIN),
+    );
+}
+
+/// Releases the pieces of the clumps whose core is gone, e.g. destroyed by a
+/// missile, as asteroids of their own.
+fn clump_release_system(
+    mut commands: Commands,
+    mut requests: ResMut<Events<SpawnRequest<Spawnable>>>,
+    mut rng: ResMut<SeededRng>,
+    asteroids: Query<&Asteroid>,
+    mut sprites: Query<(Entity, &ClumpSprite, &GlobalTransform)>,
+) {
+    let rng = &mut rng.0;
+    for (entity, sprite, transform) in &mut sprites.iter() {
+        if asteroids.get::<Asteroid>(sprite.core).is_ok() {
+            continue;
+        }
+        commands.despawn(entity);
+        let position = transform.translation();
+        request_asteroid(
+            &mut requests,
+            sprite.size,
+            Vector2::new(position.x(), position.y()),
+            Vector2::new(
+                rng.gen_range(-ASTEROID_SPEED, ASTEROID_SPEED),
+                rng.gen_range(-ASTEROID_SPEED, ASTEROID_SPEED),
+            ),
+            rng.gen_range(-ASTEROID_SPIN, ASTEROID_SPIN),
+        );
+    }
+}
+
+/// Linear velocity of a ship at the end of the previous frame, its change over
+/// the frame of a contact measuring the impulse of the contact.
+#[derive(Default)]
+struct FrameVelocity(Vector2<f32>);
+
+fn frame_velocity_system(
+    bodies: Res<RigidBodySet>,
+    mut ships: Query<(&RigidBodyHandleComponent, Mut<FrameVelocity>)>,
+) {
+    for (body_handle, mut velocity) in &mut ships.iter() {
+        if let Some(body) = bodies.get(body_handle.handle()) {
+            velocity.0 = body.linvel;
+        }
+    }
+}
+
+/// Damages the ships hitting an asteroid, unless invulnerable, and shakes the
+/// screen, both in proportion to the impulse on the ship. Ships bounce off
+/// each other through the restitution of their colliders.
+///
+/// The impulse is estimated from the velocity change of the ship over the
+/// frame, the contact being already solved by the physics step.
+fn ship_collision_system(
+    mut commands: Commands,
+    mut reader: Local<EventReader<ContactEvent>>,
+    contact_events: Res<Events<ContactEvent>>,
+    h_to_e: Res<ColliderHandleToEntity>,
+    mut players: ResMut<Players>,
+    mut state: ResMut<GameState>,
+    mut bursts: ResMut<Events<BurstEvent>>,
+    mut shake: ResMut<ScreenShake>,
+    mut bodies: ResMut<RigidBodySet>,
+    asteroids: Query<(&Asteroid, &RigidBodyHandleComponent)>,
+    invulnerables: Query<&Invulnerable>,
+    dashes: Query<&Dash>,
+    ships: Query<(&Ship, &RigidBodyHandleComponent, &FrameVelocity)>,
+    shields: Query<(&Shield, &RigidBodyHandleComponent)>,
+    hulls: Query<Mut<Hull>>,
+) {
+    // The velocity change of a ship touching several asteroids in a frame is
+    // their combined impulse
+    let mut hit = HashSet::new();
+    for &contact_event in reader.iter(&contact_events) {
+        if let ContactEvent::Started(h1, h2) = contact_event {
+            let (e1, e2) = match (h_to_e.get(h1), h_to_e.get(h2)) {
+                (Some(e1), Some(e2)) => (e1, e2),
+                _ => continue,
+            };
+            let (index, ship, other) = match (players.player_of(e1), players.player_of(e2)) {
+                (Some(_), Some(_)) => continue,
+                (Some(index), None) => (index, e1, e2),
+                (None, Some(index)) => (index, e2, e1),
+                (None, None) => continue,
+            };
+            let asteroid_handle = match (
+                asteroids.get::<Asteroid>(other),
+                asteroids.get::<RigidBodyHandleComponent>(other),
+            ) {
+                (Ok(_), Ok(asteroid_handle)) => asteroid_handle.handle(),
+                _ => continue,
+            };
+            let (ship_handle, frame_velocity) = match (
+                ships.get::<RigidBodyHandleComponent>(ship),
+                ships.get::<FrameVelocity>(ship),
+            ) {
+                (Ok(ship_handle), Ok(frame_velocity)) => (ship_handle.handle(), frame_velocity.0),
+                _ => continue,
+            };
+            if !hit.insert(ship) {
+                continue;
+            }
+            let ship_body = bodies.get(ship_handle).unwrap();
+            let ship_position = ship_body.position.translation.vector;
+            let impulse = (ship_body.linvel - frame_velocity).norm() * ship_body.mass();
+            shake.add(impulse / SHAKE_IMPULSE);
+            // Phasing through grazes while dashing
+            if invulnerables.get::<Invulnerable>(ship).is_ok()
+                || dashes
+                    .get::<Dash>(ship)
+                    .map_or(false, |dash| dash.is_dashing())
+            {
+                continue;
+            }
+            if shields.get::<Shield>(ship).is_ok() {
+                // Pushes the asteroid away from the ship
+                let mut asteroid_body = bodies.get_mut(asteroid_handle).unwrap();
+                let away = (asteroid_body.position.translation.vector - ship_position)
+                    .try_normalize(std::f32::EPSILON)
+                    .unwrap_or_else(Vector2::x);
+                let impulse = away * SHIELD_BOUNCE * asteroid_body.mass();
+                asteroid_body.apply_impulse(impulse);
+                asteroid_body.wake_up(true);
+                continue;
+            }
+            // Scrapes do no damage
+            let damage = (impulse - HULL_MIN_IMPULSE).max(0.0) * HULL_DAMAGE;
+            let destroyed = match hulls.get_mut::<Hull>(ship) {
+                Ok(mut hull) => hull.damage(damage),
+                Err(_) => true,
+            };
+            if !destroyed {
+                continue;
+            }
+            bursts.send(BurstEvent {
+                position: ship_position,
+                kind: BurstKind::Ship,
+            });
+            lose_ship(&mut commands, &mut players, &mut state, index);
+        }
+    }
+}
+
+/// Destroys the ship of a player, scheduling its respawn, or ending the game
+/// once all the players are out of lives.
+fn lose_ship(commands: &mut Commands, players: &mut Players, state: &mut GameState, index: usize) {
+    let player = &mut players.0[index];
+    if let Some(ship) = player.ship.take() {
+        commands.despawn_recursive(ship);
+    }
+    if player.score.lose_life() {
+        println!(
+            "Player {} lost a ship, {} lives left",
+            index + 1,
+            player.score.lives
+        );
+        let explosion = EXPLOSION_FRAMES as f32 * EXPLOSION_FRAME_TIME;
+        player.respawn = Some(Timer::from_seconds(explosion + RESPAWN_DELAY, false));
+    } else if players.0.iter().all(|player| player.score.lives == 0) {
+        println!("Game over");
+        *state = GameState::GameOver;
+    } else {
+        println!("Player {} is out of lives", index + 1);
+    }
+}
+
+/// Respawns the lost ships once their delay is over, at the first point clear
+/// of the asteroids and obstacles around their spawn point, or tries again
+/// shortly after.
+fn respawn_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    materials: Res<ShipMaterials>,
+    state: Res<GameState>,
+    arena: Res<ArenaDescriptor>,
+    bodies: Res<RigidBodySet>,
+    mut players: ResMut<Players>,
+    mut asteroids: Query<(&Asteroid, &RigidBodyHandleComponent)>,
+) {
+    for (index, player) in players.0.iter_mut().enumerate() {
+        let timer = match player.respawn.as_mut() {
+            Some(timer) => timer,
+            None => continue,
+        };
+        timer.tick(time.delta_seconds);
+        if !timer.finished || *state != GameState::Playing {
+            continue;
+        }
+        let obstacles: Vec<(Vec2, f32)> = asteroids
+            .iter()
+            .iter()
+            .filter_map(|(asteroid, body_handle)| {
+                let body = bodies.get(body_handle.handle())?;
+                let center = body.position.translation.vector;
+                Some((Vec2::new(center.x, center.y), asteroid.size.radius()))
+            })
+            .chain(arena.obstacles.iter().copied())
+            .collect();
+        let position = match safe_spawn_point(
+            spawn_point(PlayerId(index)),
+            &obstacles,
+            RESPAWN_CLEARANCE,
+            RESPAWN_SPACING,
+        ) {
+            Some(position) => position,
+            None => {
+                player.respawn = Some(Timer::from_seconds(RESPAWN_RETRY, false));
+                continue;
+            }
+        };
+        player.respawn = None;
+        let ship = spawn_ship_at(
+            &mut commands,
+            &materials,
+            PlayerId(index),
+            player.profile,
+            position,
+        );
+        commands.insert_one(
+            ship,
+            Invulnerable(Timer::from_seconds(INVULNERABILITY, false)),
+        );
+        player.ship = Some(ship);
+    }
+}
+
+fn hull_system(time: Res<Time>, mut hulls: Query<Mut<Hull>>) {
+    for mut hull in &mut hulls.iter() {
+        hull.grace.tick(time.delta_seconds);
+    }
+}
+
+type HullFill<'a> = (
+    &'a HullBarFill,
+    Mut<'a, Style>,
+    Mut<'a, Handle<ColorMaterial>>,
+    Mut<'a, Draw>,
+);
+
+/// Shows the hull of the ship of each player, the bars of the players who did
+/// not join being hidden.
+fn hull_bar_system(
+    players: Res<Players>,
+    ships: Query<&Hull>,
+    mut bars: Query<(&HullBar, Mut<Draw>)>,
+    mut fills: Query<HullFill>,
+) {
+    for (bar, mut draw) in &mut bars.iter() {
+        draw.is_visible = bar.player < players.0.len();
+    }
+    for (fill, mut style, mut material, mut draw) in &mut fills.iter() {
+        let ratio = players
+            .0
+            .get(fill.player)
+            .and_then(|player| player.ship)
+            .and_then(|ship| ships.get::<Hull>(ship).ok())
+            .map_or(0.0, |hull| hull.current / hull.max);
+        draw.is_visible = ratio > 0.0;
+        style.size.width = Val::Percent(100.0 * ratio);
+        let wanted = if ratio < HULL_LOW {
+            fill.low
+        } else {
+            fill.normal
+        };
+        if *material != wanted {
+            *material = wanted;
+        }
+    }
+}
+
+/// Blinks invulnerable ships, until their invulnerability ends.
+///
+/// Only the ship sprite blinks, its children such as the thruster flame keep
+/// their own visibility.
+fn invulnerability_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, Mut<Invulnerable>, Mut<Draw>)>,
+) {
+    for (entity, mut invulnerable, mut draw) in &mut query.iter() {
+        invulnerable.0.tick(time.delta_seconds);
+        if invulnerable.0.finished {
+            draw.is_visible = true;
+            commands.remove_one::<Invulnerable>(entity);
+        } else {
+            draw.is_visible = (invulnerable.0.elapsed / (BLINK_PERIOD / 2.0)) as u32 % 2 == 0;
+        }
+    }
+}
+
+/// Once a wave is fully spawned and all its asteroids, fragments included, are
+/// destroyed, gives the wave clear bonus during an intermission, then
+/// announces the next wave and queues more and faster large asteroids than
+/// the previous one. Enter starts the next wave at once during the
+/// intermission.
+///
+/// There are no waves in survival mode.
+fn wave_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    input: Res<Input<KeyCode>>,
+    assets: Res<GameAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    state: Res<GameState>,
+    mode: Res<GameMode>,
+    mut players: ResMut<Players>,
+    mut wave: ResMut<Wave>,
+    mut asteroids: Query<With<Asteroid, Entity>>,
+    mut warnings: Query<With<SpawnWarning, Entity>>,
+    mut banners: Query<With<WaveBanner, Mut<Text>>>,
+    mut cleared_banners: Query<With<ClearedBanner, Mut<Lifetime>>>,
+) {
+    if *state != GameState::Playing || *mode != GameMode::Waves {
+        // The wave is announced again after a restart
+        if wave.countdown.take().is_some() {
+            for mut text in &mut banners.iter() {
+                text.value.clear();
+            }
+        }
+        return;
+    }
+    if let Some(timer) = wave.countdown.as_mut() {
+        timer.tick(time.delta_seconds);
+        if timer.finished {
+            wave.countdown = None;
+            for mut text in &mut banners.iter() {
+                text.value.clear();
+            }
+            send_wave(&mut wave);
+        }
+        return;
+    }
+    // The only check of the end of a wave
+    if !wave.queue.is_empty()
+        || warnings.iter().iter().next().is_some()
+        || asteroids.iter().iter().next().is_some()
+    {
+        return;
+    }
+    if wave.number > 0 && !wave.cleared {
+        wave.cleared = true;
+        for player in players.0.iter_mut().filter(|player| player.score.lives > 0) {
+            player.score.add(WAVE_CLEAR_BONUS, Some(EXTRA_LIFE_SCORE));
+        }
+        commands
+            .spawn(NodeComponents {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+                material: materials.add(Color::NONE.into()),
+                ..Default::default()
+            })
+            .with(ClearedBanner)
+            .with(Lifetime(WAVE_CLEAR_BANNER))
+            .with_children(|parent| {
+                parent.spawn(TextComponents {
+                    text: Text {
+                        value: format!("WAVE {} CLEARED — +{}", wave.number, WAVE_CLEAR_BONUS),
+                        font: assets.font,
+                        style: TextStyle {
+                            font_size: 48.0,
+                            color: Color::rgb(1.0, 0.85, 0.2),
+                        },
+                    },
+                    ..Default::default()
+                });
+            });
+        return;
+    }
+    let early = input.just_pressed(KeyCode::Return);
+    let mut intermission = false;
+    for mut lifetime in &mut cleared_banners.iter() {
+        intermission = true;
+        if early {
+            lifetime.0 = 0.0;
+        }
+    }
+    if intermission && !early {
+        return;
+    }
+    wave.number += 1;
+    wave.cleared = false;
+    if intermission {
+        send_wave(&mut wave);
+        return;
+    }
+    wave.countdown = Some(Timer::from_seconds(WAVE_BANNER, false));
+    let boss = if wave.number % BOSS_WAVE_PERIOD == 0 {
+        "BOSS "
+    } else {
+        ""
+    };
+    for mut text in &mut banners.iter() {
+        text.value = format!("{}WAVE {}", boss, wave.number);
+    }
+}
+
+/// Queues the asteroids of the current wave, a boss every
+/// `BOSS_WAVE_PERIOD` waves.
+fn send_wave(wave: &mut Wave) {
+    println!("Wave {}", wave.number);
+    if wave.number % BOSS_WAVE_PERIOD == 0 {
+        wave.queue.push_back(AsteroidSize::Boss);
+        wave.speed = BOSS_SPEED;
+    } else {
+        let count = WAVE_ASTEROIDS + wave.number as usize;
+        wave.queue
+            .extend(std::iter::repeat(AsteroidSize::Large).take(count));
+        wave.speed = ASTEROID_SPEED * (1.0 + WAVE_SPEEDUP * wave.number as f32);
+    }
+    wave.drip.reset();
+}
+
+/// Releases the queued asteroids of the wave every `DRIP_PERIOD`, going around
+/// the arena edges, each shown by a warning before it appears.
+fn wave_drip_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    state: Res<GameState>,
+    arena: Res<ArenaDescriptor>,
+    material: Res<WarningMaterial>,
+    players: Res<Players>,
+    bodies: Res<RigidBodySet>,
+    mut wave: ResMut<Wave>,
+    mut rng: ResMut<SeededRng>,
+    query: Query<&RigidBodyHandleComponent>,
+) {
+    if *state != GameState::Playing || wave.queue.is_empty() {
+        return;
+    }
+    wave.drip.tick(time.delta_seconds);
+    if !wave.drip.just_finished {
+        return;
+    }
+    let size = wave.queue.pop_front().unwrap();
+    let edge = wave.edge;
+    // Opposite edges in turn
+    wave.edge = (edge + 1) % 4;
+    let rng = &mut rng.0;
+    // Away from the ships flying when the warning is shown
+    let player_positions = player_positions(&players, &bodies, &query);
+    let position = edge_spawn_point(rng, &arena, edge, size, &player_positions);
+    let speed = wave.speed;
+    let linvel = Vector2::new(rng.gen_range(-speed, speed), rng.gen_range(-speed, speed));
+    // Pointing inward, just inside the edge
+    let angle = [0.0, 1.0, 0.5, -0.5][edge] * std::f32::consts::PI;
+    let (sin, cos) = angle.sin_cos();
+    let tip = Vec2::new(position.x, position.y) + Vec2::new(cos, sin) * WARNING_SIZE;
+    let arm_offset = WARNING_SIZE * std::f32::consts::FRAC_1_SQRT_2 / 2.0;
+    commands
+        .spawn((
+            SpawnWarning {
+                timer: Timer::from_seconds(SPAWN_WARNING, false),
+                size,
+                position,
+                linvel,
+            },
+            Transform::from_translation_rotation(tip.extend(0.0), Quat::from_rotation_z(angle)),
+            GlobalTransform::default(),
+        ))
+        .with_children(|parent| {
+            for &side in &[1.0, -1.0] {
+                parent.spawn(SpriteComponents {
+                    sprite: Sprite::new(Vec2::new(WARNING_SIZE, 0.3)),
+                    transform: Transform::from_translation_rotation(
+                        Vec3::new(-arm_offset, side * arm_offset, 0.0),
+                        Quat::from_rotation_z(-side * std::f32::consts::FRAC_PI_4),
+                    ),
+                    material: material.0,
+                    ..Default::default()
+                });
+            }
+        });
+}
+
+/// Warnings with their arms
+type FlashingWarning<'a> = (Entity, Mut<'a, SpawnWarning>, &'a Children);
+
+/// Flashes the spawn warnings, then replaces them with their asteroid.
+fn spawn_warning_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    state: Res<GameState>,
+    mut requests: ResMut<Events<SpawnRequest<Spawnable>>>,
+    mut rng: ResMut<SeededRng>,
+    mut warnings: Query<FlashingWarning>,
+    arms: Query<Mut<Draw>>,
+) {
+    if *state != GameState::Playing {
+        return;
+    }
+    let rng = &mut rng.0;
+    for (entity, mut warning, children) in &mut warnings.iter() {
+        warning.timer.tick(time.delta_seconds);
+        if warning.timer.finished {
+            commands.despawn_recursive(entity);
+            request_asteroid(
+                &mut requests,
+                warning.size,
+                warning.position,
+                warning.linvel,
+                rng.gen_range(-ASTEROID_SPIN, ASTEROID_SPIN),
+            );
+            continue;
+        }
+        let visible = (warning.timer.elapsed * 8.0) as u32 % 2 == 0;
+        for &child in children.iter() {
+            if let Ok(mut draw) = arms.get_mut::<Draw>(child) {
+                draw.is_visible = visible;
+            }
+        }
+    }
+}
+
+/// In survival mode, streams asteroids in from the edges at the rate of the
+/// curve, and scores the seconds survived to the players still in the game.
+///
+/// The stream holds off while `MAX_ASTEROIDS` are alive.
+fn survival_system(
+    time: Res<Time>,
+    state: Res<GameState>,
+    mode: Res<GameMode>,
+    curve: Res<SpawnRateCurve>,
+    arena: Res<ArenaDescriptor>,
+    mut players: ResMut<Players>,
+    mut survival: ResMut<Survival>,
+    bodies: Res<RigidBodySet>,
+    mut requests: ResMut<Events<SpawnRequest<Spawnable>>>,
+    mut rng: ResMut<SeededRng>,
+    query: Query<&RigidBodyHandleComponent>,
+    mut asteroids: Query<With<Asteroid, Entity>>,
+) {
+    if *state != GameState::Playing || *mode != GameMode::Survival {
+        return;
+    }
+    survival.time += time.delta_seconds;
+    for player in players.0.iter_mut().filter(|player| player.score.lives > 0) {
+        player.score.points = survival.time as u32;
+    }
+    survival.pending += curve.rate(survival.time) * time.delta_seconds;
+    if survival.pending < 1.0 {
+        return;
+    }
+    // Dropped rather than queued at the cap, not to flood the arena after
+    survival.pending -= 1.0;
+    if asteroids.iter().iter().count() >= MAX_ASTEROIDS {
+        return;
+    }
+    let player_positions = player_positions(&players, &bodies, &query);
+    spawn_asteroids(
+        &mut requests,
+        &arena,
+        1,
+        &[AsteroidSize::Medium, AsteroidSize::Large],
+        ASTEROID_SPEED,
+        &player_positions,
+        &mut rng.0,
+    );
+}
+
+fn survival_text_system(
+    mode: Res<GameMode>,
+    survival: Res<Survival>,
+    mut query: Query<With<SurvivalText, Mut<Text>>>,
+) {
+    let value = match *mode {
+        GameMode::Waves => String::new(),
+        GameMode::Survival => format!("{:.1}", survival.time),
+    };
+    for mut text in &mut query.iter() {
+        if text.value != value {
+            text.value = value.clone();
+        }
+    }
+}
+
+/// Health pips of a boss
+type FloatingPip<'a> = (Entity, &'a HealthPip, Mut<'a, Transform>, Mut<'a, Draw>);
+
+/// Floats a row of pips above each boss, one per remaining hit.
+fn health_pip_system(
+    mut commands: Commands,
+    bodies: Res<RigidBodySet>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut assets: ResMut<GameAssets>,
+    mut new_bosses: Query<(Entity, Added<Health>)>,
+    bosses: Query<(&Asteroid, &Health, &RigidBodyHandleComponent)>,
+    mut pips: Query<FloatingPip>,
+) {
+    for (boss, health) in &mut new_bosses.iter() {
+        let material = assets.material_for(Color::rgb(1.0, 0.3, 0.3), &mut materials);
+        for index in 0..health.0 {
+            // Placed with the boss from the next frame
+            commands
+                .spawn(SpriteComponents {
+                    sprite: Sprite::new(Vec2::new(0.5, 0.5)),
+                    material,
+                    draw: Draw {
+                        is_visible: false,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .with(HealthPip { boss, index });
+        }
+    }
+    for (pip, pip_component, mut transform, mut draw) in &mut pips.iter() {
+        let (asteroid, health, body_handle) = match (
+            bosses.get::<Asteroid>(pip_component.boss),
+            bosses.get::<Health>(pip_component.boss),
+            bosses.get::<RigidBodyHandleComponent>(pip_component.boss),
+        ) {
+            (Ok(asteroid), Ok(health), Ok(body_handle)) => (asteroid, health, body_handle),
+            _ => {
+                commands.despawn(pip);
+                continue;
+            }
+        };
+        let center = match bodies.get(body_handle.handle()) {
+            Some(body) => body.position.translation.vector,
+            None => continue,
+        };
+        let x = (pip_component.index as f32 - (BOSS_HEALTH - 1) as f32 / 2.0) * PIP_SPACING;
+        let y = asteroid.size.radius() + 1.5;
+        transform.set_translation(Vec3::new(center.x + x, center.y + y, -0.5));
+        draw.is_visible = pip_component.index < health.0;
+    }
+}
+
+fn wave_text_system(
+    wave: Res<Wave>,
+    mut shown: Local<u32>,
+    mut query: Query<With<WaveText, Mut<Text>>>,
+) {
+    if *shown == wave.number {
+        return;
+    }
+    *shown = wave.number;
+    for mut text in &mut query.iter() {
+        text.value = if wave.number > 0 {
+            format!("Wave {}", wave.number)
+        } else {
+            String::new()
+        };
+    }
+}
+
+/// Shows the game over text once the game ends, recording the best score of
+/// the players, or the survival time, if it beats the high score.
+fn game_over_text_system(
+    state: Res<GameState>,
+    mode: Res<GameMode>,
+    players: Res<Players>,
+    survival: Res<Survival>,
+    mut high_score: ResMut<HighScore>,
+    mut shown: Local<Option<GameState>>,
+    mut query: Query<With<GameOverText, Mut<Text>>>,
+) {
+    if *shown == Some(*state) {
+        return;
+    }
+    *shown = Some(*state);
+    let mut record = false;
+    if *state == GameState::GameOver {
+        match *mode {
+            GameMode::Waves => {
+                let best = players.0.iter().map(|player| player.score.points).max();
+                if let Some(best) = best.filter(|&best| best > high_score.score) {
+                    high_score.score = best;
+                    record = true;
+                }
+            }
+            GameMode::Survival => {
+                if survival.time > high_score.survival {
+                    high_score.survival = survival.time;
+                    record = true;
+                }
+            }
+        }
+        if record {
+            save_high_score(&high_score);
+        }
+    }
+    for mut text in &mut query.iter() {
+        text.value = match (*state, *mode) {
+            (GameState::Menu, _)
+            | (GameState::Serving, _)
+            | (GameState::Playing, _)
+            | (GameState::Cleared, _) => String::new(),
+            (GameState::GameOver, GameMode::Waves) => {
+                let scores: Vec<String> = players
+                    .0
+                    .iter()
+                    .map(|player| player.score.points.to_string())
+                    .collect();
+                format!(
+                    "{}GAME OVER — score {} — R: restart, 1: waves, 2: survival",
+                    if record { "NEW HIGH SCORE! " } else { "" },
+                    scores.join(" / ")
+                )
+            }
+            (GameState::GameOver, GameMode::Survival) => format!(
+                "{}GAME OVER — survived {:.1} s — R: restart, 1: waves, 2: survival",
+                if record { "NEW BEST TIME! " } else { "" },
+                survival.time
+            ),
+        };
+    }
+}
+
+fn high_score_path() -> io::Result<PathBuf> {
+    platform::data_path(HIGH_SCORE_FILE)
+}
+
+/// Records the inputs of the ships at each step, or replaces them with the
+/// recorded ones, notifying the players when the replay ends.
+fn replay_system(
+    mut replay: ResMut<Replay>,
+    players: Res<Players>,
+    bodies: Res<RigidBodySet>,
+    ships: Query<(Mut<ShipControls>, &RigidBodyHandleComponent)>,
+    mut notices: Query<(Mut<NoticeText>, Mut<Text>)>,
+) {
+    let positions: Vec<Option<(f32, f32)>> = players
+        .0
+        .iter()
+        .map(|player| {
+            let body_handle = ships.get::<RigidBodyHandleComponent>(player.ship?).ok()?;
+            let position = bodies
+                .get(body_handle.handle())?
+                .position
+                .translation
+                .vector;
+            Some((position.x, position.y))
+        })
+        .collect();
+    let controls = || {
+        players
+            .0
+            .iter()
+            .map(|player| {
+                player
+                    .ship
+                    .and_then(|ship| ships.get::<ShipControls>(ship).ok())
+                    .map_or_else(RecordedControls::default, |controls| {
+                        RecordedControls::new(&controls)
+                    })
+            })
+            .collect()
+    };
+    match replay.advance(positions, controls) {
+        ReplayStep::Off | ReplayStep::Recorded => {}
+        ReplayStep::Replayed(recorded) => {
+            for (player, recorded) in players.0.iter().zip(recorded) {
+                if let Some(ship) = player.ship {
+                    if let Ok(mut controls) = ships.get_mut::<ShipControls>(ship) {
+                        *controls = recorded.controls();
+                    }
+                }
+            }
+        }
+        ReplayStep::Over => {
+            for (mut notice, mut text) in &mut notices.iter() {
+                text.value = "Replay over".to_string();
+                notice.timer.reset();
+            }
+        }
+    }
+}
+
+/// The saved high score, zero if there is none yet or it can't be read.
+fn load_high_score() -> HighScore {
+    let result = high_score_path().and_then(platform::read_to_string);
+    let result = match result {
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return HighScore::default(),
+        result => result.map_err(|error| error.to_string()).and_then(|text| {
+            ron::de::from_str(&text)
+                // Saved by older versions, without the survival time
+                .or_else(|_| {
+                    ron::de::from_str(&text).map(|score| HighScore {
+                        score,
+                        ..Default::default()
+                    })
+                })
+                .map_err(|error| error.to_string())
+        }),
+    };
+    result.unwrap_or_else(|error| {
+        eprintln!("Could not load the high score: {}", error);
+        HighScore::default()
+    })
+}
+
+/// Saves the high score, which is otherwise only kept for this session.
+fn save_high_score(high_score: &HighScore) {
+    let result = ron::ser::to_string(high_score)
+        .map_err(|error| error.to_string())
+        .and_then(|text| {
+            high_score_path()
+                .and_then(|path| platform::write(path, text))
+                .map_err(|error| error.to_string())
+        });
+    if let Err(error) = result {
+        eprintln!("Could not save the high score: {}", error);
+    }
+}
+
+/// Shows the high score, or the best survival time in survival mode.
+fn high_score_text_system(
+    high_score: Res<HighScore>,
+    mode: Res<GameMode>,
+    mut query: Query<With<HighScoreText, Mut<Text>>>,
+) {
+    let value = match *mode {
+        GameMode::Waves => format!("HI: {}", high_score.score),
+        GameMode::Survival => format!("BEST: {:.1} s", high_score.survival),
+    };
+    for mut text in &mut query.iter() {
+        if text.value != value {
+            text.value = value.clone();
+        }
+    }
+}
+
+/// Restarts the game once over, despawning all the gameplay entities and
+/// spawning the ships of the players and the first wave again.
+///
+/// R restarts in the same mode, 1 in waves mode and 2 in survival mode.
+fn restart_system(
+    mut commands: Commands,
+    mut input: ResMut<Input<KeyCode>>,
+    ship_materials: Res<ShipMaterials>,
+    mut state: ResMut<GameState>,
+    mut mode: ResMut<GameMode>,
+    mut players: ResMut<Players>,
+    mut wave: ResMut<Wave>,
+    mut survival: ResMut<Survival>,
+    mut ships: Query<With<Ship, Entity>>,
+    // Bullets of the ships and saucers, and the saucers
+    mut short_lived: Query<With<Lifetime, Entity>>,
+    mut asteroids: Query<With<Asteroid, Entity>>,
+    // The dropped ones being short lived
+    mut powerups: Query<Without<Lifetime, With<Powerup, Entity>>>,
+    mut warnings: Query<With<SpawnWarning, Entity>>,
+) {
+    if *state != GameState::GameOver {
+        return;
+    }
+    *mode = match restart_mode(&input, *mode) {
+        Some(mode) => mode,
+        None => return,
+    };
+    if input.just_pressed(KeyCode::Key2) {
+        // Not to also join a second player
+        input.reset(KeyCode::Key2);
+    }
+    // Their rapier bodies are removed by the cleanup plugin
+    for entity in ships
+        .iter()
+        .iter()
+        .chain(&mut short_lived.iter())
+        .chain(&mut asteroids.iter())
+        .chain(&mut powerups.iter())
+        .chain(&mut warnings.iter())
+    {
+        commands.despawn_recursive(entity);
+    }
+    *state = GameState::Playing;
+    // The first wave is sent by the wave system, the asteroids being gone
+    *wave = Wave::default();
+    *survival = Survival::default();
+    for (index, player) in players.0.iter_mut().enumerate() {
+        *player = PlayerState {
+            ship: Some(spawn_ship(
+                &mut commands,
+                &ship_materials,
+                PlayerId(index),
+                player.profile,
+            )),
+            profile: player.profile,
+            ..Default::default()
+        };
+    }
+}
+
+/// Spawns a shield powerup at a random place every `POWERUP_PERIOD`, unless one
+/// is still waiting to be picked. Weapon powerups are dropped by asteroids.
+fn powerup_spawn_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    state: Res<GameState>,
+    arena: Res<ArenaDescriptor>,
+    mut assets: ResMut<GameAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut spawner: ResMut<PowerupSpawner>,
+    mut rng: ResMut<SeededRng>,
+    mut powerups: Query<&Powerup>,
+) {
+    spawner.0.tick(time.delta_seconds);
+    if !spawner.0.just_finished
+        || *state != GameState::Playing
+        || powerups.iter().iter().any(|&kind| kind == Powerup::Shield)
+    {
+        return;
+    }
+    let rng = &mut rng.0;
+    let x = rng.gen_range(-0.8, 0.8) * arena.half_extents.x();
+    let y = rng.gen_range(-0.8, 0.8) * arena.half_extents.y();
+    let material = assets.sphere_tinted(Powerup::Shield.color(), &mut materials);
+    commands
+        .spawn(SpriteComponents {
+            transform: Transform::from_translation(Vec3::new(x, y, -1.0)).with_scale(2.0 / 256.0),
+            material,
+            ..Default::default()
+        })
+        .with(Powerup::Shield)
+        .with(RigidBodyBuilder::new_static().translation(x, y))
+        .with(ColliderBuilder::ball(1.0).sensor(true))
+        .with(CollisionGroups::of(groups::POWERUP));
+}
+
+/// Grants the powerups overlapped by the ships.
+fn powerup_pickup_system(
+    mut commands: Commands,
+    mut reader: Local<EventReader<ProximityEvent>>,
+    proximity_events: Res<Events<ProximityEvent>>,
+    h_to_e: Res<ColliderHandleToEntity>,
+    players: Res<Players>,
+    mut assets: ResMut<GameAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    powerups: Query<&Powerup>,
+    shields: Query<&Shield>,
+    boosts: Query<Mut<Boosts>>,
+    fuels: Query<Mut<Fuel>>,
+) {
+    let mut picked = HashSet::new();
+    for proximity_event in reader.iter(&proximity_events) {
+        if proximity_event.new_status != Proximity::Intersecting {
+            continue;
+        }
+        let e1 = h_to_e.get(proximity_event.collider1);
+        let e2 = h_to_e.get(proximity_event.collider2);
+        let (ship, powerup) = match (e1, e2) {
+            (Some(e1), Some(e2)) if players.player_of(e1).is_some() => (e1, e2),
+            (Some(e1), Some(e2)) if players.player_of(e2).is_some() => (e2, e1),
+            _ => continue,
+        };
+        let kind = match powerups.get::<Powerup>(powerup) {
+            Ok(kind) => kind,
+            Err(_) => continue,
+        };
+        if !picked.insert(powerup) {
+            continue;
+        }
+        commands.despawn(powerup);
+        match *kind {
+            Powerup::Shield => {
+                if shields.get::<Shield>(ship).is_err() {
+                    let material =
+                        assets.sphere_tinted(Color::rgba(0.3, 0.7, 1.0, 0.35), &mut materials);
+                    // In the ship texture pixels
+                    commands
+                        .spawn(SpriteComponents {
+                            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 0.1))
+                                .with_scale(2.0),
+                            material,
+                            ..Default::default()
+                        })
+                        .with(ShieldBubble);
+                    let bubble = commands.current_entity().unwrap();
+                    commands.push_children(ship, &[bubble]);
+                }
+                // Replaces any running shield
+                commands.insert_one(
+                    ship,
+                    Shield {
+                        remaining: SHIELD_DURATION,
+                    },
+                );
+            }
+            Powerup::RapidFire | Powerup::SpreadShot | Powerup::Ricochet => {
+                if let Ok(mut boosts) = boosts.get_mut::<Boosts>(ship) {
+                    boosts.grant(*kind);
+                }
+            }
+            Powerup::Fuel => {
+                if let Ok(mut fuel) = fuels.get_mut::<Fuel>(ship) {
+                    let amount = FUEL_PICKUP * fuel.max;
+                    fuel.add(amount);
+                }
+            }
+        }
+    }
+}
+
+/// Leaves a drifting weapon powerup, at random, where large asteroids are
+/// destroyed, or else sometimes a fuel pickup where any asteroid is.
+fn powerup_drop_system(
+    mut commands: Commands,
+    mut reader: Local<EventReader<BurstEvent>>,
+    bursts: Res<Events<BurstEvent>>,
+    mut assets: ResMut<GameAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut rng: ResMut<SeededRng>,
+) {
+    let rng = &mut rng.0;
+    let kinds = [
+        Powerup::RapidFire,
+        Powerup::SpreadShot,
+        Powerup::Ricochet,
+        Powerup::Fuel,
+    ];
+    for burst in reader.iter(&bursts) {
+        let index = match burst.kind {
+            BurstKind::Asteroid(AsteroidSize::Large) if rng.gen_bool(BOOST_DROP_CHANCE) => {
+                rng.gen_range(0, 3)
+            }
+            BurstKind::Asteroid(_) if rng.gen_bool(FUEL_DROP_CHANCE) => 3,
+            _ => continue,
+        };
+        let material = assets.sphere_tinted(kinds[index].color(), &mut materials);
+        let (x, y) = (burst.position.x, burst.position.y);
+        let linvel = Vector2::new(
+            rng.gen_range(-BOOST_DROP_SPEED, BOOST_DROP_SPEED),
+            rng.gen_range(-BOOST_DROP_SPEED, BOOST_DROP_SPEED),
+        );
+        commands
+            .spawn(SpriteComponents {
+                transform: Transform::from_translation(Vec3::new(x, y, -1.0))
+                    .with_scale(2.0 / 256.0),
+                material,
+                ..Default::default()
+            })
+            .with(kinds[index])
+            .with(Lifetime(BOOST_DROP_LIFETIME))
+            .with(
+                RigidBodyBuilder::new_dynamic()
+                    .translation(x, y)
+                    .linvel(linvel.x, linvel.y),
+            )
+            .with(ColliderBuilder::ball(1.0).sensor(true))
+            .with(CollisionGroups::of(groups::POWERUP));
+    }
+}
+
+/// Lists the active weapon powerups of each player with their remaining
+/// seconds.
+fn boost_text_system(
+    players: Res<Players>,
+    boosts: Query<&Boosts>,
+    mut texts: Query<(&BoostText, Mut<Text>)>,
+) {
+    for (boost_text, mut text) in &mut texts.iter() {
+        let boosts = players
+            .0
+            .get(boost_text.player)
+            .and_then(|player| player.ship)
+            .and_then(|ship| boosts.get::<Boosts>(ship).ok());
+        let value = boosts.map_or_else(String::new, |boosts| boosts.label());
+        if text.value != value {
+            text.value = value;
+        }
+    }
+}
+
+/// Removes the shield and its visual once expired.
+fn shield_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut ships: Query<(Entity, Mut<Shield>, &Children)>,
+    bubbles: Query<&ShieldBubble>,
+) {
+    for (ship, mut shield, children) in &mut ships.iter() {
+        shield.remaining -= time.delta_seconds;
+        if shield.remaining > 0.0 {
+            continue;
+        }
+        commands.remove_one::<Shield>(ship);
+        for &child in children.iter() {
+            if bubbles.get::<ShieldBubble>(child).is_ok() {
+                commands.despawn_recursive(child);
+            }
+        }
+    }
+}
+
+/// Sends a saucer from a random edge every `SAUCER_PERIOD`, unless one is
+/// still around.
+fn saucer_spawn_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    state: Res<GameState>,
+    arena: Res<ArenaDescriptor>,
+    mut assets: ResMut<GameAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut spawner: ResMut<SaucerSpawner>,
+    mut rng: ResMut<SeededRng>,
+    mut saucers: Query<With<Saucer, Entity>>,
+    mut notices: Query<(Mut<NoticeText>, Mut<Text>)>,
+) {
+    spawner.0.tick(time.delta_seconds);
+    if !spawner.0.just_finished
+        || *state != GameState::Playing
+        || saucers.iter().iter().next().is_some()
+    {
+        return;
+    }
+    let rng = &mut rng.0;
+    let half_extents = arena.inner_half_extents(SAUCER_RADIUS);
+    let (x, y) = if rng.gen() {
+        let side = if rng.gen() { 1.0 } else { -1.0 };
+        (
+            side * half_extents.x(),
+            rng.gen_range(-1.0, 1.0) * half_extents.y(),
+        )
+    } else {
+        let side = if rng.gen() { 1.0 } else { -1.0 };
+        (
+            rng.gen_range(-1.0, 1.0) * half_extents.x(),
+            side * half_extents.y(),
+        )
+    };
+    let offset = UnitComplex::new(rng.gen_range(0.0, 2.0 * std::f32::consts::PI))
+        .transform_vector(&Vector2::x())
+        * SAUCER_OFFSET;
+    let material = assets.sphere_tinted(Color::rgb(1.0, 0.3, 0.8), &mut materials);
+    commands
+        .spawn(SpriteComponents {
+            // Flattened, as a saucer
+            sprite: Sprite::new(Vec2::new(2.0 * SAUCER_RADIUS, SAUCER_RADIUS)),
+            transform: Transform::from_translation(Vec3::new(x, y, -1.0)),
+            material,
+            ..Default::default()
+        })
+        .with(Saucer {
+            offset,
+            fire: Timer::from_seconds(SAUCER_FIRE_PERIOD, true),
+        })
+        .with(Lifetime(SAUCER_LIFETIME))
+        .with(RigidBodyBuilder::new_dynamic().translation(x, y))
+        .with(ColliderBuilder::ball(SAUCER_RADIUS))
+        .with(CollisionGroups::of(groups::SAUCER));
+    for (mut notice, mut text) in &mut notices.iter() {
+        text.value = "Saucer incoming!".to_string();
+        notice.timer.reset();
+    }
+}
+
+/// Steers the saucers toward a point offset from the nearest ship, firing at
+/// it with an imperfect aim.
+fn saucer_system(
+    time: Res<Time>,
+    players: Res<Players>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut requests: ResMut<Events<SpawnRequest<Spawnable>>>,
+    mut rng: ResMut<SeededRng>,
+    mut saucers: Query<(Mut<Saucer>, &RigidBodyHandleComponent)>,
+    ships: Query<&RigidBodyHandleComponent>,
+) {
+    let rng = &mut rng.0;
+    let ship_positions: Vec<Vector2<f32>> = players
+        .ships()
+        .filter_map(|ship| ships.get::<RigidBodyHandleComponent>(ship).ok())
+        .filter_map(|body_handle| bodies.get(body_handle.handle()))
+        .map(|body| body.position.translation.vector)
+        .collect();
+    for (mut saucer, body_handle) in &mut saucers.iter() {
+        let mut body = bodies.get_mut(body_handle.handle()).unwrap();
+        let position = body.position.translation.vector;
+        let target = ship_positions
+            .iter()
+            .min_by(|a, b| {
+                let (a, b) = ((*a - position).norm(), (*b - position).norm());
+                a.partial_cmp(&b).unwrap()
+            })
+            .copied();
+        saucer.fire.tick(time.delta_seconds);
+        let target = match target {
+            Some(target) => target,
+            // Drifting until a ship respawns
+            None => continue,
+        };
+        let desired = (target + saucer.offset - position)
+            .try_normalize(std::f32::EPSILON)
+            .unwrap_or_else(Vector2::zeros)
+            * SAUCER_SPEED;
+        let force = (desired - body.linvel) * (SAUCER_STEERING * body.mass());
+        body.apply_force(force);
+        let speed = body.linvel.norm();
+        if speed > SAUCER_SPEED {
+            body.linvel *= SAUCER_SPEED / speed;
+        }
+        body.wake_up(true);
+        if !saucer.fire.just_finished {
+            continue;
+        }
+        let aim = (target - position)
+            .try_normalize(std::f32::EPSILON)
+            .unwrap_or_else(Vector2::x);
+        let direction = UnitComplex::new(rng.gen_range(-SAUCER_AIM_ERROR, SAUCER_AIM_ERROR))
+            .transform_vector(&aim);
+        let position = position + direction * (SAUCER_RADIUS + BULLET_RADIUS + 0.2);
+        let linvel = direction * SAUCER_BULLET_SPEED;
+        requests.send(SpawnRequest::new(
+            Spawnable::SaucerBullet,
+            to_vec2(position),
+            to_vec2(linvel),
+        ));
+    }
+}
+
+/// Destroys the saucers hit by the bullets of the ships.
+fn saucer_hit_system(
+    mut commands: Commands,
+    mut reader: Local<EventReader<ProximityEvent>>,
+    proximity_events: Res<Events<ProximityEvent>>,
+    h_to_e: Res<ColliderHandleToEntity>,
+    bodies: Res<RigidBodySet>,
+    mut score_events: ResMut<Events<ScoreEvent>>,
+    bullets: Query<&Bullet>,
+    saucers: Query<(&Saucer, &RigidBodyHandleComponent)>,
+) {
+    let mut destroyed = HashSet::new();
+    for proximity_event in reader.iter(&proximity_events) {
+        if proximity_event.new_status != Proximity::Intersecting {
+            continue;
+        }
+        let e1 = h_to_e.get(proximity_event.collider1);
+        let e2 = h_to_e.get(proximity_event.collider2);
+        let (bullet, saucer) = match (e1, e2) {
+            (Some(e1), Some(e2)) if saucers.get::<Saucer>(e2).is_ok() => (e1, e2),
+            (Some(e1), Some(e2)) if saucers.get::<Saucer>(e1).is_ok() => (e2, e1),
+            _ => continue,
+        };
+        let shooter = match bullets.get::<Bullet>(bullet) {
+            Ok(bullet) => bullet.0,
+            Err(_) => continue,
+        };
+        if !destroyed.insert(saucer) {
+            continue;
+        }
+        commands.despawn(bullet);
+        commands.despawn(saucer);
+        let position = saucers
+            .get::<RigidBodyHandleComponent>(saucer)
+            .ok()
+            .and_then(|body_handle| bodies.get(body_handle.handle()))
+            .map_or_else(Vector2::zeros, |body| body.position.translation.vector);
+        score_events.send(ScoreEvent {
+            player: shooter.0,
+            points: SAUCER_SCORE,
+            position,
+        });
+    }
+}
+
+/// Damages the ships hit by saucer bullets, which go through anything else.
+/// A shield absorbs the bullet.
+fn saucer_bullet_hit_system(
+    mut commands: Commands,
+    mut reader: Local<EventReader<ProximityEvent>>,
+    proximity_events: Res<Events<ProximityEvent>>,
+    h_to_e: Res<ColliderHandleToEntity>,
+    mut players: ResMut<Players>,
+    mut state: ResMut<GameState>,
+    mut bursts: ResMut<Events<BurstEvent>>,
+    bodies: Res<RigidBodySet>,
+    saucer_bullets: Query<&SaucerBullet>,
+    invulnerables: Query<&Invulnerable>,
+    shields: Query<&Shield>,
+    ships: Query<(&Ship, &RigidBodyHandleComponent)>,
+    hulls: Query<Mut<Hull>>,
+) {
+    let mut used_bullets = HashSet::new();
+    for proximity_event in reader.iter(&proximity_events) {
+        if proximity_event.new_status != Proximity::Intersecting {
+            continue;
+        }
+        let e1 = h_to_e.get(proximity_event.collider1);
+        let e2 = h_to_e.get(proximity_event.collider2);
+        let (bullet, ship) = match (e1, e2) {
+            (Some(e1), Some(e2)) if saucer_bullets.get::<SaucerBullet>(e1).is_ok() => (e1, e2),
+            (Some(e1), Some(e2)) if saucer_bullets.get::<SaucerBullet>(e2).is_ok() => (e2, e1),
+            _ => continue,
+        };
+        // Ships lost earlier this frame are no longer flown by their player
+        let index = match players.player_of(ship) {
+            Some(index) => index,
+            None => continue,
+        };
+        if invulnerables.get::<Invulnerable>(ship).is_ok() || !used_bullets.insert(bullet) {
+            continue;
+        }
+        commands.despawn(bullet);
+        if shields.get::<Shield>(ship).is_ok() {
+            continue;
+        }
+        let destroyed = match hulls.get_mut::<Hull>(ship) {
+            Ok(mut hull) => hull.damage(SAUCER_BULLET_DAMAGE),
+            Err(_) => true,
+        };
+        if !destroyed {
+            continue;
+        }
+        if let Ok(ship_handle) = ships.get::<RigidBodyHandleComponent>(ship) {
+            if let Some(body) = bodies.get(ship_handle.handle()) {
+                bursts.send(BurstEvent {
+                    position: body.position.translation.vector,
+                    kind: BurstKind::Ship,
+                });
+            }
+        }
+        lose_ship(&mut commands, &mut players, &mut state, index);
+    }
+}
+
+fn setup_saucer_indicator(mut commands: Commands, mut materials: ResMut<Assets<ColorMaterial>>) {
+    commands
+        .spawn(SpriteComponents {
+            sprite: Sprite::new(Vec2::new(1.0, 1.0)),
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 5.0)),
+            material: materials.add(Color::rgb(1.0, 0.3, 0.8).into()),
+            draw: Draw {
+                is_visible: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .with(SaucerIndicator);
+}
+
+/// Shows the indicator on the screen edge toward the saucer, while the saucer
+/// is out of view.
+fn saucer_indicator_system(
+    mut cameras: Query<&MainCamera>,
+    mut saucers: Query<With<Saucer, &Transform>>,
+    mut indicators: Query<(&SaucerIndicator, Mut<Transform>, Mut<Draw>)>,
+) {
+    // Not shaking with the screen
+    let camera = match cameras.iter().iter().next() {
+        Some(camera) => camera.base.extend(0.0),
+        None => return,
+    };
+    let saucer = saucers
+        .iter()
+        .iter()
+        .next()
+        .map(|transform| transform.translation() - camera);
+    // The view of the camera, whatever the arena size
+    let half_view = ArenaDescriptor::default().half_extents;
+    let out_of_view = saucer
+        .filter(|offset| offset.x().abs() >= half_view.x() || offset.y().abs() >= half_view.y());
+    for (_, mut transform, mut draw) in &mut indicators.iter() {
+        draw.is_visible = out_of_view.is_some();
+        if let Some(offset) = out_of_view {
+            let limit = half_view - Vec2::new(INDICATOR_MARGIN, INDICATOR_MARGIN);
+            // Scaled toward the view center, so that it points at the saucer
+            let scale = (limit.x() / offset.x().abs()).min(limit.y() / offset.y().abs());
+            let position = camera + offset * scale;
+            transform.set_translation(Vec3::new(position.x(), position.y(), 5.0));
+            transform.set_rotation(Quat::from_rotation_z(
+                offset.y().atan2(offset.x()) + std::f32::consts::FRAC_PI_4,
+            ));
+        }
+    }
+}
+
+/// The arena scaled down in the bottom right corner of the screen, with a dot
+/// for each asteroid, ship, saucer and powerup, toggled with M.
+///
+/// The dots are UI nodes children of the `field`, kept for the same entity
+/// from frame to frame and reused once it is gone.
+struct Minimap {
+    shown: bool,
+    field: Entity,
+    /// Dot of each entity shown
+    dots: HashMap<Entity, Entity>,
+    /// Hidden dots, to be reused
+    free: Vec<Entity>,
+}
+
+struct MinimapMaterials {
+    /// By asteroid size, from small to boss
+    asteroids: [Handle<ColorMaterial>; 4],
+    saucer: Handle<ColorMaterial>,
+    /// By powerup, in the order of `Powerup`
+    powerups: [Handle<ColorMaterial>; 5],
+}
+
+/// The outline of the minimap, its child being the field
+struct MinimapFrame;
+
+struct MinimapField;
+
+/// The part of the arena seen by the camera, only shown in follow mode
+struct MinimapView;
+
+struct MinimapDot;
+
+fn setup_minimap(mut commands: Commands, mut materials: ResMut<Assets<ColorMaterial>>) {
+    let mut material = |color: Color| materials.add(color.into());
+    let minimap_materials = MinimapMaterials {
+        asteroids: [
+            material(Color::rgb(0.55, 0.55, 0.55)),
+            material(Color::rgb(0.75, 0.65, 0.5)),
+            material(Color::rgb(0.95, 0.6, 0.3)),
+            material(Color::rgb(1.0, 0.25, 0.25)),
+        ],
+        saucer: material(Color::rgb(1.0, 0.3, 0.8)),
+        powerups: [
+            material(Powerup::Shield.color()),
+            material(Powerup::RapidFire.color()),
+            material(Powerup::SpreadShot.color()),
+            material(Powerup::Ricochet.color()),
+            material(Powerup::Fuel.color()),
+        ],
+    };
+    let (outline, background, view) = (
+        material(Color::rgba(0.6, 0.6, 0.7, 0.8)),
+        material(Color::rgba(0.05, 0.05, 0.1, 0.7)),
+        material(Color::rgba(1.0, 1.0, 1.0, 0.1)),
+    );
+    // Sized by the minimap system, from the arena
+    commands
+        .spawn(NodeComponents {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    bottom: Val::Px(36.0),
+                    right: Val::Px(10.0),
+                    ..Default::default()
+                },
+                padding: Rect {
+                    left: Val::Px(1.0),
+                    right: Val::Px(1.0),
+                    top: Val::Px(1.0),
+                    bottom: Val::Px(1.0),
+                },
+                ..Default::default()
+            },
+            material: outline,
+            ..Default::default()
+        })
+        .with(MinimapFrame);
+    let frame = commands.current_entity().unwrap();
+    commands
+        .spawn(NodeComponents {
+            material: background,
+            ..Default::default()
+        })
+        .with(MinimapField);
+    let field = commands.current_entity().unwrap();
+    commands
+        .spawn(NodeComponents {
+            style: Style {
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            material: view,
+            ..Default::default()
+        })
+        .with(MinimapView);
+    let view = commands.current_entity().unwrap();
+    commands
+        .push_children(frame, &[field])
+        .push_children(field, &[view])
+        .insert_resource(Minimap {
+            shown: true,
+            field,
+            dots: HashMap::new(),
+            free: Vec::new(),
+        })
+        .insert_resource(minimap_materials);
+}
+
+/// Shows or hides the minimap with M.
+fn minimap_toggle_system(
+    input: Res<Input<KeyCode>>,
+    mut minimap: ResMut<Minimap>,
+    mut notices: Query<(Mut<NoticeText>, Mut<Text>)>,
+) {
+    if !input.just_pressed(KeyCode::M) {
+        return;
+    }
+    minimap.shown = !minimap.shown;
+    for (mut notice, mut text) in &mut notices.iter() {
+        text.value = format!("Minimap: {}", if minimap.shown { "on" } else { "off" });
+        notice.timer.reset();
+    }
+}
+
+/// Something shown on the minimap
+struct Blip {
+    entity: Entity,
+    position: Vec2,
+    rotation: Quat,
+    size: f32,
+    material: Handle<ColorMaterial>,
+}
+
+/// Components of a minimap dot set each frame
+type DotNode<'a> = (
+    Mut<'a, Style>,
+    Mut<'a, Draw>,
+    Mut<'a, Transform>,
+    Mut<'a, Handle<ColorMaterial>>,
+);
+
+/// Lays the minimap out over the current arena, and places the dots of the
+/// entities alive at the end of the frame, hiding those of the entities gone.
+///
+/// A dot placed this frame is only laid out on the next one, so a new or
+/// reused dot stays hidden until then rather than being shown where it was.
+fn minimap_system(
+    mut commands: Commands,
+    arena: Res<ArenaDescriptor>,
+    mode: Res<CameraMode>,
+    players: Res<Players>,
+    ship_materials: Res<ShipMaterials>,
+    materials: Res<MinimapMaterials>,
+    mut minimap: ResMut<Minimap>,
+    mut cameras: Query<&MainCamera>,
+    mut asteroids: Query<(Entity, &Asteroid, &Transform)>,
+    mut pieces: Query<(Entity, &ClumpSprite, &GlobalTransform)>,
+    mut ships: Query<With<Ship, (Entity, &PlayerId, &Transform)>>,
+    mut saucers: Query<With<Saucer, (Entity, &Transform)>>,
+    mut powerups: Query<(Entity, &Powerup, &Transform)>,
+    mut frames: Query<With<MinimapFrame, (Mut<Style>, Mut<Draw>)>>,
+    mut fields: Query<With<MinimapField, (Mut<Style>, Mut<Draw>)>>,
+    mut views: Query<With<MinimapView, (Mut<Style>, Mut<Draw>)>>,
+    dots: Query<With<MinimapDot, DotNode>>,
+) {
+    let half_extents = arena.half_extents;
+    let scale = MINIMAP_WIDTH / (2.0 * half_extents.x());
+    let size = half_extents * (2.0 * scale);
+    let shown = minimap.shown;
+    for (mut style, mut draw) in &mut frames.iter() {
+        style.size = Size::new(Val::Px(size.x() + 2.0), Val::Px(size.y() + 2.0));
+        draw.is_visible = shown;
+    }
+    for (mut style, mut draw) in &mut fields.iter() {
+        style.size = Size::new(Val::Px(size.x()), Val::Px(size.y()));
+        draw.is_visible = shown;
+    }
+    // Bottom left corner in the field of a node of `side` centered on the
+    // world `position`, kept inside the field
+    let corner = |position: Vec2, side: Vec2| {
+        let corner = (position + half_extents) * scale - side / 2.0;
+        corner.max(Vec2::zero()).min(size - side)
+    };
+    let camera = cameras.iter().iter().next().map(|camera| camera.base);
+    for (mut style, mut draw) in &mut views.iter() {
+        draw.is_visible = shown && *mode == CameraMode::Follow;
+        if let Some(camera) = camera {
+            let side = ArenaDescriptor::default().half_extents * (2.0 * scale);
+            let corner = corner(camera, side.min(size));
+            style.position.left = Val::Px(corner.x());
+            style.position.bottom = Val::Px(corner.y());
+            style.size = Size::new(Val::Px(side.x()), Val::Px(side.y()));
+        }
+    }
+
+    let mut blips = Vec::new();
+    if shown {
+        let flat = |translation: Vec3| Vec2::new(translation.x(), translation.y());
+        let dot = |size: AsteroidSize| match size {
+            AsteroidSize::Small | AsteroidSize::Medium => 2.0,
+            AsteroidSize::Large | AsteroidSize::Boss => 3.0,
+        };
+        for (entity, asteroid, transform) in &mut asteroids.iter() {
+            blips.push(Blip {
+                entity,
+                position: flat(transform.translation()),
+                rotation: Quat::identity(),
+                size: dot(asteroid.size),
+                material: materials.asteroids[asteroid.size as usize],
+            });
+        }
+        for (entity, piece, transform) in &mut pieces.iter() {
+            blips.push(Blip {
+                entity,
+                position: flat(transform.translation()),
+                rotation: Quat::identity(),
+                size: dot(piece.size),
+                material: materials.asteroids[piece.size as usize],
+            });
+        }
+        for (entity, player, transform) in &mut ships.iter() {
+            let profile = players
+                .0
+                .get(player.0)
+                .map_or(DEFAULT_PROFILE, |player| player.profile);
+            blips.push(Blip {
+                entity,
+                position: flat(transform.translation()),
+                rotation: transform.rotation(),
+                size: MINIMAP_SHIP_SIZE,
+                material: ship_materials.ships[player.0][profile],
+            });
+        }
+        for (entity, transform) in &mut saucers.iter() {
+            blips.push(Blip {
+                entity,
+                position: flat(transform.translation()),
+                rotation: Quat::identity(),
+                size: MINIMAP_BLIP_SIZE,
+                material: materials.saucer,
+            });
+        }
+        for (entity, &powerup, transform) in &mut powerups.iter() {
+            blips.push(Blip {
+                entity,
+                position: flat(transform.translation()),
+                rotation: Quat::identity(),
+                size: MINIMAP_BLIP_SIZE,
+                material: materials.powerups[powerup as usize],
+            });
+        }
+    }
+
+    let mut previous = std::mem::take(&mut minimap.dots);
+    for blip in blips {
+        let side = Vec2::new(blip.size, blip.size);
+        let corner = corner(blip.position, side);
+        let style = Style {
+            position_type: PositionType::Absolute,
+            position: Rect {
+                left: Val::Px(corner.x()),
+                bottom: Val::Px(corner.y()),
+                ..Default::default()
+            },
+            size: Size::new(Val::Px(side.x()), Val::Px(side.y())),
+            ..Default::default()
+        };
+        let (dot, laid_out) = match previous.remove(&blip.entity) {
+            Some(dot) => (dot, true),
+            None => match minimap.free.pop() {
+                Some(dot) => (dot, false),
+                None => {
+                    commands
+                        .spawn(NodeComponents {
+                            style,
+                            material: blip.material,
+                            transform: Transform::from_rotation(blip.rotation),
+                            draw: Draw {
+                                is_visible: false,
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        })
+                        .with(MinimapDot);
+                    let dot = commands.current_entity().unwrap();
+                    commands.push_children(minimap.field, &[dot]);
+                    minimap.dots.insert(blip.entity, dot);
+                    continue;
+                }
+            },
+        };
+        *dots.get_mut::<Style>(dot).unwrap() = style;
+        dots.get_mut::<Draw>(dot).unwrap().is_visible = laid_out;
+        dots.get_mut::<Transform>(dot)
+            .unwrap()
+            .set_rotation(blip.rotation);
+        *dots.get_mut::<Handle<ColorMaterial>>(dot).unwrap() = blip.material;
+        minimap.dots.insert(blip.entity, dot);
+    }
+    // The entities despawned or no longer shown
+    for (_, dot) in previous {
+        if let Ok(mut draw) = dots.get_mut::<Draw>(dot) {
+            draw.is_visible = false;
+            minimap.free.push(dot);
+        }
+    }
+}
+
+/// Something blew up, to be shown by a burst of debris
+struct BurstEvent {
+    position: Vector2<f32>,
+    kind: BurstKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BurstKind {
+    Asteroid(AsteroidSize),
+    Ship,
+    Mine,
+}
+
+/// Emitter parameters of a burst
+struct BurstParams {
+    min_count: usize,
+    max_count: usize,
+    /// Range of the outward speeds in m/s
+    speed: (f32, f32),
+    /// Side of the particles in m
+    size: f32,
+    /// Lifetime of the particles in s
+    lifetime: f32,
+}
+
+impl BurstKind {
+    fn params(self) -> BurstParams {
+        match self {
+            BurstKind::Asteroid(size) => BurstParams {
+                min_count: size.tier().particles.0,
+                max_count: size.tier().particles.1,
+                speed: (2.0, 4.0 + 2.0 * size.radius()),
+                size: 0.15 + 0.05 * size.radius(),
+                lifetime: 0.8,
+            },
+            BurstKind::Ship => BurstParams {
+                min_count: 30,
+                max_count: 40,
+                speed: (4.0, 16.0),
+                size: 0.25,
+                lifetime: 1.2,
+            },
+            BurstKind::Mine => BurstParams {
+                min_count: 24,
+                max_count: 32,
+                speed: (4.0, 12.0),
+                size: 0.2,
+                lifetime: 0.9,
+            },
+        }
+    }
+}
+
+/// Materials of the particles, shared by all the particles of a color, from
+/// opaque to almost transparent.
+struct ParticleMaterials {
+    /// By asteroid size
+    asteroids: [[Handle<ColorMaterial>; PARTICLE_FADE_STEPS]; 3],
+    ship: [Handle<ColorMaterial>; PARTICLE_FADE_STEPS],
+    /// Translucent, for the engine trails
+    engine: [Handle<ColorMaterial>; PARTICLE_FADE_STEPS],
+}
+
+impl ParticleMaterials {
+    fn of(&self, kind: BurstKind) -> [Handle<ColorMaterial>; PARTICLE_FADE_STEPS] {
+        match kind {
+            BurstKind::Asteroid(AsteroidSize::Small) => self.asteroids[0],
+            BurstKind::Asteroid(AsteroidSize::Medium) => self.asteroids[1],
+            BurstKind::Asteroid(AsteroidSize::Large) | BurstKind::Asteroid(AsteroidSize::Boss) => {
+                self.asteroids[2]
+            }
+            BurstKind::Ship | BurstKind::Mine => self.ship,
+        }
+    }
+}
+
+/// A debris particle, moved without a rapier body and fading over its
+/// `Lifetime`
+struct Particle {
+    velocity: Vec2,
+    /// In rad/s
+    spin: f32,
+    /// Initial lifetime in s
+    duration: f32,
+    fade: [Handle<ColorMaterial>; PARTICLE_FADE_STEPS],
+}
+
+/// Plays the explosion animation where ships are destroyed, a mere sprite
+/// without any physics.
+fn ship_explosion_system(
+    mut commands: Commands,
+    mut reader: Local<EventReader<BurstEvent>>,
+    bursts: Res<Events<BurstEvent>>,
+    assets: Res<GameAssets>,
+) {
+    for burst in reader.iter(&bursts) {
+        if burst.kind != BurstKind::Ship {
+            continue;
+        }
+        commands
+            .spawn(SpriteSheetComponents {
+                texture_atlas: assets.explosion_atlas,
+                transform: Transform::from_translation(Vec3::new(
+                    burst.position.x,
+                    burst.position.y,
+                    -0.5,
+                ))
+                .with_scale(EXPLOSION_SIZE / 64.0),
+                ..Default::default()
+            })
+            .with(AnimationTimer::once(EXPLOSION_FRAME_TIME));
+    }
+}
+
+fn setup_particles(mut commands: Commands, mut materials: ResMut<Assets<ColorMaterial>>) {
+    let mut fading = |r: f32, g: f32, b: f32, a: f32| {
+        let mut fade = [Handle::default(); PARTICLE_FADE_STEPS];
+        for (step, handle) in fade.iter_mut().enumerate() {
+            let alpha = a * (1.0 - step as f32 / PARTICLE_FADE_STEPS as f32);
+            *handle = materials.add(Color::rgba(r, g, b, alpha).into());
+        }
+        fade
+    };
+    commands.insert_resource(ParticleMaterials {
+        asteroids: [
+            fading(0.85, 0.75, 0.6, 1.0),
+            fading(0.7, 0.55, 0.4, 1.0),
+            fading(0.55, 0.4, 0.3, 1.0),
+        ],
+        ship: fading(1.0, 0.6, 0.2, 1.0),
+        engine: fading(0.5, 0.7, 1.0, 0.5),
+    });
+}
+
+/// Spawns the debris of the bursts, keeping at most `MAX_PARTICLES` alive.
+fn particle_burst_system(
+    mut commands: Commands,
+    mut reader: Local<EventReader<BurstEvent>>,
+    bursts: Res<Events<BurstEvent>>,
+    materials: Res<ParticleMaterials>,
+    mut particles: Query<With<Particle, Entity>>,
+) {
+    let mut rng = thread_rng();
+    let mut alive = particles.iter().iter().count();
+    for burst in reader.iter(&bursts) {
+        let params = burst.kind.params();
+        let fade = materials.of(burst.kind);
+        let count = rng
+            .gen_range(params.min_count, params.max_count + 1)
+            .min(MAX_PARTICLES.saturating_sub(alive));
+        alive += count;
+        for _ in 0..count {
+            let angle = rng.gen_range(0.0, 2.0 * std::f32::consts::PI);
+            let speed = rng.gen_range(params.speed.0, params.speed.1);
+            let duration = params.lifetime * rng.gen_range(0.7, 1.3);
+            commands
+                .spawn(SpriteComponents {
+                    sprite: Sprite::new(Vec2::new(params.size, params.size)),
+                    transform: Transform::from_translation(Vec3::new(
+                        burst.position.x,
+                        burst.position.y,
+                        -0.5,
+                    )),
+                    material: fade[0],
+                    ..Default::default()
+                })
+                .with(Particle {
+                    velocity: Vec2::new(angle.cos(), angle.sin()) * speed,
+                    spin: rng.gen_range(-10.0, 10.0),
+                    duration,
+                    fade,
+                })
+                .with(Lifetime(duration))
+                .with(DespawnOutside {
+                    margin: DESPAWN_MARGIN,
+                });
+        }
+    }
+}
+
+/// Particles moving and fading
+type MovingParticle<'a> = (
+    &'a Particle,
+    &'a Lifetime,
+    Mut<'a, Transform>,
+    Mut<'a, Handle<ColorMaterial>>,
+);
+
+fn particle_system(time: Res<Time>, mut particles: Query<MovingParticle>) {
+    let dt = time.delta_seconds;
+    for (particle, lifetime, mut transform, mut material) in &mut particles.iter() {
+        let translation = transform.translation() + (particle.velocity * dt).extend(0.0);
+        transform.set_translation(translation);
+        transform.rotate(Quat::from_rotation_z(particle.spin * dt));
+        let age = 1.0 - (lifetime.0 / particle.duration).max(0.0);
+        let step = ((age * PARTICLE_FADE_STEPS as f32) as usize).min(PARTICLE_FADE_STEPS - 1);
+        if *material != particle.fade[step] {
+            *material = particle.fade[step];
+        }
+    }
+}
+
+/// Retriggers the thrust rumble every `THRUST_SOUND_PERIOD` while a ship
+/// thrusts, the sound being too short to outlast the release of the key.
+fn thrust_sound_system(
+    time: Res<Time>,
+    speaker: Res<Speaker>,
+    assets: Res<GameAssets>,
+    mut countdown: Local<f32>,
+    mut ships: Query<&ThrustState>,
+) {
+    if !ships.iter().iter().any(|thrust| thrust.0 != 0) {
+        // Played at once on the next thrust
+        *countdown = 0.0;
+        return;
+    }
+    *countdown -= time.delta_seconds;
+    if *countdown <= 0.0 {
+        speaker.play(&assets.sounds.thrust);
+        *countdown = THRUST_SOUND_PERIOD;
+    }
+}
+
+/// Plays an explosion per burst, sized as the asteroid, at most
+/// `MAX_EXPLOSION_SOUNDS` a frame so that a cluster does not clip.
+fn explosion_sound_system(
+    mut reader: Local<EventReader<BurstEvent>>,
+    bursts: Res<Events<BurstEvent>>,
+    speaker: Res<Speaker>,
+    assets: Res<GameAssets>,
+) {
+    for burst in reader.iter(&bursts).take(MAX_EXPLOSION_SOUNDS) {
+        let index = match burst.kind {
+            BurstKind::Asteroid(AsteroidSize::Small) => 0,
+            BurstKind::Asteroid(AsteroidSize::Medium) => 1,
+            BurstKind::Asteroid(AsteroidSize::Large)
+            | BurstKind::Asteroid(AsteroidSize::Boss)
+            | BurstKind::Ship
+            | BurstKind::Mine => 2,
+        };
+        speaker.play(&assets.sounds.explosions[index]);
+    }
+}
+
+/// Starts the sounds queued once loaded, as the bevy audio plugin does.
+fn speaker_system(speaker: Res<Speaker>, audio_sources: Res<Assets<AudioSource>>) {
+    if let Some(output) = &speaker.0 {
+        output.try_play_queued(&audio_sources);
+    }
+}
+
+fn engine_trails_toggle_system(
+    input: Res<Input<KeyCode>>,
+    mut trails: ResMut<EngineTrails>,
+    mut notices: Query<(Mut<NoticeText>, Mut<Text>)>,
+) {
+    if !input.just_pressed(KeyCode::F6) {
+        return;
+    }
+    trails.0 = !trails.0;
+    for (mut notice, mut text) in &mut notices.iter() {
+        text.value = format!("Engine trails: {}", if trails.0 { "on" } else { "off" });
+        notice.timer.reset();
+    }
+}
+
+/// Leaves fading puffs behind the exhaust of the thrusting ships, every
+/// `ENGINE_TRAIL_SPACING` traveled so that the trail follows their path.
+fn engine_trail_system(
+    mut commands: Commands,
+    enabled: Res<EngineTrails>,
+    materials: Res<ParticleMaterials>,
+    bodies: Res<RigidBodySet>,
+    mut ships: Query<(Mut<EngineTrail>, &ThrustState, &RigidBodyHandleComponent)>,
+) {
+    for (mut trail, thrust, body_handle) in &mut ships.iter() {
+        if !enabled.0 || thrust.0 <= 0 {
+            trail.last = None;
+            continue;
+        }
+        let body = bodies.get(body_handle.handle()).unwrap();
+        // Behind the ship, its nose being along its y axis
+        let exhaust = body.position.translation.vector
+            + body.position.rotation.transform_vector(&-Vector2::y());
+        let mut last = match trail.last {
+            // No trail across the arena on wraps and jumps
+            Some(last) if (exhaust - last).norm() < TELEPORT_DISTANCE => last,
+            _ => {
+                trail.last = Some(exhaust);
+                continue;
+            }
+        };
+        while (exhaust - last).norm() >= ENGINE_TRAIL_SPACING {
+            last += (exhaust - last).normalize() * ENGINE_TRAIL_SPACING;
+            commands
+                .spawn(SpriteComponents {
+                    sprite: Sprite::new(Vec2::new(0.4, 0.4)),
+                    transform: Transform::from_translation(Vec3::new(last.x, last.y, -1.2)),
+                    material: materials.engine[0],
+                    ..Default::default()
+                })
+                .with(Particle {
+                    velocity: Vec2::zero(),
+                    spin: 0.0,
+                    duration: ENGINE_TRAIL_LIFETIME,
+                    fade: materials.engine,
+                })
+                .with(Lifetime(ENGINE_TRAIL_LIFETIME));
+        }
+        trail.last = Some(last);
+    }
+}
+
+/// Background star
+struct Star;
+
+/// Scatters stars over the follow mode arena, tiled around it so that the
+/// camera never shows a void past the edges.
+///
+/// At startup, and again whenever the star density of the config changes.
+fn starfield_system(
+    mut commands: Commands,
+    mut reader: Local<EventReader<ConfigChanged>>,
+    events: Res<Events<ConfigChanged>>,
+    config: Res<ShowcaseConfig>,
+    mut density: Local<Option<usize>>,
+    mut assets: ResMut<GameAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut stars: Query<With<Star, Entity>>,
+) {
+    if reader.iter(&events).last().is_none() || *density == Some(config.star_density) {
+        return;
+    }
+    *density = Some(config.star_density);
+    for entity in &mut stars.iter() {
+        commands.despawn(entity);
+    }
+    let mut rng = thread_rng();
+    let material = assets.material_for(Color::rgb(0.8, 0.8, 0.9), &mut materials);
+    let tile = ArenaDescriptor::default().half_extents * (2.0 * FOLLOW_ARENA_SCALE);
+    let stars: Vec<(Vec2, f32)> = (0..config.star_density)
+        .map(|_| {
+            let position = Vec2::new(
+                rng.gen_range(-0.5, 0.5) * tile.x(),
+                rng.gen_range(-0.5, 0.5) * tile.y(),
+            );
+            (position, rng.gen_range(0.1, 0.3))
+        })
+        .collect();
+    for i in -1..=1 {
+        for j in -1..=1 {
+            let offset = Vec2::new(i as f32 * tile.x(), j as f32 * tile.y());
+            for &(position, size) in stars.iter() {
+                let position = position + offset;
+                commands
+                    .spawn(SpriteComponents {
+                        sprite: Sprite::new(Vec2::new(size, size)),
+                        transform: Transform::from_translation(Vec3::new(
+                            position.x(),
+                            position.y(),
+                            -10.0,
+                        )),
+                        material,
+                        ..Default::default()
+                    })
+                    .with(Star);
+            }
+        }
+    }
+}
+
+/// Applies the camera scale and the asteroid restitution of the config, the
+/// arena keeping its size.
+fn config_system(
+    mut reader: Local<EventReader<ConfigChanged>>,
+    events: Res<Events<ConfigChanged>>,
+    config: Res<ShowcaseConfig>,
+    mut arena: ResMut<ArenaDescriptor>,
+    mut cameras: Query<(&MainCamera, Mut<Transform>)>,
+) {
+    if reader.iter(&events).last().is_none() {
+        return;
+    }
+    arena.asteroid_restitution = config.spawn_restitution;
+    for (_camera, mut transform) in &mut cameras.iter() {
+        transform.set_scale(config.camera_scale);
+    }
+}
+
+/// Switches between the fixed and follow camera modes with F5, resizing the
+/// arena accordingly.
+fn camera_mode_system(
+    input: Res<Input<KeyCode>>,
+    mut mode: ResMut<CameraMode>,
+    mut arena: ResMut<ArenaDescriptor>,
+    mut notices: Query<(Mut<NoticeText>, Mut<Text>)>,
+) {
+    if !input.just_pressed(KeyCode::F5) {
+        return;
+    }
+    let (new_mode, scale) = match *mode {
+        CameraMode::Fixed => (CameraMode::Follow, FOLLOW_ARENA_SCALE),
+        CameraMode::Follow => (CameraMode::Fixed, 1.0),
+    };
+    *mode = new_mode;
+    // The walls staying where they are
+    if !arena.walls {
+        arena.half_extents = ArenaDescriptor::default().half_extents * scale;
+    }
+    for (mut notice, mut text) in &mut notices.iter() {
+        text.value = format!("Camera: {:?}", *mode);
+        notice.timer.reset();
+    }
+}
+
+/// Moves the camera toward the ship once out of the deadzone in follow mode,
+/// or keeps it centered on the arena.
+fn camera_follow_system(
+    time: Res<Time>,
+    mode: Res<CameraMode>,
+    players: Res<Players>,
+    bodies: Res<RigidBodySet>,
+    mut last_position: Local<Option<Vec2>>,
+    ships: Query<&RigidBodyHandleComponent>,
+    mut cameras: Query<Mut<MainCamera>>,
+) {
+    // Follows the first ship still flying
+    let ship_position = players
+        .ships()
+        .next()
+        .and_then(|ship| ships.get::<RigidBodyHandleComponent>(ship).ok())
+        .and_then(|body_handle| bodies.get(body_handle.handle()))
+        .map(|body| {
+            let translation = body.position.translation.vector;
+            Vec2::new(translation.x, translation.y)
+        });
+    for mut camera in &mut cameras.iter() {
+        match (*mode, ship_position) {
+            (CameraMode::Fixed, _) => camera.base = Vec2::zero(),
+            (CameraMode::Follow, Some(ship)) => {
+                // Wraps, hyperspace jumps and respawns move the camera along,
+                // instead of a sweep across the arena
+                if let Some(last) = *last_position {
+                    if (ship - last).length() > TELEPORT_DISTANCE {
+                        camera.base += ship - last;
+                    }
+                }
+                let offset = ship - camera.base;
+                let distance = offset.length();
+                if distance > FOLLOW_DEADZONE {
+                    let excess = offset * (1.0 - FOLLOW_DEADZONE / distance);
+                    camera.base += excess * (1.0 - (-FOLLOW_RATE * time.delta_seconds).exp());
+                }
+            }
+            (CameraMode::Follow, None) => {}
+        }
+    }
+    if ship_position.is_some() {
+        *last_position = ship_position;
+    }
+}
+
+/// Disables or enables the screen shake with F7.
+fn screen_shake_toggle_system(
+    input: Res<Input<KeyCode>>,
+    mut shake: ResMut<ScreenShake>,
+    mut notices: Query<(Mut<NoticeText>, Mut<Text>)>,
+) {
+    if !input.just_pressed(KeyCode::F7) {
+        return;
+    }
+    shake.intensity = if shake.intensity > 0.0 { 0.0 } else { 1.0 };
+    for (mut notice, mut text) in &mut notices.iter() {
+        text.value = format!(
+            "Screen shake: {}",
+            if shake.intensity > 0.0 { "on" } else { "off" }
+        );
+        notice.timer.reset();
+    }
+}
+
+/// Shakes the screen on the explosions near a ship, and on the loss of a ship.
+fn explosion_shake_system(
+    mut reader: Local<EventReader<BurstEvent>>,
+    bursts: Res<Events<BurstEvent>>,
+    players: Res<Players>,
+    bodies: Res<RigidBodySet>,
+    mut shake: ResMut<ScreenShake>,
+    ships: Query<&RigidBodyHandleComponent>,
+) {
+    for burst in reader.iter(&bursts) {
+        let strength = match burst.kind {
+            BurstKind::Asteroid(size) => 0.1 * size.radius(),
+            BurstKind::Mine => 0.4,
+            BurstKind::Ship => {
+                shake.add(0.6);
+                continue;
+            }
+        };
+        let distance = players
+            .ships()
+            .filter_map(|ship| ships.get::<RigidBodyHandleComponent>(ship).ok())
+            .filter_map(|body_handle| bodies.get(body_handle.handle()))
+            .map(|body| (body.position.translation.vector - burst.position).norm())
+            .fold(std::f32::INFINITY, f32::min);
+        if distance < SHAKE_RADIUS {
+            shake.add(strength * (1.0 - distance / SHAKE_RADIUS));
+        }
+    }
+}
+
+/// Offsets and rolls the camera from its base position by the screen shake,
+/// never accumulating the offsets.
+fn screen_shake_system(
+    time: Res<Time>,
+    mut shake: ResMut<ScreenShake>,
+    mut cameras: Query<(&MainCamera, Mut<Transform>)>,
+) {
+    let mut rng = thread_rng();
+    shake.trauma = (shake.trauma - SHAKE_DECAY * time.delta_seconds).max(0.0);
+    let amount = shake.intensity * shake.trauma * shake.trauma;
+    for (camera, mut transform) in &mut cameras.iter() {
+        // Back to the neutral transform once the trauma is gone
+        let (offset, roll) = if amount > 0.0 {
+            (
+                Vec2::new(rng.gen_range(-1.0, 1.0), rng.gen_range(-1.0, 1.0))
+                    * (SHAKE_OFFSET * amount),
+                rng.gen_range(-1.0, 1.0) * SHAKE_ROLL * amount,
+            )
+        } else {
+            (Vec2::zero(), 0.0)
+        };
+        let position = camera.base + offset;
+        let z = transform.translation().z();
+        transform.set_translation(Vec3::new(position.x(), position.y(), z));
+        transform.set_rotation(Quat::from_rotation_z(roll));
+    }
+}
+
+/// Whether the overview window was asked for with `--debug-window`
+struct DebugWindow(bool);
+
+impl DebugWindow {
+    fn from_args() -> Self {
+        DebugWindow(std::env::args().skip(1).any(|arg| arg == "--debug-window"))
+    }
+}
+
+/// Marks a quad of the overview window standing for another entity
+struct OverviewMarker;
+
+/// Overview quads by the entity they stand for, and their materials
+struct Overview {
+    markers: HashMap<Entity, Entity>,
+    ship_material: Handle<ColorMaterial>,
+    asteroid_material: Handle<ColorMaterial>,
+    bullet_material: Handle<ColorMaterial>,
+}
+
+/// Adds to the render graph a pass drawing the cameras named
+/// `OVERVIEW_CAMERA` to the swap chain of `window_id`.
+fn add_overview_pass(render_graph: &mut RenderGraph, window_id: WindowId) {
+    render_graph.add_node("overview_swap_chain", WindowSwapChainNode::new(window_id));
+    render_graph.add_node(
+        "overview_depth_texture",
+        WindowTextureNode::new(
+            window_id,
+            TextureDescriptor {
+                format: TextureFormat::Depth32Float,
+                usage: TextureUsage::OUTPUT_ATTACHMENT,
+                sample_count: 1,
+                ..Default::default()
+            },
+        ),
+    );
+    render_graph.add_system_node("overview_camera", CameraNode::new(OVERVIEW_CAMERA));
+    let mut pass = PassNode::<&MainPass>::new(PassDescriptor {
+        color_attachments: vec![RenderPassColorAttachmentDescriptor {
+            attachment: TextureAttachment::Input("color_attachment".to_string()),
+            resolve_target: None,
+            ops: Operations {
+                load: LoadOp::Clear(Color::rgb(0.05, 0.05, 0.08)),
+                store: true,
+            },
+        }],
+        depth_stencil_attachment: Some(RenderPassDepthStencilAttachmentDescriptor {
+            attachment: TextureAttachment::Input("depth".to_string()),
+            depth_ops: Some(Operations {
+                load: LoadOp::Clear(1.0),
+                store: true,
+            }),
+            stencil_ops: None,
+        }),
+        sample_count: 1,
+    });
+    pass.add_camera(OVERVIEW_CAMERA);
+    render_graph.add_node("overview_pass", pass);
+    render_graph
+        .add_slot_edge(
+            "overview_swap_chain",
+            WindowSwapChainNode::OUT_TEXTURE,
+            "overview_pass",
+            "color_attachment",
+        )
+        .unwrap();
+    render_graph
+        .add_slot_edge(
+            "overview_depth_texture",
+            WindowTextureNode::OUT_TEXTURE,
+            "overview_pass",
+            "depth",
+        )
+        .unwrap();
+    render_graph
+        .add_node_edge("overview_camera", "overview_pass")
+        .unwrap();
+}
+
+/// Opens the overview window with `--debug-window`, its camera showing the
+/// whole follow mode arena.
+///
+/// The markers live in a copy of the arena offset by `OVERVIEW_OFFSET`, out of
+/// sight of the main camera, and the overview camera only sees them.
+fn setup_overview_window(
+    mut commands: Commands,
+    debug_window: Res<DebugWindow>,
+    mut assets: ResMut<GameAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut create_window_events: ResMut<Events<CreateWindow>>,
+    mut active_cameras: ResMut<ActiveCameras>,
+    mut render_graph: ResMut<RenderGraph>,
+) {
+    if !debug_window.0 {
+        return;
+    }
+    let window_id = WindowId::new();
+    create_window_events.send(CreateWindow {
+        id: window_id,
+        descriptor: WindowDescriptor {
+            title: "Spaceship 01 overview".to_string(),
+            width: OVERVIEW_WIDTH,
+            height: OVERVIEW_HEIGHT,
+            vsync: false,
+            ..Default::default()
+        },
+    });
+    add_overview_pass(&mut render_graph, window_id);
+    active_cameras.add(OVERVIEW_CAMERA);
+
+    let extents = ArenaDescriptor::default().half_extents * (2.0 * FOLLOW_ARENA_SCALE);
+    // With a margin around the arena
+    let scale =
+        1.1 * (extents.x() / OVERVIEW_WIDTH as f32).max(extents.y() / OVERVIEW_HEIGHT as f32);
+    let mut camera = Camera2dComponents {
+        orthographic_projection: OrthographicProjection {
+            far: 1000.0 / scale,
+            ..Default::default()
+        },
+        transform: Transform::from_translation(Vec3::new(OVERVIEW_OFFSET, 0.0, 10.0))
+            .with_scale(scale),
+        ..Default::default()
+    };
+    camera.camera.name = Some(OVERVIEW_CAMERA.to_string());
+    camera.camera.window = window_id;
+    commands.spawn(camera);
+    commands.insert_resource(Overview {
+        markers: HashMap::new(),
+        ship_material: assets.material_for(Color::rgb(0.3, 0.9, 0.4), &mut materials),
+        asteroid_material: assets.material_for(Color::rgb(0.6, 0.55, 0.5), &mut materials),
+        bullet_material: assets.material_for(Color::rgb(1.0, 0.9, 0.3), &mut materials),
+    });
+}
+
+/// Mirrors the positions of the ships, asteroids and bullets onto their quad of
+/// the overview window, spawning the missing quads and despawning those of
+/// the entities gone.
+fn overview_marker_system(
+    mut commands: Commands,
+    overview: Option<ResMut<Overview>>,
+    ships: Query<With<Ship, (Entity, &Transform)>>,
+    asteroids: Query<(Entity, &Asteroid, &Transform)>,
+    bullets: Query<With<Bullet, (Entity, &Transform)>>,
+    mut markers: Query<With<OverviewMarker, Mut<Transform>>>,
+) {
+    let mut overview = match overview {
+        Some(overview) => overview,
+        None => return,
+    };
+    // Position, size and material of the quad of each entity
+    let mut targets = Vec::new();
+    for (entity, transform) in &mut ships.iter() {
+        targets.push((entity, transform.translation(), 2.0, overview.ship_material));
+    }
+    for (entity, asteroid, transform) in &mut asteroids.iter() {
+        let size = 2.0 * asteroid.size.radius();
+        targets.push((
+            entity,
+            transform.translation(),
+            size,
+            overview.asteroid_material,
+        ));
+    }
+    for (entity, transform) in &mut bullets.iter() {
+        targets.push((
+            entity,
+            transform.translation(),
+            0.8,
+            overview.bullet_material,
+        ));
+    }
+    let mut seen = HashSet::with_capacity(targets.len());
+    for (entity, translation, size, material) in targets {
+        seen.insert(entity);
+        let translation = Vec3::new(translation.x() + OVERVIEW_OFFSET, translation.y(), 0.0);
+        match overview.markers.get(&entity) {
+            Some(&marker) => {
+                if let Ok(mut transform) = markers.get_mut::<Transform>(marker) {
+                    transform.set_translation(translation);
+                }
+            }
+            None => {
+                commands
+                    .spawn(SpriteComponents {
+                        sprite: Sprite::new(Vec2::new(size, size)),
+                        material,
+                        transform: Transform::from_translation(translation),
+                        ..Default::default()
+                    })
+                    .with(OverviewMarker);
+                let marker = commands.current_entity().unwrap();
+                overview.markers.insert(entity, marker);
+            }
+        }
+    }
+    let gone: Vec<Entity> = overview
+        .markers
+        .keys()
+        .filter(|entity| !seen.contains(entity))
+        .copied()
+        .collect();
+    for entity in gone {
+        if let Some(marker) = overview.markers.remove(&entity) {
+            commands.despawn(marker);
+        }
+    }
+}
+
+/// Caps the asteroid velocities after the physics step to the maximum of their
+/// size, so that a chain of collisions cannot make one unplayably fast.
+fn asteroid_speed_cap_system(
+    mut bodies: ResMut<RigidBodySet>,
+    mut asteroids: Query<(&Asteroid, &RigidBodyHandleComponent)>,
+) {
+    for (asteroid, body_handle) in &mut asteroids.iter() {
+        let max_speed = asteroid.size.tier().max_speed;
+        if let Some(mut body) = bodies.get_mut(body_handle.handle()) {
+            let speed = body.linvel.norm();
+            if speed > max_speed {
+                body.linvel *= max_speed / speed;
+            }
+        }
+    }
+}
+
+/// Caps the ship velocities after the physics step, keeping their direction.
+///
+/// The cap is raised by the dash speed during a dash.
+fn speed_cap_system(
+    time_scale: Res<TimeScale>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut ships: Query<(&Ship, &RigidBodyHandleComponent, Option<&Dash>)>,
+) {
+    for (ship, body_handle, dash) in &mut ships.iter() {
+        let mut body = bodies.get_mut(body_handle.handle()).unwrap();
+        let speed = body.linvel.norm();
+        let max_speed = if dash.map_or(false, Dash::is_dashing) {
+            ship.max_speed + DASH_SPEED
+        } else {
+            ship.max_speed
+        };
+        if speed > max_speed {
+            body.linvel *= max_speed / speed;
+        }
+        // The turn rate being compensated in bullet time
+        let max_angvel = ship.max_angvel / time_scale.0;
+        body.angvel = body.angvel.max(-max_angvel).min(max_angvel);
+    }
+}
+
+/// Shows the speed and angular velocity of the first ship in the debug HUD,
+/// or dashes while it is destroyed.
+fn debug_hud_ship_system(
+    players: Res<Players>,
+    bodies: Res<RigidBodySet>,
+    mut hud: ResMut<DebugHud>,
+    ships: Query<&RigidBodyHandleComponent>,
+) {
+    if !hud.visible {
+        return;
+    }
+    let body = players
+        .ships()
+        .next()
+        .and_then(|ship| ships.get::<RigidBodyHandleComponent>(ship).ok())
+        .and_then(|body_handle| bodies.get(body_handle.handle()));
+    hud.extra = Some(match body {
+        Some(body) => format!(
+            "Ship: {:.1} m/s, {:.2} rad/s",
+            body.linvel.norm(),
+            body.angvel
+        ),
+        None => "Ship: —".to_string(),
+    });
+}
+
+fn speed_text_system(
+    players: Res<Players>,
+    bodies: Res<RigidBodySet>,
+    ships: Query<&RigidBodyHandleComponent>,
+    mut texts: Query<With<SpeedText, Mut<Text>>>,
+) {
+    let speed = players
+        .ships()
+        .next()
+        .and_then(|ship| ships.get::<RigidBodyHandleComponent>(ship).ok())
+        .and_then(|body_handle| bodies.get(body_handle.handle()))
+        .map_or(0.0, |body| body.linvel.norm());
+    for mut text in &mut texts.iter() {
+        text.value = format!("Speed: {:5.1} m/s", speed);
     }
 }
@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+
+/// Advances the `TextureAtlasSprite` of the entity by one frame at each tick.
+///
+/// Without `looping`, the entity is despawned once its last frame has been
+/// shown for a tick, e.g. for one-shot explosions.
+pub struct AnimationTimer {
+    /// Repeating, of the duration of a frame
+    pub timer: Timer,
+    pub looping: bool,
+}
+
+impl AnimationTimer {
+    /// Plays the frames once, `frame_duration` s each, then despawns.
+    pub fn once(frame_duration: f32) -> Self {
+        AnimationTimer {
+            timer: Timer::from_seconds(frame_duration, true),
+            looping: false,
+        }
+    }
+
+    /// Plays the frames in a loop, `frame_duration` s each.
+    pub fn looping(frame_duration: f32) -> Self {
+        AnimationTimer {
+            looping: true,
+            ..AnimationTimer::once(frame_duration)
+        }
+    }
+}
+
+/// Animated sprite sheets
+type Animation<'a> = (
+    Entity,
+    Mut<'a, AnimationTimer>,
+    Mut<'a, TextureAtlasSprite>,
+    &'a Handle<TextureAtlas>,
+);
+
+pub fn sprite_sheet_animation_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    atlases: Res<Assets<TextureAtlas>>,
+    mut query: Query<Animation>,
+) {
+    for (entity, mut animation, mut sprite, atlas_handle) in &mut query.iter() {
+        animation.timer.tick(time.delta_seconds);
+        if !animation.timer.just_finished {
+            continue;
+        }
+        let frames = match atlases.get(atlas_handle) {
+            Some(atlas) => atlas.len() as u32,
+            None => continue,
+        };
+        if sprite.index + 1 < frames {
+            sprite.index += 1;
+        } else if animation.looping {
+            sprite.index = 0;
+        } else {
+            commands.despawn_recursive(entity);
+        }
+    }
+}
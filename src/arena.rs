@@ -0,0 +1,85 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Where the world origin lies in the arena.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ArenaOrigin {
+    /// The arena spans from `(0, 0)` to `(width, height)`.
+    BottomLeft,
+    /// The arena spans from `-(width, height) / 2` to `(width, height) / 2`.
+    Center,
+}
+
+/// Playable area, in world units.
+#[derive(Clone, Copy, Debug)]
+pub struct Arena {
+    pub width: f32,
+    pub height: f32,
+    pub origin: ArenaOrigin,
+}
+
+impl Arena {
+    pub fn new(width: f32, height: f32) -> Self {
+        Arena {
+            width,
+            height,
+            origin: ArenaOrigin::BottomLeft,
+        }
+    }
+
+    pub fn centered(width: f32, height: f32) -> Self {
+        Arena {
+            origin: ArenaOrigin::Center,
+            ..Arena::new(width, height)
+        }
+    }
+
+    /// Bottom left corner
+    pub fn min(&self) -> Vec2 {
+        match self.origin {
+            ArenaOrigin::BottomLeft => Vec2::zero(),
+            ArenaOrigin::Center => -self.size() / 2.0,
+        }
+    }
+
+    /// Top right corner
+    pub fn max(&self) -> Vec2 {
+        self.min() + self.size()
+    }
+
+    pub fn center(&self) -> Vec2 {
+        self.min() + self.size() / 2.0
+    }
+
+    pub fn size(&self) -> Vec2 {
+        Vec2::new(self.width, self.height)
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        let (min, max) = (self.min(), self.max());
+        point.x() >= min.x() && point.x() <= max.x() && point.y() >= min.y() && point.y() <= max.y()
+    }
+
+    /// Clamps `point` inside the arena, keeping it at least `margin` away from
+    /// the borders, e.g. so that a spawned body of radius `margin` is fully in.
+    pub fn clamp(&self, point: Vec2, margin: f32) -> Vec2 {
+        let clamp = |v: f32, min: f32, max: f32| {
+            if max - min < 2.0 * margin {
+                (min + max) / 2.0
+            } else {
+                v.max(min + margin).min(max - margin)
+            }
+        };
+        let (min, max) = (self.min(), self.max());
+        Vec2::new(
+            clamp(point.x(), min.x(), max.x()),
+            clamp(point.y(), min.y(), max.y()),
+        )
+    }
+
+    /// Translation taking a point of this arena to the same place in the arena
+    /// with the `origin` convention.
+    pub fn offset_to(&self, origin: ArenaOrigin) -> Vec2 {
+        Arena { origin, ..*self }.min() - self.min()
+    }
+}
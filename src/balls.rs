@@ -0,0 +1,70 @@
+use crate::{arena::Arena, physics_math::wrap_position};
+use bevy::prelude::*;
+use bevy_rapier2d::{
+    physics::RigidBodyHandleComponent,
+    rapier::{
+        dynamics::{RigidBodyBuilder, RigidBodySet},
+        geometry::ColliderBuilder,
+    },
+};
+use rand::{rngs::StdRng, Rng};
+
+/// Body and collider of a dynamic ball.
+///
+/// With a negative friction, to kind of simulate no loss of energy.
+pub fn ball_builders(
+    position: Vec2,
+    velocity: Vec2,
+    radius: f32,
+    restitution: f32,
+) -> (RigidBodyBuilder, ColliderBuilder) {
+    let body = RigidBodyBuilder::new_dynamic()
+        .translation(position.x(), position.y())
+        .linvel(velocity.x(), velocity.y());
+    let collider = ColliderBuilder::ball(radius)
+        .friction(-0.5)
+        .restitution(restitution);
+    (body, collider)
+}
+
+/// Positions and velocities of balls laid on a grid covering the arena, with
+/// a slight jitter and tiny velocities.
+pub fn jittered_grid(
+    rng: &mut StdRng,
+    arena: &Arena,
+    columns: usize,
+    rows: usize,
+) -> Vec<(f32, f32, f32, f32)> {
+    let min = arena.min();
+    let dx = arena.width / columns as f32;
+    let dy = arena.height / rows as f32;
+    let mut balls = Vec::with_capacity(columns * rows);
+    for column in 0..columns {
+        for row in 0..rows {
+            let x = min.x() + (column as f32 + 0.5) * dx + rng.gen_range(-0.1, 0.1) * dx;
+            let y = min.y() + (row as f32 + 0.5) * dy + rng.gen_range(-0.1, 0.1) * dy;
+            balls.push((x, y, rng.gen_range(-5.0, 5.0), rng.gen_range(-5.0, 5.0)));
+        }
+    }
+    balls
+}
+
+/// Wraps the rapier bodies leaving the arena around to the opposite edge.
+pub fn wrap_system(
+    arena: Res<Arena>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut query: Query<&RigidBodyHandleComponent>,
+) {
+    for body_handle in &mut query.iter() {
+        let mut body = bodies.get_mut(body_handle.handle()).unwrap();
+        let translation = body.position.translation.vector;
+        let position = Vec2::new(translation.x, translation.y);
+        let velocity = Vec2::new(body.linvel.x, body.linvel.y);
+        if let Some(wrapped) = wrap_position(position, velocity, &arena) {
+            let mut new_position = body.position;
+            new_position.translation.vector.x = wrapped.x();
+            new_position.translation.vector.y = wrapped.y();
+            body.set_position(new_position);
+        }
+    }
+}
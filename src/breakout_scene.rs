@@ -0,0 +1,543 @@
+//! A paddle, a ball and a wall of bricks bouncing through rapier, the scene of
+//! the breakout example and of the launcher.
+
+use crate::{
+    arena::Arena,
+    game_assets::GameAssets,
+    handles::ColliderHandleToEntity,
+    mouse::{CursorCamera, MousePosition},
+    scene::{ActiveScene, Scene, SceneEntity, SceneMaterials},
+    score::{score_text_system, GameState, Score, ScoreText},
+};
+use bevy::{
+    prelude::*,
+    render::camera::{OrthographicProjection, WindowOrigin},
+};
+use bevy_rapier2d::{
+    na::{Isometry2, Vector2},
+    physics::{EventQueue, RigidBodyHandleComponent},
+    rapier::{
+        dynamics::{RigidBodyBuilder, RigidBodySet},
+        geometry::{ColliderBuilder, ContactEvent},
+    },
+};
+use std::collections::HashSet;
+
+const CAMERA_SCALE: f32 = 0.1;
+/// The arena seen through the camera in the 1280x800 window of the examples
+const ARENA_WIDTH: f32 = 1280.0 * CAMERA_SCALE;
+const ARENA_HEIGHT: f32 = 800.0 * CAMERA_SCALE;
+const LIVES: u32 = 3;
+/// Half the thickness of the walls, outside the arena
+const WALL_HALF_THICKNESS: f32 = 1.0;
+const PADDLE_HALF_WIDTH: f32 = 6.0;
+const PADDLE_HALF_HEIGHT: f32 = 0.75;
+/// Height of the paddle center above the bottom of the arena
+const PADDLE_Y: f32 = 5.0;
+/// Paddle speed with A and D, in units/s
+const PADDLE_SPEED: f32 = 90.0;
+const BALL_RADIUS: f32 = 0.8;
+/// Ball speed when served
+const BALL_SPEED: f32 = 55.0;
+/// Band the ball speed is kept within after each physics step
+const BALL_MIN_SPEED: f32 = 45.0;
+const BALL_MAX_SPEED: f32 = 85.0;
+/// Smallest vertical part of the ball velocity, as a fraction of its speed,
+/// so that the ball never bounces endlessly between the side walls.
+const BALL_MIN_VERTICAL: f32 = 0.25;
+/// Angle in rad from the vertical of a ball bouncing off a paddle end
+const MAX_BOUNCE_ANGLE: f32 = 1.0;
+const BRICK_COLUMNS: usize = 10;
+const BRICK_ROWS: usize = 5;
+const BRICK_HALF_HEIGHT: f32 = 1.4;
+/// Gap between two bricks
+const BRICK_GAP: f32 = 0.8;
+/// Height of the top of the brick grid below the top of the arena
+const BRICK_TOP_MARGIN: f32 = 10.0;
+/// Points of a brick of the bottom row, each row above giving as much more
+const BRICK_POINTS: u32 = 10;
+const BRICK_COLORS: [Color; BRICK_ROWS] = [
+    Color::rgb(0.9, 0.25, 0.25),
+    Color::rgb(0.95, 0.55, 0.2),
+    Color::rgb(0.95, 0.85, 0.25),
+    Color::rgb(0.35, 0.8, 0.35),
+    Color::rgb(0.3, 0.55, 0.95),
+];
+
+pub const SCENE: Scene = Scene {
+    name: "Breakout",
+    setup,
+    teardown,
+};
+
+/// Adds the game state, the score and the systems of the scene, running while
+/// it is the active one.
+///
+/// The ball and brick contacts are found through the `HandleToEntityPlugin`.
+pub struct BreakoutScenePlugin;
+
+impl Plugin for BreakoutScenePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_resource(GameState::Serving)
+            .add_resource(Score::new(LIVES))
+            .init_resource::<BrickMaterials>()
+            .add_system(paddle_system.system())
+            .add_system(serve_system.system())
+            .add_system(ball_system.system())
+            .add_system(restart_system.system())
+            .add_system(score_text_system::<Score>.system())
+            .add_system(message_text_system.system())
+            .add_system_to_stage(stage::POST_UPDATE, contact_system.system())
+            .add_system_to_stage(stage::POST_UPDATE, ball_speed_system.system());
+    }
+}
+
+struct Paddle;
+
+struct Ball;
+
+struct Brick {
+    points: u32,
+}
+
+/// Centered message, e.g. to serve or restart
+struct MessageText;
+
+/// Materials of the bricks of each row, from the bottom one
+#[derive(Default)]
+struct BrickMaterials([Handle<ColorMaterial>; BRICK_ROWS]);
+
+/// The camera, the texts, the walls, the paddle and the bricks.
+///
+/// The texts are seen through the UI camera of the example.
+fn setup(commands: &mut Commands, resources: &mut Resources) {
+    let mut assets = resources.get_mut::<GameAssets>().unwrap();
+    let mut materials = resources.get_mut::<Assets<ColorMaterial>>().unwrap();
+    let mut scene_materials = resources.get_mut::<SceneMaterials>().unwrap();
+    let font = assets.font;
+    commands
+        .spawn(Camera2dComponents {
+            orthographic_projection: OrthographicProjection {
+                window_origin: WindowOrigin::BottomLeft,
+                far: 1000.0 / CAMERA_SCALE,
+                ..Default::default()
+            },
+            transform: Transform::from_scale(CAMERA_SCALE),
+            ..Default::default()
+        })
+        .with(CursorCamera)
+        .with(SceneEntity)
+        .spawn(TextComponents {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(10.0),
+                    left: Val::Px(10.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text {
+                value: String::new(),
+                font,
+                style: TextStyle {
+                    font_size: 24.0,
+                    color: Color::WHITE,
+                },
+            },
+            ..Default::default()
+        })
+        .with(ScoreText::new(0, true, 0.0))
+        .with(SceneEntity)
+        .spawn(NodeComponents {
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            material: assets.material_for(Color::NONE, &mut materials),
+            ..Default::default()
+        })
+        .with(SceneEntity)
+        .with_children(|parent| {
+            parent
+                .spawn(TextComponents {
+                    text: Text {
+                        value: String::new(),
+                        font,
+                        style: TextStyle {
+                            font_size: 40.0,
+                            color: Color::WHITE,
+                        },
+                    },
+                    ..Default::default()
+                })
+                .with(MessageText);
+        });
+    commands.insert_resource(Arena::new(ARENA_WIDTH, ARENA_HEIGHT));
+    spawn_walls(
+        commands,
+        scene_materials.add(Color::rgb(0.4, 0.4, 0.5).into(), &mut materials),
+    );
+    spawn_paddle(
+        commands,
+        scene_materials.add(Color::rgb(0.85, 0.85, 0.95).into(), &mut materials),
+    );
+    let mut bricks = [Handle::default(); BRICK_ROWS];
+    for (handle, &color) in bricks.iter_mut().zip(BRICK_COLORS.iter()) {
+        *handle = scene_materials.add(color.into(), &mut materials);
+    }
+    let bricks = BrickMaterials(bricks);
+    spawn_brick_grid(commands, &bricks);
+    commands.insert_resource(bricks);
+}
+
+/// Back to a new game, for the next time the scene starts.
+fn teardown(_world: &mut World, resources: &mut Resources) {
+    *resources.get_mut::<GameState>().unwrap() = GameState::Serving;
+    *resources.get_mut::<Score>().unwrap() = Score::new(LIVES);
+}
+
+/// Static walls on the left, right and top sides, the bottom being open.
+fn spawn_walls(commands: &mut Commands, material: Handle<ColorMaterial>) {
+    let half_height = ARENA_HEIGHT / 2.0 + WALL_HALF_THICKNESS;
+    let half_width = ARENA_WIDTH / 2.0 + 2.0 * WALL_HALF_THICKNESS;
+    // Center and half extents of each wall
+    let walls = [
+        (
+            Vec2::new(-WALL_HALF_THICKNESS, ARENA_HEIGHT / 2.0),
+            Vec2::new(WALL_HALF_THICKNESS, half_height),
+        ),
+        (
+            Vec2::new(ARENA_WIDTH + WALL_HALF_THICKNESS, ARENA_HEIGHT / 2.0),
+            Vec2::new(WALL_HALF_THICKNESS, half_height),
+        ),
+        (
+            Vec2::new(ARENA_WIDTH / 2.0, ARENA_HEIGHT + WALL_HALF_THICKNESS),
+            Vec2::new(half_width, WALL_HALF_THICKNESS),
+        ),
+    ];
+    for &(center, half_extents) in &walls {
+        commands
+            .spawn(SpriteComponents {
+                sprite: Sprite::new(half_extents * 2.0),
+                material,
+                ..Default::default()
+            })
+            .with(RigidBodyBuilder::new_static().translation(center.x(), center.y()))
+            .with(
+                ColliderBuilder::cuboid(half_extents.x(), half_extents.y())
+                    .restitution(1.0)
+                    .friction(0.0),
+            )
+            .with(SceneEntity);
+    }
+}
+
+fn spawn_paddle(commands: &mut Commands, material: Handle<ColorMaterial>) {
+    commands
+        .spawn(SpriteComponents {
+            sprite: Sprite::new(Vec2::new(PADDLE_HALF_WIDTH, PADDLE_HALF_HEIGHT) * 2.0),
+            material,
+            ..Default::default()
+        })
+        .with(Paddle)
+        .with(RigidBodyBuilder::new_kinematic().translation(ARENA_WIDTH / 2.0, PADDLE_Y))
+        .with(
+            ColliderBuilder::cuboid(PADDLE_HALF_WIDTH, PADDLE_HALF_HEIGHT)
+                .restitution(1.0)
+                .friction(0.0),
+        )
+        .with(SceneEntity);
+}
+
+/// Spawns the full grid of static bricks, the higher rows giving more points.
+fn spawn_brick_grid(commands: &mut Commands, materials: &BrickMaterials) {
+    let pitch = ARENA_WIDTH / BRICK_COLUMNS as f32;
+    let half_width = (pitch - BRICK_GAP) / 2.0;
+    let row_pitch = 2.0 * BRICK_HALF_HEIGHT + BRICK_GAP;
+    let bottom = ARENA_HEIGHT - BRICK_TOP_MARGIN - BRICK_ROWS as f32 * row_pitch;
+    for row in 0..BRICK_ROWS {
+        let y = bottom + (row as f32 + 0.5) * row_pitch;
+        for column in 0..BRICK_COLUMNS {
+            let x = (column as f32 + 0.5) * pitch;
+            commands
+                .spawn(SpriteComponents {
+                    sprite: Sprite::new(Vec2::new(half_width, BRICK_HALF_HEIGHT) * 2.0),
+                    material: materials.0[row],
+                    ..Default::default()
+                })
+                .with(Brick {
+                    points: BRICK_POINTS * (row as u32 + 1),
+                })
+                .with(RigidBodyBuilder::new_static().translation(x, y))
+                .with(
+                    ColliderBuilder::cuboid(half_width, BRICK_HALF_HEIGHT)
+                        .restitution(1.0)
+                        .friction(0.0),
+                )
+                .with(SceneEntity);
+        }
+    }
+}
+
+#[derive(Default)]
+struct LocalStatePaddleSystem {
+    /// Last cursor position, the paddle following the cursor only once it moves
+    cursor: Option<Vec2>,
+    /// Paddle center target
+    x: Option<f32>,
+}
+
+/// Moves the kinematic paddle with A and D, or to the cursor x position when
+/// the cursor moves, keeping it within the walls.
+fn paddle_system(
+    mut state: Local<LocalStatePaddleSystem>,
+    active: Res<ActiveScene>,
+    time: Res<Time>,
+    input: Res<Input<KeyCode>>,
+    arena: Res<Arena>,
+    mouse_position: Res<MousePosition>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut paddles: Query<With<Paddle, &RigidBodyHandleComponent>>,
+) {
+    if !active.runs(&SCENE) {
+        // The paddle starts from the center the next time
+        *state = LocalStatePaddleSystem::default();
+        return;
+    }
+    let cursor = mouse_position.position_in_arena(&arena);
+    for body_handle in &mut paddles.iter() {
+        let mut body = bodies.get_mut(body_handle.handle()).unwrap();
+        let mut x = state.x.unwrap_or(body.position.translation.vector.x);
+        if let Some(cursor) = cursor.filter(|&cursor| Some(cursor) != state.cursor) {
+            x = cursor.x();
+        }
+        let mut direction = 0.0;
+        if input.pressed(KeyCode::A) {
+            direction -= 1.0;
+        }
+        if input.pressed(KeyCode::D) {
+            direction += 1.0;
+        }
+        x += direction * PADDLE_SPEED * time.delta_seconds;
+        x = x
+            .max(PADDLE_HALF_WIDTH)
+            .min(ARENA_WIDTH - PADDLE_HALF_WIDTH);
+        state.x = Some(x);
+        body.set_next_kinematic_position(Isometry2::translation(x, PADDLE_Y));
+    }
+    state.cursor = cursor;
+}
+
+/// Serves a new ball from above the paddle with Space or a left click.
+fn serve_system(
+    mut commands: Commands,
+    active: Res<ActiveScene>,
+    input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    assets: Res<GameAssets>,
+    bodies: Res<RigidBodySet>,
+    mut state: ResMut<GameState>,
+    mut paddles: Query<With<Paddle, &RigidBodyHandleComponent>>,
+) {
+    if !active.runs(&SCENE)
+        || *state != GameState::Serving
+        || !(input.just_pressed(KeyCode::Space) || mouse_input.just_pressed(MouseButton::Left))
+    {
+        return;
+    }
+    for body_handle in &mut paddles.iter() {
+        let x = bodies
+            .get(body_handle.handle())
+            .unwrap()
+            .position
+            .translation
+            .vector
+            .x;
+        let y = PADDLE_Y + PADDLE_HALF_HEIGHT + BALL_RADIUS * 2.0;
+        // Slightly to the side, a vertical ball staying vertical
+        let angle = MAX_BOUNCE_ANGLE / 3.0;
+        commands
+            .spawn(SpriteComponents {
+                transform: Transform::from_scale(BALL_RADIUS * 2.0 / 256.0),
+                material: assets.sphere_material,
+                ..Default::default()
+            })
+            .with(Ball)
+            .with(
+                RigidBodyBuilder::new_dynamic()
+                    .translation(x, y)
+                    .linvel(BALL_SPEED * angle.sin(), BALL_SPEED * angle.cos()),
+            )
+            .with(
+                ColliderBuilder::ball(BALL_RADIUS)
+                    .restitution(1.0)
+                    .friction(0.0),
+            )
+            .with(SceneEntity);
+        *state = GameState::Playing;
+    }
+}
+
+/// Breaks the bricks the ball hits, and sends the ball off the paddle at an
+/// angle depending on where it hit, steeper towards the paddle ends.
+fn contact_system(
+    mut commands: Commands,
+    active: Res<ActiveScene>,
+    events: Res<EventQueue>,
+    h_to_e: Res<ColliderHandleToEntity>,
+    mut score: ResMut<Score>,
+    mut bodies: ResMut<RigidBodySet>,
+    balls: Query<With<Ball, &RigidBodyHandleComponent>>,
+    paddles: Query<With<Paddle, &RigidBodyHandleComponent>>,
+    bricks: Query<&Brick>,
+) {
+    if !active.runs(&SCENE) {
+        return;
+    }
+    // A brick hit twice in a step must only be despawned once
+    let mut broken = HashSet::new();
+    while let Ok(contact_event) = events.contact_events.pop() {
+        let (h1, h2) = match contact_event {
+            ContactEvent::Started(h1, h2) => (h1, h2),
+            ContactEvent::Stopped(_, _) => continue,
+        };
+        let (e1, e2) = match (h_to_e.get(h1), h_to_e.get(h2)) {
+            (Some(e1), Some(e2)) => (e1, e2),
+            _ => continue,
+        };
+        for &(ball, other) in &[(e1, e2), (e2, e1)] {
+            let ball_handle = match balls.get::<RigidBodyHandleComponent>(ball) {
+                Ok(body_handle) => body_handle.handle(),
+                Err(_) => continue,
+            };
+            if let Ok(brick) = bricks.get::<Brick>(other) {
+                if broken.insert(other) {
+                    score.add(brick.points, None);
+                    commands.despawn(other);
+                }
+            } else if let Ok(paddle) = paddles.get::<RigidBodyHandleComponent>(other) {
+                let paddle_x = bodies
+                    .get(paddle.handle())
+                    .unwrap()
+                    .position
+                    .translation
+                    .vector
+                    .x;
+                let mut ball_body = bodies.get_mut(ball_handle).unwrap();
+                let offset = (ball_body.position.translation.vector.x - paddle_x)
+                    / (PADDLE_HALF_WIDTH + BALL_RADIUS);
+                let angle = offset.max(-1.0).min(1.0) * MAX_BOUNCE_ANGLE;
+                let speed = ball_body.linvel.norm();
+                ball_body.linvel = Vector2::new(angle.sin(), angle.cos()) * speed;
+            }
+        }
+    }
+}
+
+/// Keeps the ball speed within `BALL_MIN_SPEED` and `BALL_MAX_SPEED` after the
+/// physics step, and its vertical velocity above `BALL_MIN_VERTICAL` of it.
+fn ball_speed_system(
+    active: Res<ActiveScene>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut balls: Query<With<Ball, &RigidBodyHandleComponent>>,
+) {
+    if !active.runs(&SCENE) {
+        return;
+    }
+    for body_handle in &mut balls.iter() {
+        let mut body = bodies.get_mut(body_handle.handle()).unwrap();
+        let speed = body.linvel.norm();
+        if speed < std::f32::EPSILON {
+            continue;
+        }
+        let speed_clamped = speed.max(BALL_MIN_SPEED).min(BALL_MAX_SPEED);
+        let mut direction = body.linvel / speed;
+        if direction.y.abs() < BALL_MIN_VERTICAL {
+            // A tiny push up or down, keeping the current vertical direction
+            let sign = if direction.y < 0.0 { -1.0 } else { 1.0 };
+            direction.y = sign * BALL_MIN_VERTICAL;
+            direction.x = direction.x.signum() * (1.0 - BALL_MIN_VERTICAL.powi(2)).sqrt();
+        }
+        if (speed_clamped - speed).abs() > std::f32::EPSILON || direction != body.linvel / speed {
+            body.linvel = direction * speed_clamped;
+        }
+    }
+}
+
+/// Loses a life when the ball leaves through the bottom, ending the game once
+/// out of lives, and ends the game once all the bricks are broken.
+///
+/// The only system despawning the ball, so that a ball lost as the last brick
+/// breaks is not despawned twice.
+fn ball_system(
+    mut commands: Commands,
+    active: Res<ActiveScene>,
+    mut state: ResMut<GameState>,
+    mut score: ResMut<Score>,
+    bodies: Res<RigidBodySet>,
+    mut balls: Query<With<Ball, (Entity, &RigidBodyHandleComponent)>>,
+    mut bricks: Query<With<Brick, Entity>>,
+) {
+    if !active.runs(&SCENE) {
+        return;
+    }
+    let cleared = bricks.iter().iter().next().is_none();
+    for (entity, body_handle) in &mut balls.iter() {
+        let body = bodies.get(body_handle.handle()).unwrap();
+        if cleared {
+            *state = GameState::Cleared;
+        } else if body.position.translation.vector.y < -BALL_RADIUS {
+            *state = if score.lose_life() {
+                GameState::Serving
+            } else {
+                GameState::GameOver
+            };
+        } else {
+            continue;
+        }
+        commands.despawn(entity);
+    }
+}
+
+/// Starts a new game with Enter once the game is over or cleared, with a fresh
+/// brick grid.
+fn restart_system(
+    mut commands: Commands,
+    active: Res<ActiveScene>,
+    input: Res<Input<KeyCode>>,
+    materials: Res<BrickMaterials>,
+    mut state: ResMut<GameState>,
+    mut score: ResMut<Score>,
+    mut bricks: Query<With<Brick, Entity>>,
+) {
+    if !active.runs(&SCENE)
+        || !(*state == GameState::GameOver || *state == GameState::Cleared)
+        || !input.just_pressed(KeyCode::Return)
+    {
+        return;
+    }
+    for entity in &mut bricks.iter() {
+        commands.despawn(entity);
+    }
+    spawn_brick_grid(&mut commands, &materials);
+    *score = Score::new(LIVES);
+    *state = GameState::Serving;
+}
+
+fn message_text_system(state: Res<GameState>, mut texts: Query<With<MessageText, Mut<Text>>>) {
+    let message = match *state {
+        GameState::Serving => "Space or click to serve",
+        GameState::Menu | GameState::Playing => "",
+        GameState::GameOver => "GAME OVER — Enter to restart",
+        GameState::Cleared => "ALL CLEAR — Enter to restart",
+    };
+    for mut text in &mut texts.iter() {
+        if text.value != message {
+            text.value = message.to_string();
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Mouse wheel zoom and middle button panning of a 2D camera.
+
+use crate::mouse::{cursor_to_world, MousePosition};
+use bevy::{
+    input::mouse::MouseWheel,
+    prelude::*,
+    render::camera::{OrthographicProjection, WindowOrigin},
+};
+
+/// Moves the cameras with a `CameraController`, needs the
+/// `MousePositionPlugin`.
+pub struct CameraControllerPlugin;
+
+impl Plugin for CameraControllerPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system(camera_controller_system.system());
+    }
+}
+
+/// Zooms the camera with the mouse wheel around the cursor, pans it while the
+/// middle button is held, and resets it to its home view with Home.
+///
+/// The camera is only moved through its transform, so that the cursor
+/// conversions and the sprites follow it.
+pub struct CameraController {
+    /// Bounds of the scale, in world units per px
+    pub min_zoom: f32,
+    pub max_zoom: f32,
+    /// Scale factor of each wheel line
+    pub zoom_step: f32,
+    zoom: f32,
+    /// Translation and scale of the home view
+    home: (Vec2, f32),
+}
+
+impl CameraController {
+    pub fn new(home_translation: Vec2, home_zoom: f32) -> Self {
+        CameraController {
+            min_zoom: 0.25,
+            max_zoom: 4.0,
+            zoom_step: 1.1,
+            zoom: home_zoom,
+            home: (home_translation, home_zoom),
+        }
+    }
+
+    pub fn with_zoom_range(mut self, min_zoom: f32, max_zoom: f32) -> Self {
+        self.min_zoom = min_zoom;
+        self.max_zoom = max_zoom;
+        self
+    }
+
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// Changes the view restored by Home, without moving the camera.
+    pub fn set_home(&mut self, translation: Vec2, zoom: f32) {
+        self.home = (translation, zoom);
+    }
+
+    /// Moves the camera `transform` back to the home view.
+    pub fn reset(&mut self, transform: &mut Transform) {
+        let (translation, zoom) = self.home;
+        self.zoom = zoom;
+        place(transform, translation, zoom);
+    }
+}
+
+fn place(transform: &mut Transform, translation: Vec2, zoom: f32) {
+    let z = transform.translation().z();
+    *transform = Transform::from_translation(translation.extend(z)).with_scale(zoom);
+}
+
+/// Translation of a camera of scale `zoom` showing `world` at the `view`
+/// position, in px relative to the projection window origin.
+fn anchored_translation(world: Vec2, view: Vec2, zoom: f32) -> Vec2 {
+    world - view * zoom
+}
+
+#[derive(Default)]
+struct LocalStateCameraController {
+    reader: EventReader<MouseWheel>,
+    /// Cursor position in px at the previous frame of a drag
+    drag: Option<Vec2>,
+}
+
+fn camera_controller_system(
+    mut state: Local<LocalStateCameraController>,
+    mouse_wheel_events: Res<Events<MouseWheel>>,
+    mouse_button_input: Res<Input<MouseButton>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_position: Res<MousePosition>,
+    windows: Res<Windows>,
+    mut cameras: Query<(
+        Mut<CameraController>,
+        Mut<Transform>,
+        &OrthographicProjection,
+    )>,
+) {
+    let scroll: f32 = state
+        .reader
+        .iter(&mouse_wheel_events)
+        .map(|event| event.y)
+        .sum();
+    let window_size = windows.get_primary().map_or(Vec2::zero(), |window| {
+        Vec2::new(window.width as f32, window.height as f32)
+    });
+    let cursor = mouse_position
+        .position()
+        .filter(|_| mouse_position.in_window());
+    let drag = if mouse_button_input.pressed(MouseButton::Middle) {
+        cursor
+    } else {
+        None
+    };
+    for (mut controller, mut transform, projection) in &mut cameras.iter() {
+        if keyboard_input.just_pressed(KeyCode::Home) {
+            controller.reset(&mut transform);
+            continue;
+        }
+        let mut translation = transform.translation().truncate();
+        if let (Some(previous), Some(current)) = (state.drag, drag) {
+            // The grabbed point stays under the cursor
+            translation -= (current - previous) * controller.zoom;
+        }
+        if scroll != 0.0 {
+            // Scrolling up zooms in, seeing a smaller part of the world
+            let zoom = (controller.zoom * controller.zoom_step.powf(-scroll))
+                .max(controller.min_zoom)
+                .min(controller.max_zoom);
+            // Around the cursor, or the window center without one
+            let position = cursor.unwrap_or(window_size / 2.0);
+            let view = match projection.window_origin {
+                WindowOrigin::BottomLeft => position,
+                WindowOrigin::Center => position - window_size / 2.0,
+            };
+            let world =
+                cursor_to_world(position, window_size, projection.window_origin, &transform);
+            translation = anchored_translation(world, view, zoom);
+            controller.zoom = zoom;
+        }
+        let zoom = controller.zoom;
+        place(&mut transform, translation, zoom);
+    }
+    state.drag = drag;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zoom_keeps_the_point_under_the_cursor() {
+        let window_size = Vec2::new(1280.0, 800.0);
+        let cursor = Vec2::new(300.0, 500.0);
+        let camera = Transform::from_translation(Vec3::new(10.0, 20.0, 0.0)).with_scale(1.0);
+        let world = cursor_to_world(cursor, window_size, WindowOrigin::BottomLeft, &camera);
+        let translation = anchored_translation(world, cursor, 0.5);
+        let zoomed = Transform::from_translation(translation.extend(0.0)).with_scale(0.5);
+        let after = cursor_to_world(cursor, window_size, WindowOrigin::BottomLeft, &zoomed);
+        assert!((after - world).length() < 1e-3);
+    }
+}
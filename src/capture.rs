@@ -0,0 +1,427 @@
+//! Animated GIF clips of a few seconds of an example, recorded with F9.
+//!
+//! The recorder keeps a downscaled copy of every other `CapturedFrame` event,
+//! for at most `MAX_DURATION`, and encodes them on a background thread.
+//!
+//! bevy 0.2 cannot map a buffer for reading, so no render graph node can copy
+//! the window back to memory yet. The frames are sent by whatever renders
+//! them, and a recording without any frame is reported as such.
+
+use bevy::prelude::*;
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+/// Longest recording, in s
+pub const MAX_DURATION: f32 = 5.0;
+/// One frame kept out of this number of frames sent
+const FRAME_STEP: u32 = 2;
+/// The frames are reduced by this factor along each axis
+const DOWNSCALE: u32 = 4;
+/// Frame rate above which frames are dropped, bounding the memory of a
+/// recording with `MAX_DURATION`
+const MAX_FPS: f32 = 60.0;
+const MAX_FRAMES: usize = (MAX_DURATION * MAX_FPS) as usize / FRAME_STEP as usize;
+/// Levels of each color channel of the fixed palette
+const LEVELS: u32 = 6;
+const CAPTURE_DIR: &str = "captures";
+/// Time a toast stays on screen, in s
+const TOAST_DURATION: f32 = 3.0;
+
+/// A frame of the window, in RGBA rows from the top.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CapturedFrame {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+impl CapturedFrame {
+    /// The frame reduced by `factor` along each axis, each pixel averaging a
+    /// block of the original one.
+    pub fn downscaled(&self, factor: u32) -> CapturedFrame {
+        let (width, height) = ((self.width / factor).max(1), (self.height / factor).max(1));
+        let mut rgba = Vec::with_capacity((width * height * 4) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = [0u32; 4];
+                let mut count = 0;
+                for sy in y * factor..((y + 1) * factor).min(self.height) {
+                    for sx in x * factor..((x + 1) * factor).min(self.width) {
+                        let index = ((sy * self.width + sx) * 4) as usize;
+                        for (channel, sum) in sum.iter_mut().enumerate() {
+                            *sum += self.rgba[index + channel] as u32;
+                        }
+                        count += 1;
+                    }
+                }
+                rgba.extend(sum.iter().map(|sum| (sum / count.max(1)) as u8));
+            }
+        }
+        CapturedFrame {
+            width,
+            height,
+            rgba,
+        }
+    }
+}
+
+/// The fixed palette of the clips, a cube of `LEVELS` levels per channel.
+fn palette() -> Vec<u8> {
+    let level = |i: u32| (i * 255 / (LEVELS - 1)) as u8;
+    let mut palette = Vec::with_capacity((LEVELS * LEVELS * LEVELS * 3) as usize);
+    for r in 0..LEVELS {
+        for g in 0..LEVELS {
+            for b in 0..LEVELS {
+                palette.extend_from_slice(&[level(r), level(g), level(b)]);
+            }
+        }
+    }
+    palette
+}
+
+/// Indices in the `palette` of the nearest colors of the pixels of `rgba`.
+fn quantize(rgba: &[u8]) -> Vec<u8> {
+    let level = |c: u8| (c as u32 * (LEVELS - 1) + 127) / 255;
+    rgba.chunks(4)
+        .map(|pixel| {
+            (level(pixel[0]) * LEVELS * LEVELS + level(pixel[1]) * LEVELS + level(pixel[2])) as u8
+        })
+        .collect()
+}
+
+/// Encodes the `frames`, all of the same size, as a looping GIF shown for
+/// `delay` hundredths of s each.
+pub fn encode_gif<W: Write>(
+    frames: &[CapturedFrame],
+    delay: u16,
+    writer: W,
+) -> Result<(), gif::EncodingError> {
+    let (width, height) = frames
+        .first()
+        .map_or((1, 1), |frame| (frame.width as u16, frame.height as u16));
+    let mut encoder = gif::Encoder::new(writer, width, height, &palette())?;
+    encoder.set_repeat(gif::Repeat::Infinite)?;
+    for frame in frames {
+        encoder.write_frame(&gif::Frame {
+            width,
+            height,
+            delay,
+            buffer: quantize(&frame.rgba).into(),
+            ..Default::default()
+        })?;
+    }
+    Ok(())
+}
+
+/// Records a clip with F9 or the key given to `with_key`, and shows "REC"
+/// while recording.
+///
+/// The toasts are UI text, so the example needs a UI camera.
+pub struct CapturePlugin {
+    key: KeyCode,
+}
+
+impl Default for CapturePlugin {
+    fn default() -> Self {
+        CapturePlugin { key: KeyCode::F9 }
+    }
+}
+
+impl CapturePlugin {
+    /// For the examples already using F9
+    pub fn with_key(mut self, key: KeyCode) -> Self {
+        self.key = key;
+        self
+    }
+}
+
+impl Plugin for CapturePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_event::<CapturedFrame>()
+            .add_resource(Capture {
+                key: self.key,
+                recording: None,
+                finished: Default::default(),
+                toast: None,
+            })
+            .add_startup_system(setup_capture_ui.system())
+            .add_system(capture_system.system())
+            .add_system(capture_ui_system.system());
+    }
+}
+
+/// The frames of the recording in progress.
+#[derive(Default)]
+struct Recording {
+    frames: Vec<CapturedFrame>,
+    /// Frames sent since the start
+    sent: u32,
+    elapsed: f32,
+}
+
+impl Recording {
+    /// Keeps every `FRAME_STEP`th frame, up to `MAX_FRAMES`.
+    fn push(&mut self, frame: &CapturedFrame) {
+        self.sent += 1;
+        if (self.sent - 1) % FRAME_STEP == 0 && self.frames.len() < MAX_FRAMES {
+            self.frames.push(frame.downscaled(DOWNSCALE));
+        }
+    }
+
+    /// Display time of each frame in hundredths of s, for the clip to last as
+    /// long as the recording.
+    fn delay(&self) -> u16 {
+        let frames = self.frames.len().max(1) as f32;
+        ((self.elapsed / frames * 100.0).round() as u16).max(2)
+    }
+}
+
+struct Capture {
+    key: KeyCode,
+    recording: Option<Recording>,
+    /// Messages of the encoding threads once done
+    finished: Arc<Mutex<Vec<String>>>,
+    /// Toast on screen, with its remaining time
+    toast: Option<(String, f32)>,
+}
+
+impl Capture {
+    fn show(&mut self, message: String) {
+        println!("{}", message);
+        self.toast = Some((message, TOAST_DURATION));
+    }
+
+    /// Stops the recording, and encodes it in the background.
+    fn finish(&mut self) {
+        let recording = match self.recording.take() {
+            Some(recording) => recording,
+            None => return,
+        };
+        if recording.frames.is_empty() {
+            self.show("Nothing recorded, no frame was captured".to_string());
+            return;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        self.save(recording);
+        // Without threads nor any file system in the browser
+        #[cfg(target_arch = "wasm32")]
+        {
+            let _ = recording;
+            self.show("Clips are not saved in the browser".to_string());
+        }
+    }
+
+    /// Encodes the `recording` to a new file on a background thread, not to
+    /// freeze the game meanwhile.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save(&self, recording: Recording) {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let path = std::path::Path::new(CAPTURE_DIR).join(format!("{}.gif", timestamp));
+        let finished = self.finished.clone();
+        std::thread::spawn(move || {
+            let result = std::fs::create_dir_all(CAPTURE_DIR)
+                .and_then(|_| std::fs::File::create(&path))
+                .map_err(|error| error.to_string())
+                .and_then(|file| {
+                    let writer = std::io::BufWriter::new(file);
+                    encode_gif(&recording.frames, recording.delay(), writer)
+                        .map_err(|error| error.to_string())
+                });
+            let message = match result {
+                Ok(()) => format!("Saved {}", path.display()),
+                Err(error) => format!("Failed to save {}: {}", path.display(), error),
+            };
+            finished.lock().unwrap().push(message);
+        });
+    }
+}
+
+fn capture_system(
+    mut reader: Local<EventReader<CapturedFrame>>,
+    frames: Res<Events<CapturedFrame>>,
+    input: Res<Input<KeyCode>>,
+    time: Res<Time>,
+    mut capture: ResMut<Capture>,
+) {
+    if input.just_pressed(capture.key) {
+        if capture.recording.is_some() {
+            capture.finish();
+        } else {
+            capture.recording = Some(Recording::default());
+        }
+    }
+    let recording = match capture.recording.as_mut() {
+        Some(recording) => recording,
+        None => {
+            // Not to record the frames sent before the start
+            for _ in reader.iter(&frames) {}
+            return;
+        }
+    };
+    for frame in reader.iter(&frames) {
+        recording.push(frame);
+    }
+    recording.elapsed += time.delta_seconds;
+    if recording.elapsed >= MAX_DURATION {
+        capture.finish();
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CaptureText {
+    Rec,
+    Toast,
+}
+
+fn setup_capture_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let font_handle = asset_server.load("assets/DejaVuSansMono.ttf").unwrap();
+    let text = |value: &str, font_size: f32, color: Color| TextComponents {
+        text: Text {
+            value: value.to_string(),
+            font: font_handle,
+            style: TextStyle { font_size, color },
+        },
+        ..Default::default()
+    };
+    commands
+        .spawn(NodeComponents {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(10.0),
+                    right: Val::Px(10.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            material: materials.add(Color::NONE.into()),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(text("", 24.0, Color::rgb(1.0, 0.1, 0.1)))
+                .with(CaptureText::Rec);
+        })
+        .spawn(NodeComponents {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    bottom: Val::Px(60.0),
+                    left: Val::Px(10.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            material: materials.add(Color::NONE.into()),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(text("", 18.0, Color::WHITE))
+                .with(CaptureText::Toast);
+        });
+}
+
+fn capture_ui_system(
+    time: Res<Time>,
+    mut capture: ResMut<Capture>,
+    mut query: Query<(&CaptureText, Mut<Text>)>,
+) {
+    let finished: Vec<String> = capture.finished.lock().unwrap().drain(..).collect();
+    for message in finished {
+        capture.show(message);
+    }
+    if let Some((_, remaining)) = capture.toast.as_mut() {
+        *remaining -= time.delta_seconds;
+        if *remaining <= 0.0 {
+            capture.toast = None;
+        }
+    }
+    for (line, mut text) in &mut query.iter() {
+        let value = match line {
+            // Blinking twice a second
+            CaptureText::Rec => match &capture.recording {
+                Some(recording) if recording.elapsed % 0.5 < 0.35 => "● REC".to_string(),
+                _ => String::new(),
+            },
+            CaptureText::Toast => capture
+                .toast
+                .as_ref()
+                .map_or_else(String::new, |(message, _)| message.clone()),
+        };
+        if text.value != value {
+            text.value = value;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(width: u32, height: u32, pixel: impl Fn(u32, u32) -> [u8; 4]) -> CapturedFrame {
+        let mut rgba = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                rgba.extend_from_slice(&pixel(x, y));
+            }
+        }
+        CapturedFrame {
+            width,
+            height,
+            rgba,
+        }
+    }
+
+    #[test]
+    fn downscaling_averages_the_blocks() {
+        let checkers = frame(4, 2, |x, _| if x % 2 == 0 { [0; 4] } else { [200; 4] });
+        let small = checkers.downscaled(2);
+        assert_eq!((small.width, small.height), (2, 1));
+        assert_eq!(small.rgba, vec![100; 8]);
+    }
+
+    #[test]
+    fn quantization_picks_the_palette_colors() {
+        let palette = palette();
+        assert_eq!(palette.len(), 216 * 3);
+        let black = [0, 0, 0, 255];
+        let white = [255, 255, 255, 255];
+        let almost_red = [250, 10, 0, 255];
+        let indices = quantize(&[black, white, almost_red].concat());
+        assert_eq!(indices[..2], [0, 215]);
+        let red = indices[2] as usize;
+        assert_eq!(palette[red * 3..red * 3 + 3], [255, 0, 0]);
+    }
+
+    #[test]
+    fn recordings_keep_every_other_frame_up_to_the_cap() {
+        let mut recording = Recording::default();
+        let frame = frame(8, 8, |_, _| [10, 20, 30, 255]);
+        for _ in 0..5 {
+            recording.push(&frame);
+        }
+        assert_eq!(recording.frames.len(), 3);
+        for _ in 0..10 * MAX_FRAMES {
+            recording.push(&frame);
+        }
+        assert_eq!(recording.frames.len(), MAX_FRAMES);
+        assert_eq!(recording.frames[0].width, 8 / DOWNSCALE);
+    }
+
+    #[test]
+    fn clips_are_gif_files() {
+        let frames = vec![frame(4, 4, |x, y| [(x * 60) as u8, (y * 60) as u8, 0, 255]); 3];
+        let mut file = Vec::new();
+        encode_gif(&frames, 4, &mut file).unwrap();
+        assert_eq!(&file[..6], b"GIF89a");
+    }
+}
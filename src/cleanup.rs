@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+use bevy_rapier2d::{
+    physics::RigidBodyHandleComponent,
+    rapier::{
+        dynamics::{JointSet, RigidBodyHandle, RigidBodySet},
+        geometry::ColliderSet,
+    },
+};
+use std::collections::HashMap;
+
+/// Removes the rapier body of despawned entities.
+///
+/// `bevy_rapier2d` never removes bodies by itself, so without this plugin a
+/// despawned entity leaves an invisible body behind, still colliding.
+/// Despawning the entity, or removing its `RigidBodyHandleComponent`, is
+/// enough to remove the body along with its colliders and joints.
+pub struct RapierCleanupPlugin;
+
+impl Plugin for RapierCleanupPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<TrackedBodies>()
+            // Last stage, to see the despawns of all the other stages before
+            // the next physics step.
+            .add_system_to_stage(stage::LAST, body_cleanup_system.system());
+    }
+}
+
+/// Last known body handle of each entity, the component being gone once
+/// removed.
+#[derive(Default)]
+struct TrackedBodies(HashMap<Entity, RigidBodyHandle>);
+
+fn body_cleanup_system(
+    mut tracked: ResMut<TrackedBodies>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut colliders: ResMut<ColliderSet>,
+    mut joints: ResMut<JointSet>,
+    mut changed: Query<(Entity, Changed<RigidBodyHandleComponent>)>,
+) {
+    for entity in changed.removed::<RigidBodyHandleComponent>() {
+        if let Some(handle) = tracked.0.remove(entity) {
+            // Removing the body removes its colliders and joints too. The
+            // handle may already be gone if the body was removed by hand.
+            bodies.remove(handle, &mut colliders, &mut joints);
+        }
+    }
+    for (entity, body_handle) in &mut changed.iter() {
+        tracked.0.insert(entity, body_handle.handle());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::app::DefaultTaskPoolOptions;
+    use bevy_rapier2d::{
+        physics::RapierPhysicsPlugin,
+        rapier::{dynamics::RigidBodyBuilder, geometry::ColliderBuilder},
+    };
+
+    fn set_lengths(app: &App) -> (usize, usize) {
+        let bodies = app.resources.get::<RigidBodySet>().unwrap();
+        let colliders = app.resources.get::<ColliderSet>().unwrap();
+        (bodies.len(), colliders.len())
+    }
+
+    #[test]
+    fn despawned_bodies_are_removed() {
+        let mut app = App::build();
+        app.add_plugin(RapierPhysicsPlugin)
+            .add_plugin(RapierCleanupPlugin);
+        let mut app = app.app;
+        DefaultTaskPoolOptions::default().create_default_pools(&mut app.resources);
+        app.update();
+        let baseline = set_lengths(&app);
+
+        let entities: Vec<Entity> = (0..10)
+            .map(|i| {
+                app.world.spawn((
+                    RigidBodyBuilder::new_dynamic().translation(i as f32 * 10.0, 0.0),
+                    ColliderBuilder::ball(1.0),
+                ))
+            })
+            .collect();
+        app.update();
+        assert_eq!(set_lengths(&app), (baseline.0 + 10, baseline.1 + 10));
+
+        for entity in entities {
+            app.world.despawn(entity).unwrap();
+        }
+        for _ in 0..10 {
+            app.update();
+        }
+        assert_eq!(set_lengths(&app), baseline);
+    }
+}
@@ -0,0 +1,141 @@
+use bevy::prelude::*;
+use ncollide2d::{
+    na,
+    na::{Isometry2, Point2, Vector2},
+    pipeline::{CollisionGroups, CollisionObjectSlabHandle, CollisionWorld, GeometricQueryType},
+    shape::{Ball, Cuboid, ShapeHandle},
+};
+
+/// Velocity in px/s of a body moved by hand, without a dynamics engine, e.g.
+/// by the `NCollideResponsePlugin`
+pub struct Velocity(pub Vector2<f32>);
+
+/// Velocity `d` bouncing off a surface of unit normal `n`.
+pub fn reflect(d: Vector2<f32>, n: Vector2<f32>) -> Vector2<f32> {
+    d - 2.0 * n * (d.dot(&n))
+}
+
+/// A contact between two collision objects, as seen by one of them.
+#[derive(Clone, Copy, Debug)]
+pub struct ContactSide<T> {
+    /// Data of the object this side is about, e.g. its entity
+    pub data: T,
+    /// Data of the other object
+    pub other: T,
+    /// Unit normal pointing away from the other object, along which this
+    /// object leaves the contact.
+    pub normal: Vector2<f32>,
+    /// Penetration depth
+    pub depth: f32,
+    /// Contact point on this object, in world space
+    pub point: Point2<f32>,
+}
+
+/// Splits the deepest contact of each colliding pair of the `world` into one
+/// side per object, so that each can respond on its own.
+///
+/// The world must have been updated since the objects last moved.
+pub fn contact_sides<T: Copy>(world: &CollisionWorld<f32, T>) -> Vec<ContactSide<T>> {
+    let mut sides = Vec::new();
+    for (h1, h2, _, manifold) in world.contact_pairs(true) {
+        let contact = match manifold.deepest_contact() {
+            Some(tracked_contact) => tracked_contact.contact,
+            None => continue,
+        };
+        let (data1, data2) = match (world.collision_object(h1), world.collision_object(h2)) {
+            (Some(object1), Some(object2)) => (*object1.data(), *object2.data()),
+            _ => continue,
+        };
+        // The normal points from the first object towards the second
+        let normal = contact.normal.into_inner();
+        sides.push(ContactSide {
+            data: data1,
+            other: data2,
+            normal: -normal,
+            depth: contact.depth,
+            point: contact.world1,
+        });
+        sides.push(ContactSide {
+            data: data2,
+            other: data1,
+            normal,
+            depth: contact.depth,
+            point: contact.world2,
+        });
+    }
+    sides
+}
+
+/// Adds the collision object of a sphere of `entity`, at `position`.
+pub fn add_sphere(
+    world: &mut CollisionWorld<f32, Entity>,
+    groups: CollisionGroups,
+    entity: Entity,
+    position: Vec2,
+    radius: f32,
+) -> CollisionObjectSlabHandle {
+    let (handle, _) = world.add(
+        Isometry2::new(Vector2::new(position.x(), position.y()), na::zero()),
+        ShapeHandle::new(Ball::new(radius)),
+        groups,
+        GeometricQueryType::Contacts(0.0, 0.0),
+        entity,
+    );
+    handle
+}
+
+/// Adds the collision object of a box of `entity` centered at `position`, e.g.
+/// a wall or a paddle.
+pub fn add_cuboid(
+    world: &mut CollisionWorld<f32, Entity>,
+    groups: CollisionGroups,
+    entity: Entity,
+    position: Vec2,
+    half_extents: Vec2,
+) -> CollisionObjectSlabHandle {
+    let (handle, _) = world.add(
+        Isometry2::new(Vector2::new(position.x(), position.y()), na::zero()),
+        ShapeHandle::new(Cuboid::new(Vector2::new(
+            half_extents.x(),
+            half_extents.y(),
+        ))),
+        groups,
+        GeometricQueryType::Contacts(0.0, 0.0),
+        entity,
+    );
+    handle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reflect_flips_the_normal_component() {
+        let d = reflect(Vector2::new(3.0, -2.0), Vector2::new(0.0, 1.0));
+        assert_eq!(d, Vector2::new(3.0, 2.0));
+    }
+
+    #[test]
+    fn each_side_is_pushed_away_from_the_other() {
+        let mut world = CollisionWorld::<f32, u32>::new(0.02);
+        for (x, data) in &[(0.0, 1), (1.5, 2)] {
+            world.add(
+                Isometry2::new(Vector2::new(*x, 0.0), na::zero()),
+                ShapeHandle::new(Ball::new(1.0)),
+                CollisionGroups::new(),
+                GeometricQueryType::Contacts(0.0, 0.0),
+                *data,
+            );
+        }
+        world.update();
+        let sides = contact_sides(&world);
+        assert_eq!(sides.len(), 2);
+        for side in sides {
+            let away = if side.data == 1 { -1.0 } else { 1.0 };
+            assert_eq!(side.other, 3 - side.data);
+            assert!((side.normal.x - away).abs() < 1e-5);
+            assert!((side.depth - 0.5).abs() < 1e-5);
+        }
+    }
+}
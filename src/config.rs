@@ -0,0 +1,222 @@
+//! Tuning of the examples, read from `assets/config.ron` and reloaded live
+//! when the file changes.
+
+use crate::platform;
+use bevy::{prelude::*, render::pass::ClearColor};
+use bevy_rapier2d::{na::Vector2, physics::RapierConfiguration};
+use serde::{Deserialize, Serialize};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::SystemTime;
+
+pub const CONFIG_PATH: &str = "assets/config.ron";
+/// Period in s of the checks for a modified config file
+#[cfg(not(target_arch = "wasm32"))]
+const POLL_PERIOD: f32 = 1.0;
+
+/// The tuning of an example, every field but the window size applied live.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct ShowcaseConfig {
+    /// Window size in px, only read at startup
+    pub window_width: u32,
+    pub window_height: u32,
+    pub clear_color: (f32, f32, f32),
+    /// Gravity in m/s²
+    pub gravity: (f32, f32),
+    /// World units per px
+    pub camera_scale: f32,
+    /// Restitution of the spawned bodies
+    pub spawn_restitution: f32,
+    /// Number of stars in each background tile
+    pub star_density: usize,
+}
+
+impl Default for ShowcaseConfig {
+    fn default() -> Self {
+        ShowcaseConfig {
+            window_width: 1280,
+            window_height: 800,
+            clear_color: (0.02, 0.02, 0.04),
+            gravity: (0.0, 0.0),
+            camera_scale: 0.1,
+            spawn_restitution: 0.9,
+            star_density: 120,
+        }
+    }
+}
+
+impl ShowcaseConfig {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = platform::read_to_string(path).map_err(|error| error.to_string())?;
+        ron::de::from_str(&text).map_err(|error| error.to_string())
+    }
+
+    /// The config file, or the defaults if it can't be read.
+    pub fn load_or_default() -> Self {
+        Self::load(CONFIG_PATH).unwrap_or_else(|error| {
+            eprintln!(
+                "Could not load {}: {}, using the defaults",
+                CONFIG_PATH, error
+            );
+            Self::default()
+        })
+    }
+
+    pub fn clear_color(&self) -> Color {
+        let (r, g, b) = self.clear_color;
+        Color::rgb(r, g, b)
+    }
+
+    pub fn gravity(&self) -> Vector2<f32> {
+        Vector2::new(self.gravity.0, self.gravity.1)
+    }
+
+    /// `reloaded` with the fields that can't change at runtime kept as they
+    /// are, and whether any of them differed.
+    fn live_subset(&self, reloaded: ShowcaseConfig) -> (ShowcaseConfig, bool) {
+        let ignored = (reloaded.window_width, reloaded.window_height)
+            != (self.window_width, self.window_height);
+        let config = ShowcaseConfig {
+            window_width: self.window_width,
+            window_height: self.window_height,
+            ..reloaded
+        };
+        (config, ignored)
+    }
+}
+
+/// Sent once at startup and after each reload of the config, for the
+/// systems applying it.
+pub struct ConfigChanged;
+
+/// Inserts the `ShowcaseConfig` and applies its clear color, polling the
+/// config file for modifications except in the browser.
+pub struct ConfigPlugin(pub ShowcaseConfig);
+
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_resource(self.0.clone())
+            .add_resource(ClearColor(self.0.clear_color()))
+            .add_event::<ConfigChanged>()
+            .add_startup_system(config_changed_startup.system())
+            .add_system(clear_color_system.system());
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_resource(ConfigWatcher {
+            timer: Timer::from_seconds(POLL_PERIOD, true),
+            modified: modified_time(),
+        })
+        .add_system(config_watch_system.system());
+    }
+}
+
+fn config_changed_startup(mut events: ResMut<Events<ConfigChanged>>) {
+    events.send(ConfigChanged);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct ConfigWatcher {
+    timer: Timer,
+    /// Modification time of the config file at the last check
+    modified: Option<SystemTime>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn modified_time() -> Option<SystemTime> {
+    std::fs::metadata(CONFIG_PATH)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Reloads the config when its file was modified, keeping the previous one
+/// if the new one does not parse.
+#[cfg(not(target_arch = "wasm32"))]
+fn config_watch_system(
+    time: Res<Time>,
+    mut watcher: ResMut<ConfigWatcher>,
+    mut config: ResMut<ShowcaseConfig>,
+    mut events: ResMut<Events<ConfigChanged>>,
+) {
+    watcher.timer.tick(time.delta_seconds);
+    if !watcher.timer.just_finished {
+        return;
+    }
+    let modified = modified_time();
+    if modified == watcher.modified {
+        return;
+    }
+    watcher.modified = modified;
+    match ShowcaseConfig::load(CONFIG_PATH) {
+        Ok(reloaded) => {
+            let (reloaded, ignored) = config.live_subset(reloaded);
+            if ignored {
+                println!("Window size changes in {} need a restart", CONFIG_PATH);
+            }
+            if reloaded != *config {
+                *config = reloaded;
+                events.send(ConfigChanged);
+                println!("Reloaded {}", CONFIG_PATH);
+            }
+        }
+        Err(error) => eprintln!(
+            "Could not reload {}: {}, keeping the previous config",
+            CONFIG_PATH, error
+        ),
+    }
+}
+
+fn clear_color_system(
+    mut reader: Local<EventReader<ConfigChanged>>,
+    events: Res<Events<ConfigChanged>>,
+    config: Res<ShowcaseConfig>,
+    mut clear_color: ResMut<ClearColor>,
+) {
+    if reader.iter(&events).last().is_some() {
+        clear_color.0 = config.clear_color();
+    }
+}
+
+/// Applies the gravity of the config to rapier, for the examples using it.
+pub fn config_gravity_system(
+    mut reader: Local<EventReader<ConfigChanged>>,
+    events: Res<Events<ConfigChanged>>,
+    config: Res<ShowcaseConfig>,
+    mut configuration: ResMut<RapierConfiguration>,
+) {
+    if reader.iter(&events).last().is_some() {
+        configuration.gravity = config.gravity();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_fields_keep_their_defaults() {
+        let config: ShowcaseConfig =
+            ron::de::from_str("(camera_scale: 0.2, gravity: (0.0, -9.81))").unwrap();
+        assert_eq!(
+            config,
+            ShowcaseConfig {
+                camera_scale: 0.2,
+                gravity: (0.0, -9.81),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn window_size_is_not_reloaded() {
+        let config = ShowcaseConfig::default();
+        let (reloaded, ignored) = config.live_subset(ShowcaseConfig {
+            window_width: 640,
+            star_density: 10,
+            ..Default::default()
+        });
+        assert!(ignored);
+        assert_eq!(reloaded.window_width, config.window_width);
+        assert_eq!(reloaded.star_density, 10);
+        let (_, ignored) = config.live_subset(ShowcaseConfig::default());
+        assert!(!ignored);
+    }
+}
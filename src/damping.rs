@@ -0,0 +1,43 @@
+use bevy::prelude::*;
+use bevy_rapier2d::{physics::RigidBodyHandleComponent, rapier::dynamics::RigidBodySet};
+
+/// Velocity damping of a rapier body.
+///
+/// Each factor is the fraction of the velocity kept after one second, so
+/// `1.0` means no damping and `0.0` stops the body instantly.
+#[derive(Clone, Copy, Debug)]
+pub struct Damping {
+    pub linear: f32,
+    pub angular: f32,
+}
+
+impl Damping {
+    /// Damping that leaves the body untouched
+    pub const NONE: Damping = Damping {
+        linear: 1.0,
+        angular: 1.0,
+    };
+
+    /// Factors above 1.0 are ignored, damping never speeds a body up.
+    pub fn is_none(&self) -> bool {
+        self.linear >= 1.0 && self.angular >= 1.0
+    }
+}
+
+/// Applies the `Damping` of all bodies, rapier 0.2 having no built-in damping.
+pub fn damping_system(
+    time: Res<Time>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut query: Query<(&Damping, &RigidBodyHandleComponent)>,
+) {
+    let elapsed = time.delta_seconds;
+    for (damping, body_handle) in &mut query.iter() {
+        if damping.is_none() {
+            continue;
+        }
+        if let Some(mut body) = bodies.get_mut(body_handle.handle()) {
+            body.linvel *= damping.linear.min(1.0).powf(elapsed);
+            body.angvel *= damping.angular.min(1.0).powf(elapsed);
+        }
+    }
+}
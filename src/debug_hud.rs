@@ -0,0 +1,128 @@
+use bevy::{
+    diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+};
+use bevy_rapier2d::rapier::dynamics::RigidBodySet;
+
+/// Toggles the debug HUD
+const TOGGLE_KEY: KeyCode = KeyCode::F3;
+
+/// Debug HUD in the bottom right corner, toggled with F3, showing the FPS and
+/// what the example puts in `DebugHud`.
+///
+/// The HUD is UI text, so the example needs a UI camera.
+pub struct DebugHudPlugin;
+
+impl Plugin for DebugHudPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_plugin(FrameTimeDiagnosticsPlugin::default())
+            .init_resource::<DebugHud>()
+            .add_startup_system(setup_debug_hud.system())
+            .add_system(debug_hud_toggle_system.system())
+            .add_system(debug_hud_text_system.system());
+    }
+}
+
+/// What the debug HUD shows besides the FPS, kept up to date by the example
+/// while `visible`. The lines left to `None` are hidden.
+#[derive(Default)]
+pub struct DebugHud {
+    pub visible: bool,
+    /// Number of physics bodies
+    pub bodies: Option<usize>,
+    /// A last line, e.g. about the player
+    pub extra: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DebugHudLine {
+    Fps,
+    Bodies,
+    Extra,
+}
+
+/// Counts the rapier bodies for the debug HUD.
+pub fn rapier_body_count_system(bodies: Res<RigidBodySet>, mut hud: ResMut<DebugHud>) {
+    if hud.visible {
+        hud.bodies = Some(bodies.len());
+    }
+}
+
+fn setup_debug_hud(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let font_handle = asset_server.load("assets/DejaVuSansMono.ttf").unwrap();
+    // Above a line of text at the bottom, the first child being at the bottom
+    commands
+        .spawn(NodeComponents {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    bottom: Val::Px(36.0),
+                    right: Val::Px(10.0),
+                    ..Default::default()
+                },
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::FlexEnd,
+                ..Default::default()
+            },
+            material: materials.add(Color::NONE.into()),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            for &line in &[DebugHudLine::Extra, DebugHudLine::Bodies, DebugHudLine::Fps] {
+                parent
+                    .spawn(TextComponents {
+                        text: Text {
+                            value: String::new(),
+                            font: font_handle,
+                            style: TextStyle {
+                                font_size: 14.0,
+                                color: Color::rgb(0.6, 1.0, 0.6),
+                            },
+                        },
+                        ..Default::default()
+                    })
+                    .with(line);
+            }
+        });
+}
+
+fn debug_hud_toggle_system(input: Res<Input<KeyCode>>, mut hud: ResMut<DebugHud>) {
+    if input.just_pressed(TOGGLE_KEY) {
+        hud.visible = !hud.visible;
+    }
+}
+
+fn debug_hud_text_system(
+    hud: Res<DebugHud>,
+    diagnostics: Res<Diagnostics>,
+    mut query: Query<(&DebugHudLine, Mut<Text>)>,
+) {
+    for (line, mut text) in &mut query.iter() {
+        let value = if !hud.visible {
+            String::new()
+        } else {
+            match line {
+                DebugHudLine::Fps => {
+                    let fps = diagnostics
+                        .get(FrameTimeDiagnosticsPlugin::FPS)
+                        .and_then(|fps| fps.average());
+                    match fps {
+                        Some(fps) => format!("FPS: {:.0}", fps),
+                        None => "FPS: —".to_string(),
+                    }
+                }
+                DebugHudLine::Bodies => hud
+                    .bodies
+                    .map_or_else(String::new, |bodies| format!("Bodies: {}", bodies)),
+                DebugHudLine::Extra => hud.extra.clone().unwrap_or_default(),
+            }
+        };
+        if text.value != value {
+            text.value = value;
+        }
+    }
+}
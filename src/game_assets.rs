@@ -0,0 +1,210 @@
+//! Handles of the assets of the examples, loaded once at startup.
+
+use crate::platform;
+use bevy::{app::startup_stage, prelude::*, render::texture::TextureFormat};
+use std::collections::HashMap;
+
+pub const FONT: &str = "assets/DejaVuSansMono.ttf";
+/// Frames of the explosion sprite sheet, in a single row
+pub const EXPLOSION_FRAMES: usize = 6;
+/// Size in px of the fallback texture, and of its cells
+const MISSING_SIZE: usize = 64;
+const MISSING_CELL: usize = 8;
+
+/// Inserts the `GameAssets` resource before the startup systems of the
+/// examples run.
+pub struct GameAssetsPlugin;
+
+impl Plugin for GameAssetsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_startup_system_to_stage(startup_stage::PRE_STARTUP, setup_game_assets.system());
+    }
+}
+
+/// Sounds of the spaceship, from assets/sounds, `None` if the file is absent.
+#[derive(Default)]
+pub struct Sounds {
+    pub laser: Option<Handle<AudioSource>>,
+    /// By asteroid size, without volume control in bevy
+    pub explosions: [Option<Handle<AudioSource>>; 3],
+    pub thrust: Option<Handle<AudioSource>>,
+}
+
+/// The assets of the examples.
+///
+/// Absent texture files are replaced by `missing_texture`, a magenta
+/// checkerboard hard to overlook. The default one, of default handles, stands
+/// in for them in the headless apps, which load nothing.
+#[derive(Default)]
+pub struct GameAssets {
+    pub font: Handle<Font>,
+    pub sphere_texture: Handle<Texture>,
+    /// Ship of the spaceship_01 example
+    pub ship_texture: Handle<Texture>,
+    /// Ship of the spaceship_02 example
+    pub player_ship_texture: Handle<Texture>,
+    pub meteor_texture: Handle<Texture>,
+    pub digits_texture: Handle<Texture>,
+    pub missing_texture: Handle<Texture>,
+    pub sphere_material: Handle<ColorMaterial>,
+    pub white_material: Handle<ColorMaterial>,
+    pub explosion_atlas: Handle<TextureAtlas>,
+    pub sounds: Sounds,
+    materials: MaterialCache,
+}
+
+impl GameAssets {
+    /// Material of a plain `color`, shared by all its users.
+    pub fn material_for(
+        &mut self,
+        color: Color,
+        materials: &mut Assets<ColorMaterial>,
+    ) -> Handle<ColorMaterial> {
+        self.materials.get_or_add(None, color, materials)
+    }
+
+    /// Material of the sphere texture modulated by `color`, shared by all its
+    /// users.
+    pub fn sphere_tinted(
+        &mut self,
+        color: Color,
+        materials: &mut Assets<ColorMaterial>,
+    ) -> Handle<ColorMaterial> {
+        self.materials
+            .get_or_add(Some(self.sphere_texture), color, materials)
+    }
+}
+
+/// Materials already created, by texture and color.
+#[derive(Default)]
+struct MaterialCache(HashMap<(Option<Handle<Texture>>, [u32; 4]), Handle<ColorMaterial>>);
+
+impl MaterialCache {
+    fn get_or_add(
+        &mut self,
+        texture: Option<Handle<Texture>>,
+        color: Color,
+        materials: &mut Assets<ColorMaterial>,
+    ) -> Handle<ColorMaterial> {
+        // Colors are not hashable, their components bits are
+        let key = [
+            color.r.to_bits(),
+            color.g.to_bits(),
+            color.b.to_bits(),
+            color.a.to_bits(),
+        ];
+        *self
+            .0
+            .entry((texture, key))
+            .or_insert_with(|| match texture {
+                Some(texture) => materials.add(ColorMaterial::modulated_texture(texture, color)),
+                None => materials.add(color.into()),
+            })
+    }
+}
+
+/// RGBA pixels of a `size` square of alternating magenta and black `cell`
+/// squares.
+fn checkerboard(size: usize, cell: usize) -> Vec<u8> {
+    let mut data = Vec::with_capacity(size * size * 4);
+    for y in 0..size {
+        for x in 0..size {
+            if (x / cell + y / cell) % 2 == 0 {
+                data.extend_from_slice(&[255, 0, 255, 255]);
+            } else {
+                data.extend_from_slice(&[0, 0, 0, 255]);
+            }
+        }
+    }
+    data
+}
+
+fn setup_game_assets(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut textures: ResMut<Assets<Texture>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut atlases: ResMut<Assets<TextureAtlas>>,
+) {
+    let missing_texture = textures.add(Texture::new(
+        Vec2::new(MISSING_SIZE as f32, MISSING_SIZE as f32),
+        checkerboard(MISSING_SIZE, MISSING_CELL),
+        TextureFormat::Rgba8UnormSrgb,
+    ));
+    let texture = |path: &str| {
+        if platform::asset_exists(path) {
+            asset_server.load(path).unwrap_or(missing_texture)
+        } else {
+            eprintln!("Missing {}, replaced by a checkerboard", path);
+            missing_texture
+        }
+    };
+    let sound = |name: &str| {
+        let path = format!("assets/sounds/{}.mp3", name);
+        if platform::asset_exists(&path) {
+            asset_server.load(path).ok()
+        } else {
+            eprintln!("Missing {}, played silently", path);
+            None
+        }
+    };
+    let sphere_texture = texture("assets/sprite_sphere_256x256.png");
+    let explosion_atlas = TextureAtlas::from_grid(
+        texture("assets/explosion_sheet.png"),
+        Vec2::new(64.0 * EXPLOSION_FRAMES as f32, 64.0),
+        EXPLOSION_FRAMES,
+        1,
+    );
+    commands.insert_resource(GameAssets {
+        font: asset_server.load(FONT).unwrap(),
+        sphere_texture,
+        ship_texture: texture("assets/spaceship.png"),
+        player_ship_texture: texture("assets/playerShip2_red.png"),
+        meteor_texture: texture("assets/meteorBrown_big1.png"),
+        digits_texture: texture("assets/digits.png"),
+        missing_texture,
+        sphere_material: materials.add(sphere_texture.into()),
+        white_material: materials.add(Color::WHITE.into()),
+        explosion_atlas: atlases.add(explosion_atlas),
+        sounds: Sounds {
+            laser: sound("laser"),
+            explosions: [
+                sound("explosion_small"),
+                sound("explosion_medium"),
+                sound("explosion_large"),
+            ],
+            thrust: sound("thrust"),
+        },
+        materials: MaterialCache::default(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checkerboard_alternates_cells() {
+        let data = checkerboard(4, 2);
+        assert_eq!(data.len(), 4 * 4 * 4);
+        let pixel = |x: usize, y: usize| &data[(y * 4 + x) * 4..(y * 4 + x) * 4 + 4];
+        assert_eq!(pixel(0, 0), pixel(1, 1));
+        assert_eq!(pixel(0, 0), &[255, 0, 255, 255]);
+        assert_eq!(pixel(2, 0), &[0, 0, 0, 255]);
+        assert_eq!(pixel(2, 2), pixel(0, 0));
+    }
+
+    #[test]
+    fn material_cache_shares_materials_by_color() {
+        let mut cache = MaterialCache::default();
+        let mut materials = Assets::<ColorMaterial>::default();
+        let red = cache.get_or_add(None, Color::rgb(1.0, 0.0, 0.0), &mut materials);
+        let blue = cache.get_or_add(None, Color::rgb(0.0, 0.0, 1.0), &mut materials);
+        assert_eq!(
+            cache.get_or_add(None, Color::rgb(1.0, 0.0, 0.0), &mut materials),
+            red
+        );
+        assert_ne!(red, blue);
+        assert_eq!(materials.iter().count(), 2);
+    }
+}
@@ -0,0 +1,158 @@
+//! Golden runs of a simulation, catching any change of its outcome from one
+//! run to the next, e.g. from a change of the system order or an unseeded
+//! random generator.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{fmt, iter::once};
+
+/// Body positions at a step, in thousandths, rounded and sorted so that
+/// neither the float noise below 1e-3 nor the order of the bodies matter.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot(pub Vec<(i64, i64)>);
+
+impl Snapshot {
+    pub fn new(positions: impl IntoIterator<Item = Vec2>) -> Self {
+        let round = |v: f32| (v as f64 * 1000.0).round() as i64;
+        let mut positions: Vec<_> = positions
+            .into_iter()
+            .map(|position| (round(position.x()), round(position.y())))
+            .collect();
+        positions.sort_unstable();
+        Snapshot(positions)
+    }
+
+    pub fn hash(&self) -> u64 {
+        fnv1a(self.0.iter().flat_map(|&(x, y)| once(x).chain(once(y))))
+    }
+}
+
+/// FNV-1a hash of the values, the same on every platform and Rust version.
+fn fnv1a(values: impl IntoIterator<Item = i64>) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for value in values {
+        for byte in &value.to_le_bytes() {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+/// The snapshots of each step of a run.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GoldenRun {
+    /// Hash of all the steps, the only part compared when equal
+    pub hash: u64,
+    pub steps: Vec<Snapshot>,
+}
+
+/// First difference between two runs.
+#[derive(Debug, PartialEq)]
+pub struct Divergence {
+    pub step: usize,
+    /// Index of the body in the sorted snapshot
+    pub body: usize,
+    /// Position in thousandths in each run, `None` if absent from it
+    pub expected: Option<(i64, i64)>,
+    pub actual: Option<(i64, i64)>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let show = |position: Option<(i64, i64)>| match position {
+            Some((x, y)) => format!("({:.3}, {:.3})", x as f64 / 1000.0, y as f64 / 1000.0),
+            None => "absent".to_string(),
+        };
+        write!(
+            f,
+            "step {}, body {}: expected {}, got {}",
+            self.step,
+            self.body,
+            show(self.expected),
+            show(self.actual)
+        )
+    }
+}
+
+impl GoldenRun {
+    pub fn push(&mut self, snapshot: Snapshot) {
+        self.steps.push(snapshot);
+        self.hash = fnv1a(self.steps.iter().map(|step| step.hash() as i64));
+    }
+
+    /// First step and body at which `actual` differs from this run, `None` if
+    /// they are the same.
+    pub fn first_divergence(&self, actual: &GoldenRun) -> Option<Divergence> {
+        if self == actual {
+            return None;
+        }
+        let steps = self.steps.len().max(actual.steps.len());
+        for step in 0..steps {
+            let (expected, got) = (self.steps.get(step), actual.steps.get(step));
+            if expected == got {
+                continue;
+            }
+            let empty = Snapshot::default();
+            let (expected, got) = (expected.unwrap_or(&empty), got.unwrap_or(&empty));
+            let bodies = expected.0.len().max(got.0.len());
+            for body in 0..bodies {
+                let (expected, actual) = (expected.0.get(body), got.0.get(body));
+                if expected != actual {
+                    return Some(Divergence {
+                        step,
+                        body,
+                        expected: expected.copied(),
+                        actual: actual.copied(),
+                    });
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshots_ignore_the_order_and_the_noise() {
+        let a = Snapshot::new(vec![Vec2::new(1.0, 2.0), Vec2::new(-3.5, 0.25)]);
+        let b = Snapshot::new(vec![Vec2::new(-3.5, 0.250_01), Vec2::new(1.000_2, 2.0)]);
+        assert_eq!(a, b);
+        assert_eq!(a.hash(), b.hash());
+        let c = Snapshot::new(vec![Vec2::new(1.0, 2.0), Vec2::new(-3.5, 0.26)]);
+        assert_ne!(a.hash(), c.hash());
+    }
+
+    #[test]
+    fn first_divergence_finds_the_step_and_body() {
+        let mut golden = GoldenRun::default();
+        let mut actual = GoldenRun::default();
+        for step in 0..5 {
+            let x = step as f32;
+            golden.push(Snapshot::new(vec![Vec2::new(x, 0.0), Vec2::new(x, 1.0)]));
+            let y = if step >= 3 { 1.5 } else { 1.0 };
+            actual.push(Snapshot::new(vec![Vec2::new(x, 0.0), Vec2::new(x, y)]));
+        }
+        assert_ne!(golden.hash, actual.hash);
+        assert_eq!(
+            golden.first_divergence(&actual),
+            Some(Divergence {
+                step: 3,
+                body: 1,
+                expected: Some((3000, 1000)),
+                actual: Some((3000, 1500)),
+            })
+        );
+        assert_eq!(golden.first_divergence(&golden), None);
+        // A missing body or step diverges too
+        actual.steps.truncate(2);
+        actual.steps[1].0.pop();
+        assert_eq!(
+            golden.first_divergence(&actual).map(|d| (d.step, d.body)),
+            Some((1, 1))
+        );
+    }
+}
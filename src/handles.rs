@@ -0,0 +1,48 @@
+use bevy::prelude::*;
+use bevy_rapier2d::{physics::ColliderHandleComponent, rapier::geometry::ColliderHandle};
+use std::collections::HashMap;
+
+/// Keeps the `ColliderHandleToEntity` resource up to date.
+pub struct HandleToEntityPlugin;
+
+impl Plugin for HandleToEntityPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<ColliderHandleToEntity>()
+            // Front of the post update stage, so that systems handling the
+            // events of the physics step can find colliders created this frame.
+            .add_system_to_stage_front(stage::POST_UPDATE, collider_to_entity_system.system());
+    }
+}
+
+/// Entity of each collider, to find the entities involved in rapier events.
+#[derive(Default)]
+pub struct ColliderHandleToEntity(HashMap<ColliderHandle, Entity>);
+
+impl ColliderHandleToEntity {
+    pub fn get(&self, handle: ColliderHandle) -> Option<Entity> {
+        self.0.get(&handle).copied()
+    }
+
+    /// Maps an extra collider of the body of `entity`, tracked by hand as only
+    /// the `ColliderHandleComponent` is tracked. The mapping is dropped along
+    /// with the component of the entity.
+    pub fn insert(&mut self, handle: ColliderHandle, entity: Entity) {
+        self.0.insert(handle, entity);
+    }
+
+    pub fn remove(&mut self, handle: ColliderHandle) {
+        self.0.remove(&handle);
+    }
+}
+
+fn collider_to_entity_system(
+    mut h_to_e: ResMut<ColliderHandleToEntity>,
+    mut changed: Query<(Entity, Changed<ColliderHandleComponent>)>,
+) {
+    for entity in changed.removed::<ColliderHandleComponent>() {
+        h_to_e.0.retain(|_, e| e != entity);
+    }
+    for (entity, collider_handle) in &mut changed.iter() {
+        h_to_e.0.insert(collider_handle.handle(), entity);
+    }
+}
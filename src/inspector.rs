@@ -0,0 +1,255 @@
+//! Console dump of the components of the entity under the cursor.
+
+use crate::{
+    collide::Velocity,
+    mouse::{CursorCamera, MousePosition},
+    z_order::topmost,
+};
+use bevy::prelude::*;
+use bevy_rapier2d::{
+    physics::{ColliderHandleComponent, RigidBodyHandleComponent},
+    rapier::{dynamics::RigidBodySet, geometry::ColliderSet},
+};
+
+/// Distance in px from the cursor within which entities are picked
+const PICK_RADIUS: f32 = 20.0;
+
+/// Name of an entity, printed by the inspector
+pub struct Name(pub String);
+
+/// Lines about the components of `entity` known by an example, empty if it
+/// has none of them.
+pub type Describer = fn(&World, Entity) -> Vec<String>;
+
+/// Toggles the inspector with I. While active, a left click prints the
+/// components of the sprite drawn on top under the cursor, or else of the
+/// sprite or rapier body nearest to the cursor, within a few px.
+///
+/// The examples print their own components with describers.
+#[derive(Default)]
+pub struct InspectorPlugin {
+    describers: Vec<Describer>,
+}
+
+impl InspectorPlugin {
+    pub fn with_describer(mut self, describer: Describer) -> Self {
+        self.describers.push(describer);
+        self
+    }
+}
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_resource(Inspector {
+            active: false,
+            describers: self.describers.clone(),
+        })
+        .add_system(inspector_system.thread_local_system());
+    }
+}
+
+/// Whether the inspector is active, the examples not spawning on clicks then.
+pub struct Inspector {
+    active: bool,
+    describers: Vec<Describer>,
+}
+
+impl Inspector {
+    pub fn active(&self) -> bool {
+        self.active
+    }
+}
+
+/// Position of the rapier body of `entity`, or else its translation.
+fn position(world: &World, bodies: Option<&RigidBodySet>, entity: Entity) -> Option<Vec2> {
+    let body = world
+        .get::<RigidBodyHandleComponent>(entity)
+        .ok()
+        .and_then(|handle| bodies?.get(handle.handle()));
+    if let Some(body) = body {
+        let translation = body.position.translation.vector;
+        return Some(Vec2::new(translation.x, translation.y));
+    }
+    let transform = world.get::<Transform>(entity).ok()?;
+    Some(transform.translation().truncate())
+}
+
+/// The sprite drawn on top of those covering `cursor`, or else the sprite or
+/// rapier body nearest to `cursor`, within `radius`.
+fn pick(world: &World, bodies: Option<&RigidBodySet>, cursor: Vec2, radius: f32) -> Option<Entity> {
+    let mut nearest: Option<(Entity, f32)> = None;
+    let mut covering = Vec::new();
+    for (entity, transform) in world.query::<(Entity, &Transform)>().iter() {
+        let pickable = world.get::<Sprite>(entity).is_ok()
+            || world.get::<TextureAtlasSprite>(entity).is_ok()
+            || world.get::<RigidBodyHandleComponent>(entity).is_ok();
+        if !pickable {
+            continue;
+        }
+        let distance = match position(world, bodies, entity) {
+            Some(position) => (position - cursor).length(),
+            None => continue,
+        };
+        if distance <= radius && nearest.map_or(true, |(_, nearest)| distance < nearest) {
+            nearest = Some((entity, distance));
+        }
+        // Within the circle of the sprite
+        if let Ok(sprite) = world.get::<Sprite>(entity) {
+            let extent = sprite.size.x().max(sprite.size.y()) * transform.scale().x() / 2.0;
+            if distance <= extent {
+                covering.push((entity, transform.translation().z()));
+            }
+        }
+    }
+    topmost(covering).or_else(|| nearest.map(|(entity, _)| entity))
+}
+
+/// Lines of the components of `entity` known by the library.
+fn describe(
+    world: &World,
+    bodies: Option<&RigidBodySet>,
+    colliders: Option<&ColliderSet>,
+    entity: Entity,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    if let Ok(transform) = world.get::<Transform>(entity) {
+        let translation = transform.translation();
+        let (axis, angle) = transform.rotation().to_axis_angle();
+        lines.push(format!(
+            "translation: ({:.2}, {:.2}, {:.2})",
+            translation.x(),
+            translation.y(),
+            translation.z()
+        ));
+        lines.push(format!(
+            "rotation: {:.1}°",
+            angle.to_degrees() * axis.z().signum()
+        ));
+    }
+    if let Ok(velocity) = world.get::<Velocity>(entity) {
+        lines.push(format!(
+            "velocity: ({:.2}, {:.2})",
+            velocity.0.x, velocity.0.y
+        ));
+    }
+    let body = world
+        .get::<RigidBodyHandleComponent>(entity)
+        .ok()
+        .and_then(|handle| bodies?.get(handle.handle()));
+    if let Some(body) = body {
+        lines.push(format!(
+            "body: linvel ({:.2}, {:.2}), angvel {:.2}, mass {:.2}",
+            body.linvel.x,
+            body.linvel.y,
+            body.angvel,
+            body.mass()
+        ));
+    }
+    let collider = world
+        .get::<ColliderHandleComponent>(entity)
+        .ok()
+        .and_then(|handle| colliders?.get(handle.handle()));
+    if let Some(collider) = collider {
+        let shape = if let Some(ball) = collider.shape().as_ball() {
+            format!("ball of radius {:.2}", ball.radius)
+        } else if let Some(cuboid) = collider.shape().as_cuboid() {
+            format!(
+                "cuboid of half extents ({:.2}, {:.2})",
+                cuboid.half_extents.x, cuboid.half_extents.y
+            )
+        } else {
+            "other shape".to_string()
+        };
+        lines.push(format!(
+            "collider: {}, friction {:.2}, restitution {:.2}",
+            shape, collider.friction, collider.restitution
+        ));
+    }
+    lines
+}
+
+fn inspector_system(world: &mut World, resources: &mut Resources) {
+    let world = &*world;
+    let keyboard_input = resources.get::<Input<KeyCode>>().unwrap();
+    let mouse_button_input = resources.get::<Input<MouseButton>>().unwrap();
+    let mut inspector = resources.get_mut::<Inspector>().unwrap();
+    if keyboard_input.just_pressed(KeyCode::I) {
+        inspector.active = !inspector.active;
+        println!("Inspector {}", if inspector.active { "on" } else { "off" });
+    }
+    if !inspector.active || !mouse_button_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let mouse_position = resources.get::<MousePosition>().unwrap();
+    let cursor = match mouse_position.world_position() {
+        Some(cursor) if mouse_position.in_window() => cursor,
+        _ => return,
+    };
+    // The pick radius stays the same on screen whatever the zoom
+    let mut scale = 1.0;
+    for (_camera, transform) in world.query::<(&CursorCamera, &Transform)>().iter() {
+        scale = transform.scale().x();
+    }
+    let bodies = resources.get::<RigidBodySet>();
+    let colliders = resources.get::<ColliderSet>();
+    let entity = match pick(world, bodies.as_deref(), cursor, PICK_RADIUS * scale) {
+        Some(entity) => entity,
+        None => {
+            println!("Nothing at ({:.2}, {:.2})", cursor.x(), cursor.y());
+            return;
+        }
+    };
+    match world.get::<Name>(entity) {
+        Ok(name) => println!("Entity {:?} \"{}\"", entity, name.0),
+        Err(_) => println!("Entity {:?}", entity),
+    }
+    let lines = describe(world, bodies.as_deref(), colliders.as_deref(), entity)
+        .into_iter()
+        .chain(
+            inspector
+                .describers
+                .iter()
+                .flat_map(|describer| describer(world, entity)),
+        );
+    for line in lines {
+        println!("  {}", line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_the_nearest_sprite_within_the_radius() {
+        let mut world = World::new();
+        let sprite = |x: f32| {
+            (
+                Transform::from_translation(Vec3::new(x, 0.0, 0.0)),
+                Sprite::new(Vec2::one()),
+            )
+        };
+        let near = world.spawn(sprite(3.0));
+        world.spawn(sprite(-5.0));
+        // Not a sprite nor a body, like a camera
+        world.spawn((Transform::default(),));
+        assert_eq!(pick(&world, None, Vec2::zero(), 10.0), Some(near));
+        assert_eq!(pick(&world, None, Vec2::new(20.0, 0.0), 10.0), None);
+    }
+
+    #[test]
+    fn picks_the_sprite_on_top_under_the_cursor() {
+        let mut world = World::new();
+        let disc = |x: f32, z: f32| {
+            (
+                Transform::from_translation(Vec3::new(x, 0.0, z)),
+                Sprite::new(Vec2::new(20.0, 20.0)),
+            )
+        };
+        world.spawn(disc(1.0, 0.2));
+        let top = world.spawn(disc(8.0, 0.7));
+        world.spawn(disc(-4.0, 0.5));
+        // The nearest one is under the others
+        assert_eq!(pick(&world, None, Vec2::zero(), 10.0), Some(top));
+    }
+}
@@ -0,0 +1,39 @@
+//! Shared components and systems used by the showcase examples.
+
+pub mod animation;
+pub mod arena;
+pub mod balls;
+pub mod breakout_scene;
+pub mod camera_controller;
+pub mod capture;
+pub mod cleanup;
+pub mod collide;
+pub mod config;
+pub mod damping;
+pub mod debug_hud;
+pub mod game_assets;
+pub mod golden;
+pub mod handles;
+pub mod inspector;
+pub mod lifetime;
+pub mod mouse;
+pub mod ncollide_scene;
+pub mod physics_math;
+pub mod platform;
+pub mod rapier_scene;
+pub mod response;
+pub mod scenario;
+pub mod scene;
+pub mod score;
+pub mod settings;
+pub mod showcase;
+pub mod spaceship_abilities;
+pub mod spaceship_controls;
+pub mod spaceship_menu;
+pub mod spaceship_replay;
+pub mod spaceship_scene;
+pub mod spaceship_stats;
+pub mod spaceship_weapons;
+pub mod spawn;
+pub mod top_down_vehicle;
+pub mod z_order;
@@ -0,0 +1,102 @@
+use crate::arena::Arena;
+use bevy::prelude::*;
+use ncollide2d::pipeline::{CollisionObjectSlabHandle, CollisionWorld};
+
+/// Remaining time to live in s, the entity is despawned once it runs out.
+pub struct Lifetime(pub f32);
+
+/// Remaining arena edge wraps.
+///
+/// Decremented by the system wrapping the entities around the arena, which
+/// adds a `DespawnOutside` once they run out, the entity then leaving the
+/// arena at the next edge.
+pub struct WrapCount {
+    pub remaining: u8,
+}
+
+/// Despawns the entity once its transform is more than `margin` outside the
+/// arena, e.g. for bodies flying away instead of wrapping.
+///
+/// Not to be combined with an entity still wrapping around the arena, which
+/// never gets outside.
+pub struct DespawnOutside {
+    pub margin: f32,
+}
+
+pub fn lifetime_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, Mut<Lifetime>)>,
+) {
+    for (entity, mut lifetime) in &mut query.iter() {
+        lifetime.0 -= time.delta_seconds;
+        if lifetime.0 <= 0.0 {
+            commands.despawn_recursive(entity);
+        }
+    }
+}
+
+/// Whether `position` is more than `margin` outside the `arena`.
+fn is_outside(arena: &Arena, position: Vec2, margin: f32) -> bool {
+    let margin = Vec2::new(margin, margin);
+    let (min, max) = (arena.min() - margin, arena.max() + margin);
+    position.x() < min.x()
+        || position.x() > max.x()
+        || position.y() < min.y()
+        || position.y() > max.y()
+}
+
+/// Despawns the entities with a `DespawnOutside` which left the `Arena`.
+///
+/// Thread local to remove the collision objects of the ncollide2d examples
+/// from their `CollisionWorld` along with the entities. The rapier bodies are
+/// removed by the `RapierCleanupPlugin`.
+pub fn despawn_outside_system(world: &mut World, resources: &mut Resources) {
+    let arena = *resources.get::<Arena>().unwrap();
+    let mut outside = Vec::new();
+    for (entity, despawn, transform) in world
+        .query::<(Entity, &DespawnOutside, &Transform)>()
+        .iter()
+    {
+        debug_assert!(
+            world
+                .get::<WrapCount>(entity)
+                .map_or(true, |wrap_count| wrap_count.remaining == 0),
+            "{:?} has a DespawnOutside while still wrapping around the arena",
+            entity
+        );
+        if is_outside(&arena, transform.translation().truncate(), despawn.margin) {
+            outside.push(entity);
+        }
+    }
+    if outside.is_empty() {
+        return;
+    }
+    let mut collision_world = resources.get_mut::<CollisionWorld<f32, Entity>>();
+    for entity in outside {
+        if let (Some(collision_world), Ok(handle)) = (
+            collision_world.as_deref_mut(),
+            world.get::<CollisionObjectSlabHandle>(entity),
+        ) {
+            collision_world.remove(&[*handle]);
+        }
+        world.despawn(entity).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outside_beyond_the_margin_only() {
+        let arena = Arena::centered(100.0, 50.0);
+        assert!(!is_outside(&arena, Vec2::new(50.0, 0.0), 5.0));
+        assert!(!is_outside(&arena, Vec2::new(54.0, -29.0), 5.0));
+        assert!(is_outside(&arena, Vec2::new(56.0, 0.0), 5.0));
+        assert!(is_outside(&arena, Vec2::new(0.0, -31.0), 5.0));
+        let arena = Arena::new(100.0, 50.0);
+        assert!(is_outside(&arena, Vec2::new(-1.0, 25.0), 0.0));
+        assert!(!is_outside(&arena, Vec2::new(100.0, 50.0), 0.0));
+    }
+}
@@ -0,0 +1,159 @@
+use crate::arena::Arena;
+use bevy::{
+    prelude::*,
+    render::camera::{OrthographicProjection, WindowOrigin},
+    window::WindowFocused,
+};
+
+/// Cursor positions closer than this, in px, to a window border are considered
+/// the last event before the cursor left the window.
+const EDGE_MARGIN: f32 = 2.0;
+
+/// Keeps the `MousePosition` resource up to date from `CursorMoved` and
+/// `WindowFocused` events, and from the transform of the `CursorCamera`.
+pub struct MousePositionPlugin;
+
+impl Plugin for MousePositionPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<MousePosition>()
+            .add_system_to_stage(stage::PRE_UPDATE, mouse_position_system.system());
+    }
+}
+
+/// Marks the camera through which the cursor looks at the world, with an
+/// orthographic projection.
+///
+/// Without such a camera, window pixels are taken as world coordinates.
+pub struct CursorCamera;
+
+/// Converts a position in window pixels, from the bottom left corner, to world
+/// coordinates as seen by a camera with a `WindowOrigin::BottomLeft`
+/// projection.
+pub fn window_to_world(position: Vec2, camera: &Transform) -> Vec2 {
+    let world = *camera.value() * position.extend(0.0).extend(1.0);
+    Vec2::new(world.x(), world.y())
+}
+
+/// Converts a position in window pixels, from the bottom left corner, to world
+/// coordinates as seen by a camera whose projection has the window `origin`,
+/// its scale, rotation and translation included.
+pub fn cursor_to_world(
+    position: Vec2,
+    window_size: Vec2,
+    origin: WindowOrigin,
+    camera: &Transform,
+) -> Vec2 {
+    let view = match origin {
+        WindowOrigin::BottomLeft => position,
+        WindowOrigin::Center => position - window_size / 2.0,
+    };
+    window_to_world(view, camera)
+}
+
+/// Last known cursor position, in window pixels from the bottom left corner,
+/// and in world coordinates.
+#[derive(Default)]
+pub struct MousePosition {
+    position: Option<Vec2>,
+    world: Option<Vec2>,
+    in_window: bool,
+}
+
+impl MousePosition {
+    /// Last position received, `None` until the cursor first moves over the
+    /// window.
+    pub fn position(&self) -> Option<Vec2> {
+        self.position
+    }
+
+    /// Last position received, in world coordinates.
+    pub fn world_position(&self) -> Option<Vec2> {
+        self.world
+    }
+
+    /// Whether the cursor is believed to be over the window.
+    ///
+    /// Bevy sends no event when the cursor leaves the window, so a last
+    /// position on the window border is taken as the cursor having left. So is
+    /// the window losing the focus, until the cursor moves again.
+    pub fn in_window(&self) -> bool {
+        self.in_window
+    }
+
+    /// Cursor world position clamped to the arena, `None` if no position was
+    /// ever received or the cursor left the window.
+    pub fn position_in_arena(&self, arena: &Arena) -> Option<Vec2> {
+        if !self.in_window {
+            return None;
+        }
+        self.world.map(|position| arena.clamp(position, 0.0))
+    }
+}
+
+#[derive(Default)]
+struct LocalStateMousePositionSystem {
+    cursor_moved: EventReader<CursorMoved>,
+    focused: EventReader<WindowFocused>,
+}
+
+fn mouse_position_system(
+    mut state: Local<LocalStateMousePositionSystem>,
+    cursor_moved_events: Res<Events<CursorMoved>>,
+    focused_events: Res<Events<WindowFocused>>,
+    windows: Res<Windows>,
+    mut mouse_position: ResMut<MousePosition>,
+    mut cameras: Query<(&CursorCamera, &Transform, &OrthographicProjection)>,
+) {
+    // Out of the window until the cursor moves over it again, below
+    if state
+        .focused
+        .iter(&focused_events)
+        .any(|event| !event.focused)
+    {
+        mouse_position.in_window = false;
+    }
+    for event in state.cursor_moved.iter(&cursor_moved_events) {
+        let position = event.position;
+        mouse_position.position = Some(position);
+        mouse_position.in_window = windows.get(event.id).map_or(true, |window| {
+            let (width, height) = (window.width as f32, window.height as f32);
+            position.x() > EDGE_MARGIN
+                && position.y() > EDGE_MARGIN
+                && position.x() < width - EDGE_MARGIN
+                && position.y() < height - EDGE_MARGIN
+        });
+    }
+    // The camera may move or zoom while the cursor stays still
+    if let Some(position) = mouse_position.position {
+        let window_size = windows.get_primary().map_or(Vec2::zero(), |window| {
+            Vec2::new(window.width as f32, window.height as f32)
+        });
+        let mut world = position;
+        for (_camera, transform, projection) in &mut cameras.iter() {
+            world = cursor_to_world(position, window_size, projection.window_origin, transform);
+        }
+        mouse_position.world = Some(world);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn centered_camera_maps_the_window_center_to_its_position() {
+        let window_size = Vec2::new(1280.0, 800.0);
+        let camera = Transform::from_translation(Vec3::new(5.0, -3.0, 0.0)).with_scale(0.1);
+        let center = cursor_to_world(
+            window_size / 2.0,
+            window_size,
+            WindowOrigin::Center,
+            &camera,
+        );
+        assert!((center - Vec2::new(5.0, -3.0)).length() < 1e-4);
+        let corner = cursor_to_world(Vec2::zero(), window_size, WindowOrigin::Center, &camera);
+        assert!((corner - Vec2::new(5.0 - 64.0, -3.0 - 40.0)).length() < 1e-4);
+        let corner = cursor_to_world(Vec2::zero(), window_size, WindowOrigin::BottomLeft, &camera);
+        assert!((corner - Vec2::new(5.0, -3.0)).length() < 1e-4);
+    }
+}
@@ -0,0 +1,116 @@
+//! Spheres bouncing off each other through ncollide2d, the scene of the
+//! ncollide2d example and of the launcher.
+
+use crate::{
+    arena::Arena,
+    collide::{add_sphere, Velocity},
+    game_assets::GameAssets,
+    scene::{Scene, SceneEntity},
+    showcase::SeededRng,
+    spawn::{capped_requests, MaxBodies, SpawnRequest},
+    z_order::ZOrdered,
+};
+use bevy::{
+    prelude::*,
+    render::camera::{OrthographicProjection, WindowOrigin},
+};
+use ncollide2d::{na::Vector2, pipeline::CollisionGroups, world::CollisionWorld};
+use rand::Rng;
+
+/// The arena, in px, of the 1280x800 window of the examples
+const ARENA_WIDTH: f32 = 1280.0;
+const ARENA_HEIGHT: f32 = 800.0;
+pub const SPHERE_RADIUS: f32 = 128.0 * 0.2;
+/// Spheres spawned when the scene starts
+const SPHERE_COUNT: usize = 50;
+
+pub const SCENE: Scene = Scene {
+    name: "NCollide balls",
+    setup,
+    teardown,
+};
+
+/// The only bodies of the scene
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sphere;
+
+/// Adds the sphere spawn requests and their spawner.
+///
+/// The collisions are left to the `NCollideResponsePlugin`, added with the
+/// wrapping of the arena.
+pub struct NCollideScenePlugin;
+
+impl Plugin for NCollideScenePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let mut sphere_groups = CollisionGroups::new();
+        sphere_groups.set_membership(&[1]);
+        app.add_resource(sphere_groups)
+            .add_event::<SpawnRequest<Sphere>>()
+            .add_system_to_stage(stage::POST_UPDATE, sphere_spawner_system.system());
+    }
+}
+
+/// The camera, and spheres of random velocities sent to the spawner.
+fn setup(commands: &mut Commands, resources: &mut Resources) {
+    let arena = Arena::new(ARENA_WIDTH, ARENA_HEIGHT);
+    commands
+        .spawn(Camera2dComponents {
+            orthographic_projection: OrthographicProjection {
+                window_origin: WindowOrigin::BottomLeft,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .with(SceneEntity);
+    let mut rng = resources.get_mut::<SeededRng>().unwrap();
+    let rng = &mut rng.0;
+    let mut requests = resources.get_mut::<Events<SpawnRequest<Sphere>>>().unwrap();
+    for _ in 0..SPHERE_COUNT {
+        let (min, max) = (arena.min(), arena.max());
+        let position = arena.clamp(
+            Vec2::new(
+                rng.gen_range(min.x(), max.x()),
+                rng.gen_range(min.y(), max.y()),
+            ),
+            SPHERE_RADIUS,
+        );
+        let velocity = Vec2::new(
+            rng.gen_range(-arena.width, arena.width) / 4.0,
+            rng.gen_range(-arena.height, arena.height) / 4.0,
+        );
+        requests.send(SpawnRequest::new(Sphere, position, velocity));
+    }
+    commands.insert_resource(arena);
+}
+
+fn teardown(_world: &mut World, _resources: &mut Resources) {}
+
+/// Spawns the requested spheres with their collision object, in the order of
+/// the requests, up to the `MaxBodies` cap.
+pub fn sphere_spawner_system(
+    mut commands: Commands,
+    mut reader: Local<EventReader<SpawnRequest<Sphere>>>,
+    requests: Res<Events<SpawnRequest<Sphere>>>,
+    max_bodies: Res<MaxBodies>,
+    assets: Res<GameAssets>,
+    mut world: ResMut<CollisionWorld<f32, Entity>>,
+    sphere_groups: Res<CollisionGroups>,
+) {
+    let alive = world.collision_objects().count();
+    for request in capped_requests(&mut reader, &requests, alive, &max_bodies) {
+        let (position, velocity) = (request.position, request.velocity);
+        commands
+            .spawn(SpriteComponents {
+                transform: Transform::from_translation(position.extend(0.0)).with_scale(0.2),
+                material: assets.sphere_material,
+                ..Default::default()
+            })
+            .with(Velocity(Vector2::new(velocity.x(), velocity.y())))
+            .with(ZOrdered::default())
+            .with(SceneEntity);
+        let entity = commands.current_entity().unwrap();
+        let collision_object_handle =
+            add_sphere(&mut world, *sphere_groups, entity, position, SPHERE_RADIUS);
+        commands.insert(entity, (collision_object_handle,));
+    }
+}
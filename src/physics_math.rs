@@ -0,0 +1,156 @@
+//! Wrap and collision response math of the systems moving bodies by hand.
+
+use crate::arena::Arena;
+use bevy::prelude::*;
+
+/// Position on the opposite edge of the arena of a body at `position`, if it
+/// left the arena and still moves outwards, `None` otherwise.
+///
+/// Checking the velocity keeps a body that was just wrapped, or pushed back
+/// inside, from bouncing between the edges.
+pub fn wrap_position(position: Vec2, velocity: Vec2, arena: &Arena) -> Option<Vec2> {
+    let (min, max) = (arena.min(), arena.max());
+    let mut wrapped = position;
+    if position.x() < min.x() && velocity.x() < 0.0 {
+        wrapped.set_x(max.x());
+    } else if position.x() > max.x() && velocity.x() > 0.0 {
+        wrapped.set_x(min.x());
+    }
+    if position.y() < min.y() && velocity.y() < 0.0 {
+        wrapped.set_y(max.y());
+    } else if position.y() > max.y() && velocity.y() > 0.0 {
+        wrapped.set_y(min.y());
+    }
+    if wrapped != position {
+        Some(wrapped)
+    } else {
+        None
+    }
+}
+
+/// Velocities after the collision of two bodies of velocities `v1`, `v2` and
+/// masses `m1`, `m2`, along the unit `normal` pointing from the first towards
+/// the second.
+///
+/// The relative normal velocity is reversed and scaled by `restitution`, `1.0`
+/// being perfectly elastic. Bodies already moving apart are left alone, and an
+/// infinite mass stands for a static obstacle.
+pub fn resolve_elastic(
+    v1: Vec2,
+    v2: Vec2,
+    m1: f32,
+    m2: f32,
+    normal: Vec2,
+    restitution: f32,
+) -> (Vec2, Vec2) {
+    let approach = (v1 - v2).dot(normal);
+    if approach <= 0.0 {
+        return (v1, v2);
+    }
+    let impulse = (1.0 + restitution) * approach / (1.0 / m1 + 1.0 / m2);
+    (v1 - normal * (impulse / m1), v2 + normal * (impulse / m2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-5;
+
+    fn assert_close(a: Vec2, b: Vec2) {
+        assert!((a - b).length() < EPSILON, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn no_wrap_when_moving_inwards() {
+        let arena = Arena::new(100.0, 50.0);
+        let outside = Vec2::new(-1.0, 25.0);
+        assert_eq!(wrap_position(outside, Vec2::new(3.0, 0.0), &arena), None);
+        assert_eq!(
+            wrap_position(outside, Vec2::new(-3.0, 0.0), &arena),
+            Some(Vec2::new(100.0, 25.0))
+        );
+        // On the edge is still inside
+        assert_eq!(
+            wrap_position(Vec2::new(100.0, 25.0), Vec2::new(3.0, 0.0), &arena),
+            None
+        );
+    }
+
+    #[test]
+    fn corners_wrap_along_both_axes() {
+        let arena = Arena::centered(100.0, 50.0);
+        assert_eq!(
+            wrap_position(Vec2::new(51.0, -26.0), Vec2::new(1.0, -1.0), &arena),
+            Some(Vec2::new(-50.0, 25.0))
+        );
+        // Moving back inside along one axis only
+        assert_eq!(
+            wrap_position(Vec2::new(51.0, -26.0), Vec2::new(1.0, 1.0), &arena),
+            Some(Vec2::new(-50.0, -26.0))
+        );
+    }
+
+    #[test]
+    fn equal_masses_exchange_their_normal_velocities() {
+        let (v1, v2) = resolve_elastic(
+            Vec2::new(2.0, 1.0),
+            Vec2::new(-1.0, 3.0),
+            1.0,
+            1.0,
+            Vec2::new(1.0, 0.0),
+            1.0,
+        );
+        assert_close(v1, Vec2::new(-1.0, 1.0));
+        assert_close(v2, Vec2::new(2.0, 3.0));
+    }
+
+    #[test]
+    fn no_restitution_kills_the_normal_velocity() {
+        let normal = Vec2::new(0.6, 0.8);
+        let (v1, v2) = resolve_elastic(
+            Vec2::new(3.0, 4.0),
+            Vec2::new(-1.0, 0.5),
+            2.0,
+            3.0,
+            normal,
+            0.0,
+        );
+        assert!((v1 - v2).dot(normal).abs() < EPSILON);
+        // Against a static obstacle
+        let (v1, v2) = resolve_elastic(
+            Vec2::new(3.0, 4.0),
+            Vec2::zero(),
+            1.0,
+            f32::INFINITY,
+            normal,
+            0.0,
+        );
+        assert!(v1.dot(normal).abs() < EPSILON);
+        assert_eq!(v2, Vec2::zero());
+    }
+
+    #[test]
+    fn momentum_is_conserved() {
+        let (m1, m2) = (1.5, 4.0);
+        let (u1, u2) = (Vec2::new(5.0, -2.0), Vec2::new(-3.0, 1.0));
+        let normal = Vec2::new(1.0, -1.0).normalize();
+        for &restitution in &[0.0, 0.5, 1.0] {
+            let (v1, v2) = resolve_elastic(u1, u2, m1, m2, normal, restitution);
+            assert_close(u1 * m1 + u2 * m2, v1 * m1 + v2 * m2);
+        }
+        // Perfectly elastic also conserves the kinetic energy
+        let (v1, v2) = resolve_elastic(u1, u2, m1, m2, normal, 1.0);
+        let energy = |a: Vec2, b: Vec2| m1 * a.length_squared() + m2 * b.length_squared();
+        assert!((energy(u1, u2) - energy(v1, v2)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn separating_bodies_are_left_alone() {
+        let (v1, v2) = (Vec2::new(-1.0, 0.0), Vec2::new(1.0, 0.0));
+        assert_eq!(
+            resolve_elastic(v1, v2, 1.0, 1.0, Vec2::new(1.0, 0.0), 1.0),
+            (v1, v2)
+        );
+    }
+}
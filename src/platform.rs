@@ -0,0 +1,92 @@
+//! Differences between the native build and the browser build of the
+//! examples, built from the manifest of web/ with the `wasm` feature.
+
+use bevy::prelude::*;
+#[cfg(target_arch = "wasm32")]
+use std::{cell::RefCell, collections::HashMap};
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+/// The default plugins.
+///
+/// Without a renderer in the browser, the examples of the browser build adding
+/// the WebGL2 one, a dependency of web/ only.
+pub struct PlatformPlugins;
+
+impl Plugin for PlatformPlugins {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_default_plugins();
+    }
+}
+
+/// Path of the data file `name`, next to the executable.
+///
+/// The bare name in the browser, where the files only live in memory.
+pub fn data_path(name: &str) -> io::Result<PathBuf> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let executable = std::env::current_exe()?;
+        Ok(executable.with_file_name(name))
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        Ok(PathBuf::from(name))
+    }
+}
+
+/// Whether the asset file at `path` exists, always assumed in the browser
+/// where the assets are only fetched when loaded.
+pub fn asset_exists(path: &str) -> bool {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        Path::new(path).exists()
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = path;
+        true
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+thread_local! {
+    /// Files written during the session, the browser having no file system
+    static FILES: RefCell<HashMap<PathBuf, Vec<u8>>> = RefCell::new(HashMap::new());
+}
+
+/// `std::fs::read_to_string`, reading from the files written during the
+/// session in the browser.
+pub fn read_to_string<P: AsRef<Path>>(path: P) -> io::Result<String> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::fs::read_to_string(path)
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        let contents = FILES
+            .with(|files| files.borrow().get(path.as_ref()).cloned())
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))?;
+        String::from_utf8(contents)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+}
+
+/// `std::fs::write`, only keeping the file in memory for the session in the
+/// browser.
+pub fn write<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> io::Result<()> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::fs::write(path, contents)
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        FILES.with(|files| {
+            files
+                .borrow_mut()
+                .insert(path.as_ref().to_path_buf(), contents.as_ref().to_vec())
+        });
+        Ok(())
+    }
+}
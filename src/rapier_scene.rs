@@ -0,0 +1,152 @@
+//! A grid of rapier balls drifting into each other, the scene of the launcher
+//! made of the balls of the rapier2d benchmark and stress grids.
+
+use crate::{
+    arena::Arena,
+    balls::{jittered_grid, wrap_system},
+    game_assets::GameAssets,
+    scene::{ActiveScene, Scene, SceneEntity},
+    showcase::SeededRng,
+    spawn::{capped_requests, MaxBodies, SpawnRequest},
+};
+use bevy::{
+    prelude::*,
+    render::camera::{OrthographicProjection, WindowOrigin},
+};
+use bevy_rapier2d::{
+    physics::{ColliderHandleComponent, RigidBodyHandleComponent},
+    rapier::{
+        dynamics::{RigidBodyBuilder, RigidBodySet},
+        geometry::{ColliderBuilder, ColliderSet},
+    },
+};
+
+/// The arena, in px, of the 1280x800 window of the examples
+const ARENA_WIDTH: f32 = 1280.0;
+const ARENA_HEIGHT: f32 = 800.0;
+pub const GRID_BALL_RADIUS: f32 = 10.0;
+const COLUMNS: usize = 16;
+const ROWS: usize = 10;
+/// Factor of the tiny velocities of the grid
+const SPEED: f32 = 20.0;
+
+pub const SCENE: Scene = Scene {
+    name: "Rapier balls",
+    setup,
+    teardown,
+};
+
+/// A small ball of the grids
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GridBall;
+
+/// Adds the grid ball spawn requests, their spawner and the wrapping of the
+/// scene bodies around the arena.
+pub struct RapierScenePlugin;
+
+impl Plugin for RapierScenePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_event::<SpawnRequest<GridBall>>()
+            .add_system(scene_wrap_system.system())
+            .add_system_to_stage(stage::POST_UPDATE, grid_ball_spawner_system.system());
+    }
+}
+
+/// The camera, and the grid of balls sent to the spawner.
+fn setup(commands: &mut Commands, resources: &mut Resources) {
+    let arena = Arena::new(ARENA_WIDTH, ARENA_HEIGHT);
+    commands
+        .spawn(Camera2dComponents {
+            orthographic_projection: OrthographicProjection {
+                window_origin: WindowOrigin::BottomLeft,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .with(SceneEntity);
+    let mut rng = resources.get_mut::<SeededRng>().unwrap();
+    let mut requests = resources
+        .get_mut::<Events<SpawnRequest<GridBall>>>()
+        .unwrap();
+    for (x, y, vx, vy) in jittered_grid(&mut rng.0, &arena, COLUMNS, ROWS) {
+        requests.send(SpawnRequest::new(
+            GridBall,
+            Vec2::new(x, y),
+            Vec2::new(vx, vy) * SPEED,
+        ));
+    }
+    commands.insert_resource(arena);
+}
+
+fn teardown(_world: &mut World, _resources: &mut Resources) {}
+
+fn scene_wrap_system(
+    active: Res<ActiveScene>,
+    arena: Res<Arena>,
+    bodies: ResMut<RigidBodySet>,
+    query: Query<&RigidBodyHandleComponent>,
+) {
+    if active.runs(&SCENE) {
+        wrap_system(arena, bodies, query);
+    }
+}
+
+/// Spawns a grid ball at `position`, the spawned entity being the current one
+/// of `commands`.
+///
+/// The body and collider are inserted directly in the sets, so that a frame
+/// spawning a whole grid covers their creation by rapier.
+pub fn spawn_grid_ball<'a>(
+    commands: &'a mut Commands,
+    bodies: &mut RigidBodySet,
+    colliders: &mut ColliderSet,
+    material: Handle<ColorMaterial>,
+    position: Vec2,
+    velocity: Vec2,
+) -> &'a mut Commands {
+    let (x, y) = (position.x(), position.y());
+    let body = RigidBodyBuilder::new_dynamic()
+        .translation(x, y)
+        .linvel(velocity.x(), velocity.y())
+        .build();
+    let body_handle = bodies.insert(body);
+    let collider = ColliderBuilder::ball(GRID_BALL_RADIUS)
+        .friction(-0.5)
+        .build();
+    let collider_handle = colliders.insert(collider, body_handle, bodies);
+    commands
+        .spawn(SpriteComponents {
+            transform: Transform::from_translation(Vec3::new(x, y, 0.5))
+                .with_scale(GRID_BALL_RADIUS / 128.0),
+            material,
+            ..Default::default()
+        })
+        .with(GridBall)
+        .with(RigidBodyHandleComponent::from(body_handle))
+        .with(ColliderHandleComponent::from(collider_handle))
+}
+
+/// Spawns the requested grid balls, in the order of the requests, up to the
+/// `MaxBodies` cap.
+pub fn grid_ball_spawner_system(
+    mut commands: Commands,
+    mut reader: Local<EventReader<SpawnRequest<GridBall>>>,
+    requests: Res<Events<SpawnRequest<GridBall>>>,
+    max_bodies: Res<MaxBodies>,
+    assets: Res<GameAssets>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut colliders: ResMut<ColliderSet>,
+) {
+    let alive = bodies.len();
+    for request in capped_requests(&mut reader, &requests, alive, &max_bodies) {
+        spawn_grid_ball(
+            &mut commands,
+            &mut bodies,
+            &mut colliders,
+            assets.sphere_material,
+            request.position,
+            request.velocity,
+        )
+        .with(SceneEntity);
+    }
+}
@@ -0,0 +1,494 @@
+//! Impulse based contact response of the bodies moved by hand with ncollide2d,
+//! on a fixed step.
+
+use crate::{arena::Arena, collide::Velocity, physics_math::wrap_position};
+use bevy::prelude::*;
+use ncollide2d::{
+    na::{self, Isometry2, Vector2},
+    pipeline::{CollisionObjectSlabHandle, CollisionWorld},
+};
+use std::collections::HashMap;
+
+/// Most steps run in a single frame, the late ones being dropped after a
+/// stall rather than slowing the next frames down further
+const MAX_STEPS_PER_FRAME: usize = 5;
+
+/// Mass of a body, `1.0` without it
+#[derive(Clone, Copy, Debug)]
+pub struct Mass(pub f32);
+
+/// Restitution of a body, `1.0` without it. A contact bounces with the
+/// smallest of both.
+#[derive(Clone, Copy, Debug)]
+pub struct Restitution(pub f32);
+
+/// Marks a body of infinite mass, e.g. a wall, a paddle or a pinned sphere.
+///
+/// It still moves along its `Velocity` if it has one, unaffected by contacts
+/// and gravity. A body without any `Velocity` is immovable too.
+pub struct Immovable;
+
+/// A contact solved during a step.
+#[derive(Clone, Copy, Debug)]
+pub struct ContactEvent {
+    pub entity1: Entity,
+    pub entity2: Entity,
+    /// Unit normal pointing from the first body towards the second
+    pub normal: Vec2,
+    /// Penetration depth before the positional correction
+    pub depth: f32,
+    /// Normal impulse applied to the second body, the opposite one to the first
+    pub impulse: f32,
+}
+
+/// Tuning of the contact response.
+#[derive(Clone, Copy, Debug)]
+pub struct ResponseConfig {
+    /// Fixed step, in s
+    pub step: f32,
+    /// Passes over the contacts of a step, more of them stacking better
+    pub iterations: usize,
+    /// Fraction of the penetration beyond `slop` removed at each step
+    pub correction: f32,
+    /// Penetration left uncorrected, keeping resting contacts alive
+    pub slop: f32,
+    pub gravity: Vec2,
+    /// Whether the bodies wrap around the arena edges
+    pub wrap: bool,
+}
+
+impl Default for ResponseConfig {
+    fn default() -> Self {
+        ResponseConfig {
+            step: 1.0 / 60.0,
+            iterations: 8,
+            correction: 0.8,
+            slop: 0.5,
+            gravity: Vec2::zero(),
+            wrap: false,
+        }
+    }
+}
+
+/// Owns the `CollisionWorld` of the bodies moved by hand, and moves them along
+/// their `Velocity` on a fixed step, resolving their contacts with impulses
+/// and sending a `ContactEvent` for each.
+///
+/// The examples spawn the bodies with a `Transform`, a collision object of
+/// the world, e.g. from `add_sphere`, and a `Velocity` unless immovable.
+#[derive(Default)]
+pub struct NCollideResponsePlugin {
+    config: ResponseConfig,
+}
+
+impl NCollideResponsePlugin {
+    pub fn with_step(mut self, step: f32) -> Self {
+        self.config.step = step;
+        self
+    }
+
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.config.iterations = iterations;
+        self
+    }
+
+    /// Fraction of the penetration removed at each step, between 0 and 1
+    pub fn with_correction(mut self, correction: f32) -> Self {
+        self.config.correction = correction;
+        self
+    }
+
+    pub fn with_slop(mut self, slop: f32) -> Self {
+        self.config.slop = slop;
+        self
+    }
+
+    pub fn with_gravity(mut self, gravity: Vec2) -> Self {
+        self.config.gravity = gravity;
+        self
+    }
+
+    /// Wraps the bodies around the `Arena` edges
+    pub fn wrapping(mut self) -> Self {
+        self.config.wrap = true;
+        self
+    }
+}
+
+impl Plugin for NCollideResponsePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_resource(CollisionWorld::<f32, Entity>::new(0.02))
+            .add_resource(NCollideResponse {
+                config: self.config,
+                accumulator: 0.0,
+            })
+            .add_event::<ContactEvent>()
+            .add_system(ncollide_response_system.system());
+    }
+}
+
+/// The tuning of the response, changeable while the example runs, and the
+/// time left over for the next steps.
+pub struct NCollideResponse {
+    pub config: ResponseConfig,
+    accumulator: f32,
+}
+
+/// A body during a step
+struct Body {
+    entity: Entity,
+    handle: CollisionObjectSlabHandle,
+    position: Vec2,
+    velocity: Vec2,
+    /// Zero for the immovable bodies
+    inverse_mass: f32,
+    restitution: f32,
+}
+
+/// A contact between bodies `i` and `j` during a step
+struct Contact {
+    i: usize,
+    j: usize,
+    normal: Vec2,
+    depth: f32,
+    /// Separating speed to reach along the normal
+    bounce: f32,
+    /// Accumulated normal impulse, never pulling the bodies together
+    impulse: f32,
+}
+
+fn sync_positions(world: &mut CollisionWorld<f32, Entity>, bodies: &[Body]) {
+    for body in bodies {
+        if let Some(object) = world.get_mut(body.handle) {
+            object.set_position(Isometry2::new(
+                Vector2::new(body.position.x(), body.position.y()),
+                na::zero(),
+            ));
+        }
+    }
+}
+
+/// Moves the `bodies` over one step, resolving their contacts.
+fn step(
+    config: &ResponseConfig,
+    arena: &Arena,
+    world: &mut CollisionWorld<f32, Entity>,
+    bodies: &mut [Body],
+    indices: &HashMap<Entity, usize>,
+    events: &mut Vec<ContactEvent>,
+) {
+    let dt = config.step;
+    for body in bodies.iter_mut() {
+        if body.inverse_mass > 0.0 {
+            body.velocity += config.gravity * dt;
+        }
+        body.position += body.velocity * dt;
+        if config.wrap {
+            if let Some(wrapped) = wrap_position(body.position, body.velocity, arena) {
+                body.position = wrapped;
+            }
+        }
+    }
+    sync_positions(world, bodies);
+    world.update();
+
+    // Slower contacts rest instead of bouncing, not to jitter under gravity
+    let resting_speed = 2.0 * config.gravity.length() * dt;
+    let mut contacts = Vec::new();
+    for (h1, h2, _, manifold) in world.contact_pairs(true) {
+        let contact = match manifold.deepest_contact() {
+            Some(tracked_contact) => tracked_contact.contact,
+            None => continue,
+        };
+        let index = |handle| {
+            let entity = world.collision_object(handle)?.data();
+            indices.get(entity).copied()
+        };
+        let (i, j) = match (index(h1), index(h2)) {
+            (Some(i), Some(j)) => (i, j),
+            _ => continue,
+        };
+        let (a, b) = (&bodies[i], &bodies[j]);
+        if a.inverse_mass + b.inverse_mass <= 0.0 {
+            continue;
+        }
+        let normal = contact.normal.into_inner();
+        let normal = Vec2::new(normal.x, normal.y);
+        let approach = (a.velocity - b.velocity).dot(normal);
+        let bounce = if approach > resting_speed {
+            a.restitution.min(b.restitution) * approach
+        } else {
+            0.0
+        };
+        contacts.push(Contact {
+            i,
+            j,
+            normal,
+            depth: contact.depth,
+            bounce,
+            impulse: 0.0,
+        });
+    }
+
+    for _ in 0..config.iterations {
+        for contact in &mut contacts {
+            let (a, b) = (&bodies[contact.i], &bodies[contact.j]);
+            let (inverse_a, inverse_b) = (a.inverse_mass, b.inverse_mass);
+            let separating = (b.velocity - a.velocity).dot(contact.normal);
+            let impulse = contact.impulse + (contact.bounce - separating) / (inverse_a + inverse_b);
+            let impulse = impulse.max(0.0);
+            let delta = contact.normal * (impulse - contact.impulse);
+            contact.impulse = impulse;
+            bodies[contact.i].velocity -= delta * inverse_a;
+            bodies[contact.j].velocity += delta * inverse_b;
+        }
+    }
+
+    // Split by inverse mass, an immovable body never being pushed
+    for contact in &contacts {
+        let (inverse_a, inverse_b) = (
+            bodies[contact.i].inverse_mass,
+            bodies[contact.j].inverse_mass,
+        );
+        let correction =
+            (contact.depth - config.slop).max(0.0) * config.correction / (inverse_a + inverse_b);
+        bodies[contact.i].position -= contact.normal * (correction * inverse_a);
+        bodies[contact.j].position += contact.normal * (correction * inverse_b);
+        events.push(ContactEvent {
+            entity1: bodies[contact.i].entity,
+            entity2: bodies[contact.j].entity,
+            normal: contact.normal,
+            depth: contact.depth,
+            impulse: contact.impulse,
+        });
+    }
+    sync_positions(world, bodies);
+}
+
+fn ncollide_response_system(
+    time: Res<Time>,
+    arena: Res<Arena>,
+    mut response: ResMut<NCollideResponse>,
+    mut world: ResMut<CollisionWorld<f32, Entity>>,
+    mut contact_events: ResMut<Events<ContactEvent>>,
+    mut query: Query<(
+        Entity,
+        Mut<Transform>,
+        &CollisionObjectSlabHandle,
+        Option<&Mass>,
+        Option<&Restitution>,
+        Option<&Immovable>,
+    )>,
+    mut velocities: Query<Mut<Velocity>>,
+) {
+    let config = response.config;
+    response.accumulator += time.delta_seconds;
+    let mut steps = (response.accumulator / config.step) as usize;
+    if steps > MAX_STEPS_PER_FRAME {
+        steps = MAX_STEPS_PER_FRAME;
+        response.accumulator = 0.0;
+    } else {
+        response.accumulator -= steps as f32 * config.step;
+    }
+    if steps == 0 {
+        return;
+    }
+
+    let mut bodies = Vec::new();
+    for (entity, transform, &handle, mass, restitution, immovable) in &mut query.iter() {
+        let velocity = velocities.get::<Velocity>(entity).ok().map(|v| v.0);
+        let inverse_mass = match (velocity, immovable) {
+            (Some(_), None) => 1.0 / mass.map_or(1.0, |mass| mass.0),
+            _ => 0.0,
+        };
+        let velocity = velocity.unwrap_or_else(Vector2::zeros);
+        bodies.push(Body {
+            entity,
+            handle,
+            position: transform.translation().truncate(),
+            velocity: Vec2::new(velocity.x, velocity.y),
+            inverse_mass,
+            restitution: restitution.map_or(1.0, |restitution| restitution.0),
+        });
+    }
+    let indices: HashMap<Entity, usize> = bodies
+        .iter()
+        .enumerate()
+        .map(|(index, body)| (body.entity, index))
+        .collect();
+    let mut events = Vec::new();
+    for _ in 0..steps {
+        step(
+            &config,
+            &arena,
+            &mut world,
+            &mut bodies,
+            &indices,
+            &mut events,
+        );
+    }
+
+    for (entity, mut transform, ..) in &mut query.iter() {
+        let body = &bodies[indices[&entity]];
+        if transform.translation().truncate() != body.position {
+            let translation = transform.translation_mut();
+            translation.set_x(body.position.x());
+            translation.set_y(body.position.y());
+        }
+        if let Ok(mut velocity) = velocities.get_mut::<Velocity>(entity) {
+            velocity.0 = Vector2::new(body.velocity.x(), body.velocity.y());
+        }
+    }
+    for event in events {
+        contact_events.send(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collide::{add_cuboid, add_sphere};
+    use ncollide2d::pipeline::CollisionGroups;
+    use std::iter::once;
+
+    const RADIUS: f32 = 1.0;
+
+    /// Bodies of `(position, velocity, inverse mass)` in a new world, the
+    /// first one being a floor if `floor`.
+    fn new_bodies(
+        specs: &[(Vec2, Vec2, f32)],
+        restitution: f32,
+        floor: bool,
+    ) -> (
+        CollisionWorld<f32, Entity>,
+        Vec<Body>,
+        HashMap<Entity, usize>,
+    ) {
+        let mut ecs = World::new();
+        let mut world = CollisionWorld::<f32, Entity>::new(0.02);
+        let bodies: Vec<Body> = specs
+            .iter()
+            .enumerate()
+            .map(|(index, &(position, velocity, inverse_mass))| {
+                let entity = ecs.spawn((Transform::default(),));
+                let handle = if floor && index == 0 {
+                    add_cuboid(
+                        &mut world,
+                        CollisionGroups::new(),
+                        entity,
+                        position,
+                        Vec2::new(10.0, 1.0),
+                    )
+                } else {
+                    add_sphere(&mut world, CollisionGroups::new(), entity, position, RADIUS)
+                };
+                Body {
+                    entity,
+                    handle,
+                    position,
+                    velocity,
+                    inverse_mass,
+                    restitution,
+                }
+            })
+            .collect();
+        let indices = bodies
+            .iter()
+            .enumerate()
+            .map(|(index, body)| (body.entity, index))
+            .collect();
+        (world, bodies, indices)
+    }
+
+    #[test]
+    fn a_stack_of_balls_comes_to_rest() {
+        let config = ResponseConfig {
+            iterations: 10,
+            slop: 0.01,
+            gravity: Vec2::new(0.0, -10.0),
+            ..Default::default()
+        };
+        let arena = Arena::centered(100.0, 100.0);
+        // A floor with its top at 0, then three balls just touching
+        let specs: Vec<(Vec2, Vec2, f32)> = once((Vec2::new(0.0, -1.0), Vec2::zero(), 0.0))
+            .chain((0..3).map(|k| {
+                let y = RADIUS + 2.0 * RADIUS * k as f32;
+                (Vec2::new(0.0, y), Vec2::zero(), 1.0)
+            }))
+            .collect();
+        let (mut world, mut bodies, indices) = new_bodies(&specs, 0.0, true);
+        let mut events = Vec::new();
+        for _ in 0..600 {
+            step(
+                &config,
+                &arena,
+                &mut world,
+                &mut bodies,
+                &indices,
+                &mut events,
+            );
+        }
+        assert_eq!(bodies[0].position, Vec2::new(0.0, -1.0));
+        for k in 1..4 {
+            let expected = RADIUS + 2.0 * RADIUS * (k - 1) as f32;
+            let body = &bodies[k];
+            assert!(
+                body.velocity.length() < 0.05,
+                "ball {} moves at {:?}",
+                k,
+                body.velocity
+            );
+            assert!(
+                (body.position.y() - expected).abs() < 0.05,
+                "ball {} at {:?} instead of {}",
+                k,
+                body.position,
+                expected
+            );
+        }
+        for k in 1..3 {
+            let gap = bodies[k + 1].position.y() - bodies[k].position.y();
+            assert!(
+                gap > 2.0 * RADIUS - 0.05,
+                "balls {} sink by {}",
+                k,
+                2.0 - gap
+            );
+        }
+    }
+
+    #[test]
+    fn equal_balls_swap_velocities_head_on() {
+        let config = ResponseConfig::default();
+        let arena = Arena::centered(100.0, 100.0);
+        let specs = [
+            (Vec2::new(-3.0, 0.0), Vec2::new(10.0, 0.0), 1.0),
+            (Vec2::new(3.0, 0.0), Vec2::new(-10.0, 0.0), 1.0),
+        ];
+        let (mut world, mut bodies, indices) = new_bodies(&specs, 1.0, false);
+        let mut events = Vec::new();
+        for _ in 0..60 {
+            step(
+                &config,
+                &arena,
+                &mut world,
+                &mut bodies,
+                &indices,
+                &mut events,
+            );
+        }
+        let close = |a: Vec2, b: Vec2| (a - b).length() < 1e-3;
+        assert!(
+            close(bodies[0].velocity, Vec2::new(-10.0, 0.0)),
+            "{:?}",
+            bodies[0].velocity
+        );
+        assert!(
+            close(bodies[1].velocity, Vec2::new(10.0, 0.0)),
+            "{:?}",
+            bodies[1].velocity
+        );
+        let impulse: f32 = events.iter().map(|event| event.impulse).sum();
+        assert!((impulse - 20.0).abs() < 1e-3, "impulse {}", impulse);
+    }
+}
@@ -0,0 +1,238 @@
+use crate::{
+    arena::Arena,
+    balls::{ball_builders, jittered_grid, wrap_system},
+    cleanup::RapierCleanupPlugin,
+    collide::{add_sphere, Velocity},
+    game_assets::GameAssets,
+    response::NCollideResponsePlugin,
+};
+use bevy::{app::DefaultTaskPoolOptions, prelude::*};
+use bevy_rapier2d::{
+    na::Vector2 as RapierVector2,
+    physics::{RapierConfiguration, RapierPhysicsPlugin, RigidBodyHandleComponent},
+    rapier::dynamics::RigidBodySet,
+};
+use ncollide2d::{na::Vector2, pipeline::CollisionGroups, world::CollisionWorld};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{f32::consts::PI, time::Duration};
+
+/// A ball of a scenario, the same for both physics backends
+#[derive(Clone, Copy, Debug)]
+pub struct ScenarioBall {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub radius: f32,
+}
+
+/// Radius of a scenario ball, for the measures
+pub struct Radius(pub f32);
+
+/// Balls on a jittered grid covering the arena, of random radii fitting in
+/// their cell and random velocities up to `max_speed` along each axis.
+pub fn scenario_balls(
+    seed: u64,
+    arena: &Arena,
+    columns: usize,
+    rows: usize,
+    max_speed: f32,
+) -> Vec<ScenarioBall> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let cell = (arena.width / columns as f32).min(arena.height / rows as f32);
+    // Within the cell half size, less the grid jitter
+    let max_radius = cell * 0.4 - 1.0;
+    jittered_grid(&mut rng, arena, columns, rows)
+        .into_iter()
+        .map(|(x, y, _, _)| ScenarioBall {
+            position: Vec2::new(x, y),
+            velocity: Vec2::new(
+                rng.gen_range(-max_speed, max_speed),
+                rng.gen_range(-max_speed, max_speed),
+            ),
+            radius: rng.gen_range(max_radius / 2.0, max_radius),
+        })
+        .collect()
+}
+
+/// An app with the task pools, the default `GameAssets` of the spawners and a
+/// time advancing by exactly `step` each update, the startup systems never
+/// running.
+pub fn headless_app(step: f32, arena: Arena, build: impl FnOnce(&mut AppBuilder)) -> App {
+    let mut app = App::build();
+    build(&mut app);
+    let mut app = app.app;
+    DefaultTaskPoolOptions::default().create_default_pools(&mut app.resources);
+    let mut time = Time::default();
+    time.delta = Duration::from_secs_f32(step);
+    time.delta_seconds = step;
+    time.delta_seconds_f64 = step as f64;
+    app.resources.insert(time);
+    app.resources.insert(arena);
+    app.resources.insert(GameAssets::default());
+    app
+}
+
+/// Physics path running a scenario
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Backend {
+    /// Collision queries with the `NCollideResponsePlugin`, as in the ncollide2d
+    /// example
+    Ncollide,
+    /// The rapier solver, with perfectly elastic balls
+    Rapier,
+}
+
+/// State of a scenario after some steps
+#[derive(Clone, Copy, Debug)]
+pub struct Measures {
+    pub kinetic_energy: f32,
+    pub max_penetration: f32,
+}
+
+impl Backend {
+    /// A headless app running the `balls` with this backend, one step at each
+    /// update.
+    pub fn app(self, balls: &[ScenarioBall], arena: Arena, step: f32) -> App {
+        match self {
+            Backend::Ncollide => {
+                let mut app = headless_app(step, arena, |app| {
+                    app.add_plugin(NCollideResponsePlugin::default().with_step(step).wrapping());
+                });
+                let mut groups = CollisionGroups::new();
+                groups.set_membership(&[1]);
+                for ball in balls {
+                    let entity = app.world.spawn((
+                        Transform::from_translation(ball.position.extend(0.0)),
+                        Velocity(Vector2::new(ball.velocity.x(), ball.velocity.y())),
+                        Radius(ball.radius),
+                    ));
+                    let handle = {
+                        let mut world = app
+                            .resources
+                            .get_mut::<CollisionWorld<f32, Entity>>()
+                            .unwrap();
+                        add_sphere(&mut world, groups, entity, ball.position, ball.radius)
+                    };
+                    app.world.insert_one(entity, handle).unwrap();
+                }
+                app
+            }
+            Backend::Rapier => {
+                let mut app = headless_app(step, arena, |app| {
+                    app.add_plugin(RapierPhysicsPlugin)
+                        .add_plugin(RapierCleanupPlugin)
+                        .add_resource(RapierConfiguration {
+                            gravity: RapierVector2::zeros(),
+                            ..Default::default()
+                        })
+                        .add_system(wrap_system.system());
+                });
+                for ball in balls {
+                    let (body, collider) =
+                        ball_builders(ball.position, ball.velocity, ball.radius, 1.0);
+                    app.world.spawn((
+                        Transform::from_translation(ball.position.extend(0.0)),
+                        body,
+                        collider,
+                        Radius(ball.radius),
+                    ));
+                }
+                app
+            }
+        }
+    }
+
+    /// Kinetic energy and deepest overlap of the balls of an `app` of this
+    /// backend.
+    pub fn measure(self, app: &App) -> Measures {
+        // Position, velocity and radius of each ball
+        let balls: Vec<(Vec2, Vec2, f32)> = match self {
+            Backend::Ncollide => app
+                .world
+                .query::<(&Transform, &Velocity, &Radius)>()
+                .iter()
+                .map(|(transform, velocity, radius)| {
+                    (
+                        transform.translation().truncate(),
+                        Vec2::new(velocity.0.x, velocity.0.y),
+                        radius.0,
+                    )
+                })
+                .collect(),
+            Backend::Rapier => {
+                let bodies = app.resources.get::<RigidBodySet>().unwrap();
+                app.world
+                    .query::<(&RigidBodyHandleComponent, &Radius)>()
+                    .iter()
+                    .filter_map(|(body_handle, radius)| {
+                        let body = bodies.get(body_handle.handle())?;
+                        let translation = body.position.translation.vector;
+                        Some((
+                            Vec2::new(translation.x, translation.y),
+                            Vec2::new(body.linvel.x, body.linvel.y),
+                            radius.0,
+                        ))
+                    })
+                    .collect()
+            }
+        };
+        Measures {
+            kinetic_energy: kinetic_energy(balls.iter().map(|&(_, v, r)| (v, r))),
+            max_penetration: max_penetration(
+                &balls.iter().map(|&(p, _, r)| (p, r)).collect::<Vec<_>>(),
+            ),
+        }
+    }
+}
+
+/// Translational kinetic energy of discs of unit density, from their velocity
+/// and radius.
+pub fn kinetic_energy(balls: impl IntoIterator<Item = (Vec2, f32)>) -> f32 {
+    balls
+        .into_iter()
+        .map(|(velocity, radius)| 0.5 * PI * radius * radius * velocity.length_squared())
+        .sum()
+}
+
+/// Deepest overlap between two of the discs, from their center and radius,
+/// `0.0` if none overlap.
+pub fn max_penetration(balls: &[(Vec2, f32)]) -> f32 {
+    let mut max: f32 = 0.0;
+    for (i, &(center1, radius1)) in balls.iter().enumerate() {
+        for &(center2, radius2) in &balls[i + 1..] {
+            max = max.max(radius1 + radius2 - (center1 - center2).length());
+        }
+    }
+    max
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kinetic_energy_sums_the_balls() {
+        let energy = kinetic_energy(vec![(Vec2::new(3.0, 4.0), 1.0), (Vec2::new(0.0, 2.0), 2.0)]);
+        assert!((energy - (12.5 * PI + 8.0 * PI)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn max_penetration_is_the_deepest_overlap() {
+        let balls = [
+            (Vec2::new(0.0, 0.0), 1.0),
+            (Vec2::new(1.5, 0.0), 1.0),
+            (Vec2::new(10.0, 0.0), 2.0),
+            (Vec2::new(10.0, 3.0), 2.0),
+        ];
+        assert!((max_penetration(&balls) - 1.0).abs() < 1e-5);
+        assert_eq!(max_penetration(&balls[1..3]), 0.0);
+    }
+
+    #[test]
+    fn scenario_balls_do_not_overlap() {
+        let arena = Arena::new(1280.0, 800.0);
+        let balls = scenario_balls(214, &arena, 25, 20, 100.0);
+        assert_eq!(balls.len(), 500);
+        let discs: Vec<_> = balls.iter().map(|b| (b.position, b.radius)).collect();
+        assert_eq!(max_penetration(&discs), 0.0);
+    }
+}
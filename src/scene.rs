@@ -0,0 +1,372 @@
+//! Scenes set up and torn down within a single running app, for the launcher.
+//!
+//! bevy 0.2 has neither states nor a way to remove a plugin, so the systems of
+//! every scene stay registered and return early unless their scene is the
+//! active one, checked with `ActiveScene::runs`.
+//!
+//! The examples of a scene run it alone, started with `ScenePlugin::with_start`.
+
+use bevy::{prelude::*, render::camera::ActiveCameras};
+use bevy_rapier2d::rapier::{dynamics::RigidBodySet, geometry::ColliderSet};
+use ncollide2d::{pipeline::CollisionObjectSlabHandle, world::CollisionWorld};
+use std::collections::HashSet;
+
+/// Index of a scene, in the order of `ScenePlugin::with_scene`
+pub type SceneId = usize;
+
+/// Marks the entities of a scene, despawned with their children when the scene
+/// is torn down.
+///
+/// The scene systems spawning entities while it runs must mark them too.
+pub struct SceneEntity;
+
+/// A scene of the launcher.
+#[derive(Clone, Copy)]
+pub struct Scene {
+    pub name: &'static str,
+    /// Spawns the entities of the scene and inserts its resources
+    pub setup: fn(&mut Commands, &mut Resources),
+    /// Resets the resources of the scene, before its entities are despawned.
+    ///
+    /// The `SceneEntity` entities, their collision objects and the
+    /// `SceneMaterials` are removed without it.
+    pub teardown: fn(&mut World, &mut Resources),
+}
+
+/// Adds the `ActiveScene` and the scene switches, starting without any scene
+/// unless given one with `with_start`.
+#[derive(Default)]
+pub struct ScenePlugin {
+    scenes: Vec<Scene>,
+    start: Option<SceneId>,
+}
+
+impl ScenePlugin {
+    pub fn with_scene(mut self, scene: Scene) -> Self {
+        self.scenes.push(scene);
+        self
+    }
+
+    /// Starts scene `id` right away, e.g. for an example running a single scene
+    pub fn with_start(mut self, id: SceneId) -> Self {
+        self.start = Some(id);
+        self
+    }
+}
+
+impl Plugin for ScenePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let mut active = ActiveScene::new(self.scenes.clone());
+        if let Some(id) = self.start {
+            active.start(id);
+        }
+        app.add_resource(active)
+            .init_resource::<SceneMaterials>()
+            // Before the scene systems of the update stage
+            .add_system_to_stage(stage::PRE_UPDATE, scene_switch_system.thread_local_system());
+    }
+}
+
+/// The scene running, and the switch to another one.
+///
+/// A switch tears the current scene down right away, and sets the next one up
+/// on the following frame, once the rapier bodies of the despawned entities
+/// are removed. The census of what is left is compared in between with the
+/// one before the first scene, any difference being a leak of the scene left.
+pub struct ActiveScene {
+    scenes: Vec<Scene>,
+    current: Option<SceneId>,
+    requested: Option<Option<SceneId>>,
+    incoming: Option<SceneId>,
+    left: Option<SceneId>,
+    baseline: Option<Census>,
+}
+
+impl ActiveScene {
+    fn new(scenes: Vec<Scene>) -> Self {
+        ActiveScene {
+            scenes,
+            current: None,
+            requested: None,
+            incoming: None,
+            left: None,
+            baseline: None,
+        }
+    }
+
+    /// Whether scene `id` runs
+    pub fn is(&self, id: SceneId) -> bool {
+        self.current == Some(id)
+    }
+
+    /// Whether `scene` runs, the guard of its systems, which know their scene
+    /// but not the id it was given.
+    pub fn runs(&self, scene: &Scene) -> bool {
+        self.current
+            .map_or(false, |id| self.scenes[id].name == scene.name)
+    }
+
+    pub fn current(&self) -> Option<SceneId> {
+        self.current
+    }
+
+    /// Whether a switch is under way, the current scene being already gone
+    pub fn switching(&self) -> bool {
+        self.requested.is_some() || self.incoming.is_some()
+    }
+
+    /// Names of the scenes, by id
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.scenes.iter().map(|scene| scene.name)
+    }
+
+    /// Switches to scene `id`
+    pub fn start(&mut self, id: SceneId) {
+        debug_assert!(id < self.scenes.len(), "Unknown scene {}", id);
+        self.requested = Some(Some(id));
+    }
+
+    /// Tears the current scene down, without any next one
+    pub fn stop(&mut self) {
+        self.requested = Some(None);
+    }
+}
+
+/// Materials of the active scene, removed with it.
+///
+/// The materials shared through `GameAssets` are kept, being only created once.
+#[derive(Default)]
+pub struct SceneMaterials(Vec<Handle<ColorMaterial>>);
+
+impl SceneMaterials {
+    pub fn add(
+        &mut self,
+        material: ColorMaterial,
+        materials: &mut Assets<ColorMaterial>,
+    ) -> Handle<ColorMaterial> {
+        let handle = materials.add(material);
+        self.0.push(handle);
+        handle
+    }
+}
+
+/// What the scenes may leave behind.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Census {
+    pub entities: usize,
+    pub bodies: usize,
+    pub colliders: usize,
+    /// Objects of the ncollide2d `CollisionWorld`
+    pub collision_objects: usize,
+    pub materials: usize,
+}
+
+impl Census {
+    pub fn take(world: &World, resources: &Resources) -> Self {
+        Census {
+            entities: world.query::<Entity>().iter().count(),
+            bodies: resources
+                .get::<RigidBodySet>()
+                .map_or(0, |bodies| bodies.len()),
+            colliders: resources
+                .get::<ColliderSet>()
+                .map_or(0, |colliders| colliders.len()),
+            collision_objects: resources
+                .get::<CollisionWorld<f32, Entity>>()
+                .map_or(0, |world| world.collision_objects().count()),
+            materials: resources
+                .get::<Assets<ColorMaterial>>()
+                .map_or(0, |materials| materials.iter().count()),
+        }
+    }
+
+    /// What this census has more than the `baseline`, e.g. "3 bodies"
+    pub fn leaks(&self, baseline: &Census) -> Vec<String> {
+        let counts = [
+            (self.entities, baseline.entities, "entities"),
+            (self.bodies, baseline.bodies, "bodies"),
+            (self.colliders, baseline.colliders, "colliders"),
+            (
+                self.collision_objects,
+                baseline.collision_objects,
+                "collision objects",
+            ),
+            (self.materials, baseline.materials, "materials"),
+        ];
+        counts
+            .iter()
+            .filter(|(count, baseline, _)| count > baseline)
+            .map(|(count, baseline, name)| format!("{} {}", count - baseline, name))
+            .collect()
+    }
+}
+
+/// The `SceneEntity` entities and all their descendants.
+fn scene_entities(world: &World) -> Vec<Entity> {
+    let mut entities: Vec<Entity> = world.query::<With<SceneEntity, Entity>>().iter().collect();
+    let mut seen: HashSet<Entity> = entities.iter().copied().collect();
+    let mut next = 0;
+    while next < entities.len() {
+        if let Ok(children) = world.get::<Children>(entities[next]) {
+            for &child in children.iter() {
+                if seen.insert(child) {
+                    entities.push(child);
+                }
+            }
+        }
+        next += 1;
+    }
+    entities
+}
+
+/// Despawns the scene entities, removing their collision objects from the
+/// `CollisionWorld` with them, and the scene materials.
+///
+/// The cameras of the scene are no longer active, for the camera of the next
+/// scene to be picked instead. The rapier bodies are removed by the
+/// `RapierCleanupPlugin`.
+fn despawn_scene(world: &mut World, resources: &mut Resources) {
+    let entities = scene_entities(world);
+    let mut collision_world = resources.get_mut::<CollisionWorld<f32, Entity>>();
+    let mut active_cameras = resources.get_mut::<ActiveCameras>();
+    for entity in entities {
+        if let (Some(collision_world), Ok(handle)) = (
+            collision_world.as_deref_mut(),
+            world.get::<CollisionObjectSlabHandle>(entity),
+        ) {
+            collision_world.remove(&[*handle]);
+        }
+        if let (Some(active_cameras), Ok(camera)) =
+            (active_cameras.as_deref_mut(), world.get::<Camera>(entity))
+        {
+            if let Some(name) = &camera.name {
+                active_cameras.add(name);
+            }
+        }
+        // A child despawned by the scene may still be listed by its parent
+        world.despawn(entity).ok();
+    }
+    let handles = std::mem::take(&mut resources.get_mut::<SceneMaterials>().unwrap().0);
+    if let Some(mut materials) = resources.get_mut::<Assets<ColorMaterial>>() {
+        for handle in handles {
+            materials.remove(&handle);
+        }
+    }
+}
+
+/// Tears the current scene down on a switch, then sets the next one up on the
+/// following frame.
+fn scene_switch_system(world: &mut World, resources: &mut Resources) {
+    let (incoming, left, baseline) = {
+        let mut active = resources.get_mut::<ActiveScene>().unwrap();
+        if active.left.is_none() && active.incoming.is_none() && active.requested.is_none() {
+            return;
+        }
+        (active.incoming.take(), active.left.take(), active.baseline)
+    };
+    // Checked on every switch, the first one taking the baseline
+    if incoming.is_some() || left.is_some() {
+        let census = Census::take(world, resources);
+        let mut active = resources.get_mut::<ActiveScene>().unwrap();
+        match baseline {
+            None => active.baseline = Some(census),
+            Some(baseline) => {
+                let leaks = census.leaks(&baseline);
+                if let (Some(left), false) = (left, leaks.is_empty()) {
+                    println!(
+                        "The {} scene leaked {}",
+                        active.scenes[left].name,
+                        leaks.join(", ")
+                    );
+                }
+            }
+        }
+    }
+    if let Some(id) = incoming {
+        let setup = resources.get::<ActiveScene>().unwrap().scenes[id].setup;
+        let mut commands = Commands::default();
+        setup(&mut commands, resources);
+        commands.apply(world, resources);
+        resources.get_mut::<ActiveScene>().unwrap().current = Some(id);
+    }
+
+    let (requested, current) = {
+        let mut active = resources.get_mut::<ActiveScene>().unwrap();
+        match active.requested.take() {
+            Some(requested) => (requested, active.current.take()),
+            None => return,
+        }
+    };
+    if let Some(current) = current {
+        let teardown = resources.get::<ActiveScene>().unwrap().scenes[current].teardown;
+        teardown(world, resources);
+        despawn_scene(world, resources);
+    }
+    let mut active = resources.get_mut::<ActiveScene>().unwrap();
+    active.left = current;
+    active.incoming = requested;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Node;
+
+    fn setup_nodes(commands: &mut Commands, resources: &mut Resources) {
+        let material = {
+            let mut materials = resources.get_mut::<Assets<ColorMaterial>>().unwrap();
+            let mut scene_materials = resources.get_mut::<SceneMaterials>().unwrap();
+            scene_materials.add(Color::WHITE.into(), &mut materials)
+        };
+        commands
+            .spawn((Node, material, SceneEntity))
+            .with_children(|parent| {
+                // Not marked, despawned as children
+                parent.spawn((Node,)).spawn((Node,));
+            });
+    }
+
+    fn no_teardown(_world: &mut World, _resources: &mut Resources) {}
+
+    #[test]
+    fn switching_scenes_leaves_nothing_behind() {
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        resources.insert(Assets::<ColorMaterial>::default());
+        resources.insert(SceneMaterials::default());
+        let scene = Scene {
+            name: "Nodes",
+            setup: setup_nodes,
+            teardown: no_teardown,
+        };
+        resources.insert(ActiveScene::new(vec![scene, scene]));
+        // A persistent entity, like a camera
+        world.spawn((Transform::default(),));
+        let baseline = Census::take(&world, &resources);
+
+        for &id in &[0, 1, 0] {
+            resources.get_mut::<ActiveScene>().unwrap().start(id);
+            scene_switch_system(&mut world, &mut resources);
+            assert_eq!(resources.get::<ActiveScene>().unwrap().current(), None);
+            assert_eq!(Census::take(&world, &resources), baseline);
+            scene_switch_system(&mut world, &mut resources);
+            assert!(resources.get::<ActiveScene>().unwrap().is(id));
+            assert!(resources.get::<ActiveScene>().unwrap().runs(&scene));
+            let census = Census::take(&world, &resources);
+            assert_eq!(
+                census.leaks(&baseline),
+                vec!["3 entities".to_string(), "1 materials".to_string()]
+            );
+        }
+        resources.get_mut::<ActiveScene>().unwrap().stop();
+        scene_switch_system(&mut world, &mut resources);
+        scene_switch_system(&mut world, &mut resources);
+        assert_eq!(resources.get::<ActiveScene>().unwrap().current(), None);
+        assert_eq!(Census::take(&world, &resources), baseline);
+        assert_eq!(
+            resources.get::<ActiveScene>().unwrap().baseline,
+            Some(baseline)
+        );
+    }
+}
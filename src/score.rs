@@ -0,0 +1,165 @@
+//! Score, lives and game state of the arcade examples, and the score texts
+//! showing them.
+
+use bevy::prelude::*;
+
+/// Where the game is, each example going through the states it needs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameState {
+    /// Choosing the ship, before the first game
+    Menu,
+    /// Waiting for the ball to be served
+    Serving,
+    Playing,
+    GameOver,
+    /// All the bricks are broken
+    Cleared,
+}
+
+/// Points and lives of a player
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Score {
+    pub points: u32,
+    pub lives: u32,
+}
+
+impl Score {
+    pub fn new(lives: u32) -> Self {
+        Score { points: 0, lives }
+    }
+
+    /// Adds `points`, with a life every time the points cross a multiple of
+    /// `extra_life` if any, returning the number of lives granted.
+    pub fn add(&mut self, points: u32, extra_life: Option<u32>) -> u32 {
+        let previous = self.points;
+        self.points += points;
+        let extra_lives = extra_life.map_or(0, |every| self.points / every - previous / every);
+        self.lives += extra_lives;
+        extra_lives
+    }
+
+    /// Loses a life, returning whether any is left.
+    pub fn lose_life(&mut self) -> bool {
+        self.lives = self.lives.saturating_sub(1);
+        self.lives > 0
+    }
+}
+
+/// The scores of the players who joined, shown by the `score_text_system`.
+pub trait Scoreboard: Send + Sync + 'static {
+    fn player_count(&self) -> usize;
+
+    /// `None` for a player who did not join
+    fn score(&self, player: usize) -> Option<Score>;
+}
+
+/// A single player game
+impl Scoreboard for Score {
+    fn player_count(&self) -> usize {
+        1
+    }
+
+    fn score(&self, player: usize) -> Option<Score> {
+        Some(*self).filter(|_| player == 0)
+    }
+}
+
+/// Text showing the score of `player`, and its lives with `show_lives`
+pub struct ScoreText {
+    pub player: usize,
+    pub show_lives: bool,
+    /// Score currently shown
+    shown: Option<Score>,
+    /// Runs while the text flashes for an extra life
+    flash: Timer,
+}
+
+impl ScoreText {
+    /// The text of `player`, flashing for `flash_duration` s on an extra life.
+    pub fn new(player: usize, show_lives: bool, flash_duration: f32) -> Self {
+        ScoreText {
+            player,
+            show_lives,
+            shown: None,
+            flash: Timer {
+                finished: true,
+                ..Timer::from_seconds(flash_duration, false)
+            },
+        }
+    }
+
+    /// The text of `score`, numbered by player when more than one joined.
+    fn label(&self, score: Score, player_count: usize) -> String {
+        let mut label = if player_count > 1 {
+            format!("P{} Score: {}", self.player + 1, score.points)
+        } else {
+            format!("Score: {}", score.points)
+        };
+        if self.show_lives {
+            label.push_str(&format!("  Lives: {}", score.lives));
+        }
+        label
+    }
+}
+
+/// Updates the score texts when the scores of the `B` board change, flashing
+/// them when an extra life is granted, the lives going up with the points.
+pub fn score_text_system<B: Scoreboard>(
+    time: Res<Time>,
+    board: Res<B>,
+    mut query: Query<(Mut<ScoreText>, Mut<Text>)>,
+) {
+    for (mut score_text, mut text) in &mut query.iter() {
+        let score = board.score(score_text.player);
+        if score_text.shown != score {
+            text.value = score.map_or_else(String::new, |score| {
+                score_text.label(score, board.player_count())
+            });
+            if let (Some(score), Some(shown)) = (score, score_text.shown) {
+                if score.lives > shown.lives && score.points > shown.points {
+                    score_text.flash.reset();
+                }
+            }
+            score_text.shown = score;
+        }
+        if !score_text.flash.finished {
+            score_text.flash.tick(time.delta_seconds);
+            let on = !score_text.flash.finished && (score_text.flash.elapsed * 8.0) as u32 % 2 == 0;
+            text.style.color = if on {
+                Color::rgb(1.0, 0.85, 0.2)
+            } else {
+                Color::WHITE
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extra_life_on_each_multiple() {
+        let mut score = Score::new(3);
+        assert_eq!(score.add(9_000, Some(10_000)), 0);
+        assert_eq!(score.add(12_000, Some(10_000)), 2);
+        assert_eq!(
+            score,
+            Score {
+                points: 21_000,
+                lives: 5
+            }
+        );
+        assert_eq!(score.add(50_000, None), 0);
+        assert_eq!(score.lives, 5);
+    }
+
+    #[test]
+    fn out_of_lives_at_zero() {
+        let mut score = Score::new(2);
+        assert!(score.lose_life());
+        assert!(!score.lose_life());
+        assert!(!score.lose_life());
+        assert_eq!(score.lives, 0);
+    }
+}
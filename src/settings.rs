@@ -0,0 +1,230 @@
+//! Live options of the running example, shown in the window title and given
+//! their initial values on the command line.
+
+use bevy::prelude::*;
+use std::fmt;
+
+/// Value of a setting
+#[derive(Clone, Debug, PartialEq)]
+pub enum SettingValue {
+    Bool(bool),
+    Number(f32),
+    Count(usize),
+    Text(String),
+}
+
+impl fmt::Display for SettingValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SettingValue::Bool(true) => write!(f, "on"),
+            SettingValue::Bool(false) => write!(f, "off"),
+            SettingValue::Number(value) => write!(f, "{}", value),
+            SettingValue::Count(value) => write!(f, "{}", value),
+            SettingValue::Text(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// The types a setting can be registered with.
+pub trait Setting: Into<SettingValue> {
+    /// Value of the flag `--name`, given `arg` after it if any, `None` if it
+    /// is not valid.
+    fn from_arg(arg: Option<&str>) -> Option<Self>;
+}
+
+impl From<bool> for SettingValue {
+    fn from(value: bool) -> Self {
+        SettingValue::Bool(value)
+    }
+}
+
+impl Setting for bool {
+    /// `--walls` alone is on, `--walls off` is off
+    fn from_arg(arg: Option<&str>) -> Option<Self> {
+        match arg {
+            None | Some("on") | Some("true") => Some(true),
+            Some("off") | Some("false") => Some(false),
+            // The next flag
+            Some(arg) if arg.starts_with("--") => Some(true),
+            Some(_) => None,
+        }
+    }
+}
+
+impl From<f32> for SettingValue {
+    fn from(value: f32) -> Self {
+        SettingValue::Number(value)
+    }
+}
+
+impl Setting for f32 {
+    fn from_arg(arg: Option<&str>) -> Option<Self> {
+        arg?.parse().ok()
+    }
+}
+
+impl From<usize> for SettingValue {
+    fn from(value: usize) -> Self {
+        SettingValue::Count(value)
+    }
+}
+
+impl Setting for usize {
+    fn from_arg(arg: Option<&str>) -> Option<Self> {
+        arg?.parse().ok()
+    }
+}
+
+impl From<String> for SettingValue {
+    fn from(value: String) -> Self {
+        SettingValue::Text(value)
+    }
+}
+
+impl Setting for String {
+    fn from_arg(arg: Option<&str>) -> Option<Self> {
+        arg.filter(|arg| !arg.starts_with("--"))
+            .map(|arg| arg.to_string())
+    }
+}
+
+/// The live options of the running example, each registered by the example
+/// and updated by the systems changing them.
+///
+/// The window title lists them as `name:value` after the example title,
+/// rebuilt whenever one changes.
+pub struct Settings {
+    title: String,
+    args: Vec<String>,
+    entries: Vec<(&'static str, SettingValue)>,
+    changed: bool,
+}
+
+impl Settings {
+    /// Settings of the example of window `title`, given on its command line
+    pub fn new(title: &str) -> Self {
+        Settings::with_args(title, std::env::args().skip(1).collect())
+    }
+
+    fn with_args(title: &str, args: Vec<String>) -> Self {
+        Settings {
+            title: title.to_string(),
+            args,
+            entries: Vec::new(),
+            changed: true,
+        }
+    }
+
+    /// Adds the setting `name`, starting at `value` unless given on the command
+    /// line as `--name [value]`, and returns its initial value.
+    pub fn register<T: Setting + Clone>(&mut self, name: &'static str, value: T) -> T {
+        let flag = format!("--{}", name);
+        let value = match self.args.iter().position(|arg| *arg == flag) {
+            Some(index) => {
+                let arg = self.args.get(index + 1).map(|arg| arg.as_str());
+                T::from_arg(arg).unwrap_or_else(|| {
+                    println!("Invalid value {:?} of {}, ignored", arg.unwrap_or(""), flag);
+                    value
+                })
+            }
+            None => value,
+        };
+        match self.entries.iter_mut().find(|(entry, _)| *entry == name) {
+            Some((_, entry)) => *entry = value.clone().into(),
+            None => self.entries.push((name, value.clone().into())),
+        }
+        self.changed = true;
+        value
+    }
+
+    /// Changes the registered setting `name`.
+    pub fn set(&mut self, name: &str, value: impl Into<SettingValue>) {
+        let value = value.into();
+        match self.entries.iter_mut().find(|(entry, _)| *entry == name) {
+            Some((_, entry)) => {
+                if *entry != value {
+                    *entry = value;
+                    self.changed = true;
+                }
+            }
+            None => debug_assert!(false, "Unregistered setting {}", name),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SettingValue> {
+        self.entries
+            .iter()
+            .find(|(entry, _)| *entry == name)
+            .map(|(_, value)| value)
+    }
+
+    /// The window title, e.g. "Rapier2D showcase — walls:on gravity:down"
+    pub fn title(&self) -> String {
+        if self.entries.is_empty() {
+            return self.title.clone();
+        }
+        let entries: Vec<String> = self
+            .entries
+            .iter()
+            .map(|(name, value)| format!("{}:{}", name, value))
+            .collect();
+        format!("{} — {}", self.title, entries.join(" "))
+    }
+}
+
+/// Rebuilds the title of the primary window when a setting changed.
+pub fn settings_title_system(mut settings: ResMut<Settings>, mut windows: ResMut<Windows>) {
+    if !settings.changed {
+        return;
+    }
+    if let Some(window) = windows.get_primary_mut() {
+        window.set_title(settings.title());
+        settings.changed = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn flags_set_the_initial_values() {
+        let mut settings = Settings::with_args(
+            "Showcase",
+            args(&["--walls", "--gravity", "down", "--restitution", "0.9"]),
+        );
+        assert!(settings.register("walls", false));
+        assert_eq!(settings.register("gravity", "off".to_string()), "down");
+        assert_eq!(settings.register("restitution", 0.0f32), 0.9);
+        assert_eq!(settings.register("bodies", 0usize), 0);
+        settings.set("bodies", 213usize);
+        assert_eq!(
+            settings.title(),
+            "Showcase — walls:on gravity:down restitution:0.9 bodies:213"
+        );
+    }
+
+    #[test]
+    fn invalid_flags_keep_the_default() {
+        let mut settings =
+            Settings::with_args("Showcase", args(&["--restitution", "--walls", "maybe"]));
+        assert_eq!(settings.register("restitution", 0.5f32), 0.5);
+        assert!(!settings.register("walls", false));
+    }
+
+    #[test]
+    fn only_changes_rebuild_the_title() {
+        let mut settings = Settings::with_args("Showcase", Vec::new());
+        settings.register("walls", true);
+        settings.changed = false;
+        settings.set("walls", true);
+        assert!(!settings.changed);
+        settings.set("walls", false);
+        assert!(settings.changed);
+        assert_eq!(settings.get("walls"), Some(&SettingValue::Bool(false)));
+    }
+}
@@ -0,0 +1,124 @@
+//! The setup shared by the examples, in a single plugin.
+
+use crate::{
+    arena::Arena,
+    debug_hud::DebugHudPlugin,
+    game_assets::GameAssetsPlugin,
+    mouse::MousePositionPlugin,
+    platform::PlatformPlugins,
+    settings::{settings_title_system, Settings},
+};
+use bevy::{prelude::*, render::pass::ClearColor};
+use rand::{rngs::StdRng, SeedableRng};
+
+/// Seeded random generator shared by all systems, so that runs are reproducible
+pub struct SeededRng(pub StdRng);
+
+/// The setup common to the examples: the window, the clear color, the default
+/// plugins, the mouse position, the debug HUD, the game assets, the settings
+/// shown in the window title and an arena covering the window.
+///
+/// The window must be known before the default plugins are added, so this
+/// plugin adds them itself and must come first.
+pub struct ShowcasePlugin {
+    title: String,
+    width: u32,
+    height: u32,
+    clear_color: Option<Color>,
+    msaa: Option<u32>,
+    seed: Option<u64>,
+    default_plugins: bool,
+    mouse_position: bool,
+    debug_hud: bool,
+}
+
+impl ShowcasePlugin {
+    pub fn new(title: &str) -> Self {
+        ShowcasePlugin {
+            title: title.to_string(),
+            width: 1280,
+            height: 800,
+            clear_color: None,
+            msaa: None,
+            seed: None,
+            default_plugins: true,
+            mouse_position: true,
+            debug_hud: true,
+        }
+    }
+
+    /// Window size in px, also the arena size
+    pub fn with_window(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn with_clear_color(mut self, color: Color) -> Self {
+        self.clear_color = Some(color);
+        self
+    }
+
+    /// Multisample anti-aliasing with this number of samples
+    pub fn with_msaa(mut self, samples: u32) -> Self {
+        self.msaa = Some(samples);
+        self
+    }
+
+    /// Inserts a `SeededRng` seeded with `seed`
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// For the examples adding their own selection of the default plugins,
+    /// right after this one.
+    pub fn without_default_plugins(mut self) -> Self {
+        self.default_plugins = false;
+        self
+    }
+
+    pub fn without_mouse_position(mut self) -> Self {
+        self.mouse_position = false;
+        self
+    }
+
+    pub fn without_debug_hud(mut self) -> Self {
+        self.debug_hud = false;
+        self
+    }
+}
+
+impl Plugin for ShowcasePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // Read by the window plugin when added
+        app.add_resource(WindowDescriptor {
+            title: self.title.clone(),
+            width: self.width,
+            height: self.height,
+            ..Default::default()
+        })
+        .add_resource(Arena::new(self.width as f32, self.height as f32))
+        .add_resource(Settings::new(&self.title))
+        .add_system_to_stage(stage::POST_UPDATE, settings_title_system.system());
+        if let Some(color) = self.clear_color {
+            app.add_resource(ClearColor(color));
+        }
+        if let Some(samples) = self.msaa {
+            app.add_resource(Msaa { samples });
+        }
+        if let Some(seed) = self.seed {
+            app.add_resource(SeededRng(StdRng::seed_from_u64(seed)));
+        }
+        if self.default_plugins {
+            app.add_plugin(PlatformPlugins);
+        }
+        if self.mouse_position {
+            app.add_plugin(MousePositionPlugin);
+        }
+        if self.debug_hud {
+            app.add_plugin(DebugHudPlugin);
+        }
+        app.add_plugin(GameAssetsPlugin);
+    }
+}
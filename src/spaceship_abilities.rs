@@ -0,0 +1,256 @@
+//! The hyperspace jump, dash and bullet time of the ships of spaceship_01.
+
+use crate::{damping::Damping, spaceship_controls::ShipControls};
+use bevy::prelude::*;
+use bevy_rapier2d::{
+    na::Vector2,
+    physics::RigidBodyHandleComponent,
+    rapier::dynamics::{IntegrationParameters, RigidBodySet},
+};
+use rand::Rng;
+
+/// Delay in s between two hyperspace jumps
+pub const HYPERSPACE_COOLDOWN: f32 = 3.0;
+/// Minimum free space between the ship and asteroids after a hyperspace jump
+pub const HYPERSPACE_CLEARANCE: f32 = 3.0;
+/// Random destinations tried by a hyperspace jump before giving up
+const HYPERSPACE_TRIES: usize = 10;
+/// Velocity change in m/s of a dash, along the ship facing
+pub const DASH_SPEED: f32 = 30.0;
+/// Duration in s of a dash, during which asteroids do no damage to the ship
+pub const DASH_WINDOW: f32 = 0.2;
+/// Delay in s between two dashes
+pub const DASH_COOLDOWN: f32 = 3.0;
+/// Speed of the world during bullet time, relative to real time
+pub const BULLET_TIME_SCALE: f32 = 0.3;
+/// Bullet time energy used per s while active, out of 1
+pub const BULLET_TIME_DRAIN: f32 = 0.25;
+/// Bullet time energy recovered per s while inactive
+pub const BULLET_TIME_RECHARGE: f32 = 0.05;
+
+pub struct Hyperspace {
+    /// Finished when the jump is available
+    pub cooldown: Timer,
+}
+
+impl Default for Hyperspace {
+    fn default() -> Self {
+        Hyperspace {
+            cooldown: Timer {
+                finished: true,
+                ..Timer::from_seconds(HYPERSPACE_COOLDOWN, false)
+            },
+        }
+    }
+}
+
+/// A random destination of a hyperspace jump within `half_extents` of the
+/// arena center, `HYPERSPACE_CLEARANCE` away from the `obstacles`, given by
+/// position and radius. `None` if there seems to be no room.
+pub fn hyperspace_destination(
+    rng: &mut impl Rng,
+    half_extents: Vec2,
+    obstacles: &[(Vector2<f32>, f32)],
+) -> Option<Vector2<f32>> {
+    (0..HYPERSPACE_TRIES)
+        .map(|_| {
+            Vector2::new(
+                rng.gen_range(-half_extents.x(), half_extents.x()),
+                rng.gen_range(-half_extents.y(), half_extents.y()),
+            )
+        })
+        .find(|candidate| {
+            obstacles.iter().all(|(position, radius)| {
+                (candidate - position).norm() > radius + HYPERSPACE_CLEARANCE
+            })
+        })
+}
+
+/// Energy of the bullet time of a ship, between 0 and 1
+pub struct BulletTime {
+    pub energy: f32,
+}
+
+impl Default for BulletTime {
+    fn default() -> Self {
+        BulletTime { energy: 1.0 }
+    }
+}
+
+impl BulletTime {
+    /// Drains the energy while `held`, as long as some is left, recharging it
+    /// otherwise. Returns whether the bullet time is active.
+    pub fn update(&mut self, held: bool, delta_seconds: f32) -> bool {
+        let active = held && self.energy > 0.0;
+        if active {
+            self.energy -= BULLET_TIME_DRAIN * delta_seconds;
+        } else {
+            self.energy += BULLET_TIME_RECHARGE * delta_seconds;
+        }
+        self.energy = self.energy.max(0.0).min(1.0);
+        active
+    }
+}
+
+/// Speed of the physics world relative to real time, slowed during bullet time.
+///
+/// The forces and torques of the ships are scaled by its inverse, so that they
+/// still maneuver at full speed.
+pub struct TimeScale(pub f32);
+
+/// Impulse along the ship facing, phasing through asteroids for a moment
+pub struct Dash {
+    /// Finished when the dash is available
+    pub cooldown: Timer,
+    /// Running during the dash
+    pub window: Timer,
+}
+
+impl Default for Dash {
+    fn default() -> Self {
+        Dash {
+            cooldown: Timer {
+                finished: true,
+                ..Timer::from_seconds(DASH_COOLDOWN, false)
+            },
+            window: Timer {
+                finished: true,
+                ..Timer::from_seconds(DASH_WINDOW, false)
+            },
+        }
+    }
+}
+
+impl Dash {
+    /// Whether asteroids do no damage, and the speed cap is raised
+    pub fn is_dashing(&self) -> bool {
+        !self.window.finished
+    }
+
+    /// Between 0 right after a dash and 1 when available
+    pub fn readiness(&self) -> f32 {
+        if self.cooldown.finished {
+            1.0
+        } else {
+            self.cooldown.elapsed / self.cooldown.duration
+        }
+    }
+
+    /// Velocity change of the impulse, so that `DASH_SPEED` is left after the
+    /// `damping` of the dash window.
+    fn speed(damping: &Damping) -> f32 {
+        DASH_SPEED / damping.linear.min(1.0).powf(DASH_WINDOW)
+    }
+}
+
+/// Slows the physics world down while the bullet time key of a ship with
+/// energy left is held, draining it, the energy of the others recharging.
+pub fn bullet_time_system(
+    time: Res<Time>,
+    mut time_scale: ResMut<TimeScale>,
+    mut integration_parameters: ResMut<IntegrationParameters>,
+    mut ships: Query<(&ShipControls, Mut<BulletTime>)>,
+) {
+    let mut active = false;
+    for (controls, mut bullet_time) in &mut ships.iter() {
+        active |= bullet_time.update(controls.bullet_time, time.delta_seconds);
+    }
+    time_scale.0 = if active { BULLET_TIME_SCALE } else { 1.0 };
+    integration_parameters.set_dt(IntegrationParameters::default().dt() * time_scale.0);
+}
+
+/// Boosts the ship along its facing with the dash key, once per cooldown.
+///
+/// The impulse goes through the center of mass, keeping the angular velocity,
+/// and is sized so that the damping over the dash window does not eat it.
+pub fn dash_system(
+    time: Res<Time>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut ships: Query<(
+        &ShipControls,
+        &RigidBodyHandleComponent,
+        &Damping,
+        Mut<Dash>,
+    )>,
+) {
+    for (controls, body_handle, damping, mut dash) in &mut ships.iter() {
+        dash.cooldown.tick(time.delta_seconds);
+        dash.window.tick(time.delta_seconds);
+        if !controls.dash || !dash.cooldown.finished {
+            continue;
+        }
+        let mut body = match bodies.get_mut(body_handle.handle()) {
+            Some(body) => body,
+            None => continue,
+        };
+        let direction = body.position.rotation.transform_vector(&Vector2::y());
+        let impulse = direction * Dash::speed(damping) * body.mass();
+        body.apply_impulse(impulse);
+        body.wake_up(true);
+        dash.cooldown.reset();
+        dash.window.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn bullet_time_runs_out_then_recharges() {
+        let mut bullet_time = BulletTime::default();
+        let full = 1.0 / BULLET_TIME_DRAIN;
+        assert!(bullet_time.update(true, full / 2.0));
+        assert!(bullet_time.update(true, full));
+        assert_eq!(bullet_time.energy, 0.0);
+        // Recharging even while held once empty
+        assert!(!bullet_time.update(true, 1.0));
+        assert!((bullet_time.energy - BULLET_TIME_RECHARGE).abs() < 1e-6);
+        assert!(!bullet_time.update(false, 1.0 / BULLET_TIME_RECHARGE));
+        assert_eq!(bullet_time.energy, 1.0);
+    }
+
+    #[test]
+    fn dash_outruns_the_damping_of_its_window() {
+        let damping = Damping {
+            linear: 0.5,
+            angular: 1.0,
+        };
+        let left = Dash::speed(&damping) * damping.linear.powf(DASH_WINDOW);
+        assert!((left - DASH_SPEED).abs() < 1e-3);
+        assert_eq!(Dash::speed(&Damping::NONE), DASH_SPEED);
+
+        let mut dash = Dash::default();
+        assert!(!dash.is_dashing());
+        assert_eq!(dash.readiness(), 1.0);
+        dash.cooldown.reset();
+        dash.window.reset();
+        assert!(dash.is_dashing());
+        assert_eq!(dash.readiness(), 0.0);
+    }
+
+    #[test]
+    fn hyperspace_lands_clear_of_the_obstacles() {
+        let mut rng = StdRng::seed_from_u64(214);
+        let half_extents = Vec2::new(50.0, 30.0);
+        let obstacles = [
+            (Vector2::new(-25.0, 0.0), 10.0),
+            (Vector2::new(25.0, 0.0), 10.0),
+        ];
+        for _ in 0..20 {
+            let destination = hyperspace_destination(&mut rng, half_extents, &obstacles)
+                .expect("most of the arena is clear");
+            assert!(destination.x.abs() <= 50.0 && destination.y.abs() <= 30.0);
+            for (position, radius) in &obstacles {
+                assert!((destination - position).norm() > radius + HYPERSPACE_CLEARANCE);
+            }
+        }
+        // Covered by a single asteroid
+        let obstacles = [(Vector2::zeros(), 100.0)];
+        assert_eq!(
+            hyperspace_destination(&mut rng, half_extents, &obstacles),
+            None
+        );
+    }
+}
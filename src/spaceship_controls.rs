@@ -0,0 +1,30 @@
+//! Inputs of the ships of spaceship_01, from the keyboard and gamepads of their
+//! player or from a replay.
+
+use bevy::prelude::*;
+
+/// Ship inputs of this frame, from the keyboard and gamepads of its player
+#[derive(Default)]
+pub struct ShipControls {
+    /// Between -1 and 1, positive counterclockwise
+    pub rotation: f32,
+    /// 1 forward, -1 reverse, 0 none
+    pub thrust: i32,
+    /// In world axes, of length at most 1
+    pub direction: Vec2,
+    pub fire: bool,
+    /// Just pressed this frame
+    pub hyperspace: bool,
+    /// Just pressed this frame
+    pub missile: bool,
+    /// Held to charge the beam, fired on release
+    pub beam: bool,
+    /// Held to drag an asteroid
+    pub tractor: bool,
+    /// Just pressed this frame
+    pub mine: bool,
+    /// Just pressed this frame
+    pub dash: bool,
+    /// Held to slow the world down
+    pub bullet_time: bool,
+}
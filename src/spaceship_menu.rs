@@ -0,0 +1,156 @@
+//! The keys of the menu of spaceship_01, before the first game, and of its
+//! game over screen.
+
+use bevy::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameMode {
+    /// Waves of asteroids, destroying them for points
+    Waves,
+    /// A growing stream of asteroids, the score being the seconds survived
+    Survival,
+}
+
+impl GameMode {
+    /// The mode picked with 1 or 2, the keys of the menu and the game over
+    /// screen, the second player only joining while playing.
+    pub fn picked(input: &Input<KeyCode>) -> Option<GameMode> {
+        if input.just_pressed(KeyCode::Key1) {
+            Some(GameMode::Waves)
+        } else if input.just_pressed(KeyCode::Key2) {
+            Some(GameMode::Survival)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMode::Waves => "waves",
+            GameMode::Survival => "survival",
+        }
+    }
+}
+
+/// Lines of the menu
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MenuText {
+    /// The chosen ship
+    Ship,
+    /// The arena variant and how to start
+    Options,
+}
+
+/// The menu keys pressed this frame
+#[derive(Debug, Default, PartialEq)]
+pub struct MenuInput {
+    /// -1 for the previous ship with the left arrow, 1 for the next one with
+    /// the right arrow
+    pub ship_step: isize,
+    pub mode: Option<GameMode>,
+    /// Toggled with O
+    pub obstacles: bool,
+    /// Toggled with W
+    pub walls: bool,
+    /// Toggled with G
+    pub black_hole: bool,
+    /// Enter
+    pub start: bool,
+}
+
+impl MenuInput {
+    pub fn new(input: &Input<KeyCode>) -> Self {
+        MenuInput {
+            ship_step: input.just_pressed(KeyCode::Right) as isize
+                - input.just_pressed(KeyCode::Left) as isize,
+            mode: GameMode::picked(input),
+            obstacles: input.just_pressed(KeyCode::O),
+            walls: input.just_pressed(KeyCode::W),
+            black_hole: input.just_pressed(KeyCode::G),
+            start: input.just_pressed(KeyCode::Return),
+        }
+    }
+
+    /// The ship after `ship` of the `count` ones, wrapping around
+    pub fn next_ship(&self, ship: usize, count: usize) -> usize {
+        (ship as isize + self.ship_step).rem_euclid(count as isize) as usize
+    }
+}
+
+/// The `MenuText::Options` line, with the current mode and arena variant.
+pub fn options_line(mode: GameMode, obstacles: bool, walls: bool, black_hole: bool) -> String {
+    let on_off = |on| if on { "on" } else { "off" };
+    format!(
+        "1/2: {}  —  O: obstacles {}  —  W: walls {}  —  G: black hole {}  —  Enter: start",
+        mode.name(),
+        on_off(obstacles),
+        on_off(walls),
+        on_off(black_hole)
+    )
+}
+
+/// The mode of the next game once over, `None` until R restarts in the same
+/// `mode` or another one is picked.
+pub fn restart_mode(input: &Input<KeyCode>, mode: GameMode) -> Option<GameMode> {
+    GameMode::picked(input).or_else(|| Some(mode).filter(|_| input.just_pressed(KeyCode::R)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pressed(keys: &[KeyCode]) -> Input<KeyCode> {
+        let mut input = Input::default();
+        for &key in keys {
+            input.press(key);
+        }
+        input
+    }
+
+    #[test]
+    fn menu_cycles_the_ships_and_picks_the_mode() {
+        let input = MenuInput::new(&pressed(&[KeyCode::Left, KeyCode::Key2, KeyCode::W]));
+        assert_eq!(
+            input,
+            MenuInput {
+                ship_step: -1,
+                mode: Some(GameMode::Survival),
+                walls: true,
+                ..Default::default()
+            }
+        );
+        assert_eq!(input.next_ship(0, 3), 2);
+        let input = MenuInput::new(&pressed(&[KeyCode::Right, KeyCode::Return]));
+        assert_eq!(input.next_ship(2, 3), 0);
+        assert!(input.start && input.mode.is_none());
+        // Both arrows cancel out
+        let input = MenuInput::new(&pressed(&[KeyCode::Left, KeyCode::Right]));
+        assert_eq!(input.next_ship(1, 3), 1);
+    }
+
+    #[test]
+    fn options_line_shows_the_mode() {
+        assert_eq!(
+            options_line(GameMode::Survival, true, false, false),
+            "1/2: survival  —  O: obstacles on  —  W: walls off  —  G: black hole off  —  Enter: start"
+        );
+    }
+
+    #[test]
+    fn r_restarts_in_the_same_mode() {
+        let survival = GameMode::Survival;
+        assert_eq!(restart_mode(&pressed(&[]), survival), None);
+        assert_eq!(
+            restart_mode(&pressed(&[KeyCode::R]), survival),
+            Some(survival)
+        );
+        assert_eq!(
+            restart_mode(&pressed(&[KeyCode::Key1]), survival),
+            Some(GameMode::Waves)
+        );
+        assert_eq!(
+            restart_mode(&pressed(&[KeyCode::R, KeyCode::Key2]), GameMode::Waves),
+            Some(GameMode::Survival)
+        );
+    }
+}
@@ -0,0 +1,296 @@
+//! Recording of the ship inputs of spaceship_01 with `--record`, and their
+//! replay in place of the keyboard and gamepads with `--replay`.
+
+use crate::{platform, spaceship_controls::ShipControls};
+use bevy::{app::AppExit, prelude::*};
+use rand::{thread_rng, Rng};
+use serde::{Deserialize, Serialize};
+use std::{io, path::PathBuf, time::Duration};
+
+/// Next to the executable, written with `--record` and read with `--replay`
+pub const REPLAY_FILE: &str = "replay.ron";
+/// Duration in s of a frame while recording or replaying, a physics step
+pub const REPLAY_STEP: f32 = 1.0 / 60.0;
+/// The ship positions are logged every this many steps
+pub const REPLAY_LOG_PERIOD: usize = 60;
+
+pub fn replay_path() -> io::Result<PathBuf> {
+    platform::data_path(REPLAY_FILE)
+}
+
+/// The inputs of a ship during a step
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecordedControls {
+    pub rotation: f32,
+    pub thrust: i32,
+    pub fire: bool,
+    pub dash: bool,
+}
+
+impl RecordedControls {
+    pub fn new(controls: &ShipControls) -> Self {
+        RecordedControls {
+            rotation: controls.rotation,
+            thrust: controls.thrust,
+            fire: controls.fire,
+            dash: controls.dash,
+        }
+    }
+
+    /// The controls of the ship, the other inputs being released
+    pub fn controls(self) -> ShipControls {
+        ShipControls {
+            rotation: self.rotation,
+            thrust: self.thrust,
+            fire: self.fire,
+            dash: self.dash,
+            ..Default::default()
+        }
+    }
+}
+
+/// A recorded run, saved in `REPLAY_FILE`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct InputLog {
+    /// Seed of the `SeededRng` of the run, drawing its spawns and hazards
+    pub seed: u64,
+    /// The inputs of each step, by player
+    pub steps: Vec<Vec<RecordedControls>>,
+    /// The ship positions every `REPLAY_LOG_PERIOD` steps, by player
+    pub positions: Vec<Vec<Option<(f32, f32)>>>,
+}
+
+/// Recording of the ship inputs with `--record`, or replay of the recorded
+/// ones in place of the keyboard and gamepads with `--replay`.
+pub enum Replay {
+    Off,
+    Recording(InputLog),
+    Replaying {
+        log: InputLog,
+        step: usize,
+        /// Largest distance in m so far between the logged ship positions of
+        /// the two runs
+        divergence: f32,
+    },
+}
+
+/// What a step of the `Replay` did to the controls of the ships
+#[derive(Debug, PartialEq)]
+pub enum ReplayStep {
+    /// Neither recording nor replaying, the controls are left to the players
+    Off,
+    Recorded,
+    /// The controls of the step by player, in place of the ones of the players
+    Replayed(Vec<RecordedControls>),
+    /// The log ran out, giving the controls back
+    Over,
+}
+
+impl Replay {
+    pub fn from_args() -> Self {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        if args.iter().any(|arg| arg == "--replay") {
+            let result = replay_path()
+                .and_then(platform::read_to_string)
+                .map_err(|error| error.to_string())
+                .and_then(|text| ron::de::from_str(&text).map_err(|error| error.to_string()));
+            match result {
+                Ok(log) => Replay::Replaying {
+                    log,
+                    step: 0,
+                    divergence: 0.0,
+                },
+                Err(error) => {
+                    eprintln!("Could not load the replay: {}", error);
+                    Replay::Off
+                }
+            }
+        } else if args.iter().any(|arg| arg == "--record") {
+            Replay::Recording(InputLog {
+                seed: thread_rng().gen(),
+                ..Default::default()
+            })
+        } else {
+            Replay::Off
+        }
+    }
+
+    pub fn is_off(&self) -> bool {
+        matches!(self, Replay::Off)
+    }
+
+    /// Seed of the `SeededRng`, that of the log when replaying so that the
+    /// replay meets the same asteroids, saucers, powerups and black holes.
+    ///
+    /// The purely visual effects keep drawing from `thread_rng`, not to change
+    /// the run with their settings.
+    pub fn seed(&self) -> u64 {
+        match self {
+            Replay::Off => thread_rng().gen(),
+            Replay::Recording(log) | Replay::Replaying { log, .. } => log.seed,
+        }
+    }
+
+    /// Records the `controls` of the ships for this step, or gives the
+    /// recorded ones, logging the ship `positions` by player every
+    /// `REPLAY_LOG_PERIOD` steps.
+    ///
+    /// The replay turns off when the log runs out.
+    pub fn advance(
+        &mut self,
+        positions: Vec<Option<(f32, f32)>>,
+        controls: impl FnOnce() -> Vec<RecordedControls>,
+    ) -> ReplayStep {
+        let advanced = match self {
+            Replay::Off => ReplayStep::Off,
+            Replay::Recording(log) => {
+                if log.steps.len() % REPLAY_LOG_PERIOD == 0 {
+                    println!("Step {}: ships at {:?}", log.steps.len(), positions);
+                    log.positions.push(positions);
+                }
+                log.steps.push(controls());
+                ReplayStep::Recorded
+            }
+            Replay::Replaying {
+                log,
+                step,
+                divergence,
+            } => match log.steps.get(*step) {
+                Some(recorded) => {
+                    if *step % REPLAY_LOG_PERIOD == 0 {
+                        println!("Step {}: ships at {:?}", step, positions);
+                        let logged = log.positions.get(*step / REPLAY_LOG_PERIOD);
+                        for (position, logged) in positions.iter().zip(logged.into_iter().flatten())
+                        {
+                            if let (Some((x, y)), Some((logged_x, logged_y))) = (position, logged) {
+                                let distance = Vec2::new(x - logged_x, y - logged_y).length();
+                                *divergence = divergence.max(distance);
+                            }
+                        }
+                    }
+                    *step += 1;
+                    ReplayStep::Replayed(recorded.clone())
+                }
+                None => {
+                    println!(
+                        "Replay over after {} steps, max divergence {:.3} m",
+                        step, divergence
+                    );
+                    ReplayStep::Over
+                }
+            },
+        };
+        if advanced == ReplayStep::Over {
+            *self = Replay::Off;
+        }
+        advanced
+    }
+}
+
+/// Saves the recorded inputs in `REPLAY_FILE`.
+fn save_input_log(log: &InputLog) {
+    let result = ron::ser::to_string(log)
+        .map_err(|error| error.to_string())
+        .and_then(|text| {
+            replay_path()
+                .and_then(|path| platform::write(path, text))
+                .map_err(|error| error.to_string())
+        });
+    match result {
+        Ok(()) => println!("Recorded {} steps", log.steps.len()),
+        Err(error) => eprintln!("Could not save the replay: {}", error),
+    }
+}
+
+/// Advances the time by exactly one physics step each frame while recording
+/// or replaying, so that the frame rate does not change the run.
+pub fn fixed_step_system(replay: Res<Replay>, mut time: ResMut<Time>) {
+    if replay.is_off() {
+        return;
+    }
+    time.delta = Duration::from_secs_f32(REPLAY_STEP);
+    time.delta_seconds = REPLAY_STEP;
+    time.delta_seconds_f64 = REPLAY_STEP as f64;
+}
+
+/// Saves the recording, or reports the divergence of the replay, when the
+/// game is closed.
+pub fn replay_exit_system(
+    replay: Res<Replay>,
+    mut reader: Local<EventReader<AppExit>>,
+    exits: Res<Events<AppExit>>,
+) {
+    if reader.iter(&exits).next().is_none() {
+        return;
+    }
+    match &*replay {
+        Replay::Off => {}
+        Replay::Recording(log) => save_input_log(log),
+        Replay::Replaying {
+            step, divergence, ..
+        } => println!(
+            "Replay stopped after {} steps, max divergence {:.3} m",
+            step, divergence
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thrusting(thrust: i32) -> RecordedControls {
+        RecordedControls {
+            thrust,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn replays_the_recorded_steps_then_gives_the_controls_back() {
+        let mut replay = Replay::Recording(InputLog::default());
+        for thrust in 0..3 {
+            let step = replay.advance(vec![Some((0.0, 0.0))], || vec![thrusting(thrust)]);
+            assert_eq!(step, ReplayStep::Recorded);
+        }
+        let log = match replay {
+            Replay::Recording(log) => log,
+            _ => panic!("still recording"),
+        };
+        assert_eq!(log.positions.len(), 1);
+        let text = ron::ser::to_string(&log).unwrap();
+        let mut replay = Replay::Replaying {
+            log: ron::de::from_str(&text).unwrap(),
+            step: 0,
+            divergence: 0.0,
+        };
+        for thrust in 0..3 {
+            // Neither the controls of the players nor the positions of later
+            // steps are used
+            let step = replay.advance(vec![Some((3.0, 4.0))], || vec![thrusting(-1)]);
+            assert_eq!(step, ReplayStep::Replayed(vec![thrusting(thrust)]));
+        }
+        match replay {
+            Replay::Replaying { divergence, .. } => assert_eq!(divergence, 5.0),
+            _ => panic!("still replaying"),
+        }
+        assert_eq!(replay.advance(vec![None], Vec::new), ReplayStep::Over);
+        assert!(replay.is_off());
+        assert_eq!(replay.advance(vec![None], Vec::new), ReplayStep::Off);
+    }
+
+    #[test]
+    fn recorded_controls_release_the_other_inputs() {
+        let controls = ShipControls {
+            rotation: -1.0,
+            fire: true,
+            hyperspace: true,
+            bullet_time: true,
+            ..Default::default()
+        };
+        let replayed = RecordedControls::new(&controls).controls();
+        assert_eq!(replayed.rotation, -1.0);
+        assert!(replayed.fire);
+        assert!(!replayed.hyperspace && !replayed.bullet_time);
+    }
+}
@@ -0,0 +1,283 @@
+//! A ship steered with W, A, S and D, hit by a drifting asteroid, the scene of
+//! the spaceship_02 example and of the launcher.
+
+use crate::{
+    arena::Arena,
+    balls::wrap_system,
+    game_assets::GameAssets,
+    scene::{ActiveScene, Scene, SceneEntity, SceneMaterials},
+};
+use bevy::{prelude::*, render::camera::OrthographicProjection};
+use bevy_rapier2d::{
+    na::Vector2,
+    physics::{EventQueue, RigidBodyHandleComponent},
+    rapier::{
+        dynamics::{RigidBodyBuilder, RigidBodyHandle, RigidBodySet},
+        geometry::ColliderBuilder,
+        //        math::Point,
+    },
+};
+use ncollide2d::narrow_phase::ContactEvent;
+use rand::{thread_rng, Rng};
+use std::collections::HashMap;
+
+const CAMERA_SCALE: f32 = 0.1;
+/// The arena seen through the camera in the 1280x800 window of the examples
+const ARENA_WIDTH: f32 = 1280.0 * CAMERA_SCALE;
+const ARENA_HEIGHT: f32 = 800.0 * CAMERA_SCALE;
+
+pub const SCENE: Scene = Scene {
+    name: "Spaceship",
+    setup,
+    teardown,
+};
+
+/// Adds the systems of the scene, running while it is the active one.
+pub struct SpaceshipScenePlugin;
+
+impl Plugin for SpaceshipScenePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_resource(BodyHandleToEntity(HashMap::new()))
+            .add_system(position_system.system())
+            .add_system(user_input_system.system())
+            .add_system(player_dampening_system.system())
+            .add_system(body_to_entity_system.system())
+            .add_system_to_stage(stage::POST_UPDATE, contact_system.system());
+    }
+}
+
+/// Marks the ship of the player
+struct Player;
+
+struct Ship {
+    /// Ship rotation speed in rad/s
+    rotation_speed: f32,
+    /// Ship thrust N
+    thrust: f32,
+    /// Ship life points
+    life: u32,
+}
+
+struct Asteroid {}
+struct Damage {
+    value: u32,
+}
+
+struct BodyHandleToEntity(HashMap<RigidBodyHandle, Entity>);
+
+fn setup(commands: &mut Commands, resources: &mut Resources) {
+    commands
+        .spawn(Camera2dComponents {
+            orthographic_projection: OrthographicProjection {
+                far: 1000.0 / CAMERA_SCALE,
+                ..Default::default()
+            },
+            transform: Transform::from_scale(CAMERA_SCALE),
+            ..Default::default()
+        })
+        .with(SceneEntity);
+    commands.insert_resource(Arena::centered(ARENA_WIDTH, ARENA_HEIGHT));
+    let assets = resources.get::<GameAssets>().unwrap();
+    let mut materials = resources.get_mut::<Assets<ColorMaterial>>().unwrap();
+    let mut scene_materials = resources.get_mut::<SceneMaterials>().unwrap();
+    spawn_player(
+        commands,
+        scene_materials.add(assets.player_ship_texture.into(), &mut materials),
+    );
+    spawn_asteroid(
+        commands,
+        scene_materials.add(assets.meteor_texture.into(), &mut materials),
+    );
+}
+
+fn teardown(_world: &mut World, resources: &mut Resources) {
+    resources.get_mut::<BodyHandleToEntity>().unwrap().0.clear();
+}
+
+fn spawn_player(commands: &mut Commands, material: Handle<ColorMaterial>) {
+    let body = RigidBodyBuilder::new_dynamic();
+    let collider = ColliderBuilder::ball(1.0);
+    // The triangle Collider does not compute mass
+    //let collider = ColliderBuilder::triangle(
+    //    Point::new(1.0, -0.5),
+    //    Point::new(0.0, 0.8),
+    //    Point::new(-1.0, -0.5),
+    //);
+    commands
+        .spawn(SpriteComponents {
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, -1.0))
+                .with_scale(1.0 / 37.0),
+            material,
+            ..Default::default()
+        })
+        .with(Ship {
+            rotation_speed: 10.0,
+            thrust: 60.0,
+            life: 4,
+        })
+        .with(Player)
+        .with(body)
+        .with(collider)
+        .with(SceneEntity);
+}
+
+fn spawn_asteroid(commands: &mut Commands, material: Handle<ColorMaterial>) {
+    // The triangle Collider does not compute mass
+    //let collider = ColliderBuilder::triangle(
+    //    Point::new(1.0, -0.5),
+    //    Point::new(0.0, 0.8),
+    //    Point::new(-1.0, -0.5),
+    //);
+    let mut rng = thread_rng();
+    // 0: Top , 1:Left
+    let side = rng.gen_range(0, 2);
+    let (x, y) = match side {
+        0 => (
+            rng.gen_range(-ARENA_WIDTH / 2.0, ARENA_WIDTH / 2.0),
+            ARENA_HEIGHT / 2.0,
+        ),
+        _ => (
+            -ARENA_WIDTH / 2.0,
+            rng.gen_range(-ARENA_HEIGHT / 2.0, ARENA_HEIGHT / 2.0),
+        ),
+    };
+    let vx = rng.gen_range(-ARENA_WIDTH / 4.0, ARENA_WIDTH / 4.0);
+    let vy = rng.gen_range(-ARENA_HEIGHT / 4.0, ARENA_HEIGHT / 4.0);
+    let angvel = rng.gen_range(-10.0, 10.0);
+    let body = RigidBodyBuilder::new_dynamic()
+        .translation(x, y)
+        .linvel(vx, vy)
+        .angvel(angvel);
+    let collider = ColliderBuilder::ball(5.0);
+    commands
+        .spawn(SpriteComponents {
+            transform: Transform::from_translation(Vec3::new(x, y, -1.0)).with_scale(1.0 / 10.0),
+            material,
+            ..Default::default()
+        })
+        .with(Asteroid {})
+        .with(Damage { value: 1 })
+        .with(body)
+        .with(collider)
+        .with(SceneEntity);
+}
+
+/// Wraps the bodies around the screen edges.
+fn position_system(
+    active: Res<ActiveScene>,
+    arena: Res<Arena>,
+    bodies: ResMut<RigidBodySet>,
+    query: Query<&RigidBodyHandleComponent>,
+) {
+    if active.runs(&SCENE) {
+        wrap_system(arena, bodies, query);
+    }
+}
+
+fn player_dampening_system(
+    active: Res<ActiveScene>,
+    time: Res<Time>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut query: Query<With<Player, &RigidBodyHandleComponent>>,
+) {
+    if !active.runs(&SCENE) {
+        return;
+    }
+    let elapsed = time.delta_seconds;
+    for body_handle in &mut query.iter() {
+        let mut body = bodies.get_mut(body_handle.handle()).unwrap();
+        body.angvel *= 0.1f32.powf(elapsed);
+        body.linvel *= 0.8f32.powf(elapsed);
+    }
+}
+
+fn user_input_system(
+    active: Res<ActiveScene>,
+    input: Res<Input<KeyCode>>,
+    mut bodies: ResMut<RigidBodySet>,
+    mut query: Query<With<Player, (&RigidBodyHandleComponent, &Ship)>>,
+) {
+    if !active.runs(&SCENE) {
+        return;
+    }
+    let mut rotation = 0;
+    let mut thrust = 0;
+    if input.pressed(KeyCode::W) {
+        thrust += 1
+    }
+    if input.pressed(KeyCode::S) {
+        thrust -= 1
+    }
+    if input.pressed(KeyCode::A) {
+        rotation += 1
+    }
+    if input.pressed(KeyCode::D) {
+        rotation -= 1
+    }
+    if rotation == 0 && thrust == 0 {
+        return;
+    }
+    for (body_handle, ship) in &mut query.iter() {
+        let mut body = bodies.get_mut(body_handle.handle()).unwrap();
+        if rotation != 0 {
+            let rotation = rotation as f32 * ship.rotation_speed;
+            body.wake_up(true);
+            body.apply_torque(rotation);
+        }
+        if thrust != 0 {
+            let force = body.position.rotation.transform_vector(&Vector2::y())
+                * thrust as f32
+                * ship.thrust;
+            body.wake_up(true);
+            body.apply_force(force);
+        }
+    }
+}
+
+#[allow(clippy::absurd_extreme_comparisons)]
+fn contact_system(
+    active: Res<ActiveScene>,
+    events: Res<EventQueue>,
+    h_to_e: Res<BodyHandleToEntity>,
+    damages: Query<&Damage>,
+    ships: Query<Mut<Ship>>,
+) {
+    if !active.runs(&SCENE) {
+        return;
+    }
+    while let Ok(contact_event) = events.contact_events.pop() {
+        if let ContactEvent::Started(h1, h2) = contact_event {
+            let e1 = h_to_e.0.get(&h1).unwrap();
+            let e2 = h_to_e.0.get(&h2).unwrap();
+            if let Ok(mut ship) = ships.get_mut::<Ship>(*e1) {
+                if let Ok(damage) = damages.get::<Damage>(*e2) {
+                    ship.life -= damage.value;
+                    if ship.life <= 0 {
+                        println!("Player DEAD")
+                    } else {
+                        println!("Player contact Life: {}", ship.life)
+                    }
+                }
+            }
+            if let Ok(mut ship) = ships.get_mut::<Ship>(*e2) {
+                if let Ok(damage) = damages.get::<Damage>(*e1) {
+                    ship.life -= damage.value;
+                    if ship.life <= 0 {
+                        println!("Player DEAD")
+                    } else {
+                        println!("Player contact remains {}", ship.life)
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn body_to_entity_system(
+    mut h_to_e: ResMut<BodyHandleToEntity>,
+    mut added: Query<(Entity, Added<RigidBodyHandleComponent>)>,
+) {
+    for (entity, body_handle) in &mut added.iter() {
+        h_to_e.0.insert(body_handle.handle(), entity);
+    }
+}
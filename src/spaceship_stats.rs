@@ -0,0 +1,155 @@
+//! Physical stats of the ships and asteroids of spaceship_01, shared with the
+//! tests checking how they collide.
+
+use bevy::prelude::*;
+use bevy_rapier2d::rapier::{dynamics::RigidBodyBuilder, geometry::ColliderBuilder};
+
+/// Ship restitution, so that ships bounce on each other
+pub const SHIP_RESTITUTION: f32 = 0.8;
+pub const SHIP_RADIUS: f32 = 1.0;
+/// Collider density of the ships, as heavy as a small asteroid
+pub const SHIP_DENSITY: f32 = 1.0;
+/// Default restitution of the asteroids, so that they rebound crisply
+pub const ASTEROID_RESTITUTION: f32 = 0.9;
+pub const ASTEROID_FRICTION: f32 = 0.1;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AsteroidSize {
+    Small,
+    Medium,
+    Large,
+    /// Sent alone every few waves of spaceship_01, with its `Health`
+    Boss,
+}
+
+/// Tuning of an asteroid size
+pub struct AsteroidTier {
+    pub radius: f32,
+    /// Collider density, a large asteroid weighing about 10 ships and a small
+    /// one about 1
+    pub density: f32,
+    /// Points awarded for destroying it
+    pub score: u32,
+    /// Number of asteroids of the smaller size it splits into
+    pub children: usize,
+    /// Sprite diameter relative to the collider one
+    pub sprite_scale: f32,
+    /// Speed in m/s the asteroid is capped to after collisions
+    pub max_speed: f32,
+    /// Range of the number of particles of its burst
+    pub particles: (usize, usize),
+}
+
+impl AsteroidSize {
+    pub fn tier(self) -> AsteroidTier {
+        match self {
+            AsteroidSize::Small => AsteroidTier {
+                radius: 1.0,
+                density: 1.0,
+                score: 100,
+                children: 0,
+                sprite_scale: 1.0,
+                max_speed: 26.0,
+                particles: (8, 12),
+            },
+            AsteroidSize::Medium => AsteroidTier {
+                radius: 2.0,
+                density: 1.0,
+                score: 50,
+                children: 2,
+                sprite_scale: 1.0,
+                max_speed: 22.0,
+                particles: (12, 20),
+            },
+            AsteroidSize::Large => AsteroidTier {
+                radius: 3.5,
+                density: 0.8,
+                score: 20,
+                children: 2,
+                sprite_scale: 1.05,
+                max_speed: 18.0,
+                particles: (20, 30),
+            },
+            AsteroidSize::Boss => AsteroidTier {
+                radius: 7.0,
+                density: 1.0,
+                score: 500,
+                children: 4,
+                sprite_scale: 1.05,
+                max_speed: 4.0,
+                particles: (60, 80),
+            },
+        }
+    }
+
+    pub fn radius(self) -> f32 {
+        self.tier().radius
+    }
+
+    /// Size of the asteroids a hit one splits into
+    pub fn smaller(self) -> Option<AsteroidSize> {
+        match self {
+            AsteroidSize::Small => None,
+            AsteroidSize::Medium => Some(AsteroidSize::Small),
+            AsteroidSize::Large => Some(AsteroidSize::Medium),
+            AsteroidSize::Boss => Some(AsteroidSize::Large),
+        }
+    }
+
+    /// Points awarded for destroying an asteroid of this size
+    pub fn score(self) -> u32 {
+        self.tier().score
+    }
+}
+
+/// Body and collider of a ship at rest at `position`.
+pub fn ship_builders(position: Vec2) -> (RigidBodyBuilder, ColliderBuilder) {
+    let body = RigidBodyBuilder::new_dynamic().translation(position.x(), position.y());
+    let collider = ColliderBuilder::ball(SHIP_RADIUS)
+        .density(SHIP_DENSITY)
+        .restitution(SHIP_RESTITUTION);
+    (body, collider)
+}
+
+/// Collider of an asteroid of `size`, also added to the core body of the
+/// clumps in the sticky mode.
+pub fn asteroid_collider(size: AsteroidSize, restitution: f32) -> ColliderBuilder {
+    let tier = size.tier();
+    ColliderBuilder::ball(tier.radius)
+        .density(tier.density)
+        .restitution(restitution)
+        .friction(ASTEROID_FRICTION)
+}
+
+/// Body and collider of a tumbling asteroid of `size`.
+pub fn asteroid_builders(
+    size: AsteroidSize,
+    restitution: f32,
+    position: Vec2,
+    velocity: Vec2,
+    angvel: f32,
+) -> (RigidBodyBuilder, ColliderBuilder) {
+    let body = RigidBodyBuilder::new_dynamic()
+        .translation(position.x(), position.y())
+        .linvel(velocity.x(), velocity.y())
+        .angvel(angvel);
+    (body, asteroid_collider(size, restitution))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_down_to_small_for_more_points() {
+        let mut size = AsteroidSize::Boss;
+        while let Some(smaller) = size.smaller() {
+            assert!(smaller.radius() < size.radius());
+            assert!(size.tier().children > 0);
+            size = smaller;
+        }
+        assert_eq!(size, AsteroidSize::Small);
+        assert_eq!(size.tier().children, 0);
+        assert!(AsteroidSize::Small.score() > AsteroidSize::Large.score());
+    }
+}
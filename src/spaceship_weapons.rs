@@ -0,0 +1,248 @@
+//! The gun of the ships of spaceship_01, its energy, and the powerups boosting
+//! it.
+
+use bevy::prelude::*;
+
+/// Weapon energy used by each bullet fired, the spread shot firing 3
+pub const SHOT_ENERGY: f32 = 1.0;
+/// Duration in s the weapon cannot fire after being emptied
+pub const OVERHEAT_DURATION: f32 = 1.5;
+/// Duration in s of a weapon powerup
+pub const BOOST_DURATION: f32 = 10.0;
+/// Obstacle or wall bounces of a ricochet bullet before it is stopped
+pub const RICOCHET_BOUNCES: u32 = 2;
+/// Angle in rad between the bullets of the spread shot, a 20° fan
+pub const SPREAD_ANGLE: f32 = 10.0 * std::f32::consts::PI / 180.0;
+
+/// Angles of the bullets off the ship nose
+const SINGLE_SHOT: [f32; 1] = [0.0];
+const SPREAD_SHOT: [f32; 3] = [-SPREAD_ANGLE, 0.0, SPREAD_ANGLE];
+
+pub struct Weapon {
+    /// Minimum delay between two shots, finished when ready to fire
+    pub cooldown: Timer,
+    /// Bullet speed in m/s, relative to the ship
+    pub projectile_speed: f32,
+}
+
+impl Weapon {
+    /// Ticks the weapon and regains energy, then fires if `fire` is held and
+    /// the weapon is ready, returning the angles of the bullets off the ship
+    /// nose, 3 with the spread shot.
+    ///
+    /// Ticking even when not firing, so a tap fires at once after a pause but
+    /// tapping faster than the cooldown does not fire faster. Rapid fire ticks
+    /// twice as fast, halving the cooldown.
+    pub fn update(
+        &mut self,
+        energy: &mut WeaponEnergy,
+        boosts: &Boosts,
+        fire: bool,
+        delta_seconds: f32,
+    ) -> Option<&'static [f32]> {
+        energy.regen(delta_seconds);
+        let rate = if boosts.rapid_fire > 0.0 { 2.0 } else { 1.0 };
+        self.cooldown.tick(delta_seconds * rate);
+        if !fire || !self.cooldown.finished || !energy.can_fire() {
+            return None;
+        }
+        let angles: &[f32] = if boosts.spread_shot > 0.0 {
+            &SPREAD_SHOT
+        } else {
+            &SINGLE_SHOT
+        };
+        self.cooldown.reset();
+        energy.fire(SHOT_ENERGY * angles.len() as f32);
+        Some(angles)
+    }
+}
+
+/// Energy of the weapon of a ship, used by each shot and regained over time.
+///
+/// Emptying it overheats the weapon, which cannot fire until the overheat is
+/// over, even with energy regained meanwhile.
+pub struct WeaponEnergy {
+    pub current: f32,
+    pub max: f32,
+    pub regen_per_s: f32,
+    /// Remaining overheat in s, 0 when the weapon can fire
+    pub overheat: f32,
+}
+
+impl WeaponEnergy {
+    pub fn new(max: f32, regen_per_s: f32) -> Self {
+        WeaponEnergy {
+            current: max,
+            max,
+            regen_per_s,
+            overheat: 0.0,
+        }
+    }
+
+    pub fn regen(&mut self, delta_seconds: f32) {
+        self.current = (self.current + self.regen_per_s * delta_seconds).min(self.max);
+        self.overheat = (self.overheat - delta_seconds).max(0.0);
+    }
+
+    pub fn can_fire(&self) -> bool {
+        self.overheat <= 0.0 && self.current > 0.0
+    }
+
+    /// Uses the energy of a shot, overheating the weapon when it empties it
+    pub fn fire(&mut self, cost: f32) {
+        self.current -= cost;
+        if self.current <= 0.0 {
+            self.current = 0.0;
+            self.overheat = OVERHEAT_DURATION;
+        }
+    }
+
+    pub fn level(&self) -> f32 {
+        self.current / self.max
+    }
+}
+
+/// Remaining obstacle and wall bounces of a ricochet bullet, stopped on
+/// the next one at zero
+pub struct Bounces {
+    pub remaining: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Powerup {
+    Shield,
+    /// Halves the weapon cooldown
+    RapidFire,
+    /// Fires 3 bullets in a fan
+    SpreadShot,
+    /// Fires bullets bouncing off the obstacles and walls
+    Ricochet,
+    /// Restores a part of the fuel tank
+    Fuel,
+}
+
+impl Powerup {
+    pub fn color(self) -> Color {
+        match self {
+            Powerup::Shield => Color::rgb(0.2, 1.0, 0.6),
+            Powerup::RapidFire => Color::rgb(1.0, 0.5, 0.1),
+            Powerup::SpreadShot => Color::rgb(0.9, 0.3, 1.0),
+            Powerup::Ricochet => Color::rgb(0.3, 0.8, 1.0),
+            Powerup::Fuel => Color::rgb(1.0, 0.9, 0.2),
+        }
+    }
+}
+
+/// Remaining time in s of the weapon powerups of a ship, inactive at zero
+#[derive(Default)]
+pub struct Boosts {
+    pub rapid_fire: f32,
+    pub spread_shot: f32,
+    pub ricochet: f32,
+}
+
+impl Boosts {
+    /// Starts the boost of a weapon `powerup` for `BOOST_DURATION`, refreshing
+    /// rather than stacking a running one. The others are not weapon boosts.
+    pub fn grant(&mut self, powerup: Powerup) {
+        match powerup {
+            Powerup::RapidFire => self.rapid_fire = BOOST_DURATION,
+            Powerup::SpreadShot => self.spread_shot = BOOST_DURATION,
+            Powerup::Ricochet => self.ricochet = BOOST_DURATION,
+            Powerup::Shield | Powerup::Fuel => {}
+        }
+    }
+
+    pub fn run_down(&mut self, delta_seconds: f32) {
+        self.rapid_fire = (self.rapid_fire - delta_seconds).max(0.0);
+        self.spread_shot = (self.spread_shot - delta_seconds).max(0.0);
+        self.ricochet = (self.ricochet - delta_seconds).max(0.0);
+    }
+
+    /// The active boosts with their remaining seconds, for the HUD
+    pub fn label(&self) -> String {
+        let active: Vec<String> = [
+            ("RAPID FIRE", self.rapid_fire),
+            ("SPREAD SHOT", self.spread_shot),
+            ("RICOCHET", self.ricochet),
+        ]
+        .iter()
+        .filter(|(_, remaining)| *remaining > 0.0)
+        .map(|(name, remaining)| format!("{} {:.0}s", name, remaining.ceil()))
+        .collect();
+        active.join("  ")
+    }
+}
+
+/// Runs down the weapon powerups of the ships.
+pub fn boost_system(time: Res<Time>, mut boosts: Query<Mut<Boosts>>) {
+    for mut boosts in &mut boosts.iter() {
+        boosts.run_down(time.delta_seconds);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weapon(cooldown: f32) -> Weapon {
+        Weapon {
+            cooldown: Timer::from_seconds(cooldown, false),
+            projectile_speed: 60.0,
+        }
+    }
+
+    #[test]
+    fn rapid_fire_halves_the_cooldown() {
+        let mut energy = WeaponEnergy::new(100.0, 0.0);
+        let mut boosts = Boosts::default();
+        let mut gun = weapon(0.2);
+        assert_eq!(
+            gun.update(&mut energy, &boosts, true, 0.2),
+            Some(&[0.0][..])
+        );
+        assert_eq!(gun.update(&mut energy, &boosts, true, 0.1), None);
+        boosts.grant(Powerup::RapidFire);
+        assert_eq!(
+            gun.update(&mut energy, &boosts, true, 0.05),
+            Some(&[0.0][..])
+        );
+        // Ready, but not fired without the trigger
+        assert_eq!(gun.update(&mut energy, &boosts, false, 0.1), None);
+        assert!(gun.cooldown.finished);
+        boosts.run_down(BOOST_DURATION);
+        assert_eq!(boosts.rapid_fire, 0.0);
+    }
+
+    #[test]
+    fn spread_shot_overheats_an_emptied_weapon() {
+        let mut energy = WeaponEnergy::new(4.0, 1.0);
+        let mut boosts = Boosts::default();
+        boosts.grant(Powerup::SpreadShot);
+        let mut gun = weapon(0.0);
+        let angles = gun.update(&mut energy, &boosts, true, 0.0).unwrap();
+        assert_eq!(angles.len(), 3);
+        // The last shot may overdraw what is left
+        assert!(gun.update(&mut energy, &boosts, true, 0.0).is_some());
+        assert_eq!(energy.overheat, OVERHEAT_DURATION);
+        assert_eq!(gun.update(&mut energy, &boosts, true, 1.0), None);
+        assert!(energy.level() > 0.0);
+        assert!(gun
+            .update(&mut energy, &boosts, true, OVERHEAT_DURATION)
+            .is_some());
+    }
+
+    #[test]
+    fn boosts_are_refreshed_and_listed() {
+        let mut boosts = Boosts::default();
+        assert_eq!(boosts.label(), "");
+        boosts.grant(Powerup::Ricochet);
+        boosts.run_down(4.5);
+        boosts.grant(Powerup::Shield);
+        assert_eq!(boosts.label(), "RICOCHET 6s");
+        boosts.grant(Powerup::Ricochet);
+        boosts.grant(Powerup::SpreadShot);
+        assert_eq!(boosts.ricochet, BOOST_DURATION);
+        assert_eq!(boosts.label(), "SPREAD SHOT 10s  RICOCHET 10s");
+    }
+}
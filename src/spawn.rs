@@ -0,0 +1,133 @@
+use bevy::prelude::*;
+
+/// Number of candidate points tried by `safe_spawn_point`
+pub const SPAWN_CANDIDATES: usize = 20;
+
+/// Angle in rad between two consecutive points of the spiral, spreading them
+/// evenly around the preferred point.
+const GOLDEN_ANGLE: f32 = 2.399_963;
+
+/// A body to spawn, sent by the input systems, the stress keys or the scripted
+/// runs alike, and consumed by the single spawner system of the example.
+///
+/// `K` is what to spawn, specific to each example.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpawnRequest<K> {
+    pub kind: K,
+    pub position: Vec2,
+    pub velocity: Vec2,
+}
+
+impl<K> SpawnRequest<K> {
+    pub fn new(kind: K, position: Vec2, velocity: Vec2) -> Self {
+        SpawnRequest {
+            kind,
+            position,
+            velocity,
+        }
+    }
+}
+
+/// Cap on the number of bodies, spawn requests above it being dropped
+pub struct MaxBodies(pub usize);
+
+/// The requests of `events` not read yet by `reader`, in the order they were
+/// sent, without those that would take the `alive` bodies above `max_bodies`.
+pub fn capped_requests<K: Clone + Send + Sync + 'static>(
+    reader: &mut EventReader<SpawnRequest<K>>,
+    events: &Events<SpawnRequest<K>>,
+    alive: usize,
+    max_bodies: &MaxBodies,
+) -> Vec<SpawnRequest<K>> {
+    let mut requests: Vec<_> = reader.iter(events).cloned().collect();
+    let room = max_bodies.0.saturating_sub(alive);
+    if requests.len() > room {
+        println!(
+            "{} spawn requests dropped, {} bodies would exceed the cap of {}",
+            requests.len() - room,
+            alive + requests.len(),
+            max_bodies.0
+        );
+        requests.truncate(room);
+    }
+    requests
+}
+
+/// First point clear of all the `obstacles`, given as center and radius, by at
+/// least `margin`.
+///
+/// `preferred` is tried first, then a spiral of points around it, `spacing`
+/// apart. `None` if none of the `SPAWN_CANDIDATES` points is clear, e.g. to
+/// try again later.
+pub fn safe_spawn_point(
+    preferred: Vec2,
+    obstacles: &[(Vec2, f32)],
+    margin: f32,
+    spacing: f32,
+) -> Option<Vec2> {
+    (0..SPAWN_CANDIDATES)
+        .map(|i| {
+            let (sin, cos) = (i as f32 * GOLDEN_ANGLE).sin_cos();
+            preferred + Vec2::new(cos, sin) * spacing * (i as f32).sqrt()
+        })
+        .find(|&point| {
+            obstacles
+                .iter()
+                .all(|&(center, radius)| (point - center).length() > radius + margin)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_clear(point: Vec2, obstacles: &[(Vec2, f32)], margin: f32) -> bool {
+        obstacles
+            .iter()
+            .all(|&(center, radius)| (point - center).length() > radius + margin)
+    }
+
+    #[test]
+    fn requests_are_read_in_order_up_to_the_cap() {
+        let mut events = Events::<SpawnRequest<u32>>::default();
+        let mut reader = events.get_reader();
+        for kind in 0..5 {
+            events.send(SpawnRequest::new(kind, Vec2::zero(), Vec2::zero()));
+        }
+        let requests = capped_requests(&mut reader, &events, 7, &MaxBodies(10));
+        let kinds: Vec<u32> = requests.iter().map(|request| request.kind).collect();
+        assert_eq!(kinds, vec![0, 1, 2]);
+        // Read once, even if dropped
+        assert!(capped_requests(&mut reader, &events, 0, &MaxBodies(10)).is_empty());
+    }
+
+    #[test]
+    fn clear_preferred_point_is_kept() {
+        let obstacles = [(Vec2::new(20.0, 0.0), 3.0)];
+        let point = safe_spawn_point(Vec2::zero(), &obstacles, 2.0, 4.0);
+        assert_eq!(point, Some(Vec2::zero()));
+    }
+
+    #[test]
+    fn crowded_arena_gives_a_safe_point_or_a_deferral() {
+        // A grid of overlapping asteroids around the center, but for a gap
+        let gap = Vec2::new(2.0 * 5.0, 1.0 * 5.0);
+        let mut obstacles = Vec::new();
+        for x in -5..=5 {
+            for y in -5..=5 {
+                if (x, y) != (2, 1) {
+                    obstacles.push((Vec2::new(x as f32 * 5.0, y as f32 * 5.0), 3.0));
+                }
+            }
+        }
+        let point = safe_spawn_point(Vec2::new(8.0, 3.0), &obstacles, 1.0, 1.0)
+            .expect("the gap is reachable");
+        assert!(is_clear(point, &obstacles, 1.0));
+        // Nearer to the missing asteroid than to its neighbours
+        assert!((point - gap).length() < 2.5);
+
+        // Too crowded for any candidate
+        let obstacles = [(Vec2::zero(), 100.0)];
+        assert_eq!(safe_spawn_point(Vec2::zero(), &obstacles, 1.0, 4.0), None);
+    }
+}
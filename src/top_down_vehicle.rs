@@ -0,0 +1,111 @@
+use bevy::prelude::*;
+use bevy_rapier2d::{
+    na::Vector2,
+    physics::RigidBodyHandleComponent,
+    rapier::dynamics::{RigidBody, RigidBodySet},
+};
+
+/// Tire grip of a top-down vehicle, facing its local y axis.
+///
+/// Each factor is the fraction of the sideways velocity cancelled each frame,
+/// so `1.0` drives on rails and `0.0` slides like on ice.
+#[derive(Clone, Copy, Debug)]
+pub struct Grip {
+    pub lateral: f32,
+    /// Lateral grip while the handbrake is pulled, low enough to drift
+    pub handbrake: f32,
+    pub handbrake_on: bool,
+}
+
+impl Grip {
+    /// Lateral grip currently applied, depending on the handbrake
+    pub fn current(&self) -> f32 {
+        if self.handbrake_on {
+            self.handbrake
+        } else {
+            self.lateral
+        }
+    }
+}
+
+/// Unit vector the body faces
+pub fn forward(body: &RigidBody) -> Vector2<f32> {
+    body.position.rotation.transform_vector(&Vector2::y())
+}
+
+/// Unit vector to the right of the body facing
+pub fn right(body: &RigidBody) -> Vector2<f32> {
+    body.position.rotation.transform_vector(&Vector2::x())
+}
+
+/// Signed speed along `forward`, negative when reversing.
+pub fn forward_speed(linvel: Vector2<f32>, forward: Vector2<f32>) -> f32 {
+    linvel.dot(&forward)
+}
+
+/// Sideways part of `linvel`, projected onto the unit `right` vector.
+pub fn lateral_velocity(linvel: Vector2<f32>, right: Vector2<f32>) -> Vector2<f32> {
+    right * linvel.dot(&right)
+}
+
+/// `linvel` with the `grip` fraction of its sideways part cancelled, the
+/// forward part being untouched.
+pub fn apply_grip(linvel: Vector2<f32>, right: Vector2<f32>, grip: f32) -> Vector2<f32> {
+    linvel - lateral_velocity(linvel, right) * grip.max(0.0).min(1.0)
+}
+
+/// Applies the `Grip` of all vehicles, cancelling part of their sideways
+/// velocity.
+pub fn lateral_friction_system(
+    mut bodies: ResMut<RigidBodySet>,
+    mut query: Query<(&Grip, &RigidBodyHandleComponent)>,
+) {
+    for (grip, body_handle) in &mut query.iter() {
+        if let Some(mut body) = bodies.get_mut(body_handle.handle()) {
+            let right = right(&body);
+            let linvel = apply_grip(body.linvel, right, grip.current());
+            if linvel != body.linvel {
+                body.linvel = linvel;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-5;
+
+    #[test]
+    fn full_grip_keeps_only_the_forward_part() {
+        let right = Vector2::new(1.0, 1.0).normalize();
+        let forward = Vector2::new(-1.0, 1.0).normalize();
+        let linvel = forward * 10.0 + right * 4.0;
+        let gripped = apply_grip(linvel, right, 1.0);
+        assert!((gripped - forward * 10.0).norm() < EPSILON);
+        assert!((forward_speed(gripped, forward) - 10.0).abs() < EPSILON);
+        assert!(lateral_velocity(gripped, right).norm() < EPSILON);
+    }
+
+    #[test]
+    fn partial_grip_leaves_a_drift() {
+        let right = Vector2::x();
+        let linvel = Vector2::new(4.0, 10.0);
+        assert_eq!(apply_grip(linvel, right, 0.0), linvel);
+        let gripped = apply_grip(linvel, right, 0.25);
+        assert!((gripped - Vector2::new(3.0, 10.0)).norm() < EPSILON);
+    }
+
+    #[test]
+    fn handbrake_switches_the_grip() {
+        let mut grip = Grip {
+            lateral: 0.9,
+            handbrake: 0.1,
+            handbrake_on: false,
+        };
+        assert_eq!(grip.current(), 0.9);
+        grip.handbrake_on = true;
+        assert_eq!(grip.current(), 0.1);
+    }
+}
@@ -0,0 +1,164 @@
+//! Deterministic stacking of overlapping sprites, and the matching pick order.
+
+use crate::arena::Arena;
+use bevy::prelude::*;
+
+/// Distinct z given by the `Newest` policy between 0 and 1, far enough apart
+/// for the depth buffer of the 2d camera
+const Z_SLOTS: u32 = 4096;
+
+/// How the `ZOrdered` sprites are stacked, inserted by each example.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ZOrderPolicy {
+    /// The last spawned on top
+    Newest,
+    /// The lower in the arena in front, for top-down scenes
+    YSorted,
+}
+
+impl Default for ZOrderPolicy {
+    fn default() -> Self {
+        ZOrderPolicy::Newest
+    }
+}
+
+/// Gives the z of the sprite, between 0 and 1 following the `ZOrderPolicy`.
+///
+/// Its children are stacked with it.
+#[derive(Default)]
+pub struct ZOrdered {
+    /// Spawn order, given by the `z_order_system` on the first frame
+    rank: Option<u32>,
+}
+
+/// Adds the `ZOrderPolicy`, newest on top unless the example inserts another
+/// one, and the `z_order_system`.
+pub struct ZOrderPlugin;
+
+impl Plugin for ZOrderPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<ZOrderPolicy>()
+            .init_resource::<ZOrderCounter>()
+            // Before the transforms are propagated
+            .add_system(z_order_system.system());
+    }
+}
+
+/// Next rank of the `ZOrdered` entities.
+#[derive(Default)]
+pub struct ZOrderCounter {
+    next: u32,
+}
+
+impl ZOrderCounter {
+    /// Ranks the unranked entries after the others. Out of slots, all of them
+    /// are renumbered from 0 first, in the same order.
+    fn rank(&mut self, ranks: &mut [Option<u32>]) {
+        let unranked = ranks.iter().filter(|rank| rank.is_none()).count() as u32;
+        if self.next + unranked > Z_SLOTS {
+            let mut ranked: Vec<usize> = (0..ranks.len())
+                .filter(|&index| ranks[index].is_some())
+                .collect();
+            ranked.sort_by_key(|&index| ranks[index]);
+            for (rank, &index) in ranked.iter().enumerate() {
+                ranks[index] = Some(rank as u32);
+            }
+            self.next = ranked.len() as u32;
+        }
+        for rank in ranks.iter_mut().filter(|rank| rank.is_none()) {
+            *rank = Some(self.next);
+            self.next += 1;
+        }
+    }
+
+    /// Z of the entity of `rank`, spread over more than the slots when there
+    /// are more entities
+    fn z(&self, rank: u32) -> f32 {
+        rank as f32 / self.next.max(Z_SLOTS) as f32
+    }
+}
+
+/// Z of a sprite at `y` in the `arena` for the `YSorted` policy.
+fn y_sorted_z(y: f32, arena: &Arena) -> f32 {
+    ((arena.max().y() - y) / arena.height).max(0.0).min(1.0)
+}
+
+/// Sets the z of the `ZOrdered` entities, ranking the new ones.
+pub fn z_order_system(
+    policy: Res<ZOrderPolicy>,
+    arena: Res<Arena>,
+    mut counter: ResMut<ZOrderCounter>,
+    mut query: Query<(Mut<ZOrdered>, Mut<Transform>)>,
+) {
+    let mut ranks: Vec<Option<u32>> = query
+        .iter()
+        .iter()
+        .map(|(ordered, _)| ordered.rank)
+        .collect();
+    if ranks.iter().any(Option::is_none) {
+        counter.rank(&mut ranks);
+    }
+    for ((mut ordered, mut transform), rank) in query.iter().iter().zip(ranks) {
+        ordered.rank = rank;
+        let mut translation = transform.translation();
+        translation.set_z(match *policy {
+            ZOrderPolicy::Newest => counter.z(rank.unwrap()),
+            ZOrderPolicy::YSorted => y_sorted_z(translation.y(), &arena),
+        });
+        transform.set_translation(translation);
+    }
+}
+
+/// The entity drawn on top among the `candidates` with their z, the one of
+/// highest z as stacked by the `z_order_system`.
+///
+/// The picking of the examples goes through it, so that the entity picked is
+/// the one seen.
+pub fn topmost(candidates: impl IntoIterator<Item = (Entity, f32)>) -> Option<Entity> {
+    candidates
+        .into_iter()
+        .fold(None, |top: Option<(Entity, f32)>, (entity, z)| match top {
+            Some((_, top_z)) if top_z >= z => top,
+            _ => Some((entity, z)),
+        })
+        .map(|(entity, _)| entity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn newest_on_top_across_the_wraparound() {
+        let mut counter = ZOrderCounter::default();
+        let mut ranks = vec![None; 3];
+        counter.rank(&mut ranks);
+        assert_eq!(ranks, vec![Some(0), Some(1), Some(2)]);
+        // Most of them despawned, while the counter runs out of slots
+        counter.next = Z_SLOTS - 1;
+        let mut ranks = vec![Some(2), Some(Z_SLOTS - 2), Some(1), None, None];
+        counter.rank(&mut ranks);
+        assert_eq!(ranks, vec![Some(1), Some(2), Some(0), Some(3), Some(4)]);
+        assert_eq!(counter.next, 5);
+        let z: Vec<f32> = ranks.iter().map(|rank| counter.z(rank.unwrap())).collect();
+        assert!(z[2] < z[0] && z[0] < z[1] && z[1] < z[3] && z[3] < z[4]);
+        assert!(z.iter().all(|&z| (0.0..1.0).contains(&z)));
+    }
+
+    #[test]
+    fn lower_y_in_front() {
+        let arena = Arena::new(100.0, 50.0);
+        assert!(y_sorted_z(10.0, &arena) > y_sorted_z(40.0, &arena));
+        assert_eq!(y_sorted_z(-5.0, &arena), 1.0);
+        assert_eq!(y_sorted_z(60.0, &arena), 0.0);
+    }
+
+    #[test]
+    fn topmost_is_the_highest_z() {
+        let mut world = World::new();
+        let mut spawn = || world.spawn((ZOrdered::default(),));
+        let (a, b, c) = (spawn(), spawn(), spawn());
+        assert_eq!(topmost(vec![(a, 0.2), (b, 0.7), (c, 0.5)]), Some(b));
+        assert_eq!(topmost(Vec::new()), None);
+    }
+}
@@ -0,0 +1,387 @@
+//! Runs the core systems of the examples headless, without rendering nor
+//! windowing, for a few hundred frames of scripted spawns, checking that the
+//! physics state stays consistent and finite.
+
+use bevy::prelude::*;
+use bevy_rapier2d::{
+    na::Vector2 as RapierVector2,
+    physics::{RapierConfiguration, RapierPhysicsPlugin, RigidBodyHandleComponent},
+    rapier::{dynamics::RigidBodySet, geometry::ColliderSet},
+};
+use bevy_showcase::{
+    arena::Arena,
+    balls::{jittered_grid, wrap_system},
+    cleanup::RapierCleanupPlugin,
+    collide::Velocity,
+    golden::{GoldenRun, Snapshot},
+    ncollide_scene::{self, NCollideScenePlugin, Sphere},
+    physics_math::resolve_elastic,
+    rapier_scene::{self, GridBall, RapierScenePlugin},
+    response::NCollideResponsePlugin,
+    scenario,
+    scene::{ActiveScene, Census, ScenePlugin},
+    showcase::SeededRng,
+    spaceship_stats::{
+        asteroid_builders, ship_builders, AsteroidSize, ASTEROID_RESTITUTION, SHIP_RESTITUTION,
+    },
+    spawn::{MaxBodies, SpawnRequest},
+};
+use ncollide2d::{pipeline::CollisionObjectSlabHandle, world::CollisionWorld};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const TICKS: usize = 300;
+/// Duration in s of each update
+const STEP: f32 = 1.0 / 60.0;
+const SEED: u64 = 214;
+const ARENA_WIDTH: f32 = 1280.0;
+const ARENA_HEIGHT: f32 = 800.0;
+/// Body cap of the spawners, above all the bodies of the runs
+const MAX_BODIES: usize = 4000;
+/// Distance in px the bodies may be out of the arena, before being wrapped
+/// around or pushed back in by a contact
+const ARENA_MARGIN: f32 = 100.0;
+/// Steps of the golden runs
+const GOLDEN_STEPS: usize = 600;
+
+fn headless_app(build: impl FnOnce(&mut AppBuilder)) -> App {
+    scenario::headless_app(STEP, Arena::new(ARENA_WIDTH, ARENA_HEIGHT), |app| {
+        app.add_resource(MaxBodies(MAX_BODIES));
+        build(app);
+    })
+}
+
+/// Sends a spawn request to the spawner of the `K` bodies, spawning them on
+/// the next update.
+fn request_spawn<K: Send + Sync + 'static>(app: &mut App, kind: K, position: Vec2, velocity: Vec2) {
+    app.resources
+        .get_mut::<Events<SpawnRequest<K>>>()
+        .unwrap()
+        .send(SpawnRequest::new(kind, position, velocity));
+}
+
+fn assert_in_arena(x: f32, y: f32) {
+    assert!(x.is_finite() && y.is_finite(), "position ({}, {})", x, y);
+    assert!(
+        x > -ARENA_MARGIN
+            && x < ARENA_WIDTH + ARENA_MARGIN
+            && y > -ARENA_MARGIN
+            && y < ARENA_HEIGHT + ARENA_MARGIN,
+        "position ({}, {}) out of the arena",
+        x,
+        y
+    );
+}
+
+fn random_velocity(rng: &mut StdRng) -> (f32, f32) {
+    (
+        rng.gen_range(-ARENA_WIDTH / 4.0, ARENA_WIDTH / 4.0),
+        rng.gen_range(-ARENA_HEIGHT / 4.0, ARENA_HEIGHT / 4.0),
+    )
+}
+
+#[test]
+fn ncollide2d_spheres() {
+    let mut app = headless_app(|app| {
+        app.add_plugin(NCollideResponsePlugin::default().with_step(STEP).wrapping())
+            .add_plugin(NCollideScenePlugin);
+    });
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let mut spawned = 0;
+    for tick in 0..TICKS {
+        // A wave of spheres every 10 frames for the first 200, two of each
+        // wave at the very same place for a degenerate contact
+        if tick % 10 == 0 && tick < 200 {
+            let position = Vec2::new(
+                rng.gen_range(0.0, ARENA_WIDTH),
+                rng.gen_range(0.0, ARENA_HEIGHT),
+            );
+            for i in 0..5 {
+                let position = if i < 2 {
+                    position
+                } else {
+                    Vec2::new(
+                        rng.gen_range(0.0, ARENA_WIDTH),
+                        rng.gen_range(0.0, ARENA_HEIGHT),
+                    )
+                };
+                let (vx, vy) = random_velocity(&mut rng);
+                request_spawn(&mut app, Sphere, position, Vec2::new(vx, vy));
+                spawned += 1;
+            }
+        }
+        app.update();
+    }
+
+    let world = app.resources.get::<CollisionWorld<f32, Entity>>().unwrap();
+    let handles = app
+        .world
+        .query::<&CollisionObjectSlabHandle>()
+        .iter()
+        .count();
+    assert_eq!(handles, spawned);
+    assert_eq!(world.collision_objects().count(), spawned);
+    let mut spheres = 0;
+    for (transform, velocity) in app.world.query::<(&Transform, &Velocity)>().iter() {
+        spheres += 1;
+        assert_in_arena(transform.translation().x(), transform.translation().y());
+        assert!(
+            velocity.0.x.is_finite() && velocity.0.y.is_finite(),
+            "velocity {:?}",
+            velocity.0
+        );
+    }
+    assert_eq!(spheres, spawned);
+    for (_, object) in world.collision_objects() {
+        let translation = object.position().translation.vector;
+        assert_in_arena(translation.x, translation.y);
+    }
+}
+
+fn set_lengths(app: &App) -> (usize, usize) {
+    let bodies = app.resources.get::<RigidBodySet>().unwrap();
+    let colliders = app.resources.get::<ColliderSet>().unwrap();
+    (bodies.len(), colliders.len())
+}
+
+#[test]
+fn rapier2d_balls() {
+    let mut app = headless_app(|app| {
+        app.add_plugin(RapierPhysicsPlugin)
+            .add_plugin(RapierCleanupPlugin)
+            .add_resource(RapierConfiguration {
+                gravity: RapierVector2::zeros(),
+                ..Default::default()
+            })
+            .add_plugin(ScenePlugin::default())
+            .add_plugin(RapierScenePlugin)
+            .add_system(wrap_system.system());
+    });
+    app.update();
+    let baseline = set_lengths(&app);
+    let arena = Arena::new(ARENA_WIDTH, ARENA_HEIGHT);
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let mut balls = 0;
+    for tick in 0..TICKS {
+        match tick {
+            0 | 200 => {
+                for (x, y, vx, vy) in jittered_grid(&mut rng, &arena, 16, 10) {
+                    let velocity = Vec2::new(vx, vy) * 20.0;
+                    request_spawn(&mut app, GridBall, Vec2::new(x, y), velocity);
+                    balls += 1;
+                }
+            }
+            // A pile of balls at the very same place, for degenerate contacts
+            50 => {
+                for _ in 0..10 {
+                    let (vx, vy) = random_velocity(&mut rng);
+                    request_spawn(&mut app, GridBall, arena.center(), Vec2::new(vx, vy));
+                    balls += 1;
+                }
+            }
+            // Half of the balls, left for the cleanup plugin to remove
+            150 => {
+                let despawned: Vec<Entity> = app
+                    .world
+                    .query::<(Entity, &GridBall)>()
+                    .iter()
+                    .map(|(entity, _)| entity)
+                    .skip(1)
+                    .step_by(2)
+                    .collect();
+                balls -= despawned.len();
+                for entity in despawned {
+                    app.world.despawn(entity).unwrap();
+                }
+            }
+            _ => {}
+        }
+        app.update();
+    }
+
+    let (bodies_len, colliders_len) = set_lengths(&app);
+    assert_eq!(bodies_len, baseline.0 + balls);
+    assert_eq!(colliders_len, baseline.1 + balls);
+    let bodies = app.resources.get::<RigidBodySet>().unwrap();
+    let mut handles = 0;
+    for (transform, body_handle) in app
+        .world
+        .query::<(&Transform, &RigidBodyHandleComponent)>()
+        .iter()
+    {
+        handles += 1;
+        assert_in_arena(transform.translation().x(), transform.translation().y());
+        let body = bodies.get(body_handle.handle()).unwrap();
+        let translation = body.position.translation.vector;
+        assert_in_arena(translation.x, translation.y);
+        assert!(
+            body.linvel.x.is_finite() && body.linvel.y.is_finite(),
+            "velocity {:?}",
+            body.linvel
+        );
+    }
+    assert_eq!(handles, balls);
+}
+
+/// The snapshots of the balls of a scripted rapier scenario, a wave of balls
+/// every 30 steps for the first 300.
+fn rapier2d_golden_steps() -> GoldenRun {
+    let mut app = headless_app(|app| {
+        app.add_plugin(RapierPhysicsPlugin)
+            .add_plugin(RapierCleanupPlugin)
+            .add_resource(RapierConfiguration {
+                gravity: RapierVector2::zeros(),
+                ..Default::default()
+            })
+            .add_plugin(ScenePlugin::default())
+            .add_plugin(RapierScenePlugin)
+            .add_system(wrap_system.system());
+    });
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let mut run = GoldenRun::default();
+    for step in 0..GOLDEN_STEPS {
+        if step % 30 == 0 && step < 300 {
+            for _ in 0..4 {
+                let position = Vec2::new(
+                    rng.gen_range(0.0, ARENA_WIDTH),
+                    rng.gen_range(0.0, ARENA_HEIGHT),
+                );
+                let (vx, vy) = random_velocity(&mut rng);
+                request_spawn(&mut app, GridBall, position, Vec2::new(vx, vy));
+            }
+        }
+        app.update();
+        let bodies = app.resources.get::<RigidBodySet>().unwrap();
+        run.push(Snapshot::new(bodies.iter().map(|(_, body)| {
+            let translation = body.position.translation.vector;
+            Vec2::new(translation.x, translation.y)
+        })));
+    }
+    run
+}
+
+#[test]
+fn rapier2d_runs_are_deterministic() {
+    let run = rapier2d_golden_steps();
+    if let Some(divergence) = rapier2d_golden_steps().first_divergence(&run) {
+        panic!("Two runs in a row diverged, first at {}", divergence);
+    }
+}
+
+/// A ship flying head-on into an asteroid, bouncing off it as in an elastic
+/// collision of the restitution of both colliders, the average in rapier 0.2.
+#[test]
+fn spaceship_head_on_knockback() {
+    let ship_speed = 15.0;
+    for &(size, asteroid_speed, mass_ratio) in &[
+        (AsteroidSize::Large, -5.0, 10.0),
+        (AsteroidSize::Small, -10.0, 1.0),
+    ] {
+        let mut app = headless_app(|app| {
+            app.add_plugin(RapierPhysicsPlugin)
+                .add_resource(RapierConfiguration {
+                    gravity: RapierVector2::zeros(),
+                    ..Default::default()
+                });
+        });
+        // Through the builders of spaceship_01
+        let (ship_body, ship_collider) = ship_builders(Vec2::new(-10.0, 0.0));
+        let ship = app.world.spawn((
+            Transform::default(),
+            ship_body.linvel(ship_speed, 0.0),
+            ship_collider,
+        ));
+        let (asteroid_body, asteroid_collider) = asteroid_builders(
+            size,
+            ASTEROID_RESTITUTION,
+            Vec2::new(10.0, 0.0),
+            Vec2::new(asteroid_speed, 0.0),
+            0.0,
+        );
+        let asteroid = app
+            .world
+            .spawn((Transform::default(), asteroid_body, asteroid_collider));
+        // In contact after less than a second, and apart again after it
+        for _ in 0..90 {
+            app.update();
+        }
+
+        let bodies = app.resources.get::<RigidBodySet>().unwrap();
+        let body = |entity| {
+            let handle = app.world.get::<RigidBodyHandleComponent>(entity).unwrap();
+            bodies.get(handle.handle()).unwrap()
+        };
+        let (ship_body, asteroid_body) = (body(ship), body(asteroid));
+        let ratio = asteroid_body.mass() / ship_body.mass();
+        assert!(
+            (ratio - mass_ratio).abs() < 0.1 * mass_ratio,
+            "mass ratio {}",
+            ratio
+        );
+        let (ship_velocity, asteroid_velocity) = resolve_elastic(
+            Vec2::new(ship_speed, 0.0),
+            Vec2::new(asteroid_speed, 0.0),
+            ship_body.mass(),
+            asteroid_body.mass(),
+            Vec2::unit_x(),
+            (SHIP_RESTITUTION + ASTEROID_RESTITUTION) / 2.0,
+        );
+        let tolerance = 0.1 * (ship_speed - asteroid_speed);
+        for (linvel, expected) in &[
+            (ship_body.linvel, ship_velocity),
+            (asteroid_body.linvel, asteroid_velocity),
+        ] {
+            assert!(
+                (linvel.x - expected.x()).abs() < tolerance && linvel.y.abs() < tolerance,
+                "velocity {:?} instead of {:?}",
+                linvel,
+                expected
+            );
+        }
+    }
+}
+
+#[test]
+fn scene_switches_leave_nothing_behind() {
+    let mut app = headless_app(|app| {
+        app.add_plugin(RapierPhysicsPlugin)
+            .add_plugin(RapierCleanupPlugin)
+            .add_resource(RapierConfiguration {
+                gravity: RapierVector2::zeros(),
+                ..Default::default()
+            })
+            .add_plugin(NCollideResponsePlugin::default().with_step(STEP).wrapping())
+            .add_resource(SeededRng(StdRng::seed_from_u64(SEED)))
+            .add_plugin(RapierScenePlugin)
+            .add_plugin(NCollideScenePlugin)
+            // The scenes of the showcase launcher, both backends in turn
+            .add_plugin(
+                ScenePlugin::default()
+                    .with_scene(rapier_scene::SCENE)
+                    .with_scene(ncollide_scene::SCENE),
+            );
+    });
+    app.update();
+    let baseline = Census::take(&app.world, &app.resources);
+    for switch in 0..6 {
+        let scene = switch % 2;
+        app.resources.get_mut::<ActiveScene>().unwrap().start(scene);
+        for _ in 0..30 {
+            app.update();
+        }
+        assert!(app.resources.get::<ActiveScene>().unwrap().is(scene));
+        let census = Census::take(&app.world, &app.resources);
+        let running = if scene == 0 {
+            census.bodies - baseline.bodies
+        } else {
+            census.collision_objects - baseline.collision_objects
+        };
+        assert!(running > 0, "scene {} spawned nothing", scene);
+
+        app.resources.get_mut::<ActiveScene>().unwrap().stop();
+        // The teardown, then the removal of the rapier bodies
+        app.update();
+        app.update();
+        let census = Census::take(&app.world, &app.resources);
+        assert_eq!(census, baseline, "leaks: {:?}", census.leaks(&baseline));
+    }
+}